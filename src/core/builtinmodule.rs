@@ -1,28 +1,117 @@
 use std::rc::Rc;
+use std::thread::LocalKey;
 
 use env::*;
 use error::*;
-use eval::PyRes;
+use eval::*;
+use utils::compile_source;
 
 use object::*;
+use object::bigint::BigInt;
 use object::boolobj::*;
+use object::bytesobj::*;
+use object::cellobj::*;
+use object::complexobj::*;
+use object::dictobj::*;
 use object::excobj::*;
+use object::floatobj::*;
+use object::generic::*;
+use object::genobj::*;
 use object::listobj::*;
 use object::longobj::*;
 use object::rustfunobj::*;
+use object::setobj::*;
+use object::sliceobj::*;
+use object::strobj::*;
+use object::tupleobj::*;
 use object::typeobj::*;
 
 fn builtin_len(_module: Rc<PyObject>, obj: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
-    let ob_type = obj.ob_type();
-    let typ = ob_type.pytype_typeobj_borrow();
-    match typ.tp_len {
-        Some(ref fun) => (*fun)(Rc::clone(&obj)),
+    Ok(PyObject::from_bigint(BigInt::from(pyobj_len(obj)? as i64)))
+}
+
+fn builtin_repr(_module: Rc<PyObject>, obj: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pyobj_repr(obj)
+}
+
+fn builtin_format(_module: Rc<PyObject>, args: &Vec<Rc<PyObject>>) -> PyRes<Rc<PyObject>> {
+    check_arg_count("format", args, 1, 2)?;
+    let spec = args.get(1).map_or(PyObject::from_str(""), |spec| Rc::clone(spec));
+    if !spec.pystr_check() {
+        pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                         "format() argument 2 must be str");
+        return Err(());
+    }
+    pyobj_format(Rc::clone(&args[0]), spec)
+}
+
+// Unlike a for loop, next() lets the StopIteration of an exhausted iterator
+// through unless there is a default to return instead.
+fn builtin_next(_module: Rc<PyObject>, args: &Vec<Rc<PyObject>>) -> PyRes<Rc<PyObject>> {
+    check_arg_count("next", args, 1, 2)?;
+    let it = Rc::clone(&args[0]);
+    let nextfun = match it.ob_type().pytype_tp_iternext() {
+        Some(nextfun) => nextfun,
         None => {
-            pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
-                             "no __lt__ operation");
-            Err(())
+            let msg = format!("'{}' object is not an iterator", it.ob_type().pytype_typeobj_borrow().tp_name);
+            pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)), &msg);
+            return Err(());
         }
+    };
+    if let Some(v) = nextfun(it)? {
+        return Ok(v);
     }
+    let stopped = pyerr_check(PY_STOPITERATION_TYPE.with(|tp| Rc::clone(tp)));
+    match args.get(1) {
+        Some(default) => {
+            if stopped {
+                pyerr_clear();
+            }
+            Ok(Rc::clone(default))
+        },
+        None => {
+            if !stopped {
+                pyerr_set_stop_iteration(PyObject::none_obj());
+            }
+            Err(())
+        },
+    }
+}
+
+fn builtin_compile(_module: Rc<PyObject>, args: &Vec<Rc<PyObject>>) -> PyRes<Rc<PyObject>> {
+    check_arg_count("compile", args, 3, 3)?;
+    let source = pyobj_to_string(Rc::clone(&args[0]))?;
+    let filename = pyobj_to_string(Rc::clone(&args[1]))?;
+    if pyobj_to_string(Rc::clone(&args[2]))? != "exec" {
+        pyerr_set_string(PY_VALUEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                         "compile() mode must be 'exec'");
+        return Err(());
+    }
+    compile_source(&source, &filename).map_err(|errors| {
+        pyerr_set_syntax_error(&errors[0], &filename, &source)
+    })
+}
+
+// runs the module in a namespace of its own
+fn builtin_exec(_module: Rc<PyObject>, obj: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    let codeobj = match obj.inner {
+        PyInnerObject::StrObj(_) => {
+            let source = pyobj_to_string(Rc::clone(&obj))?;
+            compile_source(&source, "<string>").map_err(|errors| {
+                pyerr_set_syntax_error(&errors[0], "<string>", &source)
+            })?
+        },
+        PyInnerObject::CodeObj(_) => Rc::clone(&obj),
+        _ => {
+            pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                             "exec() arg 1 must be a string or code object");
+            return Err(());
+        }
+    };
+    let env = Rc::new(Env::new());
+    load_builtins(Rc::clone(&env))?;
+    eval(&codeobj, env)?;
+    Ok(PyObject::none_obj())
 }
 
 macro_rules! set_builtin_fun {
@@ -41,17 +130,53 @@ macro_rules! set_builtin_fun {
     }
 }
 
-pub fn load_builtins(env: Rc<Env>) {
+pub fn load_builtins(env: Rc<Env>) -> PyRes<()> {
     set_builtin_fun!(env, "len", MethO, builtin_len);
+    set_builtin_fun!(env, "repr", MethO, builtin_repr);
+    set_builtin_fun!(env, "format", MethVarArgs, builtin_format);
+    set_builtin_fun!(env, "next", MethVarArgs, builtin_next);
+    set_builtin_fun!(env, "compile", MethVarArgs, builtin_compile);
+    set_builtin_fun!(env, "exec", MethO, builtin_exec);
     env.update("type".to_string(), PY_TYPE_TYPE.with(|tp| { Rc::clone(tp) }));
     env.update("int".to_string(), PY_LONG_TYPE.with(|tp| { Rc::clone(tp) }));
+    env.update("float".to_string(), PY_FLOAT_TYPE.with(|tp| { Rc::clone(tp) }));
+    env.update("complex".to_string(), PY_COMPLEX_TYPE.with(|tp| { Rc::clone(tp) }));
+    env.update("str".to_string(), PY_STRING_TYPE.with(|tp| { Rc::clone(tp) }));
+    env.update("dict".to_string(), PY_DICT_TYPE.with(|tp| { Rc::clone(tp) }));
     env.update("bool".to_string(), PY_BOOL_TYPE.with(|tp| { Rc::clone(tp) }));
-    env.update("Exception".to_string(), PY_EXC_TYPE.with(|tp| { Rc::clone(tp) }));
-    env.update("TypeError".to_string(), PY_TYPEERROR_TYPE.with(|tp| { Rc::clone(tp) }));
-    PY_BASEOBJ_TYPE.with(|tp| { pytype_ready(Rc::clone(tp)) });
-    PY_BOOL_TYPE.with(|booltp| { pytype_ready(Rc::clone(booltp)) });
-    PY_LIST_TYPE.with(|listtp| { pytype_ready(Rc::clone(listtp)) });
-    PY_BASEEXC_TYPE.with(|exctp| { pytype_ready(Rc::clone(exctp)) });
-    PY_EXC_TYPE.with(|exctp| { pytype_ready(Rc::clone(exctp)) });
-    PY_TYPEERROR_TYPE.with(|exctp| { pytype_ready(Rc::clone(exctp)) });
+    env.update("bytes".to_string(), PY_BYTES_TYPE.with(|tp| { Rc::clone(tp) }));
+    env.update("tuple".to_string(), PY_TUPLE_TYPE.with(|tp| { Rc::clone(tp) }));
+    env.update("set".to_string(), PY_SET_TYPE.with(|tp| { Rc::clone(tp) }));
+    env.update("slice".to_string(), PY_SLICE_TYPE.with(|tp| { Rc::clone(tp) }));
+    env.update("NotImplemented".to_string(), PyObject::notimpl_obj());
+    PY_BASEOBJ_TYPE.with(|tp| { pytype_ready(Rc::clone(tp)) })?;
+    PY_BOOL_TYPE.with(|booltp| { pytype_ready(Rc::clone(booltp)) })?;
+    PY_LIST_TYPE.with(|listtp| { pytype_ready(Rc::clone(listtp)) })?;
+    PY_DICT_TYPE.with(|dicttp| { pytype_ready(Rc::clone(dicttp)) })?;
+    PY_SET_TYPE.with(|settp| { pytype_ready(Rc::clone(settp)) })?;
+    PY_STRING_TYPE.with(|strtp| { pytype_ready(Rc::clone(strtp)) })?;
+    PY_COMPLEX_TYPE.with(|complextp| { pytype_ready(Rc::clone(complextp)) })?;
+    PY_SLICE_TYPE.with(|slicetp| { pytype_ready(Rc::clone(slicetp)) })?;
+    PY_GEN_TYPE.with(|gentp| { pytype_ready(Rc::clone(gentp)) })?;
+    PY_CELL_TYPE.with(|celltp| { pytype_ready(Rc::clone(celltp)) })?;
+
+    // bases come before their subclasses so that each mro can be built
+    let exc_types: Vec<&'static LocalKey<Rc<PyObject>>> = vec![
+        &PY_BASEEXC_TYPE, &PY_EXC_TYPE, &PY_GENERATOREXIT_TYPE,
+        &PY_TYPEERROR_TYPE, &PY_STOPITERATION_TYPE, &PY_ATTRIBUTEERROR_TYPE,
+        &PY_ARITHMETICERROR_TYPE, &PY_ZERODIVERROR_TYPE, &PY_OVERFLOWERROR_TYPE,
+        &PY_LOOKUPERROR_TYPE, &PY_KEYERROR_TYPE, &PY_INDEXERROR_TYPE,
+        &PY_VALUEERROR_TYPE, &PY_NAMEERROR_TYPE, &PY_UNBOUNDLOCALERROR_TYPE,
+        &PY_RUNTIMEERROR_TYPE, &PY_RECURSIONERROR_TYPE, &PY_NOTIMPLEMENTEDERROR_TYPE,
+        &PY_SYNTAXERROR_TYPE, &PY_INDENTATIONERROR_TYPE, &PY_TABERROR_TYPE,
+        &PY_ASSERTIONERROR_TYPE, &PY_MEMORYERROR_TYPE,
+    ];
+    for exc_type in exc_types {
+        exc_type.with(|exctp| {
+            let name = exctp.pytype_typeobj_borrow().tp_name.clone();
+            env.update(name, Rc::clone(exctp));
+            pytype_ready(Rc::clone(exctp))
+        })?;
+    }
+    Ok(())
 }
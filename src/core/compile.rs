@@ -1,99 +1,567 @@
+use std::rc::Rc;
+
 use object::*;
+use object::codeobj::ArgSpec;
 use opcode::*;
+use symtable::*;
 use syntax::*;
 
+/// The block being compiled: its file and the symbol table that tells
+/// where each of its names lives.
+struct Unit {
+    filename: Rc<String>,
+    symtable: Rc<SymbolTable>,
+}
+
+impl Unit {
+    fn child<T>(&self, node: &T) -> Rc<Unit> {
+        Rc::new(Unit {
+            filename: Rc::clone(&self.filename),
+            symtable: self.symtable.child(node),
+        })
+    }
+
+    // Functions keep their locals apart from their globals, while module
+    // and class bodies look names up through their environments.
+    fn load_name(&self, id: &Id) -> Opcode {
+        let id = id.clone();
+        match (self.symtable.scope(&id), self.symtable.kind) {
+            (Scope::Cell, _) | (Scope::Free, _) => Opcode::LoadDeref(id),
+            (Scope::GlobalExplicit, _) => Opcode::LoadGlobal(id),
+            (Scope::Local, BlockKind::Function) => Opcode::LoadFast(id),
+            (Scope::GlobalImplicit, BlockKind::Function) => Opcode::LoadGlobal(id),
+            _ => Opcode::LoadName(id),
+        }
+    }
+
+    fn store_name(&self, id: &Id) -> Opcode {
+        let id = id.clone();
+        match (self.symtable.scope(&id), self.symtable.kind) {
+            (Scope::Cell, _) | (Scope::Free, _) => Opcode::StoreDeref(id),
+            (Scope::GlobalExplicit, _) => Opcode::StoreGlobal(id),
+            (Scope::Local, BlockKind::Function) => Opcode::StoreFast(id),
+            (Scope::GlobalImplicit, BlockKind::Function) => Opcode::StoreGlobal(id),
+            _ => Opcode::StoreName(id),
+        }
+    }
+
+    fn delete_name(&self, id: &Id) -> Opcode {
+        let id = id.clone();
+        match (self.symtable.scope(&id), self.symtable.kind) {
+            (Scope::Cell, _) | (Scope::Free, _) => Opcode::DeleteDeref(id),
+            (Scope::GlobalExplicit, _) => Opcode::DeleteGlobal(id),
+            (Scope::Local, BlockKind::Function) => Opcode::DeleteFast(id),
+            (Scope::GlobalImplicit, BlockKind::Function) => Opcode::DeleteGlobal(id),
+            _ => Opcode::DeleteName(id),
+        }
+    }
+
+    fn code_object(&self, code: Code, argspec: ArgSpec, name: &str, line: Line) -> Rc<PyObject> {
+        PyObject::pycode_new(code, argspec, self.symtable.cellvars.clone(), self.symtable.freevars.clone(),
+                             name, &self.filename, line)
+    }
+}
+
+// Makes a function of the code of the block `unit`, giving it the cells of
+// the block's free variables. The code making the defaults comes first.
+fn make_closure(unit: &Unit, mut code: Code, codeobj: Rc<PyObject>, name: &str,
+                has_defaults: bool, has_kwdefaults: bool) -> Code {
+    let freevars = &unit.symtable.freevars;
+    for var in freevars {
+        code.push(Opcode::LoadClosure(var.clone()));
+    }
+    if !freevars.is_empty() {
+        code.push(Opcode::BuildTuple(freevars.len()));
+    }
+    code.push(Opcode::LoadConst(codeobj));
+    code.push(Opcode::LoadConst(PyObject::from_str(name)));
+    code.push(Opcode::MakeFunction(has_defaults, has_kwdefaults, !freevars.is_empty()));
+    code
+}
+
 struct AddrInfo {
     start: Addr,
     cont: Option<Addr>,
+    unit: Rc<Unit>,
 }
 
 impl AddrInfo {
-    fn new() -> AddrInfo {
+    fn new(unit: Rc<Unit>) -> AddrInfo {
         AddrInfo {
             start: 0,
             cont: None,
+            unit: unit,
         }
     }
 
     fn change_start(&self, start: Addr) -> AddrInfo {
         AddrInfo {
             start: start,
-            cont: self.cont
+            cont: self.cont,
+            unit: Rc::clone(&self.unit),
+        }
+    }
+
+    fn change_cont(&self, start: Addr, cont: Addr) -> AddrInfo {
+        AddrInfo {
+            start: start,
+            cont: Some(cont),
+            unit: Rc::clone(&self.unit),
         }
     }
 }
 
-fn compile_expr(expr: &Expr) -> Code {
+fn compare_opcode(op: CompOp) -> Opcode {
+    match op {
+        CompOp::Lt => Opcode::BinaryLt,
+        CompOp::LtE => Opcode::BinaryLtE,
+        CompOp::Gt => Opcode::BinaryGt,
+        CompOp::GtE => Opcode::BinaryGtE,
+        CompOp::EqEq => Opcode::BinaryEq,
+        CompOp::NotEq => Opcode::BinaryNotEq,
+        CompOp::In => Opcode::BinaryIn,
+        CompOp::NotIn => Opcode::BinaryNotIn,
+        CompOp::Is => Opcode::BinaryIs,
+        CompOp::IsNot => Opcode::BinaryIsNot,
+    }
+}
+
+fn is_starred(expr: &Expr) -> bool {
+    match expr.kind {
+        ExprKind::StarredExpr(_) => true,
+        _ => false,
+    }
+}
+
+// A display with starred items is built as a list, which the items after
+// the first starred one are appended to or extend.
+fn compile_starred_display(elts: &Vec<Expr>, start: Addr, unit: &Unit) -> Code {
     let mut code = vec![];
-    match expr {
-        &Expr::VarExpr(ref id) => code.push(Opcode::LoadName(id.clone())),
-        &Expr::IntExpr(i) => code.push(Opcode::LoadConst(PyObject::from_i32(i))),
-        &Expr::BoolExpr(b) => code.push(Opcode::LoadConst(PyObject::from_bool(b))),
-        &Expr::StrExpr(ref s) => code.push(Opcode::LoadConst(PyObject::from_string(s.clone()))),
-        &Expr::NoneExpr => code.push(Opcode::LoadConst(PyObject::none_obj())),
-        &Expr::AddExpr(ref e1, ref e2) => {
-            code.append(&mut compile_expr(e1));
-            code.append(&mut compile_expr(e2));
+    let prefix = elts.iter().take_while(|elt| !is_starred(elt)).count();
+    for elt in &elts[..prefix] {
+        code.append(&mut compile_expr(elt, start + code.len(), unit));
+    }
+    code.push(Opcode::BuildList(prefix));
+    for elt in &elts[prefix..] {
+        match elt.kind {
+            ExprKind::StarredExpr(ref e) => {
+                code.append(&mut compile_expr(e, start + code.len(), unit));
+                code.push(Opcode::ListExtend);
+            },
+            _ => {
+                code.append(&mut compile_expr(elt, start + code.len(), unit));
+                code.push(Opcode::ListAppend(1));
+            },
+        }
+    }
+    code
+}
+
+// Makes a function of `body_code`, the code of the block `body_unit`, with
+// the defaults evaluated once, when the function is defined.
+fn compile_make_function(parms: &Parameters, body_unit: &Unit, body_code: Code, name: &str, line: Line,
+                         start: Addr, unit: &Unit) -> Code {
+    let mut code = vec![];
+    for default in &parms.defaults {
+        code.append(&mut compile_expr(default, start + code.len(), unit));
+    }
+    if !parms.defaults.is_empty() {
+        code.push(Opcode::BuildTuple(parms.defaults.len()));
+    }
+    let kwdefaults: Vec<_> = parms.kwonly.iter().filter_map(|&(ref arg, ref default)| {
+        default.as_ref().map(|default| (arg, default))
+    }).collect();
+    for &(arg, default) in &kwdefaults {
+        code.push(Opcode::LoadConst(PyObject::from_str(arg)));
+        code.append(&mut compile_expr(default, start + code.len(), unit));
+    }
+    if !kwdefaults.is_empty() {
+        code.push(Opcode::BuildMap(kwdefaults.len()));
+    }
+    let argspec = ArgSpec {
+        args: parms.args.clone(),
+        posonly: parms.posonly,
+        vararg: parms.vararg.clone(),
+        kwonly: parms.kwonly.iter().map(|&(ref arg, _)| arg.clone()).collect(),
+        kwarg: parms.kwarg.clone(),
+    };
+    let codeobj = body_unit.code_object(body_code, argspec, name, line);
+    make_closure(body_unit, code, codeobj, name, !parms.defaults.is_empty(), !kwdefaults.is_empty())
+}
+
+fn compile_expr(expr: &Expr, start: Addr, unit: &Unit) -> Code {
+    let mut code = vec![];
+    match expr.kind {
+        ExprKind::VarExpr(ref id) => code.push(unit.load_name(id)),
+        ExprKind::IntExpr(ref i) => code.push(Opcode::LoadConst(PyObject::from_bigint(i.clone()))),
+        ExprKind::FloatExpr(f) => code.push(Opcode::LoadConst(PyObject::from_f64(f))),
+        ExprKind::ImagExpr(f) => code.push(Opcode::LoadConst(PyObject::from_complex(0.0, f))),
+        ExprKind::BoolExpr(b) => code.push(Opcode::LoadConst(PyObject::from_bool(b))),
+        ExprKind::StrExpr(ref s) => code.push(Opcode::LoadConst(PyObject::from_string(s.clone()))),
+        ExprKind::BytesExpr(ref b) => code.push(Opcode::LoadConst(PyObject::from_bytes(b.clone()))),
+        ExprKind::JoinedStr(ref values) => {
+            for value in values {
+                code.append(&mut compile_expr(value, start + code.len(), unit));
+            };
+            code.push(Opcode::BuildString(values.len()));
+        },
+        ExprKind::FormattedValue(ref value, conversion, ref spec) => {
+            code.append(&mut compile_expr(value, start + code.len(), unit));
+            if let Some(ref spec) = *spec {
+                code.append(&mut compile_expr(spec, start + code.len(), unit));
+            }
+            code.push(Opcode::FormatValue(conversion, spec.is_some()));
+        },
+        ExprKind::NoneExpr => code.push(Opcode::LoadConst(PyObject::none_obj())),
+        ExprKind::AddExpr(ref e1, ref e2) => {
+            code.append(&mut compile_expr(e1, start + code.len(), unit));
+            code.append(&mut compile_expr(e2, start + code.len(), unit));
             code.push(Opcode::BinaryAdd);
         },
-        &Expr::LtExpr(ref e1, ref e2) => {
-            code.append(&mut compile_expr(e1));
-            code.append(&mut compile_expr(e2));
-            code.push(Opcode::BinaryLt);
+        ExprKind::SubExpr(ref e1, ref e2) => {
+            code.append(&mut compile_expr(e1, start + code.len(), unit));
+            code.append(&mut compile_expr(e2, start + code.len(), unit));
+            code.push(Opcode::BinarySub);
         },
-        &Expr::EqEqExpr(ref e1, ref e2) => {
-            code.append(&mut compile_expr(e1));
-            code.append(&mut compile_expr(e2));
-            code.push(Opcode::BinaryEq);
+        ExprKind::MulExpr(ref e1, ref e2) => {
+            code.append(&mut compile_expr(e1, start + code.len(), unit));
+            code.append(&mut compile_expr(e2, start + code.len(), unit));
+            code.push(Opcode::BinaryMul);
         },
-        &Expr::CallExpr(ref fun, ref args) => {
-            code.append(&mut compile_expr(fun));
+        ExprKind::TrueDivExpr(ref e1, ref e2) => {
+            code.append(&mut compile_expr(e1, start + code.len(), unit));
+            code.append(&mut compile_expr(e2, start + code.len(), unit));
+            code.push(Opcode::BinaryTrueDiv);
+        },
+        ExprKind::FloorDivExpr(ref e1, ref e2) => {
+            code.append(&mut compile_expr(e1, start + code.len(), unit));
+            code.append(&mut compile_expr(e2, start + code.len(), unit));
+            code.push(Opcode::BinaryFloorDiv);
+        },
+        ExprKind::ModExpr(ref e1, ref e2) => {
+            code.append(&mut compile_expr(e1, start + code.len(), unit));
+            code.append(&mut compile_expr(e2, start + code.len(), unit));
+            code.push(Opcode::BinaryMod);
+        },
+        ExprKind::PowExpr(ref e1, ref e2) => {
+            code.append(&mut compile_expr(e1, start + code.len(), unit));
+            code.append(&mut compile_expr(e2, start + code.len(), unit));
+            code.push(Opcode::BinaryPow);
+        },
+        ExprKind::NegExpr(ref e) => {
+            code.append(&mut compile_expr(e, start + code.len(), unit));
+            code.push(Opcode::UnaryNeg);
+        },
+        ExprKind::PosExpr(ref e) => {
+            code.append(&mut compile_expr(e, start + code.len(), unit));
+            code.push(Opcode::UnaryPos);
+        },
+        ExprKind::CompareExpr(ref e1, ref comps) => {
+            code.append(&mut compile_expr(e1, start, unit));
+            let (last, init) = comps.split_last().expect("Never fails");
+            let mut cleanup_jumps = vec![];
+            for &(op, ref e2) in init {
+                code.append(&mut compile_expr(e2, start + code.len(), unit));
+                code.push(Opcode::DupTop);
+                code.push(Opcode::RotThree);
+                code.push(compare_opcode(op));
+                cleanup_jumps.push(code.len());
+                code.push(Opcode::JumpIfFalseOrPop(0));
+            }
+            code.append(&mut compile_expr(&last.1, start + code.len(), unit));
+            code.push(compare_opcode(last.0));
+            if !cleanup_jumps.is_empty() {
+                let cleanup_addr = start + code.len() + 1;
+                for i in cleanup_jumps {
+                    code[i] = Opcode::JumpIfFalseOrPop(cleanup_addr);
+                }
+                code.push(Opcode::JumpAbsolute(cleanup_addr + 2));
+                code.push(Opcode::RotTwo);
+                code.push(Opcode::PopTop);
+            }
+        },
+        ExprKind::AndExpr(ref e1, ref e2) => {
+            code.append(&mut compile_expr(e1, start, unit));
+            let mut e2_code = compile_expr(e2, start + code.len() + 1, unit);
+            let end_addr = start + code.len() + 1 + e2_code.len();
+            code.push(Opcode::JumpIfFalseOrPop(end_addr));
+            code.append(&mut e2_code);
+        },
+        ExprKind::OrExpr(ref e1, ref e2) => {
+            code.append(&mut compile_expr(e1, start, unit));
+            let mut e2_code = compile_expr(e2, start + code.len() + 1, unit);
+            let end_addr = start + code.len() + 1 + e2_code.len();
+            code.push(Opcode::JumpIfTrueOrPop(end_addr));
+            code.append(&mut e2_code);
+        },
+        ExprKind::CondExpr(ref cond, ref e1, ref e2) => {
+            code.append(&mut compile_expr(cond, start, unit));
+            let mut e1_code = compile_expr(e1, start + code.len() + 1, unit);
+            let else_addr = start + code.len() + 1 + e1_code.len() + 1;
+            let mut e2_code = compile_expr(e2, else_addr, unit);
+            code.push(Opcode::PopJumpIfFalse(else_addr));
+            code.append(&mut e1_code);
+            code.push(Opcode::JumpAbsolute(else_addr + e2_code.len()));
+            code.append(&mut e2_code);
+        },
+        ExprKind::LambdaExpr(ref parms, ref body) => {
+            let line = expr.span.start.line;
+            let body_unit = unit.child(&**parms);
+            let mut body_code = vec![Opcode::SetLineno(line)];
+            body_code.append(&mut compile_expr(body, 1, &body_unit));
+            body_code.push(Opcode::ReturnValue);
+            code.append(&mut compile_make_function(parms, &body_unit, body_code, "<lambda>", line, start, unit));
+        },
+        ExprKind::NotExpr(ref e) => {
+            code.append(&mut compile_expr(e, start, unit));
+            code.push(Opcode::UnaryNot);
+        },
+        ExprKind::CallExpr(ref fun, ref args, ref keywords)
+            if args.iter().any(is_starred) || keywords.iter().any(|kw| kw.arg.is_none()) => {
+            code.append(&mut compile_expr(fun, start + code.len(), unit));
+            let mut args_code = compile_starred_display(args, start + code.len(), unit);
+            code.append(&mut args_code);
+            code.push(Opcode::ListToTuple);
+            if !keywords.is_empty() {
+                code.push(Opcode::BuildMap(0));
+                for kw in keywords {
+                    match kw.arg {
+                        Some(ref id) => {
+                            code.push(Opcode::LoadConst(PyObject::from_str(id)));
+                            code.append(&mut compile_expr(&kw.value, start + code.len(), unit));
+                            code.push(Opcode::BuildMap(1));
+                        },
+                        None => code.append(&mut compile_expr(&kw.value, start + code.len(), unit)),
+                    }
+                    code.push(Opcode::DictMerge);
+                }
+            }
+            code.push(Opcode::CallFunctionEx(!keywords.is_empty()));
+        },
+        ExprKind::CallExpr(ref fun, ref args, ref keywords) => {
+            code.append(&mut compile_expr(fun, start + code.len(), unit));
             for arg in args {
-                code.append(&mut compile_expr(arg));
+                code.append(&mut compile_expr(arg, start + code.len(), unit));
             };
-            code.push(Opcode::CallFunction(args.len()));
+            if keywords.is_empty() {
+                code.push(Opcode::CallFunction(args.len()));
+            } else {
+                let mut names = vec![];
+                for kw in keywords {
+                    code.append(&mut compile_expr(&kw.value, start + code.len(), unit));
+                    names.push(PyObject::from_str(kw.arg.as_ref().expect("Never fails")));
+                };
+                code.push(Opcode::LoadConst(PyObject::pytuple_from_vec(names)));
+                code.push(Opcode::CallFunctionKw(args.len() + keywords.len()));
+            }
         },
-        &Expr::AttrExpr(ref e, ref ident) => {
-            code.append(&mut compile_expr(e));
+        ExprKind::AttrExpr(ref e, ref ident) => {
+            code.append(&mut compile_expr(e, start + code.len(), unit));
             code.push(Opcode::LoadAttr(ident.clone()));
         },
-        &Expr::SubscrExpr(ref e1, ref e2) => {
-            code.append(&mut compile_expr(e1));
-            code.append(&mut compile_expr(e2));
+        ExprKind::SubscrExpr(ref e1, ref e2) => {
+            code.append(&mut compile_expr(e1, start + code.len(), unit));
+            code.append(&mut compile_expr(e2, start + code.len(), unit));
             code.push(Opcode::BinarySubScr);
         },
-        &Expr::ListExpr(ref cl) => {
+        ExprKind::SliceExpr(ref lower, ref upper, ref step) => {
+            for part in &[lower, upper] {
+                match **part {
+                    Some(ref e) => code.append(&mut compile_expr(e, start + code.len(), unit)),
+                    None => code.push(Opcode::LoadConst(PyObject::none_obj())),
+                }
+            }
+            match *step {
+                Some(ref e) => {
+                    code.append(&mut compile_expr(e, start + code.len(), unit));
+                    code.push(Opcode::BuildSlice(3));
+                },
+                None => code.push(Opcode::BuildSlice(2)),
+            }
+        },
+        ExprKind::ListExpr(ref cl) if cl.iter().any(is_starred) => {
+            code.append(&mut compile_starred_display(cl, start, unit));
+        },
+        ExprKind::ListExpr(ref cl) => {
             for c in cl {
-                code.append(&mut compile_expr(c));
+                code.append(&mut compile_expr(c, start + code.len(), unit));
             };
             code.push(Opcode::BuildList(cl.len()));
         },
-        &Expr::DictExpr(ref pl) => {
+        ExprKind::TupleExpr(ref cl) if cl.iter().any(is_starred) => {
+            code.append(&mut compile_starred_display(cl, start, unit));
+            code.push(Opcode::ListToTuple);
+        },
+        ExprKind::TupleExpr(ref cl) => {
+            for c in cl {
+                code.append(&mut compile_expr(c, start + code.len(), unit));
+            };
+            code.push(Opcode::BuildTuple(cl.len()));
+        },
+        ExprKind::DictExpr(ref pl) => {
             for (e1, e2) in pl {
-                code.append(&mut compile_expr(e1));
-                code.append(&mut compile_expr(e2));
+                code.append(&mut compile_expr(e1, start + code.len(), unit));
+                code.append(&mut compile_expr(e2, start + code.len(), unit));
             };
             code.push(Opcode::BuildMap(pl.len()));
         },
+        ExprKind::SetExpr(ref cl) => {
+            for c in cl {
+                code.append(&mut compile_expr(c, start + code.len(), unit));
+            };
+            code.push(Opcode::BuildSet(cl.len()));
+        },
+        ExprKind::ListComp(ref elt, ref comps) => {
+            code = compile_comprehension(comps, Some(Opcode::BuildList(0)), "<listcomp>",
+                                         expr.span.start.line, start, unit, |addr, depth, unit| {
+                let mut code = compile_expr(elt, addr, unit);
+                code.push(Opcode::ListAppend(depth));
+                code
+            });
+        },
+        ExprKind::SetComp(ref elt, ref comps) => {
+            code = compile_comprehension(comps, Some(Opcode::BuildSet(0)), "<setcomp>",
+                                         expr.span.start.line, start, unit, |addr, depth, unit| {
+                let mut code = compile_expr(elt, addr, unit);
+                code.push(Opcode::SetAdd(depth));
+                code
+            });
+        },
+        ExprKind::DictComp(ref key, ref value, ref comps) => {
+            code = compile_comprehension(comps, Some(Opcode::BuildMap(0)), "<dictcomp>",
+                                         expr.span.start.line, start, unit, |addr, depth, unit| {
+                let mut code = compile_expr(key, addr, unit);
+                let mut value_code = compile_expr(value, addr + code.len(), unit);
+                code.append(&mut value_code);
+                code.push(Opcode::MapAdd(depth));
+                code
+            });
+        },
+        ExprKind::GeneratorExp(ref elt, ref comps) => {
+            code = compile_comprehension(comps, None, "<genexpr>",
+                                         expr.span.start.line, start, unit, |addr, _, unit| {
+                let mut code = compile_expr(elt, addr, unit);
+                code.push(Opcode::YieldValue);
+                code.push(Opcode::PopTop);
+                code
+            });
+        },
+        ExprKind::YieldExpr(ref value) => {
+            match *value {
+                Some(ref e) => code.append(&mut compile_expr(e, start, unit)),
+                None => code.push(Opcode::LoadConst(PyObject::none_obj())),
+            }
+            code.push(Opcode::YieldValue);
+        },
+        ExprKind::YieldFrom(ref e) => {
+            code.append(&mut compile_expr(e, start, unit));
+            code.push(Opcode::GetIter);
+            code.push(Opcode::LoadConst(PyObject::none_obj()));
+            code.push(Opcode::YieldFrom);
+        },
+        ExprKind::StarredExpr(_) => panic!("Implementation Error: compile StarredExpr"),
     };
     code
 }
 
-fn compile_target(target: &Target) -> Code {
+// A comprehension runs as a function of its own, called right away with an
+// iterator over its first `for` clause as the argument `.0`, so that its loop
+// variables stay out of the enclosing scope.  `init` builds the collection
+// the elements go into (there is none for a generator expression), and
+// `add` compiles the storing of one element, given its start address, how
+// deep below the top of the stack the collection sits and the comprehension's
+// block.
+fn compile_comprehension<F>(comps: &Vec<Comprehension>, init: Option<Opcode>, name: &str, line: Line,
+                            start: Addr, unit: &Unit, add: F) -> Code
+where F: Fn(Addr, usize, &Unit) -> Code {
+    let body_unit = unit.child(comps);
+    let mut body_code = vec![Opcode::SetLineno(line)];
+    let is_genexp = init.is_none();
+    body_code.extend(init);
+    let mut loop_code = compile_comp_clause(comps, 0, body_code.len(), &body_unit, &add);
+    body_code.append(&mut loop_code);
+    if is_genexp {
+        body_code.push(Opcode::LoadConst(PyObject::none_obj()));
+    }
+    body_code.push(Opcode::ReturnValue);
+    let argspec = ArgSpec { args: vec![".0".to_string()], ..Default::default() };
+    let codeobj = body_unit.code_object(body_code, argspec, name, line);
+
+    let mut code = make_closure(&body_unit, vec![], codeobj, name, false, false);
+    code.append(&mut compile_expr(&comps[0].iter, start + code.len(), unit));
+    code.push(Opcode::GetIter);
+    code.push(Opcode::CallFunction(1));
+    code
+}
+
+// the loop for `comps[i]`, with the loops of the clauses after it nested inside
+fn compile_comp_clause<F>(comps: &Vec<Comprehension>, i: usize, start: Addr, unit: &Unit,
+                          add: &F) -> Code
+where F: Fn(Addr, usize, &Unit) -> Code {
+    let comp = &comps[i];
+    let mut code = if i == 0 {
+        vec![unit.load_name(&".0".to_string())]
+    } else {
+        let mut iter_code = compile_expr(&comp.iter, start, unit);
+        iter_code.push(Opcode::GetIter);
+        iter_code
+    };
+    let for_iter_addr = start + code.len();
+    let mut addr = for_iter_addr + 1;
+    let mut target_code = compile_target(&comp.target, addr, unit);
+    addr += target_code.len();
+    let mut ifs_code = vec![];
+    for cond in &comp.ifs {
+        ifs_code.append(&mut compile_expr(cond, addr + ifs_code.len(), unit));
+        ifs_code.push(Opcode::PopJumpIfFalse(for_iter_addr));
+    }
+    addr += ifs_code.len();
+    let mut inner_code = if i + 1 < comps.len() {
+        compile_comp_clause(comps, i + 1, addr, unit, add)
+    } else {
+        add(addr, comps.len() + 1, unit)
+    };
+    addr += inner_code.len() + 1;
+
+    code.push(Opcode::ForIter(addr));
+    code.append(&mut target_code);
+    code.append(&mut ifs_code);
+    code.append(&mut inner_code);
+    code.push(Opcode::JumpAbsolute(for_iter_addr));
+    code
+}
+
+fn compile_target(target: &Target, start: Addr, unit: &Unit) -> Code {
     let mut code = vec![];
     match target {
         &Target::IdentTarget(ref id) => {
-            code.push(Opcode::StoreName(id.clone()));
+            code.push(unit.store_name(id));
         },
         &Target::AttrTarget(ref lexpr, ref id) => {
-            code.append(&mut compile_expr(lexpr));
+            code.append(&mut compile_expr(lexpr, start + code.len(), unit));
             code.push(Opcode::StoreAttr(id.clone()));
         },
         &Target::SubscrTarget(ref e1, ref e2) => {
-            code.append(&mut compile_expr(e1));
-            code.append(&mut compile_expr(e2));
+            code.append(&mut compile_expr(e1, start + code.len(), unit));
+            code.append(&mut compile_expr(e2, start + code.len(), unit));
             code.push(Opcode::StoreSubScr);
-        }
+        },
+        &Target::TupleTarget(ref targets) => {
+            let starred = targets.iter().position(|target| match target {
+                &Target::StarredTarget(_) => true,
+                _ => false,
+            });
+            match starred {
+                Some(i) => code.push(Opcode::UnpackEx(i, targets.len() - i - 1)),
+                None => code.push(Opcode::UnpackSequence(targets.len())),
+            }
+            for target in targets {
+                code.append(&mut compile_target(target, start + code.len(), unit));
+            }
+        },
+        &Target::StarredTarget(ref target) => {
+            code.append(&mut compile_target(target, start + code.len(), unit));
+        },
     };
     code
 }
@@ -102,47 +570,66 @@ fn compile_simple_stmt(stmt: &SimpleStmt, addr_info: AddrInfo) -> Code {
     let mut code = vec![];
     match stmt {
         &SimpleStmt::ExprStmt(ref expr) => {
-            code.append(&mut compile_expr(expr));
+            code.append(&mut compile_expr(expr, addr_info.start + code.len(), &addr_info.unit));
             code.push(Opcode::PopTop);
         },
         &SimpleStmt::AssignStmt(ref target, ref expr) => {
-            code.append(&mut compile_expr(expr));
-            code.append(&mut compile_target(target));
+            code.append(&mut compile_expr(expr, addr_info.start + code.len(), &addr_info.unit));
+            code.append(&mut compile_target(target, addr_info.start + code.len(), &addr_info.unit));
         },
         &SimpleStmt::ReturnStmt(ref expr) => {
-            code.append(&mut compile_expr(expr));
+            code.append(&mut compile_expr(expr, addr_info.start + code.len(), &addr_info.unit));
             code.push(Opcode::ReturnValue);
         },
         &SimpleStmt::BreakStmt => code.push(Opcode::BreakLoop),
+        &SimpleStmt::PassStmt => (),
         &SimpleStmt::ContinueStmt => {
             if let Some(cont) = addr_info.cont {
                 code.push(Opcode::ContinueLoop(cont));
             } else {
-                panic!("continue outside loop block")
+                panic!("Implementation Error: continue outside loop block")
             }
         },
-        &SimpleStmt::RaiseStmt(ref expr) => {
-            code.append(&mut compile_expr(expr));
-            code.push(Opcode::Raise);
+        &SimpleStmt::RaiseStmt(Some(ref expr), None) => {
+            code.append(&mut compile_expr(expr, addr_info.start + code.len(), &addr_info.unit));
+            code.push(Opcode::Raise(1));
         },
+        &SimpleStmt::RaiseStmt(Some(ref expr), Some(ref cause)) => {
+            code.append(&mut compile_expr(expr, addr_info.start + code.len(), &addr_info.unit));
+            code.append(&mut compile_expr(cause, addr_info.start + code.len(), &addr_info.unit));
+            code.push(Opcode::Raise(2));
+        },
+        &SimpleStmt::RaiseStmt(None, _) => code.push(Opcode::Raise(0)),
+        // the symbol table pass has already placed the names
+        &SimpleStmt::GlobalStmt(_) | &SimpleStmt::NonlocalStmt(_) => (),
         &SimpleStmt::AssertStmt(ref expr) => {
-            let mut expr_code = compile_expr(expr);
+            let mut expr_code = compile_expr(expr, addr_info.start, &addr_info.unit);
             let pop_jump_addr = addr_info.start + expr_code.len() + 3;
 
             code.append(&mut expr_code);
             code.push(Opcode::PopJumpIfTrue(pop_jump_addr));
-            code.push(Opcode::LoadName("Exception".to_string()));
-            code.push(Opcode::Raise);
+            code.push(addr_info.unit.load_name(&"AssertionError".to_string()));
+            code.push(Opcode::Raise(1));
+        },
+        &SimpleStmt::DelStmt(ref target) => {
+            match target {
+                &Target::SubscrTarget(ref e1, ref e2) => {
+                    code.append(&mut compile_expr(e1, addr_info.start + code.len(), &addr_info.unit));
+                    code.append(&mut compile_expr(e2, addr_info.start + code.len(), &addr_info.unit));
+                    code.push(Opcode::DeleteSubScr);
+                },
+                _ => panic!("Implementation Error: compile DelStmt")
+            }
         }
     };
     code
 }
 
-fn compile_compound_stmt(stmt: &CompoundStmt, addr_info: AddrInfo) -> Code {
+fn compile_compound_stmt(stmt: &CompoundStmt, line: Line, addr_info: AddrInfo) -> Code {
     let mut code = vec![];
     match stmt {
         &CompoundStmt::IfStmt(ref expr, ref prog_then, ref prog_else) => {
-            let mut expr_code = compile_expr(expr);
+            let mut expr_code = compile_expr(expr, addr_info.start, &addr_info.unit);
             let mut addr = addr_info.start + expr_code.len() + 1;
             let mut then_code = compile_program(prog_then, addr_info.change_start(addr));
             addr += then_code.len() + 1;
@@ -157,12 +644,9 @@ fn compile_compound_stmt(stmt: &CompoundStmt, addr_info: AddrInfo) -> Code {
             code.append(&mut else_code);
         },
         &CompoundStmt::WhileStmt(ref expr, ref prog) => {
-            let mut expr_code = compile_expr(expr);
+            let mut expr_code = compile_expr(expr, addr_info.start + 1, &addr_info.unit);
             let mut addr = addr_info.start + 1 + expr_code.len() + 1;
-            let body_addr_info = AddrInfo {
-                start: addr,
-                cont: Some(addr_info.start + 1),
-            };
+            let body_addr_info = addr_info.change_cont(addr, addr_info.start + 1);
             let mut body_code = compile_program(prog, body_addr_info);
             addr += body_code.len() + 1;
 
@@ -174,15 +658,12 @@ fn compile_compound_stmt(stmt: &CompoundStmt, addr_info: AddrInfo) -> Code {
             code.push(Opcode::PopBlock);
         },
         &CompoundStmt::ForStmt(ref target, ref expr, ref prog) => {
-            let mut expr_code = compile_expr(expr);
+            let mut expr_code = compile_expr(expr, addr_info.start + 1, &addr_info.unit);
             let mut addr = addr_info.start + expr_code.len() + 3;
             let for_iter_addr = addr - 1;
-            let mut target_code = compile_target(target);
+            let mut target_code = compile_target(target, addr, &addr_info.unit);
             addr += target_code.len();
-            let body_addr_info = AddrInfo {
-                start: addr,
-                cont: Some(for_iter_addr),
-            };
+            let body_addr_info = addr_info.change_cont(addr, for_iter_addr);
             let mut body_code = compile_program(prog, body_addr_info);
             addr += body_code.len() + 1;
 
@@ -195,47 +676,130 @@ fn compile_compound_stmt(stmt: &CompoundStmt, addr_info: AddrInfo) -> Code {
             code.push(Opcode::JumpAbsolute(for_iter_addr));
             code.push(Opcode::PopBlock);
         },
-        &CompoundStmt::TryStmt(ref prog_try, ref prog_except) => {
+        &CompoundStmt::TryStmt(ref prog_try, ref handlers, ref prog_else, None) => {
+            code = compile_try_except(prog_try, handlers, prog_else, addr_info);
+        },
+        &CompoundStmt::TryStmt(ref prog_try, ref handlers, ref prog_else, Some(ref prog_finally)) => {
             let mut addr = addr_info.start + 1;
-            let mut try_code = compile_program(prog_try, addr_info.change_start(addr));
-            addr += try_code.len() + 2;
-            let exc_jump_addr = addr;
-            let mut exc_code = compile_program(prog_except, addr_info.change_start(addr));
-            addr += exc_code.len();
-
-            code.push(Opcode::SetupExcept(exc_jump_addr - addr_info.start));
-            code.append(&mut try_code);
+            let mut body_code = if handlers.is_empty() {
+                compile_program(prog_try, addr_info.change_start(addr))
+            } else {
+                compile_try_except(prog_try, handlers, prog_else, addr_info.change_start(addr))
+            };
+            addr += body_code.len() + 2;
+            let finally_addr = addr;
+            let mut finally_code = compile_program(prog_finally, addr_info.change_start(addr));
+
+            code.push(Opcode::SetupFinally(finally_addr - addr_info.start));
+            code.append(&mut body_code);
             code.push(Opcode::PopBlock);
-            code.push(Opcode::JumpAbsolute(addr));
-            code.append(&mut exc_code);
+            code.push(Opcode::LoadConst(PyObject::none_obj()));
+            code.append(&mut finally_code);
+            code.push(Opcode::EndFinally);
         },
         &CompoundStmt::DefStmt(ref id, ref parms, ref prog) => {
-            let body_code = compile(prog);
-            code.push(Opcode::LoadConst(PyObject::pycode_new(body_code, parms.clone())));
-            code.push(Opcode::LoadConst(PyObject::from_string(id.clone())));
-            code.push(Opcode::MakeFunction);
-            code.push(Opcode::StoreName(id.clone()));
+            let body_unit = addr_info.unit.child(parms);
+            let body_code = compile_body(prog, &body_unit);
+            code.append(&mut compile_make_function(parms, &body_unit, body_code, id, line,
+                                                   addr_info.start, &addr_info.unit));
+            code.push(addr_info.unit.store_name(id));
         },
         &CompoundStmt::ClassStmt(ref id, ref bases, ref prog) => {
             for base in bases {
-                code.append(&mut compile_expr(base));
+                code.append(&mut compile_expr(base, addr_info.start + code.len(), &addr_info.unit));
             };
-            let body_code = compile(prog);
-            code.push(Opcode::LoadConst(PyObject::pycode_new(body_code, vec![])));
+            let body_unit = addr_info.unit.child(prog);
+            let body_code = compile_body(prog, &body_unit);
+            let codeobj = body_unit.code_object(body_code, ArgSpec::default(), id, line);
+            code = make_closure(&body_unit, code, codeobj, id, false, false);
             code.push(Opcode::LoadConst(PyObject::from_str(id)));
             code.push(Opcode::MakeClass(bases.len()));
-            code.push(Opcode::StoreName(id.clone()));
+            code.push(addr_info.unit.store_name(id));
         }
     };
     code
 }
 
-fn compile_stmt(stmt: &Stmt, addr_info: AddrInfo) -> Code {
-    match stmt {
-        &Stmt::StmtSimple(ref simple_stmt) => compile_simple_stmt(simple_stmt, addr_info),
-        &Stmt::StmtCompound(ref compound_stmt) =>
-            compile_compound_stmt(compound_stmt, addr_info),
+// The handlers run with the exception on top of the stack. One that does not
+// match jumps to the next, and EndFinally re-raises when none of them does.
+fn compile_try_except(prog_try: &Program, handlers: &Vec<ExceptHandler>, prog_else: &Program,
+                      addr_info: AddrInfo) -> Code {
+    let mut code = vec![];
+    let mut addr = addr_info.start + 1;
+    let mut try_code = compile_program(prog_try, addr_info.change_start(addr));
+    addr += try_code.len() + 2;
+    let handler_addr = addr;
+
+    let mut handlers_code = vec![];
+    let mut end_jumps = vec![];
+    for handler in handlers {
+        let mut next_jump = None;
+        handlers_code.push(Opcode::SetLineno(handler.span.start.line));
+        if let Some(ref typ) = handler.typ {
+            handlers_code.push(Opcode::DupTop);
+            let typ_addr = handler_addr + handlers_code.len();
+            handlers_code.append(&mut compile_expr(typ, typ_addr, &addr_info.unit));
+            handlers_code.push(Opcode::ExcMatch);
+            next_jump = Some(handlers_code.len());
+            handlers_code.push(Opcode::PopJumpIfFalse(0));
+        }
+        match handler.name {
+            // the name is unbound however the body ends, as if by a finally
+            Some(ref id) => {
+                handlers_code.push(addr_info.unit.store_name(id));
+                let setup = handlers_code.len();
+                handlers_code.push(Opcode::SetupFinally(0));
+                let start = handler_addr + handlers_code.len();
+                handlers_code.append(&mut compile_program(&handler.body, addr_info.change_start(start)));
+                handlers_code.push(Opcode::PopBlock);
+                handlers_code.push(Opcode::LoadConst(PyObject::none_obj()));
+                handlers_code[setup] = Opcode::SetupFinally(handlers_code.len() - setup);
+                handlers_code.push(addr_info.unit.delete_name(id));
+                handlers_code.push(Opcode::EndFinally);
+            },
+            None => {
+                handlers_code.push(Opcode::PopTop);
+                let start = handler_addr + handlers_code.len();
+                handlers_code.append(&mut compile_program(&handler.body, addr_info.change_start(start)));
+            },
+        }
+        handlers_code.push(Opcode::PopExcept);
+        end_jumps.push(handlers_code.len());
+        handlers_code.push(Opcode::JumpAbsolute(0));
+        if let Some(i) = next_jump {
+            handlers_code[i] = Opcode::PopJumpIfFalse(handler_addr + handlers_code.len());
+        }
     }
+    handlers_code.push(Opcode::EndFinally);
+    addr += handlers_code.len();
+    let else_addr = addr;
+    let mut else_code = compile_program(prog_else, addr_info.change_start(addr));
+    addr += else_code.len();
+
+    for i in end_jumps {
+        handlers_code[i] = Opcode::JumpAbsolute(addr);
+    }
+
+    code.push(Opcode::SetupExcept(handler_addr - addr_info.start));
+    code.append(&mut try_code);
+    code.push(Opcode::PopBlock);
+    code.push(Opcode::JumpAbsolute(else_addr));
+    code.append(&mut handlers_code);
+    code.append(&mut else_code);
+    code
+}
+
+fn compile_stmt(stmt: &Stmt, addr_info: AddrInfo) -> Code {
+    let (line, mut code) = match stmt {
+        &Stmt::StmtSimple(ref simple_stmt, span) =>
+            (span.start.line, compile_simple_stmt(simple_stmt, addr_info.change_start(addr_info.start + 1))),
+        &Stmt::StmtCompound(ref compound_stmt, span) => {
+            let line = span.start.line;
+            (line, compile_compound_stmt(compound_stmt, line, addr_info.change_start(addr_info.start + 1)))
+        },
+    };
+    code.insert(0, Opcode::SetLineno(line));
+    code
 }
 
 fn compile_program(prog: &Program, addr_info: AddrInfo) -> Code {
@@ -250,9 +814,18 @@ fn compile_program(prog: &Program, addr_info: AddrInfo) -> Code {
     code
 }
 
-pub fn compile(prog: &Program) -> Code {
-    let mut code = compile_program(prog, AddrInfo::new());
+fn compile_body(prog: &Program, unit: &Rc<Unit>) -> Code {
+    let mut code = compile_program(prog, AddrInfo::new(Rc::clone(unit)));
     code.push(Opcode::LoadConst(PyObject::none_obj()));
     code.push(Opcode::ReturnValue);
     code
 }
+
+/// Compiles a module, given the symbol table of its blocks.
+pub fn compile(prog: &Program, filename: &str, symtable: Rc<SymbolTable>) -> Code {
+    let unit = Unit {
+        filename: Rc::new(filename.to_string()),
+        symtable: symtable,
+    };
+    compile_body(prog, &Rc::new(unit))
+}
@@ -2,17 +2,22 @@ use std::collections::HashMap;
 use std::cell::RefCell;
 use std::rc::Rc;
 
+use eval::PyRes;
 use object::PyObject;
 use syntax::Id;
 
+/// The names of a module, class body or function call. A call's parent is
+/// the module the function was defined in, whose names are its globals;
+/// the variables it shares with nested functions are kept in `cells`.
 pub struct Env {
     parent: Option<Rc<Env>>,
     map: RefCell<HashMap<Id, Rc<PyObject>>>,
+    cells: RefCell<HashMap<Id, Rc<PyObject>>>,
 }
 
 impl Env {
     pub fn new() -> Env {
-        Env { parent: None, map: RefCell::new(HashMap::new()) }
+        Env { parent: None, map: RefCell::new(HashMap::new()), cells: RefCell::new(HashMap::new()) }
     }
 
     pub fn new_child(parent: &Rc<Env>, keys: &Vec<Id>, vals: &Vec<Rc<PyObject>>) -> Env {
@@ -23,15 +28,15 @@ impl Env {
         for (k, v) in keys.iter().zip(vals.iter()) {
             map.insert(k.clone(), Rc::clone(v));
         }
-        Env { parent: Some(Rc::clone(parent)), map: RefCell::new(map) }
+        Env { parent: Some(Rc::clone(parent)), map: RefCell::new(map), cells: RefCell::new(HashMap::new()) }
     }
 
-    pub fn get(self: &Rc<Env>, key: &Id) -> Rc<PyObject> {
+    pub fn get(self: &Rc<Env>, key: &Id) -> Option<Rc<PyObject>> {
         match self.map.borrow().get(key) {
-            Some(ref v) => Rc::clone(v),
+            Some(ref v) => Some(Rc::clone(v)),
             None => match self.parent {
                 Some(ref parent) => Rc::clone(parent).get(key),
-                None => panic!("Unbound variable: {}", key),
+                None => None,
             }
         }
     }
@@ -40,12 +45,37 @@ impl Env {
         self.map.borrow_mut().insert(key, val);
     }
 
-    pub fn dictobj(self: &Rc<Env>) -> Rc<PyObject> {
+    pub fn remove(self: &Rc<Env>, key: &Id) -> () {
+        self.map.borrow_mut().remove(key);
+    }
+
+    /// Looks `key` up in this environment alone.
+    pub fn get_local(self: &Rc<Env>, key: &Id) -> Option<Rc<PyObject>> {
+        self.map.borrow().get(key).cloned()
+    }
+
+    /// The environment of the module, at the root of the chain.
+    pub fn globals(self: &Rc<Env>) -> Rc<Env> {
+        match self.parent {
+            Some(ref parent) => parent.globals(),
+            None => Rc::clone(self),
+        }
+    }
+
+    pub fn cell(self: &Rc<Env>, key: &Id) -> Option<Rc<PyObject>> {
+        self.cells.borrow().get(key).cloned()
+    }
+
+    pub fn set_cell(self: &Rc<Env>, key: Id, cell: Rc<PyObject>) -> () {
+        self.cells.borrow_mut().insert(key, cell);
+    }
+
+    pub fn dictobj(self: &Rc<Env>) -> PyRes<Rc<PyObject>> {
         let dictobj = PyObject::pydict_new();
         for (k, v) in self.map.borrow().iter() {
             let key = PyObject::from_string(k.clone());
-            dictobj.pydict_update(key, Rc::clone(v));
+            dictobj.pydict_update(key, Rc::clone(v))?;
         }
-        dictobj
+        Ok(dictobj)
     }
 }
@@ -1,11 +1,16 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
+use syntax::{SyntaxError, SyntaxErrorKind};
 use object::PyObject;
+use object::excobj::*;
 use object::generic::*;
+use object::tracebackobj::*;
 
 thread_local! (
     pub static EXC_INDICATOR: RefCell<Option<Rc<PyObject>>> = RefCell::new(None);
+    // the exception being handled by the innermost active except clause
+    pub static EXC_INFO: RefCell<Option<Rc<PyObject>>> = RefCell::new(None);
 );
 
 pub fn pyerr_occurred() -> bool {
@@ -21,7 +26,28 @@ pub fn pyerr_check(exception: Rc<PyObject>) -> bool {
     })
 }
 
+/// Raises `err`. An exception raised while another is being handled gets
+/// the handled one as its `__context__`.
 pub fn pyerr_set(err: Rc<PyObject>) {
+    if let Some(handled) = pyerr_get_exc_info() {
+        if !Rc::ptr_eq(&handled, &err) {
+            // cut `err` out of the handled chain so that it cannot loop
+            let mut exc = Rc::clone(&handled);
+            while let Some(context) = exc.pyexc_context() {
+                if Rc::ptr_eq(&context, &err) {
+                    exc.pyexc_set_context(None);
+                    break;
+                }
+                exc = context;
+            }
+            err.pyexc_set_context(Some(handled));
+        }
+    }
+    pyerr_restore(err);
+}
+
+/// Puts back an exception taken by `pyerr_fetch` without chaining it.
+pub fn pyerr_restore(err: Rc<PyObject>) {
     EXC_INDICATOR.with(|ind| { ind.replace(Some(err)) });
 }
 
@@ -34,10 +60,111 @@ pub fn pyerr_set_string(exception: Rc<PyObject>, s: &str) {
         Ok(err) => {
             pyerr_set(err);
         },
-        Err(_) => {} // エラーを入れ子にする仕組みが必要
+        Err(_) => {} // the constructor left its own error set
     }
 }
 
+/// Raises MemoryError, which carries no message.
+pub fn pyerr_no_memory() {
+    if let Ok(exc) = call_func(PY_MEMORYERROR_TYPE.with(|tp| Rc::clone(tp)), &vec![]) {
+        pyerr_set(exc);
+    }
+}
+
+/// Raises StopIteration for an iterator that returned `value`. A None
+/// value leaves the exception without arguments.
+pub fn pyerr_set_stop_iteration(value: Rc<PyObject>) {
+    let args = if value.pynone_check() { vec![] } else { vec![value] };
+    if let Ok(exc) = call_func(PY_STOPITERATION_TYPE.with(|tp| Rc::clone(tp)), &args) {
+        pyerr_set(exc);
+    }
+}
+
+/// Raises `err` as a `SyntaxError` carrying the location CPython exposes:
+/// `msg`, `filename`, `lineno`, `offset` and the source line as `text`.
+pub fn pyerr_set_syntax_error(err: &SyntaxError, filename: &str, source: &str) {
+    let exctp = match err.kind {
+        SyntaxErrorKind::Syntax => PY_SYNTAXERROR_TYPE.with(|tp| Rc::clone(tp)),
+        SyntaxErrorKind::Indentation => PY_INDENTATIONERROR_TYPE.with(|tp| Rc::clone(tp)),
+        SyntaxErrorKind::Tab => PY_TABERROR_TYPE.with(|tp| Rc::clone(tp)),
+    };
+    let exc = match call_func(exctp, &vec![PyObject::from_str(&err.msg)]) {
+        Ok(exc) => exc,
+        Err(_) => return,
+    };
+    let text = match err.pos.line.checked_sub(1).and_then(|i| source.lines().nth(i)) {
+        Some(line) => PyObject::from_str(line),
+        None => PyObject::none_obj(),
+    };
+    let attrs = vec![
+        ("msg", PyObject::from_str(&err.msg)),
+        ("filename", PyObject::from_str(filename)),
+        ("lineno", PyObject::from_i32(err.pos.line as i32)),
+        ("offset", PyObject::from_i32(err.pos.col as i32)),
+        ("text", text),
+    ];
+    for (name, value) in attrs {
+        if pyobj_set_attr(Rc::clone(&exc), PyObject::from_str(name), value).is_err() {
+            return;
+        }
+    }
+    pyerr_set(exc);
+}
+
 pub fn pyerr_clear() {
     EXC_INDICATOR.with(|ind| { ind.replace(None) });
 }
+
+pub fn pyerr_fetch() -> Option<Rc<PyObject>> {
+    EXC_INDICATOR.with(|ind| { ind.replace(None) })
+}
+
+pub fn pyerr_get_exc_info() -> Option<Rc<PyObject>> {
+    EXC_INFO.with(|info| { info.borrow().clone() })
+}
+
+pub fn pyerr_set_exc_info(exc: Option<Rc<PyObject>>) {
+    EXC_INFO.with(|info| { info.replace(exc) });
+}
+
+fn format_exception(exc: &Rc<PyObject>, filename: &str, source: &str,
+                    seen: &mut Vec<Rc<PyObject>>) -> String {
+    seen.push(Rc::clone(exc));
+    let mut res = String::new();
+
+    let unseen = |e: &Rc<PyObject>, seen: &Vec<Rc<PyObject>>| !seen.iter().any(|s| Rc::ptr_eq(s, e));
+    if let Some(cause) = exc.pyexc_cause() {
+        if unseen(&cause, seen) {
+            res.push_str(&format_exception(&cause, filename, source, seen));
+            res.push_str("\nThe above exception was the direct cause of the following exception:\n\n");
+        }
+    } else if let Some(context) = exc.pyexc_context() {
+        if !exc.pyexc_suppress_context() && unseen(&context, seen) {
+            res.push_str(&format_exception(&context, filename, source, seen));
+            res.push_str("\nDuring handling of the above exception, another exception occurred:\n\n");
+        }
+    }
+
+    if let Some(tb) = exc.pyexc_traceback() {
+        res.push_str(&pytraceback_format(&tb, filename, source));
+    }
+    let name = exc.ob_type().pytype_typeobj_borrow().tp_name.clone();
+    match Rc::clone(exc).pyexc_message() {
+        Ok(ref msg) if msg.is_empty() => res.push_str(&format!("{}\n", name)),
+        Ok(msg) => res.push_str(&format!("{}: {}\n", name, msg)),
+        Err(_) => {
+            pyerr_clear();
+            res.push_str(&format!("{}: <exception str() failed>\n", name))
+        },
+    }
+    res
+}
+
+/// Prints the pending exception and its chain to stderr the way CPython
+/// reports an uncaught exception. The exception stays set.
+pub fn pyerr_print(filename: &str, source: &str) {
+    if let Some(exc) = pyerr_fetch() {
+        eprint!("{}", format_exception(&exc, filename, source, &mut vec![]));
+        pyerr_restore(exc);
+    }
+}
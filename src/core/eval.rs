@@ -1,17 +1,21 @@
+use std::cell::Cell;
 use std::rc::Rc;
 
 use opcode::*;
+use syntax::{CompOp, Conversion};
 use env::*;
 use error::*;
 
 use object::*;
 use object::excobj::*;
 use object::generic::*;
+use object::genobj::*;
+use object::tracebackobj::*;
 use object::typeobj::*;
 
 pub type PyRes<T> = Result<T, ()>;
 
-#[derive(PartialEq)]
+#[derive(Clone, Copy, PartialEq)]
 enum Why {
     WhyNot,
     WhyReturn,
@@ -20,10 +24,25 @@ enum Why {
     WhyException,
 }
 
+impl Why {
+    // a finally block entered by return, break or continue keeps the reason
+    // on the stack as an int until EndFinally resumes it
+    fn from_code(code: i32) -> Why {
+        match code {
+            1 => Why::WhyReturn,
+            2 => Why::WhyBreak,
+            3 => Why::WhyContinue,
+            _ => panic!("Implementation Error: Why::from_code"),
+        }
+    }
+}
+
 #[derive(PartialEq)]
 enum BlockType {
     LoopBlock,
     TryBlock,
+    FinallyBlock,
+    ExceptHandlerBlock,
 }
 
 struct Block {
@@ -32,21 +51,33 @@ struct Block {
     b_level: usize,
 }
 
-struct StackMachine {
+/// The state of a running frame, kept by a generator between its yields.
+pub struct StackMachine {
     pc: usize,
     stack: Vec<Rc<PyObject>>,
     blocks: Vec<Block>,
+    // whether exec last stopped at a yield rather than a return
+    yielded: bool,
+    // whether exec should raise the pending exception before running on
+    throwing: bool,
 }
 
 impl StackMachine {
-    fn new() -> StackMachine {
+    pub fn new() -> StackMachine {
         StackMachine {
             pc: 0,
             stack: vec![],
             blocks: vec![],
+            yielded: false,
+            throwing: false,
         }
     }
 
+    /// Whether the frame has run at all.
+    pub fn started(&self) -> bool {
+        self.pc > 0
+    }
+
     fn top(&self) -> Rc<PyObject> {
         Rc::clone(self.stack.last().expect("Top"))
     }
@@ -73,126 +104,637 @@ impl StackMachine {
         }
     }
 
-    fn exec(&mut self, code: &Code, env: Rc<Env>) -> PyRes<Rc<PyObject>> {
+    // Leaves an except clause: drops what the clause left on the stack and
+    // restores the exception that was being handled before it.
+    fn unwind_except_handler(&mut self, block: Block) {
+        self.unwind_stack(block.b_level + 1);
+        let prev = self.pop();
+        if prev.pynone_check() {
+            pyerr_set_exc_info(None);
+        } else {
+            pyerr_set_exc_info(Some(prev));
+        }
+    }
+
+    fn exec(&mut self, codeobj: &Rc<PyObject>, env: Rc<Env>) -> PyRes<Rc<PyObject>> {
+        let code = codeobj.pycode_code();
         let mut retval = Err(());
-        let mut why = Why::WhyNot;
+        let mut why;
 
         while let Some(op) = code.get(self.pc) {
+            // re-raising keeps the traceback the exception already has
+            let mut reraise = false;
             match op {
+                // an exception thrown into a generator is raised where it stopped
+                _ if self.throwing => {
+                    self.throwing = false;
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::SetLineno(_) => {
+                    self.pc += 1;
+                    continue;
+                },
                 &Opcode::PopTop => {
                     self.pop();
                     self.pc += 1;
                     continue;
                 },
-                &Opcode::LoadConst(ref cnst) => {
-                    self.push(Rc::clone(cnst));
+                &Opcode::RotTwo => {
+                    let top = self.pop();
+                    let second = self.pop();
+                    self.push(top);
+                    self.push(second);
+                    self.pc += 1;
+                    continue;
+                },
+                &Opcode::RotThree => {
+                    let top = self.pop();
+                    let second = self.pop();
+                    let third = self.pop();
+                    self.push(top);
+                    self.push(third);
+                    self.push(second);
+                    self.pc += 1;
+                    continue;
+                },
+                &Opcode::DupTop => {
+                    let top = self.top();
+                    self.push(top);
+                    self.pc += 1;
+                    continue;
+                },
+                &Opcode::LoadConst(ref cnst) => {
+                    self.push(Rc::clone(cnst));
+                    self.pc += 1;
+                    continue;
+                },
+                &Opcode::LoadName(ref id) => {
+                    if let Some(v) = env.get(id) {
+                        self.push(v);
+                        self.pc += 1;
+                        continue;
+                    }
+                    pyerr_set_string(PY_NAMEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                                     &format!("name '{}' is not defined", id));
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::StoreName(ref id) => {
+                    let top = self.pop();
+                    env.update(id.clone(), top);
+                    self.pc += 1;
+                    continue;
+                },
+                &Opcode::LoadFast(ref id) => {
+                    if let Some(v) = env.get_local(id) {
+                        self.push(v);
+                        self.pc += 1;
+                        continue;
+                    }
+                    pyerr_set_string(PY_UNBOUNDLOCALERROR_TYPE.with(|tp| Rc::clone(tp)),
+                                     &format!("local variable '{}' referenced before assignment", id));
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::StoreFast(ref id) => {
+                    let top = self.pop();
+                    env.update(id.clone(), top);
+                    self.pc += 1;
+                    continue;
+                },
+                &Opcode::LoadGlobal(ref id) => {
+                    if let Some(v) = env.globals().get_local(id) {
+                        self.push(v);
+                        self.pc += 1;
+                        continue;
+                    }
+                    pyerr_set_string(PY_NAMEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                                     &format!("name '{}' is not defined", id));
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::StoreGlobal(ref id) => {
+                    let top = self.pop();
+                    env.globals().update(id.clone(), top);
+                    self.pc += 1;
+                    continue;
+                },
+                &Opcode::LoadDeref(ref id) => {
+                    let cell = env.cell(id).expect("Implementation Error: LoadDeref");
+                    if let Some(v) = cell.pycell_get() {
+                        self.push(v);
+                        self.pc += 1;
+                        continue;
+                    }
+                    if codeobj.pycode_cellvars().contains(id) {
+                        pyerr_set_string(PY_UNBOUNDLOCALERROR_TYPE.with(|tp| Rc::clone(tp)),
+                                         &format!("local variable '{}' referenced before assignment", id));
+                    } else {
+                        pyerr_set_string(PY_NAMEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                                         &format!("free variable '{}' referenced before assignment in enclosing scope", id));
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::StoreDeref(ref id) => {
+                    let top = self.pop();
+                    env.cell(id).expect("Implementation Error: StoreDeref").pycell_set(top);
+                    self.pc += 1;
+                    continue;
+                },
+                &Opcode::LoadClosure(ref id) => {
+                    self.push(env.cell(id).expect("Implementation Error: LoadClosure"));
+                    self.pc += 1;
+                    continue;
+                },
+                &Opcode::DeleteName(ref id) | &Opcode::DeleteFast(ref id) => {
+                    env.remove(id);
+                    self.pc += 1;
+                    continue;
+                },
+                &Opcode::DeleteGlobal(ref id) => {
+                    env.globals().remove(id);
+                    self.pc += 1;
+                    continue;
+                },
+                &Opcode::DeleteDeref(ref id) => {
+                    env.cell(id).expect("Implementation Error: DeleteDeref").pycell_clear();
+                    self.pc += 1;
+                    continue;
+                },
+                &Opcode::BinaryAdd => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    let res = pyobj_binop(left, right, "+", |typ| typ.tp_fun_add.clone(),
+                                          |typ| typ.tp_fun_radd.clone());
+                    if res.is_ok() {
+                        self.push(res.expect("Never fails"));
+                        self.pc += 1;
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::BinarySub => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    let res = pyobj_binop(left, right, "-", |typ| typ.tp_fun_sub.clone(),
+                                          |typ| typ.tp_fun_rsub.clone());
+                    if res.is_ok() {
+                        self.push(res.expect("Never fails"));
+                        self.pc += 1;
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::BinaryMul => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    let res = pyobj_binop(left, right, "*", |typ| typ.tp_fun_mul.clone(),
+                                          |typ| typ.tp_fun_rmul.clone());
+                    if res.is_ok() {
+                        self.push(res.expect("Never fails"));
+                        self.pc += 1;
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::BinaryTrueDiv => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    let res = pyobj_binop(left, right, "/", |typ| typ.tp_fun_truediv.clone(),
+                                          |typ| typ.tp_fun_rtruediv.clone());
+                    if res.is_ok() {
+                        self.push(res.expect("Never fails"));
+                        self.pc += 1;
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::BinaryFloorDiv => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    let res = pyobj_binop(left, right, "//", |typ| typ.tp_fun_floordiv.clone(),
+                                          |typ| typ.tp_fun_rfloordiv.clone());
+                    if res.is_ok() {
+                        self.push(res.expect("Never fails"));
+                        self.pc += 1;
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::BinaryMod => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    let res = pyobj_binop(left, right, "%", |typ| typ.tp_fun_mod.clone(),
+                                          |typ| typ.tp_fun_rmod.clone());
+                    if res.is_ok() {
+                        self.push(res.expect("Never fails"));
+                        self.pc += 1;
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::BinaryPow => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    let res = pyobj_binop(left, right, "** or pow()", |typ| typ.tp_fun_pow.clone(),
+                                          |typ| typ.tp_fun_rpow.clone());
+                    if res.is_ok() {
+                        self.push(res.expect("Never fails"));
+                        self.pc += 1;
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::UnaryNeg => {
+                    let v = self.pop();
+                    let res = pyobj_unaryop(v, "__neg__", |typ| typ.tp_fun_neg.clone());
+                    if res.is_ok() {
+                        self.push(res.expect("Never fails"));
+                        self.pc += 1;
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::UnaryPos => {
+                    let v = self.pop();
+                    let res = pyobj_unaryop(v, "__pos__", |typ| typ.tp_fun_pos.clone());
+                    if res.is_ok() {
+                        self.push(res.expect("Never fails"));
+                        self.pc += 1;
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::UnaryNot => {
+                    let v = self.pop();
+                    let b = pyobj_to_bool(v);
+                    if b.is_ok() {
+                        self.push(PyObject::from_bool(!b.expect("Never fails")));
+                        self.pc += 1;
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::BinaryLt => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    let res = pyobj_richcompare(left, right, CompOp::Lt);
+                    if res.is_ok() {
+                        self.push(res.expect("Never fails"));
+                        self.pc += 1;
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::BinaryEq => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    let res = pyobj_richcompare(left, right, CompOp::EqEq);
+                    if res.is_ok() {
+                        self.push(res.expect("Never fails"));
+                        self.pc += 1;
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::BinaryNotEq => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    let res = pyobj_richcompare(left, right, CompOp::NotEq);
+                    if res.is_ok() {
+                        self.push(res.expect("Never fails"));
+                        self.pc += 1;
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::BinaryLtE => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    let res = pyobj_richcompare(left, right, CompOp::LtE);
+                    if res.is_ok() {
+                        self.push(res.expect("Never fails"));
+                        self.pc += 1;
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::BinaryGt => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    let res = pyobj_richcompare(left, right, CompOp::Gt);
+                    if res.is_ok() {
+                        self.push(res.expect("Never fails"));
+                        self.pc += 1;
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::BinaryGtE => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    let res = pyobj_richcompare(left, right, CompOp::GtE);
+                    if res.is_ok() {
+                        self.push(res.expect("Never fails"));
+                        self.pc += 1;
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::BinaryIn => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    let res = pyobj_contains(right, left);
+                    if res.is_ok() {
+                        self.push(PyObject::from_bool(res.expect("Never fails")));
+                        self.pc += 1;
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::BinaryNotIn => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    let res = pyobj_contains(right, left);
+                    if res.is_ok() {
+                        self.push(PyObject::from_bool(!res.expect("Never fails")));
+                        self.pc += 1;
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::BinaryIs => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    self.push(PyObject::from_bool(left == right));
+                    self.pc += 1;
+                    continue;
+                },
+                &Opcode::BinaryIsNot => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    self.push(PyObject::from_bool(left != right));
+                    self.pc += 1;
+                    continue;
+                },
+                &Opcode::MakeFunction(has_defaults, has_kwdefaults, has_closure) => {
+                    self.pop();  // qualname
+                    let codeobj = self.pop();
+                    let closure = if has_closure { self.pop().pytuple_items() } else { vec![] };
+                    let kwdefaults = if has_kwdefaults { Some(self.pop()) } else { None };
+                    let defaults = if has_defaults { self.pop().pytuple_items() } else { vec![] };
+                    self.push(PyObject::pyfun_new(&env.globals(), codeobj, defaults, kwdefaults, closure));
+                    self.pc += 1;
+                    continue;
+                },
+                &Opcode::CallFunction(argcnt) => {
+                    let args = self.pop_as_vec(argcnt);
+                    let fun = self.pop();
+                    let res = call_func(fun, &args);
+                    if res.is_ok() {
+                        self.push(res.expect("Never fails"));
+                        self.pc += 1;
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::CallFunctionKw(argcnt) => {
+                    let names = self.pop().pytuple_items();
+                    let mut args = self.pop_as_vec(argcnt);
+                    let fun = self.pop();
+                    let kwvals = args.split_off(argcnt - names.len());
+                    let kwargs = PyObject::pydict_new();
+                    for (name, value) in names.into_iter().zip(kwvals.into_iter()) {
+                        kwargs.pydict_update(name, value).expect("Implementation Error: CallFunctionKw");
+                    }
+                    let res = call_func_kw(fun, &args, Some(kwargs));
+                    if res.is_ok() {
+                        self.push(res.expect("Never fails"));
+                        self.pc += 1;
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::CallFunctionEx(has_kwargs) => {
+                    let kwargs = if has_kwargs { Some(self.pop()) } else { None };
+                    let args = self.pop().pytuple_items();
+                    let fun = self.pop();
+                    let res = call_func_kw(fun, &args, kwargs);
+                    if res.is_ok() {
+                        self.push(res.expect("Never fails"));
+                        self.pc += 1;
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::ReturnValue => {
+                    retval = Ok(self.pop());
+                    why = Why::WhyReturn;
+                },
+                &Opcode::YieldValue => {
+                    // the stack and blocks stay as they are until the
+                    // generator resumes
+                    self.yielded = true;
+                    self.pc += 1;
+                    return Ok(self.pop());
+                },
+                &Opcode::YieldFrom => {
+                    let v = self.pop();
+                    let iter = self.top();
+                    match pyiter_send(iter, v) {
+                        // the pc stays here so that the next value sent goes
+                        // on to the iterator too
+                        Ok(Resumed::Yield(v)) => {
+                            self.yielded = true;
+                            return Ok(v);
+                        },
+                        Ok(Resumed::Return(v)) => {
+                            self.pop();
+                            self.push(v);
+                            self.pc += 1;
+                            continue;
+                        },
+                        Err(()) => {},
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::LoadAttr(ref id) => {
+                    let v = self.pop();
+                    let attr = PyObject::from_string(id.clone());
+                    let res = pyobj_get_attr(v, attr);
+                    if res.is_ok() {
+                        self.push(res.expect("Never fails"));
+                        self.pc += 1;
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::StoreAttr(ref id) => {
+                    let lv = self.pop();
+                    let rv = self.pop();
+                    let attr = PyObject::from_string(id.clone());
+                    let res = pyobj_set_attr(lv, attr, rv);
+                    if res.is_ok() {
+                        self.pc += 1;
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::BinarySubScr => {
+                    let v2 = self.pop();
+                    let v1 = self.pop();
+                    let res = pyobj_getitem(v1, v2);
+                    if res.is_ok() {
+                        self.push(res.expect("Never fails"));
+                        self.pc += 1;
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::StoreSubScr => {
+                    let v2 = self.pop();
+                    let v1 = self.pop();
+                    let rv = self.pop();
+                    let res = pyobj_setitem(v1, v2, rv);
+                    if res.is_ok() {
+                        self.pc += 1;
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::DeleteSubScr => {
+                    let v2 = self.pop();
+                    let v1 = self.pop();
+                    let res = pyobj_delitem(v1, v2);
+                    if res.is_ok() {
+                        self.pc += 1;
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::BuildSlice(argc) => {
+                    let step = if argc == 3 { self.pop() } else { PyObject::none_obj() };
+                    let stop = self.pop();
+                    let start = self.pop();
+                    self.push(PyObject::pyslice_new(start, stop, step));
                     self.pc += 1;
                     continue;
                 },
-                &Opcode::LoadName(ref id) => {
-                    self.push(env.get(id));
+                &Opcode::BuildList(len) => {
+                    let vs = self.pop_as_vec(len);
+                    self.push(PyObject::pylist_from_vec(&vs));
                     self.pc += 1;
                     continue;
                 },
-                &Opcode::StoreName(ref id) => {
-                    let top = self.pop();
-                    env.update(id.clone(), top);
+                &Opcode::BuildTuple(len) => {
+                    let vs = self.pop_as_vec(len);
+                    self.push(PyObject::pytuple_from_vec(vs));
                     self.pc += 1;
                     continue;
                 },
-                &Opcode::BinaryAdd => {
-                    let right = self.pop();
-                    let left = self.pop();
-                    let ob_type = left.ob_type();
-                    let typ = ob_type.pytype_typeobj_borrow();
-                    match typ.tp_fun_add {
-                        Some(ref fun) => {
-                            let res = fun(left, right);
-                            if res.is_ok() {
-                                self.push(res.expect("Never fails"));
-                                self.pc += 1;
-                                continue;
-                            }
-                        },
-                        None => {
-                            pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
-                                             "no __add__ operation");
-                        }
+                &Opcode::ListAppend(depth) => {
+                    let v = self.pop();
+                    let list = Rc::clone(&self.stack[self.stack.len() - depth]);
+                    let res = list.pylist_append(v);
+                    if res.is_ok() {
+                        self.pc += 1;
+                        continue;
                     }
                     why = Why::WhyException;
                     retval = Err(());
                 },
-                &Opcode::BinaryLt => {
-                    let right = self.pop();
-                    let left = self.pop();
-                    let ob_type = left.ob_type();
-                    let typ = ob_type.pytype_typeobj_borrow();
-                    match typ.tp_fun_lt {
-                        Some(ref fun) => {
-                            let res = fun(left, right);
-                            if res.is_ok() {
-                                self.push(res.expect("Never fails"));
-                                self.pc += 1;
-                                continue;
-                            }
-                        },
-                        None => {
-                            pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
-                                             "no __lt__ operation");
+                &Opcode::ListExtend => {
+                    let v = self.pop();
+                    let list = self.top();
+                    let res = pyobj_to_vec(v).and_then(|items| {
+                        for item in items {
+                            Rc::clone(&list).pylist_append(item)?;
                         }
+                        Ok(())
+                    });
+                    if res.is_ok() {
+                        self.pc += 1;
+                        continue;
                     }
                     why = Why::WhyException;
                     retval = Err(());
                 },
-                &Opcode::BinaryEq => {
-                    let right = self.pop();
-                    let left = self.pop();
-                    let ob_type = left.ob_type();
-                    let typ = ob_type.pytype_typeobj_borrow();
-                    match typ.tp_fun_eq {
-                        Some(ref fun) => {
-                            let res = fun(left, right);
-                            if res.is_ok() {
-                                self.push(res.expect("Never fails"));
-                                self.pc += 1;
-                                continue;
-                            }
-                        },
-                        None => {
-                            pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
-                                             "no __eq__ operation");
-                        }
+                &Opcode::ListToTuple => {
+                    let list = self.pop();
+                    self.push(PyObject::pytuple_from_vec(list.pylist_clone()));
+                    self.pc += 1;
+                    continue;
+                },
+                &Opcode::UnpackSequence(count) => {
+                    let v = self.pop();
+                    let res = unpack_iterable(v, count, None);
+                    if res.is_ok() {
+                        self.stack.extend(res.expect("Never fails").into_iter().rev());
+                        self.pc += 1;
+                        continue;
                     }
                     why = Why::WhyException;
                     retval = Err(());
                 },
-                &Opcode::MakeFunction => {
-                    self.pop();  // qualname
-                    let codeobj = self.pop();
-                    self.push(PyObject::pyfun_new(&Rc::clone(&env), codeobj));
-                    self.pc += 1;
-                    continue;
-                },
-                &Opcode::CallFunction(argcnt) => {
-                    let args = self.pop_as_vec(argcnt);
-                    let fun = self.pop();
-                    let res = call_func(fun, &args);
+                &Opcode::UnpackEx(before, after) => {
+                    let v = self.pop();
+                    let res = unpack_iterable(v, before + after, Some(before));
                     if res.is_ok() {
-                        self.push(res.expect("Never fails"));
+                        self.stack.extend(res.expect("Never fails").into_iter().rev());
                         self.pc += 1;
                         continue;
                     }
                     why = Why::WhyException;
                     retval = Err(());
                 },
-                &Opcode::ReturnValue => {
-                    retval = Ok(self.pop());
-                    why = Why::WhyReturn;
+                &Opcode::BuildString(len) => {
+                    let vs = self.pop_as_vec(len);
+                    let mut s = String::new();
+                    for v in vs {
+                        s.push_str(&pyobj_to_string(v).expect("Implementation Error: BuildString"));
+                    }
+                    self.push(PyObject::from_string(s));
+                    self.pc += 1;
+                    continue;
                 },
-                &Opcode::LoadAttr(ref id) => {
+                &Opcode::FormatValue(conversion, has_spec) => {
+                    let spec = if has_spec { self.pop() } else { PyObject::from_str("") };
                     let v = self.pop();
-                    let attr = PyObject::from_string(id.clone());
-                    let res = pyobj_get_attr(v, attr);
+                    let res = match conversion {
+                        Some(Conversion::Str) => pyobj_str(v),
+                        Some(Conversion::Repr) => pyobj_repr(v),
+                        Some(Conversion::Ascii) => pyobj_ascii(v),
+                        None => Ok(v),
+                    }.and_then(|v| pyobj_format(v, spec));
                     if res.is_ok() {
                         self.push(res.expect("Never fails"));
                         self.pc += 1;
@@ -201,63 +743,37 @@ impl StackMachine {
                     why = Why::WhyException;
                     retval = Err(());
                 },
-                &Opcode::StoreAttr(ref id) => {
-                    let lv = self.pop();
-                    let rv = self.pop();
-                    let attr = PyObject::from_string(id.clone());
-                    let res = pyobj_set_attr(lv, attr, rv);
+                &Opcode::BuildMap(len) => {
+                    let dictobj = PyObject::pydict_new();
+                    let vs = self.pop_as_vec(len * 2);
+                    let res = (0..len).map(|i| {
+                        dictobj.pydict_update(Rc::clone(&vs[i*2]), Rc::clone(&vs[i*2+1]))
+                    }).collect::<PyRes<Vec<()>>>();
                     if res.is_ok() {
+                        self.push(dictobj);
                         self.pc += 1;
                         continue;
                     }
                     why = Why::WhyException;
                     retval = Err(());
                 },
-                &Opcode::BinarySubScr => {
-                    let v2 = self.pop();
-                    let v1 = self.pop();
-
-                    if v1.pylist_check() {
-                        let index = pyobj_to_i32(v2);
-                        if index.is_ok() {
-                            let res = v1.pylist_getitem(index.expect("Never fails") as usize);
-                            if res.is_ok() {
-                                self.push(res.expect("Never fails"));
-                                self.pc += 1;
-                                continue;
-                            }
-                        }
-                    } else if v1.pydict_check() {
-                        let res = v1.pydict_lookup(v2);
-                        if res.is_ok() {
-                            match res.expect("Never fails") {
-                                Some(res) => {
-                                    self.push(res);
-                                    self.pc += 1;
-                                    continue;
-                                },
-                                None => {
-                                    pyerr_set_string(
-                                        PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
-                                        "item not found");
-                                }
-                            }
-                        }
-                    } else {
-                        pyerr_set_string(
-                            PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
-                            "subscripting not supported");
-                    };
-
+                &Opcode::DictMerge => {
+                    let other = self.pop();
+                    // below the dict are the positional arguments and the function
+                    let fun = Rc::clone(&self.stack[self.stack.len() - 3]);
+                    let res = dict_merge_kwargs(self.top(), other, &fun);
+                    if res.is_ok() {
+                        self.pc += 1;
+                        continue;
+                    }
                     why = Why::WhyException;
                     retval = Err(());
                 },
-                &Opcode::StoreSubScr => {
-                    let v2 = self.pop();
-                    let v1 = self.pop();
-                    let rv = self.pop();
-                    let res = v1.pydict_update(v2, rv);
-
+                &Opcode::MapAdd(depth) => {
+                    let value = self.pop();
+                    let key = self.pop();
+                    let dict = Rc::clone(&self.stack[self.stack.len() - depth]);
+                    let res = dict.pydict_update(key, value);
                     if res.is_ok() {
                         self.pc += 1;
                         continue;
@@ -265,21 +781,28 @@ impl StackMachine {
                     why = Why::WhyException;
                     retval = Err(());
                 },
-                &Opcode::BuildList(len) => {
+                &Opcode::BuildSet(len) => {
+                    let setobj = PyObject::pyset_new();
                     let vs = self.pop_as_vec(len);
-                    self.push(PyObject::pylist_from_vec(&vs));
-                    self.pc += 1;
-                    continue;
+                    let res = vs.into_iter().map(|v| setobj.pyset_add(v)).collect::<PyRes<Vec<()>>>();
+                    if res.is_ok() {
+                        self.push(setobj);
+                        self.pc += 1;
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
                 },
-                &Opcode::BuildMap(len) => {
-                    let mut dictobj = PyObject::pydict_new();
-                    let vs = self.pop_as_vec(len * 2);
-                    for i in 0..len {
-                        dictobj.pydict_update(Rc::clone(&vs[i*2]), Rc::clone(&vs[i*2+1]));
-                    };
-                    self.push(dictobj);
-                    self.pc += 1;
-                    continue;
+                &Opcode::SetAdd(depth) => {
+                    let v = self.pop();
+                    let setobj = Rc::clone(&self.stack[self.stack.len() - depth]);
+                    let res = setobj.pyset_add(v);
+                    if res.is_ok() {
+                        self.pc += 1;
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
                 },
                 &Opcode::PopJumpIfTrue(addr) => {
                     let cond = self.pop();
@@ -309,6 +832,36 @@ impl StackMachine {
                     why = Why::WhyException;
                     retval = Err(());
                 },
+                &Opcode::JumpIfFalseOrPop(addr) => {
+                    let cond = self.top();
+                    let b = pyobj_to_bool(cond);
+                    if b.is_ok() {
+                        if b.expect("Never fails") {
+                            self.pop();
+                            self.pc += 1;
+                        } else {
+                            self.pc = addr;
+                        };
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::JumpIfTrueOrPop(addr) => {
+                    let cond = self.top();
+                    let b = pyobj_to_bool(cond);
+                    if b.is_ok() {
+                        if b.expect("Never fails") {
+                            self.pc = addr;
+                        } else {
+                            self.pop();
+                            self.pc += 1;
+                        };
+                        continue;
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
                 &Opcode::JumpAbsolute(addr) => {
                     self.pc = addr;
                     continue;
@@ -332,28 +885,18 @@ impl StackMachine {
                 },
                 &Opcode::GetIter => {
                     let v = self.pop();
-                    match v.ob_type().pytype_tp_iter() {
-                        Some(ref iterfun) => {
-                            let iter = iterfun(Rc::clone(&v));
-                            if iter.is_ok() {
-                                self.push(iter.expect("Never fails"));
-                                self.pc += 1;
-                                continue;
-                            }
-                        },
-                        None => {
-                            pyerr_set_string(
-                                PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
-                                "__iter__ is not found");
-                        }
+                    let iter = pyobj_get_iter(v);
+                    if iter.is_ok() {
+                        self.push(iter.expect("Never fails"));
+                        self.pc += 1;
+                        continue;
                     }
                     why = Why::WhyException;
                     retval = Err(());
                 },
                 &Opcode::ForIter(addr) => {
                     let it = self.top();
-                    let nextfun = it.ob_type().pytype_tp_iternext().expect("Never fails");
-                    let next = nextfun(Rc::clone(&it));
+                    let next = pyobj_iter_next(it);
                     if next.is_ok() {
                         match next.expect("Never fails") {
                             Some(next) => {
@@ -362,22 +905,14 @@ impl StackMachine {
                                 continue;
                             },
                             None => {
-                                if pyerr_check(PY_STOPITERATION_TYPE.with(|tp| Rc::clone(tp))) {
-                                    pyerr_clear();
-                                } else if !pyerr_occurred() {
-                                    self.pop();
-                                    self.pc = addr;
-                                    continue;
-                                } else {
-                                    why = Why::WhyException;
-                                    retval = Err(());
-                                }
+                                self.pop();
+                                self.pc = addr;
+                                continue;
                             }
                         }
-                    } else {
-                        why = Why::WhyException;
-                        retval = Err(());
                     }
+                    why = Why::WhyException;
+                    retval = Err(());
                 },
                 &Opcode::SetupExcept(offset) => {
                     self.blocks.push(Block {
@@ -388,21 +923,76 @@ impl StackMachine {
                     self.pc += 1;
                     continue;
                 },
-                &Opcode::Raise => {
-                    let mut exc = self.pop();
-
-                    if PyObject::pyexc_is_exc_subclass(Rc::clone(&exc)) {
-                        let res = type_call(exc, &vec![]);
-                        if res.is_ok() {
-                            exc = res.expect("Never fails");
-                            pyerr_set(exc);
+                &Opcode::SetupFinally(offset) => {
+                    self.blocks.push(Block {
+                        b_type: BlockType::FinallyBlock,
+                        b_handler: self.pc + offset,
+                        b_level: self.stack.len(),
+                    });
+                    self.pc += 1;
+                    continue;
+                },
+                &Opcode::ExcMatch => {
+                    let typ = self.pop();
+                    let exc = self.pop();
+                    let types = if typ.pytuple_check() { typ.pytuple_items() } else { vec![typ] };
+                    if types.iter().all(|typ| PyObject::pyexc_is_exc_subclass(Rc::clone(typ))) {
+                        let matched = types.into_iter().any(|typ| pyobj_isinstance(Rc::clone(&exc), typ));
+                        self.push(PyObject::from_bool(matched));
+                        self.pc += 1;
+                        continue;
+                    }
+                    pyerr_set_string(
+                        PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                        "catching classes that do not inherit from BaseException is not allowed");
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::PopExcept => {
+                    let block = self.blocks.pop().unwrap();
+                    if block.b_type != BlockType::ExceptHandlerBlock {
+                        panic!("Implementation Error: popped block is not an except handler");
+                    }
+                    self.unwind_except_handler(block);
+                    self.pc += 1;
+                    continue;
+                },
+                &Opcode::EndFinally => {
+                    let v = self.pop();
+                    if v.pynone_check() {
+                        self.pc += 1;
+                        continue;
+                    }
+                    if let PyInnerObject::LongObj(_) = v.inner {
+                        why = Why::from_code(pyobj_to_i32(Rc::clone(&v)).expect("Never fails"));
+                        if why == Why::WhyReturn || why == Why::WhyContinue {
+                            retval = Ok(self.pop());
                         }
-                    } else if PyObject::pyexc_is_exc_instance(Rc::clone(&exc)) {
-                        pyerr_set(exc);
                     } else {
-                        pyerr_set_string(
-                            PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
-                            "raise expects exception type or exception instance");
+                        pyerr_set(v);
+                        reraise = true;
+                        why = Why::WhyException;
+                        retval = Err(());
+                    }
+                },
+                &Opcode::Raise(0) => {
+                    match pyerr_get_exc_info() {
+                        Some(exc) => {
+                            pyerr_set(exc);
+                            reraise = true;
+                        },
+                        None => pyerr_set_string(
+                            PY_RUNTIMEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                            "No active exception to reraise"),
+                    }
+                    why = Why::WhyException;
+                    retval = Err(());
+                },
+                &Opcode::Raise(argc) => {
+                    let cause = if argc == 2 { Some(self.pop()) } else { None };
+                    let exc = self.pop();
+                    if let Ok(exc) = do_raise(exc, cause) {
+                        pyerr_set(exc);
                     }
                     why = Why::WhyException;
                     retval = Err(());
@@ -415,15 +1005,20 @@ impl StackMachine {
                 },
                 &Opcode::MakeClass(nbases) => {
                     let nameobj = self.pop();
-                    let codeobj = self.pop();
+                    let funobj = self.pop();
                     let bases = PyObject::pylist_from_vec(&self.pop_as_vec(nbases));
 
-                    let new_env = Rc::new(Env::new_child(&env, &vec![], &vec![]));
-                    eval(&codeobj.pycode_code(), Rc::clone(&new_env));
-                    let dictobj = new_env.dictobj();
-
-                    let meta = PY_TYPE_TYPE.with(|tp| Rc::clone(tp));
-                    let cls = meta.pytype_tp_call().unwrap()(Rc::clone(&meta), &vec![nameobj, bases, dictobj]);
+                    // the body runs like a call of a function without parameters
+                    let fun = match funobj.inner {
+                        PyInnerObject::FunObj(ref fun) => fun,
+                        _ => panic!("Type Error: MakeClass"),
+                    };
+                    let new_env = fun.new_env(&vec![], &vec![]);
+                    let cls = eval(&fun.codeobj, Rc::clone(&new_env)).and_then(|_| {
+                        let dictobj = new_env.dictobj()?;
+                        let meta = PY_TYPE_TYPE.with(|tp| Rc::clone(tp));
+                        meta.pytype_tp_call().unwrap()(Rc::clone(&meta), &vec![nameobj, bases, dictobj], None)
+                    });
 
                     if cls.is_ok() {
                         self.push(cls.expect("Never fails"));
@@ -435,6 +1030,10 @@ impl StackMachine {
                 },
             }
 
+            if why == Why::WhyException && !reraise {
+                pytraceback_here(codeobj, codeobj.pycode_line(self.pc));
+            }
+
             while self.blocks.len() > 0 {
 
                 {
@@ -449,6 +1048,10 @@ impl StackMachine {
                 }
 
                 let block = self.blocks.pop().unwrap();
+                if block.b_type == BlockType::ExceptHandlerBlock {
+                    self.unwind_except_handler(block);
+                    continue;
+                }
                 self.unwind_stack(block.b_level);
 
                 if block.b_type == BlockType::LoopBlock && why == Why::WhyBreak {
@@ -457,9 +1060,29 @@ impl StackMachine {
                     break;
                 }
 
-                if block.b_type == BlockType::TryBlock && why == Why::WhyException {
-                    if !pyerr_occurred() { panic!("Implementation Error: try block") };
-                    pyerr_clear();
+                if why == Why::WhyException &&
+                    (block.b_type == BlockType::TryBlock || block.b_type == BlockType::FinallyBlock) {
+                    let exc = pyerr_fetch().expect("Implementation Error: try block");
+                    // the handler's level sits below the saved exception
+                    self.blocks.push(Block {
+                        b_type: BlockType::ExceptHandlerBlock,
+                        b_handler: block.b_handler,
+                        b_level: self.stack.len(),
+                    });
+                    let prev = pyerr_get_exc_info().unwrap_or_else(PyObject::none_obj);
+                    self.push(prev);
+                    self.push(Rc::clone(&exc));
+                    pyerr_set_exc_info(Some(exc));
+                    why = Why::WhyNot;
+                    self.pc = block.b_handler;
+                    break;
+                }
+
+                if block.b_type == BlockType::FinallyBlock {
+                    if why == Why::WhyReturn || why == Why::WhyContinue {
+                        self.push(Rc::clone(retval.as_ref().expect("Finally ret value")));
+                    }
+                    self.push(PyObject::from_i32(why as i32));
                     why = Why::WhyNot;
                     self.pc = block.b_handler;
                     break;
@@ -476,7 +1099,161 @@ impl StackMachine {
     }
 }
 
-pub fn eval(code: &Code, env: Rc<Env>) -> PyRes<Rc<PyObject>> {
-    let mut stack_machine = StackMachine::new();
-    stack_machine.exec(code, env)
+fn value_error<T>(msg: &str) -> PyRes<T> {
+    pyerr_set_string(PY_VALUEERROR_TYPE.with(|tp| Rc::clone(tp)), msg);
+    Err(())
+}
+
+// Unpacks an iterable into `count` items, or, when a starred target is at
+// index `starred`, into `count` items around a list of the rest.
+fn unpack_iterable(v: Rc<PyObject>, count: usize, starred: Option<usize>) -> PyRes<Vec<Rc<PyObject>>> {
+    if v.ob_type().pytype_tp_iter().is_none() {
+        let msg = format!("cannot unpack non-iterable {} object",
+                          v.ob_type().pytype_typeobj_borrow().tp_name);
+        pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)), &msg);
+        return Err(());
+    }
+    let it = pyobj_get_iter(v)?;
+    let mut items = vec![];
+    // without a starred target one item too many is enough to tell
+    while starred.is_some() || items.len() <= count {
+        match pyobj_iter_next(Rc::clone(&it))? {
+            Some(item) => items.push(item),
+            None => break,
+        }
+    }
+    match starred {
+        None if items.len() < count => {
+            value_error(&format!("not enough values to unpack (expected {}, got {})", count, items.len()))
+        },
+        None if items.len() > count => {
+            value_error(&format!("too many values to unpack (expected {})", count))
+        },
+        None => Ok(items),
+        Some(_) if items.len() < count => {
+            value_error(&format!("not enough values to unpack (expected at least {}, got {})",
+                                 count, items.len()))
+        },
+        Some(before) => {
+            let after = count - before;
+            let rest_end = items.len() - after;
+            let rest: Vec<Rc<PyObject>> = items.drain(before..rest_end).collect();
+            items.insert(before, PyObject::pylist_from_vec(&rest));
+            Ok(items)
+        },
+    }
+}
+
+// Adds the `**mapping` argument of a call to the keyword arguments in `dict`.
+fn dict_merge_kwargs(dict: Rc<PyObject>, other: Rc<PyObject>, fun: &Rc<PyObject>) -> PyRes<()> {
+    let type_error = |msg: String| {
+        pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)), &msg);
+        Err(())
+    };
+    if !other.pydict_check() {
+        return type_error(format!("{}() argument after ** must be a mapping, not {}",
+                                  pyobj_func_name(fun), other.ob_type().pytype_typeobj_borrow().tp_name));
+    }
+    for (key, value) in other.pydict_entries() {
+        if !key.pystr_check() {
+            return type_error("keywords must be strings".to_string());
+        }
+        if dict.pydict_lookup(Rc::clone(&key))?.is_some() {
+            return type_error(format!("{}() got multiple values for keyword argument '{}'",
+                                      pyobj_func_name(fun), pyobj_to_string(key)?));
+        }
+        dict.pydict_update(key, value)?;
+    }
+    Ok(())
+}
+
+// An exception class is instantiated with no arguments.
+pub fn new_exception(v: Rc<PyObject>, msg: &str) -> PyRes<Rc<PyObject>> {
+    if PyObject::pyexc_is_exc_subclass(Rc::clone(&v)) {
+        type_call(v, &vec![], None)
+    } else if PyObject::pyexc_is_exc_instance(Rc::clone(&v)) {
+        Ok(v)
+    } else {
+        pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)), msg);
+        Err(())
+    }
+}
+
+fn do_raise(exc: Rc<PyObject>, cause: Option<Rc<PyObject>>) -> PyRes<Rc<PyObject>> {
+    let exc = new_exception(exc, "raise expects exception type or exception instance")?;
+    if let Some(cause) = cause {
+        if cause.pynone_check() {
+            exc.pyexc_set_cause(None);
+        } else {
+            let cause = new_exception(cause, "exception causes must derive from BaseException")?;
+            exc.pyexc_set_cause(Some(cause));
+        }
+    }
+    Ok(exc)
+}
+
+const RECURSION_LIMIT: usize = 1000;
+
+thread_local! (
+    static RECURSION_DEPTH: Cell<usize> = Cell::new(0);
+);
+
+// Runs `f` one level deeper in the Python call stack.
+fn with_recursion_check<T, F>(f: F) -> PyRes<T> where F: FnOnce() -> PyRes<T> {
+    let depth = RECURSION_DEPTH.with(|depth| { depth.set(depth.get() + 1); depth.get() });
+    let res = if depth > RECURSION_LIMIT {
+        pyerr_set_string(PY_RECURSIONERROR_TYPE.with(|tp| Rc::clone(tp)),
+                         "maximum recursion depth exceeded");
+        Err(())
+    } else {
+        f()
+    };
+    RECURSION_DEPTH.with(|depth| depth.set(depth.get() - 1));
+    res
+}
+
+pub fn eval(codeobj: &Rc<PyObject>, env: Rc<Env>) -> PyRes<Rc<PyObject>> {
+    with_recursion_check(|| StackMachine::new().exec(codeobj, env))
+}
+
+/// How a resumed frame stopped.
+pub enum Resumed {
+    Yield(Rc<PyObject>),
+    Return(Rc<PyObject>),
+}
+
+/// Runs the frame of a generator from where it stopped. `value` becomes the
+/// result of the yield the frame is suspended at; a frame that has not
+/// started yet takes none.
+pub fn resume(frame: &mut StackMachine, codeobj: &Rc<PyObject>, env: Rc<Env>,
+              value: Option<Rc<PyObject>>) -> PyRes<Resumed> {
+    if let Some(value) = value {
+        frame.push(value);
+    }
+    frame.yielded = false;
+    let res = with_recursion_check(|| frame.exec(codeobj, env))?;
+    Ok(if frame.yielded { Resumed::Yield(res) } else { Resumed::Return(res) })
+}
+
+/// Raises the pending exception in the frame of a generator where it
+/// stopped. A frame inside `yield from` first passes it on to the generator
+/// it delegates to, and only sees it if that one does not handle it.
+pub fn resume_throw(frame: &mut StackMachine, codeobj: &Rc<PyObject>, env: Rc<Env>) -> PyRes<Resumed> {
+    if let Some(&Opcode::YieldFrom) = codeobj.pycode_code().get(frame.pc) {
+        let iter = frame.top();
+        if iter.pygen_check() {
+            match iter.pygen_throw_ex() {
+                Ok(Resumed::Yield(v)) => return Ok(Resumed::Yield(v)),
+                Ok(Resumed::Return(v)) => {
+                    frame.pop();
+                    frame.push(v);
+                    frame.pc += 1;
+                    return resume(frame, codeobj, env, None);
+                },
+                Err(()) => {},
+            }
+        }
+    }
+    frame.throwing = true;
+    resume(frame, codeobj, env, None)
 }
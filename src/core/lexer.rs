@@ -1,27 +1,17 @@
+use std::char;
 use std::iter::Peekable;
+use std::mem;
 use std::str::Chars;
-use token::Token;
-
-pub struct LexingError {
-    line: usize,
-    row: usize,
-    msg: String,
-}
-
-impl LexingError {
-    fn new(line: usize, row: usize, msg: String) -> LexingError {
-        LexingError { line: line, row: row, msg: msg }
-    }
-
-    pub fn to_string(self) -> String {
-        format!("Lexing Error: line {}, row {}, {}", self.line, self.row, self.msg)
-    }
-}
+use object::bigint::BigInt;
+use syntax::{Pos, Span, SyntaxError};
+use token::{FStrPiece, Token};
+use unicode_names2;
 
 fn symbol_to_token(ch: char) -> Token {
     match ch {
         '+' => Token::Plus,
-        '<' => Token::Lt,
+        '-' => Token::Minus,
+        '%' => Token::Percent,
         '(' => Token::LParen,
         ')' => Token::RParen,
         '[' => Token::LBracket,
@@ -41,19 +31,33 @@ fn ident_to_token(s: String) -> Token {
         "True" => Token::True,
         "False" => Token::False,
         "if" => Token::If,
+        "elif" => Token::Elif,
         "else" => Token::Else,
         "while" => Token::While,
         "for" => Token::For,
         "in" => Token::In,
+        "and" => Token::And,
+        "or" => Token::Or,
+        "not" => Token::Not,
+        "is" => Token::Is,
         "break" => Token::Break,
         "continue" => Token::Continue,
+        "pass" => Token::Pass,
         "try" => Token::Try,
         "except" => Token::Except,
+        "finally" => Token::Finally,
+        "as" => Token::As,
         "raise" => Token::Raise,
+        "from" => Token::From,
         "def" => Token::Def,
         "return" => Token::Return,
         "assert" => Token::Assert,
+        "del" => Token::Del,
+        "global" => Token::Global,
+        "nonlocal" => Token::Nonlocal,
         "class" => Token::Class,
+        "lambda" => Token::Lambda,
+        "yield" => Token::Yield,
         _ => Token::Ident(s),
     }
 }
@@ -77,30 +81,39 @@ fn is_alphanumeric(ch: char) -> bool {
 }
 
 fn is_whitespace(ch: char) -> bool {
-    ch == ' '
+    ch == ' ' || ch == '\t' || ch == '\x0c'
 }
 
-fn is_not_quote(ch: char) -> bool {
-    ch != '\''
+fn closing_bracket(ch: char) -> char {
+    match ch {
+        '(' => ')',
+        '[' => ']',
+        '{' => '}',
+        _ => panic!("Invalid bracket"),
+    }
 }
 
-fn is_not_dquote(ch: char) -> bool {
-    ch != '"'
+fn is_quote(ch: char) -> bool {
+    ch == '\'' || ch == '"'
 }
 
 struct Lexer<'a> {
     it: Peekable<Chars<'a>>,
     line: usize,
     row: usize,
-    stack: Vec<usize>,
+    // indentation columns of the enclosing blocks, see `indentation`
+    stack: Vec<(usize, usize)>,
+    // open brackets, inside which line breaks do not end the line
+    brackets: Vec<(char, Pos)>,
     is_line_head: bool,
-    tokens: Vec<Token>
+    tokens: Vec<Token>,
+    spans: Vec<Span>,
 }
 
 impl <'a>Lexer<'a> {
     fn new(s: &'a String) -> Lexer<'a> {
-        Lexer { it: s.chars().peekable(), line: 1, row: 1, stack: vec![0],
-                is_line_head: true, tokens: vec![] }
+        Lexer { it: s.chars().peekable(), line: 1, row: 0, stack: vec![(0, 0)], brackets: vec![],
+                is_line_head: true, tokens: vec![], spans: vec![] }
     }
 
     fn next(&mut self) -> Option<char> {
@@ -118,26 +131,56 @@ impl <'a>Lexer<'a> {
         }
     }
 
-    fn calc_indent(&mut self, indent_level: usize) -> Result<(), LexingError> {
-        let mut last_indent_level = *(self.stack.last().unwrap());
-        if indent_level > last_indent_level {
-            self.stack.push(indent_level);
-            self.tokens.push(Token::Indent);
-        } else if indent_level < last_indent_level {
-            loop {
-                self.stack.pop();
-                self.tokens.push(Token::Dedent);
-                last_indent_level = *(self.stack.last().unwrap());
-                if indent_level == last_indent_level {
-                    break;
-                } else if indent_level > last_indent_level {
-                    return Err(self.error("Invalid indentation".to_string()))
-                }
+    // Reads the leading whitespace of a line. Like CPython it measures the
+    // indentation twice, with tabs advancing to the next multiple of 8 and
+    // with tabs as a single column. Two lines whose order differs between
+    // the measures mix tabs and spaces ambiguously.
+    fn indentation(&mut self) -> (usize, usize) {
+        let (mut col, mut alt_col) = (0, 0);
+        loop {
+            match self.it.peek() {
+                Some(&' ') => { col += 1; alt_col += 1 },
+                Some(&'\t') => { col = (col / 8 + 1) * 8; alt_col += 1 },
+                Some(&'\x0c') => { col = 0; alt_col = 0 },
+                _ => break,
             }
-        };
+            self.next();
+        }
+        (col, alt_col)
+    }
+
+    fn calc_indent(&mut self, col: usize, alt_col: usize) -> Result<(), SyntaxError> {
+        let tab_error = "inconsistent use of tabs and spaces in indentation";
+        let (mut last_col, mut last_alt_col) = *(self.stack.last().unwrap());
+        if col > last_col {
+            if alt_col <= last_alt_col {
+                return Err(SyntaxError::tab(tab_error, self.pos()));
+            }
+            self.stack.push((col, alt_col));
+            self.tokens.push(Token::Indent);
+            return Ok(());
+        }
+        while col < last_col {
+            self.stack.pop();
+            self.tokens.push(Token::Dedent);
+            let last = *(self.stack.last().unwrap());
+            last_col = last.0;
+            last_alt_col = last.1;
+        }
+        if col != last_col {
+            return Err(SyntaxError::indentation("unindent does not match any outer indentation level",
+                                                self.pos()));
+        }
+        if alt_col != last_alt_col {
+            return Err(SyntaxError::tab(tab_error, self.pos()));
+        }
         Ok(())
     }
 
+    fn skip_comment(&mut self) {
+        self.consume_while(|ch| ch != '\n');
+    }
+
     fn consume(&mut self, c1: char) -> Option<char> {
         match self.next() {
             Some(c2) if c1 == c2 => {
@@ -159,30 +202,457 @@ impl <'a>Lexer<'a> {
         }
         v
     }
-    fn error(&self, s: String) -> LexingError {
-        LexingError::new(self.line, self.row, s)
+    fn peek_second(&self) -> Option<char> {
+        let mut it = self.it.clone();
+        it.next();
+        it.next()
+    }
+
+    // Reads a run of decimal digits into `num`, where single underscores may
+    // separate the digits.
+    fn decimal_digits(&mut self, num: &mut String) -> Result<(), SyntaxError> {
+        let mut has_digits = false;
+        loop {
+            num.extend(self.consume_while(is_number));
+            has_digits = has_digits || num.ends_with(is_number);
+            if !has_digits || self.it.peek() != Some(&'_') {
+                return Ok(());
+            }
+            self.next();
+            if !self.it.peek().map_or(false, |&ch| is_number(ch)) {
+                return Err(self.error("invalid decimal literal".to_string()));
+            }
+        }
+    }
+
+    // '0' ('x' | 'o' | 'b') ('_'? digit)+, after the prefix
+    fn radix_number(&mut self, radix: u32, name: &str) -> Result<Token, SyntaxError> {
+        let mut num = String::new();
+        loop {
+            let underscore = self.it.peek() == Some(&'_');
+            if underscore {
+                self.next();
+            }
+            match self.it.peek() {
+                Some(&ch) if ch.is_digit(radix) => {
+                    self.next();
+                    num.push(ch);
+                },
+                Some(&ch) if is_number(ch) => {
+                    return Err(self.error(format!("invalid digit '{}' in {} literal", ch, name)));
+                },
+                Some(&ch) if is_alphanumeric(ch) => {
+                    return Err(self.error(format!("invalid {} literal", name)));
+                },
+                _ if underscore || num.is_empty() => {
+                    return Err(self.error(format!("invalid {} literal", name)));
+                },
+                _ => break,
+            }
+        }
+        Ok(Token::Int(BigInt::parse(&num, radix).expect("Never fails")))
+    }
+
+    // radix_number
+    // | digits ('.' digits?)? (('e' | 'E') ('+' | '-')? digits)? ('j' | 'J')?
+    // | '.' digits (('e' | 'E') ('+' | '-')? digits)? ('j' | 'J')?
+    fn number(&mut self) -> Result<Token, SyntaxError> {
+        let start = self.pos();
+        if self.it.peek() == Some(&'0') {
+            let radix = match self.peek_second() {
+                Some('x') | Some('X') => Some((16, "hexadecimal")),
+                Some('o') | Some('O') => Some((8, "octal")),
+                Some('b') | Some('B') => Some((2, "binary")),
+                _ => None,
+            };
+            if let Some((radix, name)) = radix {
+                self.next();
+                self.next();
+                return self.radix_number(radix, name);
+            }
+        }
+
+        let mut num = String::new();
+        self.decimal_digits(&mut num)?;
+        let mut is_float = false;
+
+        if self.it.peek() == Some(&'.') {
+            is_float = true;
+            self.next();
+            num.push('.');
+            self.decimal_digits(&mut num)?;
+        }
+
+        if self.it.peek() == Some(&'e') || self.it.peek() == Some(&'E') {
+            let sign = self.peek_second();
+            let has_exp = match sign {
+                Some('+') | Some('-') => {
+                    let mut it = self.it.clone();
+                    it.next();
+                    it.next();
+                    it.next().map_or(false, is_number)
+                },
+                Some(ch) => is_number(ch),
+                None => false,
+            };
+            if has_exp {
+                is_float = true;
+                self.next();
+                num.push('e');
+                if let Some(sign @ '+') | Some(sign @ '-') = sign {
+                    self.next();
+                    num.push(sign);
+                }
+                self.decimal_digits(&mut num)?;
+            }
+        }
+
+        if self.it.peek() == Some(&'j') || self.it.peek() == Some(&'J') {
+            self.next();
+            return Ok(Token::Imag(num.parse::<f64>().expect("Never fails")));
+        }
+        if is_float {
+            Ok(Token::Float(num.parse::<f64>().expect("Never fails")))
+        } else if num.starts_with('0') && num.contains(|ch| ch != '0') {
+            Err(SyntaxError::new("leading zeros in decimal integer literals are not permitted; \
+                                  use an 0o prefix for octal integers", start))
+        } else {
+            Ok(Token::Int(BigInt::parse(&num, 10).expect("Never fails")))
+        }
+    }
+
+    // The lowercased prefix of a string literal starting here, if any.
+    fn string_prefix(&self) -> Option<String> {
+        let mut it = self.it.clone();
+        let mut prefix = String::new();
+        while let Some(&ch) = it.peek() {
+            if !is_alphabet(ch) || prefix.len() == 2 {
+                break;
+            }
+            prefix.push(ch.to_ascii_lowercase());
+            it.next();
+        }
+        match (it.peek(), &prefix[..]) {
+            (Some(&ch), "r") | (Some(&ch), "u") | (Some(&ch), "b") | (Some(&ch), "f") |
+            (Some(&ch), "rb") | (Some(&ch), "br") |
+            (Some(&ch), "rf") | (Some(&ch), "fr") if is_quote(ch) => Some(prefix),
+            _ => None,
+        }
+    }
+
+    fn at_closing_quote(&self, quote: char, triple: bool) -> bool {
+        let mut it = self.it.clone();
+        let n = if triple { 3 } else { 1 };
+        (0..n).all(|_| it.next() == Some(quote))
+    }
+
+    fn skip_closing_quote(&mut self, triple: bool) {
+        let n = if triple { 3 } else { 1 };
+        for _ in 0..n {
+            self.next();
+        }
+    }
+
+    // The next character inside a literal starting at `start`, which is
+    // unterminated if the line or the source ends first.
+    fn string_char(&mut self, triple: bool, start: Pos) -> Result<char, SyntaxError> {
+        match self.it.peek() {
+            Some(&'\n') if !triple => (),
+            Some(_) => return Ok(self.next().expect("Never fails")),
+            None => (),
+        }
+        let msg = if triple {
+            format!("unterminated triple-quoted string literal (detected at line {})", self.line)
+        } else {
+            format!("unterminated string literal (detected at line {})", self.line)
+        };
+        Err(SyntaxError::new(&msg, start))
+    }
+
+    // prefix quote (char | escape)* quote, or the same between triple quotes
+    fn string(&mut self, prefix: &str) -> Result<Token, SyntaxError> {
+        let start = self.pos();
+        for _ in 0..prefix.len() {
+            self.next();
+        }
+        let raw = prefix.contains('r');
+        let bytes = prefix.contains('b');
+        let quote = self.next().expect("Never fails");
+        let triple = self.it.peek() == Some(&quote) && self.peek_second() == Some(quote);
+        if triple {
+            self.next();
+            self.next();
+        }
+        if prefix.contains('f') {
+            let pieces = self.fstring_pieces(quote, triple, raw, start, false)?;
+            return Ok(Token::FStr(pieces));
+        }
+
+        let mut s = String::new();
+        while !self.at_closing_quote(quote, triple) {
+            let ch = self.string_char(triple, start)?;
+            if ch == '\\' {
+                self.backslash(raw, bytes, &mut s)?;
+            } else if bytes && !ch.is_ascii() {
+                return Err(SyntaxError::new("bytes can only contain ASCII literal characters", start));
+            } else {
+                s.push(ch);
+            }
+        }
+        self.skip_closing_quote(triple);
+
+        if bytes {
+            Ok(Token::Bytes(s.chars().map(|ch| ch as u8).collect()))
+        } else {
+            Ok(Token::Str(s))
+        }
+    }
+
+    // appends what a backslash and the characters after it stand for
+    fn backslash(&mut self, raw: bool, bytes: bool, s: &mut String) -> Result<(), SyntaxError> {
+        if raw {
+            // the backslash stays, but still keeps the quote from ending the string
+            s.push('\\');
+            if let Some(ch) = self.next() {
+                s.push(ch);
+            }
+        } else {
+            let pos = self.pos();
+            let escaped = self.escape(bytes).map_err(|msg| SyntaxError::new(&msg, pos))?;
+            s.push_str(&escaped);
+        }
+        Ok(())
+    }
+
+    // The literal text and replacement fields of an f-string up to its
+    // closing quote, or of a format spec up to the '}' ending its field.
+    // Outside a format spec, doubled braces stand for single ones.
+    fn fstring_pieces(&mut self, quote: char, triple: bool, raw: bool, start: Pos, in_spec: bool)
+                      -> Result<Vec<FStrPiece>, SyntaxError> {
+        let mut pieces = vec![];
+        let mut s = String::new();
+        loop {
+            let pos = self.pos();
+            if self.at_closing_quote(quote, triple) {
+                if in_spec {
+                    return Err(SyntaxError::new("f-string: expecting '}'", pos));
+                }
+                self.skip_closing_quote(triple);
+                break;
+            }
+            match self.string_char(triple, start)? {
+                '{' if !in_spec && self.it.peek() == Some(&'{') => { self.next(); s.push('{') },
+                '}' if !in_spec && self.it.peek() == Some(&'}') => { self.next(); s.push('}') },
+                '{' => {
+                    if !s.is_empty() {
+                        pieces.push(FStrPiece::Lit(mem::replace(&mut s, String::new())));
+                    }
+                    let field = self.fstring_field(quote, triple, raw, start)?;
+                    pieces.push(field);
+                },
+                '}' if in_spec => break,
+                '}' => return Err(SyntaxError::new("f-string: single '}' is not allowed", pos)),
+                // in a raw f-string a brace after a backslash still delimits
+                '\\' if raw && (self.it.peek() == Some(&'{') || self.it.peek() == Some(&'}')) => s.push('\\'),
+                '\\' => self.backslash(raw, false, &mut s)?,
+                ch => s.push(ch),
+            }
+        }
+        if !s.is_empty() {
+            pieces.push(FStrPiece::Lit(s));
+        }
+        Ok(pieces)
+    }
+
+    // expr ('!' conversion)? (':' spec)? '}', after the '{' of a replacement
+    // field. The expression is kept as source and parsed with the f-string.
+    fn fstring_field(&mut self, quote: char, triple: bool, raw: bool, start: Pos)
+                     -> Result<FStrPiece, SyntaxError> {
+        let pos = self.pos();
+        let mut expr = String::new();
+        let mut depth = 0;
+        let mut end = loop {
+            if self.at_closing_quote(quote, triple) {
+                return Err(self.error("f-string: expecting '}'".to_string()));
+            }
+            let ch_pos = self.pos();
+            match self.string_char(triple, start)? {
+                '\\' => {
+                    return Err(SyntaxError::new("f-string expression part cannot include a backslash",
+                                                ch_pos));
+                },
+                '#' => return Err(SyntaxError::new("f-string expression part cannot include '#'", ch_pos)),
+                ch @ '}' | ch @ ':' if depth == 0 => break ch,
+                '!' if depth == 0 && self.it.peek() != Some(&'=') => break '!',
+                ch @ '\'' | ch @ '"' => {
+                    // a string nested in the expression
+                    expr.push(ch);
+                    loop {
+                        let nested = self.string_char(triple, start)?;
+                        expr.push(nested);
+                        if nested == ch {
+                            break;
+                        }
+                    }
+                },
+                ch @ '(' | ch @ '[' | ch @ '{' => { depth += 1; expr.push(ch) },
+                ch @ ')' | ch @ ']' | ch @ '}' if depth > 0 => { depth -= 1; expr.push(ch) },
+                ch => expr.push(ch),
+            }
+        };
+        if expr.trim().is_empty() {
+            return Err(SyntaxError::new("f-string: empty expression not allowed", pos));
+        }
+
+        let mut conversion = None;
+        if end == '!' {
+            let conv_pos = self.pos();
+            match self.string_char(triple, start)? {
+                ch @ 's' | ch @ 'r' | ch @ 'a' => conversion = Some(ch),
+                _ => {
+                    return Err(SyntaxError::new(
+                        "f-string: invalid conversion character: expected 's', 'r', or 'a'", conv_pos));
+                },
+            }
+            let end_pos = self.pos();
+            end = self.string_char(triple, start)?;
+            if end != '}' && end != ':' {
+                return Err(SyntaxError::new("f-string: expecting '}'", end_pos));
+            }
+        }
+        let spec = if end == ':' {
+            Some(self.fstring_pieces(quote, triple, raw, start, true)?)
+        } else {
+            None
+        };
+        Ok(FStrPiece::Field { expr: expr, pos: pos, conversion: conversion, spec: spec })
+    }
+
+    fn hex_escape(&mut self, digits: usize) -> Option<u32> {
+        let mut value = 0;
+        for _ in 0..digits {
+            let digit = self.it.peek().and_then(|ch| ch.to_digit(16))?;
+            self.next();
+            value = value * 16 + digit;
+        }
+        Some(value)
+    }
+
+    // Reads the escape sequence after a backslash. An unknown escape keeps
+    // its backslash, and a backslash before a line break joins the lines.
+    // In bytes literals, characters stand for byte values.
+    fn escape(&mut self, bytes: bool) -> Result<String, String> {
+        let ch = match self.next() {
+            Some(ch) => ch,
+            None => return Ok("\\".to_string()),
+        };
+        let unicode_error = |msg: &str| {
+            let codec = if bytes { "" } else { "(unicode error) 'unicodeescape' codec can't decode bytes: " };
+            format!("{}{}", codec, msg)
+        };
+        let escaped = match ch {
+            '\n' => return Ok(String::new()),
+            '\\' | '\'' | '"' => ch,
+            'a' => '\x07',
+            'b' => '\x08',
+            'f' => '\x0c',
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'v' => '\x0b',
+            '0' ... '7' => {
+                let mut value = ch.to_digit(8).expect("Never fails");
+                for _ in 0..2 {
+                    match self.it.peek().and_then(|ch| ch.to_digit(8)) {
+                        Some(digit) => { self.next(); value = value * 8 + digit },
+                        None => break,
+                    }
+                }
+                if bytes { (value & 0xff) as u8 as char } else { char::from_u32(value).expect("Never fails") }
+            },
+            'x' => match self.hex_escape(2) {
+                Some(value) => char::from_u32(value).expect("Never fails"),
+                None => return Err(unicode_error("truncated \\xXX escape")),
+            },
+            'u' | 'U' if !bytes => {
+                let (digits, msg) = if ch == 'u' {
+                    (4, "truncated \\uXXXX escape")
+                } else {
+                    (8, "truncated \\UXXXXXXXX escape")
+                };
+                match self.hex_escape(digits) {
+                    Some(value) => match char::from_u32(value) {
+                        Some(ch) => ch,
+                        None => return Err(unicode_error("illegal Unicode character")),
+                    },
+                    None => return Err(unicode_error(msg)),
+                }
+            },
+            'N' if !bytes => {
+                if self.it.peek() != Some(&'{') {
+                    return Err(unicode_error("malformed \\N character escape"));
+                }
+                self.next();
+                let name: String = self.consume_while(|ch| ch != '}' && ch != '\n').into_iter().collect();
+                if self.consume('}').is_none() {
+                    return Err(unicode_error("malformed \\N character escape"));
+                }
+                match unicode_names2::character(&name) {
+                    Some(ch) => ch,
+                    None => return Err(unicode_error("unknown Unicode character name")),
+                }
+            },
+            _ => return Ok(format!("\\{}", ch)),
+        };
+        Ok(escaped.to_string())
+    }
+
+    // position of the next character; `row` counts the characters read
+    fn pos(&self) -> Pos {
+        Pos { line: self.line, col: self.row + 1 }
+    }
+
+    // gives the tokens pushed since the last call the span from `start` up
+    // to the current position
+    fn mark_spans(&mut self, start: Pos) {
+        let end = self.pos();
+        while self.spans.len() < self.tokens.len() {
+            self.spans.push(Span::new(start, end));
+        }
+    }
+
+    fn error(&self, s: String) -> SyntaxError {
+        SyntaxError::new(&s, self.pos())
     }
 }
 
-pub fn tokenize(s: String) -> Result<Vec<Token>, LexingError> {
+pub fn tokenize(s: String) -> Result<Vec<(Token, Span)>, SyntaxError> {
+    let s = s.replace("\r\n", "\n").replace('\r', "\n");
     let mut lexer = Lexer::new(&s);
     loop {
-        // consume blank lines
+        // consume blank lines and lines holding only a comment
         if lexer.is_line_head {
-            let indent_level = lexer.consume_while(is_whitespace).len();
+            let (col, alt_col) = lexer.indentation();
             match lexer.it.peek() {
                 Some('\n') => {
                     lexer.next();
                     continue
                 },
+                Some('#') => {
+                    lexer.skip_comment();
+                    continue
+                },
                 Some(_) => {
-                    try!(lexer.calc_indent(indent_level));
+                    try!(lexer.calc_indent(col, alt_col));
+                    let start = lexer.pos();
+                    lexer.mark_spans(start);
                     lexer.is_line_head = false;
                 },
                 _ => break,
             }
         };
 
+        let start = lexer.pos();
         let mut ch = '0';
         match lexer.it.peek() {
             Some(&ch_) => { ch = ch_ },
@@ -191,28 +661,92 @@ pub fn tokenize(s: String) -> Result<Vec<Token>, LexingError> {
 
         match ch {
             '0' ... '9' => {
-                let num: String = lexer.consume_while(is_number).into_iter().collect();
-                lexer.tokens.push(Token::Int(num.parse::<i32>().unwrap()));
+                let token = lexer.number()?;
+                lexer.tokens.push(token);
+            },
+            '.' if lexer.peek_second().map_or(false, is_number) => {
+                let token = lexer.number()?;
+                lexer.tokens.push(token);
+            },
+            '\'' | '"' => {
+                let token = lexer.string("")?;
+                lexer.tokens.push(token);
+            },
+            '(' | '[' | '{' => {
+                let pos = lexer.pos();
+                let nch = lexer.next().unwrap();
+                lexer.brackets.push((nch, pos));
+                lexer.tokens.push(symbol_to_token(nch))
             },
-            '\'' => {
+            ')' | ']' | '}' => {
+                match lexer.brackets.pop() {
+                    Some((open, _)) if closing_bracket(open) == ch => (),
+                    Some((open, _)) => return Err(lexer.error(format!(
+                        "closing parenthesis '{}' does not match opening parenthesis '{}'", ch, open))),
+                    None => return Err(lexer.error(format!("unmatched '{}'", ch))),
+                }
+                let nch = lexer.next().unwrap();
+                lexer.tokens.push(symbol_to_token(nch))
+            },
+            '#' => lexer.skip_comment(),
+            '\\' => {
+                lexer.next();
+                if lexer.it.peek() != Some(&'\n') {
+                    return Err(lexer.error("unexpected character after line continuation character".to_string()));
+                }
                 lexer.next();
-                let s: String = lexer.consume_while(is_not_quote).into_iter().collect();
-                lexer.tokens.push(Token::Str(s));
-                try!(lexer.consume('\'').ok_or(lexer.error("\' expected".to_string())));
             },
-            '"' => {
+            '\n' if !lexer.brackets.is_empty() => {
                 lexer.next();
-                let s: String = lexer.consume_while(is_not_dquote).into_iter().collect();
-                lexer.tokens.push(Token::Str(s));
-                lexer.consume('"').ok_or(lexer.error("\" expected".to_string()))?;
             },
-            '+' | '<' | '(' | ')' | '[' | ']' | '{' | '}' | ':' | ',' | '.' => {
+            '+' | '-' | '%' | ':' | ',' | '.' => {
                 let nch = lexer.next().unwrap();
                 lexer.tokens.push(symbol_to_token(nch))
             },
+            '*' => {
+                lexer.next();
+                if lexer.it.peek() == Some(&'*') {
+                    lexer.next();
+                    lexer.tokens.push(Token::DoubleStar)
+                } else {
+                    lexer.tokens.push(Token::Star)
+                }
+            },
+            '/' => {
+                lexer.next();
+                if lexer.it.peek() == Some(&'/') {
+                    lexer.next();
+                    lexer.tokens.push(Token::DoubleSlash)
+                } else {
+                    lexer.tokens.push(Token::Slash)
+                }
+            },
+            '<' => {
+                lexer.next();
+                if lexer.it.peek() == Some(&'=') {
+                    lexer.next();
+                    lexer.tokens.push(Token::LtE)
+                } else {
+                    lexer.tokens.push(Token::Lt)
+                }
+            },
+            '>' => {
+                lexer.next();
+                if lexer.it.peek() == Some(&'=') {
+                    lexer.next();
+                    lexer.tokens.push(Token::GtE)
+                } else {
+                    lexer.tokens.push(Token::Gt)
+                }
+            },
+            '!' => {
+                lexer.next();
+                lexer.consume('=').ok_or(lexer.error("invalid syntax".to_string()))?;
+                lexer.tokens.push(Token::NotEq)
+            },
             '=' => {
                 lexer.next();
-                if *lexer.it.peek().unwrap() != '=' {
+                if lexer.it.peek() != Some(&'=') {
                     lexer.tokens.push(Token::Eq)
                 } else {
                     lexer.next();
@@ -224,6 +758,11 @@ pub fn tokenize(s: String) -> Result<Vec<Token>, LexingError> {
                 lexer.tokens.push(Token::NewLine);
                 lexer.is_line_head = true;
             }
+            ch if is_alphabet(ch) && lexer.string_prefix().is_some() => {
+                let prefix = lexer.string_prefix().expect("Never fails");
+                let token = lexer.string(&prefix)?;
+                lexer.tokens.push(token);
+            },
             ch if is_alphabet(ch) => {
                 let nch = lexer.next().unwrap();
                 let mut id_vec = lexer.consume_while(is_alphanumeric);
@@ -233,23 +772,37 @@ pub fn tokenize(s: String) -> Result<Vec<Token>, LexingError> {
             ch if is_whitespace(ch) => {
                 lexer.consume_while(is_whitespace);
             }
-            _ => return Err(lexer.error(format!("Invalid character {} used", ch)))
+            _ => return Err(lexer.error(format!("invalid character '{}'", ch)))
         }
+        lexer.mark_spans(start);
     };
 
+    if let Some(&(open, pos)) = lexer.brackets.last() {
+        return Err(SyntaxError::new(&format!("'{}' was never closed", open), pos));
+    }
+
+    // the last line may end without a line break
+    if !lexer.is_line_head {
+        lexer.tokens.push(Token::NewLine);
+        let start = lexer.pos();
+        lexer.mark_spans(start);
+    }
+
     loop {
         match lexer.stack.pop() {
-            Some(i) if i != 0 => lexer.tokens.push(Token::Dedent),
+            Some((col, _)) if col != 0 => lexer.tokens.push(Token::Dedent),
             _ => break,
         }
     }
 
     lexer.tokens.push(Token::EOF);
-    Ok(lexer.tokens)
+    let start = lexer.pos();
+    lexer.mark_spans(start);
+    Ok(lexer.tokens.into_iter().zip(lexer.spans).collect())
 }
 
-pub fn print_tokens(tokens: &Vec<Token>) {
-    for t in tokens {
-        println!("{:?}", t);
+pub fn print_tokens(tokens: &Vec<(Token, Span)>) {
+    for &(ref t, span) in tokens {
+        println!("{}:{:<5} {:?}", span.start.line, span.start.col, t);
     }
 }
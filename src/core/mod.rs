@@ -1,9 +1,12 @@
 #![feature(arbitrary_self_types)]
 #![feature(iterator_find_map)]
+extern crate unicode_names2;
+
 pub mod token;
 pub mod lexer;
 pub mod syntax;
 pub mod opcode;
+pub mod symtable;
 pub mod compile;
 pub mod parser;
 pub mod env;
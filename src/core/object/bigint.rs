@@ -0,0 +1,446 @@
+use std::cmp::Ordering;
+use std::fmt;
+
+// Modulus of CPython's numeric hash, 2**61 - 1
+pub const HASH_MODULUS: u64 = (1 << 61) - 1;
+
+/// Arbitrary precision integer backing `int`.
+///
+/// Values that fit in an `i64` are kept inline; anything larger is stored as
+/// a sign and a little-endian magnitude in base 2**32. Every constructor
+/// normalizes, so a `Large` never holds a value that would fit in `Small`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BigInt {
+    Small(i64),
+    Large(bool, Vec<u32>),
+}
+
+fn trim(mut mag: Vec<u32>) -> Vec<u32> {
+    while mag.last() == Some(&0) {
+        mag.pop();
+    }
+    mag
+}
+
+fn mag_from_u64(n: u64) -> Vec<u32> {
+    trim(vec![n as u32, (n >> 32) as u32])
+}
+
+fn mag_cmp(a: &[u32], b: &[u32]) -> Ordering {
+    if a.len() != b.len() {
+        return a.len().cmp(&b.len());
+    }
+    for i in (0..a.len()).rev() {
+        if a[i] != b[i] {
+            return a[i].cmp(&b[i]);
+        }
+    }
+    Ordering::Equal
+}
+
+fn mag_add(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut res = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0u64;
+    for i in 0..a.len().max(b.len()) {
+        let sum = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        res.push(sum as u32);
+        carry = sum >> 32;
+    }
+    if carry > 0 {
+        res.push(carry as u32);
+    }
+    trim(res)
+}
+
+// requires a >= b
+fn mag_sub(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut res = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for i in 0..a.len() {
+        let mut diff = a[i] as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        if diff < 0 {
+            diff += 1 << 32;
+            borrow = 1;
+        } else {
+            borrow = 0;
+        }
+        res.push(diff as u32);
+    }
+    trim(res)
+}
+
+fn mag_mul(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut res = vec![0u32; a.len() + b.len()];
+    for i in 0..a.len() {
+        let mut carry = 0u64;
+        for j in 0..b.len() {
+            let t = res[i + j] as u64 + a[i] as u64 * b[j] as u64 + carry;
+            res[i + j] = t as u32;
+            carry = t >> 32;
+        }
+        res[i + b.len()] = carry as u32;
+    }
+    trim(res)
+}
+
+fn mag_divmod_small(a: &[u32], d: u32) -> (Vec<u32>, u32) {
+    let mut q = vec![0u32; a.len()];
+    let mut rem = 0u64;
+    for i in (0..a.len()).rev() {
+        let cur = (rem << 32) | a[i] as u64;
+        q[i] = (cur / d as u64) as u32;
+        rem = cur % d as u64;
+    }
+    (trim(q), rem as u32)
+}
+
+fn mag_bits(a: &[u32]) -> i64 {
+    match a.last() {
+        Some(&w) => a.len() as i64 * 32 - w.leading_zeros() as i64,
+        None => 0,
+    }
+}
+
+// x * 2**exp in steps, so that no intermediate power overflows
+fn ldexp(mut x: f64, mut exp: i64) -> f64 {
+    while exp > 1000 && x.is_finite() {
+        x *= 2f64.powi(1000);
+        exp -= 1000;
+    }
+    while exp < -1000 {
+        x *= 2f64.powi(-1000);
+        exp += 1000;
+    }
+    x * 2f64.powi(exp as i32)
+}
+
+fn mag_shl(a: &[u32], bits: usize) -> Vec<u32> {
+    let (words, bits) = (bits / 32, bits % 32);
+    let mut res = vec![0u32; words];
+    let mut carry = 0u32;
+    for &w in a {
+        if bits == 0 {
+            res.push(w);
+        } else {
+            res.push((w << bits) | carry);
+            carry = w >> (32 - bits);
+        }
+    }
+    if carry > 0 {
+        res.push(carry);
+    }
+    trim(res)
+}
+
+// truncating division of magnitudes by shift and subtract
+fn mag_divmod(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if b.len() == 1 {
+        let (q, r) = mag_divmod_small(a, b[0]);
+        return (q, mag_from_u64(r as u64));
+    }
+    if mag_cmp(a, b) == Ordering::Less {
+        return (vec![], a.to_vec());
+    }
+
+    let mut q = vec![0u32; a.len()];
+    let mut r: Vec<u32> = vec![];
+    for i in (0..a.len() * 32).rev() {
+        r = mag_shl(&r, 1);
+        if (a[i / 32] >> (i % 32)) & 1 == 1 {
+            if r.is_empty() { r.push(1) } else { r[0] |= 1 }
+        }
+        if mag_cmp(&r, b) != Ordering::Less {
+            r = mag_sub(&r, b);
+            q[i / 32] |= 1 << (i % 32);
+        }
+    }
+    (trim(q), r)
+}
+
+impl BigInt {
+    fn from_parts(negative: bool, mag: Vec<u32>) -> BigInt {
+        let mag = trim(mag);
+        if mag.len() <= 2 {
+            let n = *mag.get(0).unwrap_or(&0) as u64 | (*mag.get(1).unwrap_or(&0) as u64) << 32;
+            if n <= i64::max_value() as u64 {
+                return BigInt::Small(if negative { -(n as i64) } else { n as i64 });
+            }
+            if negative && n == 1 << 63 {
+                return BigInt::Small(i64::min_value());
+            }
+        }
+        BigInt::Large(negative, mag)
+    }
+
+    fn to_parts(&self) -> (bool, Vec<u32>) {
+        match self {
+            &BigInt::Small(n) => (n < 0, mag_from_u64(n.unsigned_abs())),
+            &BigInt::Large(negative, ref mag) => (negative, mag.clone()),
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        *self == BigInt::Small(0)
+    }
+
+    pub fn is_negative(&self) -> bool {
+        match self {
+            &BigInt::Small(n) => n < 0,
+            &BigInt::Large(negative, _) => negative,
+        }
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        match self {
+            &BigInt::Small(n) => Some(n),
+            &BigInt::Large(_, _) => None,
+        }
+    }
+
+    pub fn add(&self, other: &BigInt) -> BigInt {
+        if let (&BigInt::Small(a), &BigInt::Small(b)) = (self, other) {
+            if let Some(n) = a.checked_add(b) {
+                return BigInt::Small(n);
+            }
+        }
+        let (a_neg, a_mag) = self.to_parts();
+        let (b_neg, b_mag) = other.to_parts();
+        if a_neg == b_neg {
+            BigInt::from_parts(a_neg, mag_add(&a_mag, &b_mag))
+        } else if mag_cmp(&a_mag, &b_mag) != Ordering::Less {
+            BigInt::from_parts(a_neg, mag_sub(&a_mag, &b_mag))
+        } else {
+            BigInt::from_parts(b_neg, mag_sub(&b_mag, &a_mag))
+        }
+    }
+
+    pub fn neg(&self) -> BigInt {
+        if let &BigInt::Small(n) = self {
+            if let Some(n) = n.checked_neg() {
+                return BigInt::Small(n);
+            }
+        }
+        let (negative, mag) = self.to_parts();
+        BigInt::from_parts(!negative, mag)
+    }
+
+    pub fn sub(&self, other: &BigInt) -> BigInt {
+        self.add(&other.neg())
+    }
+
+    pub fn mul(&self, other: &BigInt) -> BigInt {
+        if let (&BigInt::Small(a), &BigInt::Small(b)) = (self, other) {
+            if let Some(n) = a.checked_mul(b) {
+                return BigInt::Small(n);
+            }
+        }
+        let (a_neg, a_mag) = self.to_parts();
+        let (b_neg, b_mag) = other.to_parts();
+        BigInt::from_parts(a_neg != b_neg, mag_mul(&a_mag, &b_mag))
+    }
+
+    /// Floor division and modulo with Python semantics. Returns `None` when
+    /// `other` is zero.
+    pub fn divmod(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+        if other.is_zero() {
+            return None;
+        }
+        if let (&BigInt::Small(a), &BigInt::Small(b)) = (self, other) {
+            if let (Some(q), Some(r)) = (a.checked_div(b), a.checked_rem(b)) {
+                if r != 0 && ((r < 0) != (b < 0)) {
+                    return Some((BigInt::Small(q - 1), BigInt::Small(r + b)));
+                }
+                return Some((BigInt::Small(q), BigInt::Small(r)));
+            }
+        }
+        let (a_neg, a_mag) = self.to_parts();
+        let (b_neg, b_mag) = other.to_parts();
+        let (q_mag, r_mag) = mag_divmod(&a_mag, &b_mag);
+        let q = BigInt::from_parts(a_neg != b_neg, q_mag);
+        let r = BigInt::from_parts(a_neg, r_mag);
+        if !r.is_zero() && a_neg != b_neg {
+            Some((q.sub(&BigInt::Small(1)), r.add(other)))
+        } else {
+            Some((q, r))
+        }
+    }
+
+    /// The quotient rounded once to the nearest f64, infinite if it is out
+    /// of range. Returns `None` when `other` is zero.
+    pub fn true_div(&self, other: &BigInt) -> Option<f64> {
+        if other.is_zero() {
+            return None;
+        }
+        if let (&BigInt::Small(a), &BigInt::Small(b)) = (self, other) {
+            // both are exact as doubles, so the float division rounds once
+            if a.unsigned_abs() <= 1 << 53 && b.unsigned_abs() <= 1 << 53 {
+                return Some(a as f64 / b as f64);
+            }
+        }
+        let (a_neg, a_mag) = self.to_parts();
+        let (b_neg, b_mag) = other.to_parts();
+        if a_mag.is_empty() {
+            return Some(if a_neg != b_neg { -0.0 } else { 0.0 });
+        }
+
+        // scale so that the integer quotient has 55 or 56 bits
+        let shift = mag_bits(&b_mag) - mag_bits(&a_mag) + 55;
+        let (q, r) = if shift >= 0 {
+            mag_divmod(&mag_shl(&a_mag, shift as usize), &b_mag)
+        } else {
+            mag_divmod(&a_mag, &mag_shl(&b_mag, -shift as usize))
+        };
+        let q = *q.get(0).unwrap_or(&0) as u64 | (*q.get(1).unwrap_or(&0) as u64) << 32;
+        let bits = 64 - q.leading_zeros() as i64;
+
+        // round half to even to 53 bits, or fewer if the result is subnormal
+        let drop = ::std::cmp::max(bits - 53, shift - 1074).min(bits + 1);
+        let rest = q & ((1 << drop) - 1);
+        let half = 1 << (drop - 1);
+        let mut m = q >> drop;
+        if rest > half || (rest == half && (!r.is_empty() || m & 1 == 1)) {
+            m += 1;
+        }
+        let f = ldexp(m as f64, drop - shift);
+        Some(if a_neg != b_neg { -f } else { f })
+    }
+
+    pub fn pow(&self, mut exp: u64) -> BigInt {
+        let mut base = self.clone();
+        let mut res = BigInt::Small(1);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                res = res.mul(&base);
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = base.mul(&base);
+            }
+        }
+        res
+    }
+
+    /// Parses an optionally signed digit string in the given radix.
+    pub fn parse(s: &str, radix: u32) -> Option<BigInt> {
+        let (negative, digits) = if s.starts_with('-') {
+            (true, &s[1..])
+        } else if s.starts_with('+') {
+            (false, &s[1..])
+        } else {
+            (false, s)
+        };
+        if digits.is_empty() {
+            return None;
+        }
+
+        let mut mag: Vec<u32> = vec![];
+        for ch in digits.chars() {
+            let d = ch.to_digit(radix)?;
+            let mut carry = d as u64;
+            for w in mag.iter_mut() {
+                let t = *w as u64 * radix as u64 + carry;
+                *w = t as u32;
+                carry = t >> 32;
+            }
+            if carry > 0 {
+                mag.push(carry as u32);
+            }
+        }
+        Some(BigInt::from_parts(negative, mag))
+    }
+
+    pub fn to_str_radix(&self, radix: u32) -> String {
+        if let &BigInt::Small(n) = self {
+            if radix == 10 {
+                return n.to_string();
+            }
+        }
+        let (negative, mut mag) = self.to_parts();
+        let mut digits = vec![];
+        while !mag.is_empty() {
+            let (q, r) = mag_divmod_small(&mag, radix);
+            digits.push(::std::char::from_digit(r, radix).expect("Never fails"));
+            mag = q;
+        }
+        if digits.is_empty() {
+            digits.push('0');
+        }
+        if negative {
+            digits.push('-');
+        }
+        digits.into_iter().rev().collect()
+    }
+
+    /// Nearest f64; infinite if the value is out of range.
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            &BigInt::Small(n) => n as f64,
+            // the decimal parser rounds correctly
+            &BigInt::Large(_, _) => self.to_string().parse::<f64>().expect("Never fails"),
+        }
+    }
+
+    /// Truncates a finite f64 toward zero.
+    pub fn from_f64(f: f64) -> BigInt {
+        let f = f.trunc();
+        if f.abs() < 9.2e18 {
+            return BigInt::Small(f as i64);
+        }
+        let bits = f.to_bits();
+        let exp = ((bits >> 52) & 0x7ff) as usize;
+        let mantissa = (bits & ((1 << 52) - 1)) | (1 << 52);
+        BigInt::from_parts(f < 0.0, mag_shl(&mag_from_u64(mantissa), exp - 1075))
+    }
+
+    /// The value reduced modulo 2**61 - 1 with the sign kept, as CPython
+    /// hashes ints.
+    pub fn hash(&self) -> i64 {
+        let (negative, mag) = self.to_parts();
+        let mut acc = 0u128;
+        for &w in mag.iter().rev() {
+            acc = ((acc << 32) | w as u128) % HASH_MODULUS as u128;
+        }
+        let h = if negative { -(acc as i64) } else { acc as i64 };
+        if h == -1 { -2 } else { h }
+    }
+}
+
+impl From<i64> for BigInt {
+    fn from(n: i64) -> BigInt {
+        BigInt::Small(n)
+    }
+}
+
+impl From<i32> for BigInt {
+    fn from(n: i32) -> BigInt {
+        BigInt::Small(n as i64)
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &BigInt) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &BigInt) -> Ordering {
+        if let (&BigInt::Small(a), &BigInt::Small(b)) = (self, other) {
+            return a.cmp(&b);
+        }
+        let (a_neg, a_mag) = self.to_parts();
+        let (b_neg, b_mag) = other.to_parts();
+        match (a_neg, b_neg) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => mag_cmp(&a_mag, &b_mag),
+            (true, true) => mag_cmp(&b_mag, &a_mag),
+        }
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_str_radix(10))
+    }
+}
@@ -1,16 +1,27 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
+use eval::PyRes;
 use object::{PyObject, PyInnerObject};
+use object::bigint::BigInt;
 use object::longobj::{PyLongObject, PY_LONG_TYPE};
 use object::typeobj::{PyTypeObject, PY_TYPE_TYPE};
 
+fn pybool_repr(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    if PY_TRUE.with(|obj| { v == *obj }) {
+        Ok(PyObject::from_str("True"))
+    } else {
+        Ok(PyObject::from_str("False"))
+    }
+}
+
 thread_local! (
     pub static PY_BOOL_TYPE: Rc<PyObject> = {
         let booltp = PY_LONG_TYPE.with(|longtp| {
             PyTypeObject {
                 tp_name: "bool".to_string(),
                 tp_base: Some(Rc::clone(longtp)),
+                tp_repr: Some(Rc::new(pybool_repr)),
                 ..Default::default()
             }
         });
@@ -23,7 +34,7 @@ thread_local! (
 
     pub static PY_TRUE: Rc<PyObject> = {
         PY_BOOL_TYPE.with(|tp| {
-            let inner = PyLongObject { n: 1 };
+            let inner = PyLongObject { n: BigInt::from(1) };
             Rc::new(PyObject {
                 ob_type: Some(Rc::clone(tp)),
                 ob_dict: None,
@@ -34,7 +45,7 @@ thread_local! (
 
     pub static PY_FALSE: Rc<PyObject> = {
         PY_BOOL_TYPE.with(|tp| {
-            let inner = PyLongObject { n: 0 };
+            let inner = PyLongObject { n: BigInt::from(0) };
             Rc::new(PyObject {
                 ob_type: Some(Rc::clone(tp)),
                 ob_dict: None,
@@ -0,0 +1,172 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use error::*;
+use eval::PyRes;
+use object::{PyObject, PyInnerObject};
+use object::excobj::*;
+use object::generic::*;
+use object::typeobj::*;
+
+fn pybytes_add(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    match (&lv.inner, &rv.inner) {
+        (&PyInnerObject::BytesObj(ref l_obj), &PyInnerObject::BytesObj(ref r_obj)) => {
+            let mut b = l_obj.b.clone();
+            b.extend(&r_obj.b);
+            Ok(PyObject::from_bytes(b))
+        },
+        _ => Ok(PyObject::notimpl_obj()),
+    }
+}
+
+fn pybytes_richcmp<F>(lv: Rc<PyObject>, rv: Rc<PyObject>, f: F) -> PyRes<Rc<PyObject>>
+where F: Fn(&Vec<u8>, &Vec<u8>) -> bool {
+    match (&lv.inner, &rv.inner) {
+        (&PyInnerObject::BytesObj(ref l_obj), &PyInnerObject::BytesObj(ref r_obj)) => {
+            Ok(PyObject::from_bool(f(&l_obj.b, &r_obj.b)))
+        },
+        _ => Ok(PyObject::notimpl_obj()),
+    }
+}
+
+fn pybytes_eq(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pybytes_richcmp(lv, rv, |l, r| l == r)
+}
+
+fn pybytes_ne(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pybytes_richcmp(lv, rv, |l, r| l != r)
+}
+
+fn pybytes_lt(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pybytes_richcmp(lv, rv, |l, r| l < r)
+}
+
+fn pybytes_le(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pybytes_richcmp(lv, rv, |l, r| l <= r)
+}
+
+fn pybytes_gt(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pybytes_richcmp(lv, rv, |l, r| l > r)
+}
+
+fn pybytes_ge(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pybytes_richcmp(lv, rv, |l, r| l >= r)
+}
+
+fn pybytes_hash(obj: Rc<PyObject>) -> PyRes<u64> {
+    let mut hasher = DefaultHasher::new();
+    obj.pybytes_bytes().hash(&mut hasher);
+    Ok(hasher.finish())
+}
+
+fn pybytes_len(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    Ok(PyObject::from_i32(v.pybytes_bytes().len() as i32))
+}
+
+fn pybytes_bool(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    Ok(PyObject::from_bool(!v.pybytes_bytes().is_empty()))
+}
+
+fn pybytes_repr(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    let b = v.pybytes_bytes();
+    let quote = if b.contains(&b'\'') && !b.contains(&b'"') { b'"' } else { b'\'' };
+    let mut repr = String::from("b");
+    repr.push(quote as char);
+    for &byte in &b {
+        match byte {
+            b'\\' => repr.push_str("\\\\"),
+            b'\n' => repr.push_str("\\n"),
+            b'\r' => repr.push_str("\\r"),
+            b'\t' => repr.push_str("\\t"),
+            byte if byte == quote => { repr.push('\\'); repr.push(byte as char) },
+            0x20 ..= 0x7e => repr.push(byte as char),
+            byte => repr.push_str(&format!("\\x{:02x}", byte)),
+        }
+    }
+    repr.push(quote as char);
+    Ok(PyObject::from_string(repr))
+}
+
+// an int is looked up as a byte, bytes as a subsequence
+fn pybytes_contains(v: Rc<PyObject>, item: Rc<PyObject>) -> PyRes<bool> {
+    let b = v.pybytes_bytes();
+    match item.inner {
+        PyInnerObject::BytesObj(ref sub) => {
+            Ok(sub.b.is_empty() || b.windows(sub.b.len()).any(|w| w == &sub.b[..]))
+        },
+        PyInnerObject::LongObj(_) => {
+            let byte = pyobj_to_i32(item)?;
+            if byte < 0 || byte > 255 {
+                pyerr_set_string(PY_VALUEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                                 "byte must be in range(0, 256)");
+                return Err(());
+            }
+            Ok(b.contains(&(byte as u8)))
+        },
+        _ => {
+            pyerr_set_string(
+                PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                "a bytes-like object is required"
+            );
+            Err(())
+        }
+    }
+}
+
+thread_local! (
+    pub static PY_BYTES_TYPE: Rc<PyObject> = {
+        let bytestp = PyTypeObject {
+            tp_name: "bytes".to_string(),
+            tp_hash: Some(Rc::new(pybytes_hash)),
+            tp_bool: Some(Rc::new(pybytes_bool)),
+            tp_fun_eq: Some(Rc::new(pybytes_eq)),
+            tp_fun_ne: Some(Rc::new(pybytes_ne)),
+            tp_fun_lt: Some(Rc::new(pybytes_lt)),
+            tp_fun_le: Some(Rc::new(pybytes_le)),
+            tp_fun_gt: Some(Rc::new(pybytes_gt)),
+            tp_fun_ge: Some(Rc::new(pybytes_ge)),
+            tp_fun_add: Some(Rc::new(pybytes_add)),
+            tp_len: Some(Rc::new(pybytes_len)),
+            tp_repr: Some(Rc::new(pybytes_repr)),
+            tp_contains: Some(Rc::new(pybytes_contains)),
+            ..Default::default()
+        };
+        Rc::new(PyObject {
+            ob_type: PY_TYPE_TYPE.with(|tp| { Some(Rc::clone(tp)) }),
+            ob_dict: None,
+            inner: PyInnerObject::TypeObj(Rc::new(RefCell::new(bytestp))),
+        })
+    }
+);
+
+pub struct PyBytesObject {
+    pub b: Vec<u8>,
+}
+
+impl PyObject {
+    pub fn from_bytes(b: Vec<u8>) -> Rc<PyObject> {
+        PY_BYTES_TYPE.with(|tp| {
+            Rc::new(PyObject {
+                ob_type: Some(Rc::clone(&tp)),
+                ob_dict: None,
+                inner: PyInnerObject::BytesObj(Rc::new(PyBytesObject { b: b }))
+            })
+        })
+    }
+
+    pub fn pybytes_check(&self) -> bool {
+        match self.inner {
+            PyInnerObject::BytesObj(_) => true,
+            _ => false,
+        }
+    }
+
+    pub fn pybytes_bytes(&self) -> Vec<u8> {
+        match self.inner {
+            PyInnerObject::BytesObj(ref obj) => obj.b.clone(),
+            _ => panic!("Type Error: pybytes_bytes")
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use error::*;
+use eval::PyRes;
+use object::{PyObject, PyInnerObject};
+use object::excobj::*;
+use object::generic::*;
+use object::typeobj::*;
+
+thread_local! (
+    pub static PY_CELL_TYPE: Rc<PyObject> = {
+        let celltp = PyTypeObject {
+            tp_name: "cell".to_string(),
+            tp_hash: Some(Rc::new(default_hash)),
+            tp_getattro: Some(Rc::new(pycell_getattro)),
+            ..Default::default()
+        };
+        Rc::new(PyObject {
+            ob_type: PY_TYPE_TYPE.with(|tp| { Some(Rc::clone(tp)) }),
+            ob_dict: None,
+            inner: PyInnerObject::TypeObj(Rc::new(RefCell::new(celltp))),
+        })
+    }
+);
+
+/// The home of a variable that nested functions share with the function
+/// binding it. None until the variable is first assigned.
+pub struct PyCellObject {
+    ob_ref: RefCell<Option<Rc<PyObject>>>,
+}
+
+fn pycell_getattro(cell: Rc<PyObject>, key: Rc<PyObject>) -> PyRes<Option<Rc<PyObject>>> {
+    match &pyobj_to_string(Rc::clone(&key))?[..] {
+        "cell_contents" => match cell.pycell_get() {
+            Some(v) => Ok(Some(v)),
+            None => {
+                pyerr_set_string(PY_VALUEERROR_TYPE.with(|tp| Rc::clone(tp)), "Cell is empty");
+                Err(())
+            },
+        },
+        _ => pyobj_generic_get_attro(cell, key),
+    }
+}
+
+impl PyObject {
+    pub fn pycell_new(v: Option<Rc<PyObject>>) -> Rc<PyObject> {
+        Rc::new(PyObject {
+            ob_type: PY_CELL_TYPE.with(|tp| { Some(Rc::clone(tp)) }),
+            ob_dict: None,
+            inner: PyInnerObject::CellObj(Rc::new(PyCellObject { ob_ref: RefCell::new(v) })),
+        })
+    }
+
+    fn pycell_inner(&self) -> &PyCellObject {
+        match self.inner {
+            PyInnerObject::CellObj(ref cell) => cell,
+            _ => panic!("Type Error: pycell_inner"),
+        }
+    }
+
+    pub fn pycell_get(&self) -> Option<Rc<PyObject>> {
+        self.pycell_inner().ob_ref.borrow().clone()
+    }
+
+    pub fn pycell_set(&self, v: Rc<PyObject>) {
+        *self.pycell_inner().ob_ref.borrow_mut() = Some(v);
+    }
+
+    pub fn pycell_clear(&self) {
+        *self.pycell_inner().ob_ref.borrow_mut() = None;
+    }
+}
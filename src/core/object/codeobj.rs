@@ -1,15 +1,19 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use syntax::Id;
-use opcode::Code;
+use eval::PyRes;
+use syntax::{Id, Line};
+use opcode::{Code, Opcode};
 use object::{PyObject, PyInnerObject};
-use object::typeobj::{PyTypeObject, PY_TYPE_TYPE};
+use object::generic::*;
+use object::typeobj::*;
 
 thread_local! (
     pub static PY_CODE_TYPE: Rc<PyObject> = {
         let codetp =  PyTypeObject {
             tp_name: "code".to_string(),
+            tp_hash: Some(Rc::new(default_hash)),
+            tp_getattro: Some(Rc::new(pycode_getattro)),
             ..Default::default()
         };
         Rc::new(PyObject {
@@ -20,23 +24,107 @@ thread_local! (
     };
 );
 
+/// The parameter names of a function, in the order their locals are bound:
+/// `args`, then `kwonly`, then `vararg` and `kwarg` when present.
+#[derive(Clone, Default)]
+pub struct ArgSpec {
+    pub args: Vec<Id>,
+    /// how many of `args` are positional-only
+    pub posonly: usize,
+    pub vararg: Option<Id>,
+    pub kwonly: Vec<Id>,
+    pub kwarg: Option<Id>,
+}
+
 pub struct PyCodeObject {
     co_code: Code,
-    co_argnames: Vec<Id>,
+    co_argspec: ArgSpec,
+    co_name: String,
+    co_filename: String,
+    co_firstlineno: Line,
+    co_lines: Vec<Line>,
+    co_generator: bool,
+    /// the locals nested functions use, kept in cells
+    co_cellvars: Vec<Id>,
+    /// the variables of enclosing functions, in the order of the closure
+    co_freevars: Vec<Id>,
+}
+
+fn pycode_getattro(code: Rc<PyObject>, key: Rc<PyObject>) -> PyRes<Option<Rc<PyObject>>> {
+    let obj = match code.inner {
+        PyInnerObject::CodeObj(ref obj) => obj,
+        _ => panic!("Type Error: pycode_getattro")
+    };
+    match &pyobj_to_string(Rc::clone(&key))?[..] {
+        "co_name" => Ok(Some(PyObject::from_str(&obj.co_name))),
+        "co_filename" => Ok(Some(PyObject::from_str(&obj.co_filename))),
+        "co_firstlineno" => Ok(Some(PyObject::from_i32(obj.co_firstlineno as i32))),
+        "co_cellvars" => Ok(Some(names_tuple(&obj.co_cellvars))),
+        "co_freevars" => Ok(Some(names_tuple(&obj.co_freevars))),
+        _ => Ok(None),
+    }
+}
+
+fn names_tuple(names: &Vec<Id>) -> Rc<PyObject> {
+    PyObject::pytuple_from_vec(names.iter().map(|name| PyObject::from_str(name)).collect())
+}
+
+// each instruction belongs to the statement of the closest SetLineno before it
+fn line_table(code: &Code) -> Vec<Line> {
+    let mut line = 0;
+    code.iter().map(|op| {
+        if let &Opcode::SetLineno(l) = op {
+            line = l;
+        }
+        line
+    }).collect()
 }
 
 impl PyObject {
-    pub fn pycode_new(code: Code, argnames: Vec<Id>) -> Rc<PyObject> {
+    pub fn pycode_new(code: Code, argspec: ArgSpec, cellvars: Vec<Id>, freevars: Vec<Id>,
+                      name: &str, filename: &str, firstlineno: Line) -> Rc<PyObject> {
         Rc::new(PyObject {
             ob_type: PY_CODE_TYPE.with(|tp| { Some(Rc::clone(tp)) }),
             ob_dict: None,
             inner: PyInnerObject::CodeObj(Rc::new(PyCodeObject {
+                co_lines: line_table(&code),
+                // nested functions are constants, so a yield here is our own
+                co_generator: code.iter().any(|op| match op {
+                    &Opcode::YieldValue | &Opcode::YieldFrom => true,
+                    _ => false,
+                }),
                 co_code: code,
-                co_argnames: argnames,
+                co_argspec: argspec,
+                co_cellvars: cellvars,
+                co_freevars: freevars,
+                co_name: name.to_string(),
+                co_filename: filename.to_string(),
+                co_firstlineno: firstlineno,
             }))
         })
     }
 
+    pub fn pycode_name(self: &Rc<PyObject>) -> String {
+        match self.inner {
+            PyInnerObject::CodeObj(ref obj) => obj.co_name.clone(),
+            _ => panic!("Type Error: pycode_name")
+        }
+    }
+
+    pub fn pycode_filename(self: &Rc<PyObject>) -> String {
+        match self.inner {
+            PyInnerObject::CodeObj(ref obj) => obj.co_filename.clone(),
+            _ => panic!("Type Error: pycode_filename")
+        }
+    }
+
+    pub fn pycode_line(self: &Rc<PyObject>, pc: usize) -> Line {
+        match self.inner {
+            PyInnerObject::CodeObj(ref obj) => obj.co_lines.get(pc).cloned().unwrap_or(0),
+            _ => panic!("Type Error: pycode_line")
+        }
+    }
+
     pub fn pycode_code(self: &Rc<PyObject>) -> Code {
         match self.inner {
             PyInnerObject::CodeObj(ref obj) => obj.co_code.clone(),
@@ -44,10 +132,32 @@ impl PyObject {
         }
     }
 
-    pub fn pycode_argnames(self: &Rc<PyObject>) -> Vec<Id> {
+    /// Whether calling the function makes a generator instead of running it.
+    pub fn pycode_is_generator(self: &Rc<PyObject>) -> bool {
+        match self.inner {
+            PyInnerObject::CodeObj(ref obj) => obj.co_generator,
+            _ => panic!("Type Error: pycode_is_generator")
+        }
+    }
+
+    pub fn pycode_argspec(self: &Rc<PyObject>) -> &ArgSpec {
+        match self.inner {
+            PyInnerObject::CodeObj(ref obj) => &obj.co_argspec,
+            _ => panic!("Type Error: pycode_argspec")
+        }
+    }
+
+    pub fn pycode_cellvars(self: &Rc<PyObject>) -> &Vec<Id> {
+        match self.inner {
+            PyInnerObject::CodeObj(ref obj) => &obj.co_cellvars,
+            _ => panic!("Type Error: pycode_cellvars")
+        }
+    }
+
+    pub fn pycode_freevars(self: &Rc<PyObject>) -> &Vec<Id> {
         match self.inner {
-            PyInnerObject::CodeObj(ref obj) => obj.co_argnames.clone(),
-            _ => panic!("Type Error: pycode_argnames")
+            PyInnerObject::CodeObj(ref obj) => &obj.co_freevars,
+            _ => panic!("Type Error: pycode_freevars")
         }
     }
 }
@@ -0,0 +1,333 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use error::*;
+use eval::PyRes;
+use object::{PyObject, PyInnerObject};
+use object::excobj::*;
+use object::floatobj::{float_repr, hash_f64};
+use object::generic::*;
+use object::rustfunobj::*;
+use object::typeobj::*;
+
+type Complex = (f64, f64);
+
+fn zero_division<T>(msg: &str) -> PyRes<T> {
+    pyerr_set_string(PY_ZERODIVERROR_TYPE.with(|tp| Rc::clone(tp)), msg);
+    Err(())
+}
+
+fn complex_mul((a, b): Complex, (c, d): Complex) -> Complex {
+    (a * c - b * d, a * d + b * c)
+}
+
+// Smith's algorithm, which avoids overflow in the squared divisor
+fn complex_div((a, b): Complex, (c, d): Complex) -> PyRes<Complex> {
+    if c == 0.0 && d == 0.0 {
+        return zero_division("complex division by zero");
+    }
+    if c.abs() >= d.abs() {
+        let ratio = d / c;
+        let denom = c + d * ratio;
+        Ok(((a + b * ratio) / denom, (b - a * ratio) / denom))
+    } else {
+        let ratio = c / d;
+        let denom = c * ratio + d;
+        Ok(((a * ratio + b) / denom, (b * ratio - a) / denom))
+    }
+}
+
+// small integral exponents are done by repeated squaring, which is exact
+// where the polar form is not
+fn complex_pow(base: Complex, exp: Complex) -> PyRes<Complex> {
+    if exp == (0.0, 0.0) {
+        return Ok((1.0, 0.0));
+    }
+    if base == (0.0, 0.0) {
+        if exp.1 != 0.0 || exp.0 < 0.0 {
+            return zero_division("0.0 to a negative or complex power");
+        }
+        return Ok((0.0, 0.0));
+    }
+    if exp.1 == 0.0 && exp.0.fract() == 0.0 && exp.0.abs() <= 100.0 {
+        let mut n = exp.0.abs() as u32;
+        let mut acc = (1.0, 0.0);
+        let mut sq = base;
+        while n > 0 {
+            if n & 1 == 1 {
+                acc = complex_mul(acc, sq);
+            }
+            sq = complex_mul(sq, sq);
+            n >>= 1;
+        }
+        return if exp.0 < 0.0 { complex_div((1.0, 0.0), acc) } else { Ok(acc) };
+    }
+    let vabs = base.0.hypot(base.1);
+    let arg = base.1.atan2(base.0);
+    let mut len = vabs.powf(exp.0);
+    let mut phase = arg * exp.0;
+    if exp.1 != 0.0 {
+        len /= (arg * exp.1).exp();
+        phase += exp.1 * vabs.ln();
+    }
+    Ok((len * phase.cos(), len * phase.sin()))
+}
+
+// ints and floats take part in complex arithmetic with a zero imaginary part
+fn pycomplex_binop<F>(lv: Rc<PyObject>, rv: Rc<PyObject>, name: &str, f: F) -> PyRes<Rc<PyObject>>
+where F: Fn(Complex, Complex) -> PyRes<Complex> {
+    match (lv.pycomplex_as_parts()?, rv.pycomplex_as_parts()?) {
+        (Some(l), Some(r)) => {
+            let (re, im) = f(l, r)?;
+            Ok(PyObject::from_complex(re, im))
+        },
+        _ => {
+            pyerr_set_string(
+                PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                &format!("{} expects complex objects", name)
+            );
+            Err(())
+        }
+    }
+}
+
+pub fn pycomplex_add(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pycomplex_binop(lv, rv, "__add__", |l, r| Ok((l.0 + r.0, l.1 + r.1)))
+}
+
+pub fn pycomplex_sub(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pycomplex_binop(lv, rv, "__sub__", |l, r| Ok((l.0 - r.0, l.1 - r.1)))
+}
+
+pub fn pycomplex_mul(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pycomplex_binop(lv, rv, "__mul__", |l, r| Ok(complex_mul(l, r)))
+}
+
+pub fn pycomplex_truediv(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pycomplex_binop(lv, rv, "__truediv__", complex_div)
+}
+
+pub fn pycomplex_pow(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pycomplex_binop(lv, rv, "__pow__", complex_pow)
+}
+
+fn pycomplex_floor(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    let _ = (lv, rv);
+    pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                     "can't take floor or mod of complex number.");
+    Err(())
+}
+
+/// The complex counterpart of an arithmetic slot, for ints and floats whose
+/// other operand is complex.
+pub fn pycomplex_binop_by_name(lv: Rc<PyObject>, rv: Rc<PyObject>, name: &str) -> PyRes<Rc<PyObject>> {
+    match name {
+        "__add__" => pycomplex_add(lv, rv),
+        "__sub__" => pycomplex_sub(lv, rv),
+        "__mul__" => pycomplex_mul(lv, rv),
+        "__truediv__" => pycomplex_truediv(lv, rv),
+        "__pow__" => pycomplex_pow(lv, rv),
+        _ => pycomplex_floor(lv, rv),
+    }
+}
+
+fn pycomplex_neg(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    let (re, im) = v.pycomplex_parts();
+    Ok(PyObject::from_complex(-re, -im))
+}
+
+fn pycomplex_pos(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    let (re, im) = v.pycomplex_parts();
+    Ok(PyObject::from_complex(re, im))
+}
+
+fn pycomplex_richcmp<F>(lv: Rc<PyObject>, rv: Rc<PyObject>, f: F) -> PyRes<Rc<PyObject>>
+where F: Fn(Complex, Complex) -> bool {
+    match (lv.pycomplex_as_parts(), rv.pycomplex_as_parts()) {
+        (Ok(Some(l)), Ok(Some(r))) => Ok(PyObject::from_bool(f(l, r))),
+        (Err(()), _) | (_, Err(())) => {
+            // an int too large for a float equals no complex number
+            pyerr_clear();
+            Ok(PyObject::notimpl_obj())
+        },
+        _ => Ok(PyObject::notimpl_obj()),
+    }
+}
+
+fn pycomplex_eq(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pycomplex_richcmp(lv, rv, |l, r| l == r)
+}
+
+fn pycomplex_ne(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pycomplex_richcmp(lv, rv, |l, r| l != r)
+}
+
+// agrees with the hash of an equal int or float
+fn pycomplex_hash(v: Rc<PyObject>) -> PyRes<u64> {
+    let (re, im) = v.pycomplex_parts();
+    Ok(hash_f64(re).wrapping_add(hash_f64(im).wrapping_mul(1000003)) as u64)
+}
+
+fn pycomplex_bool(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    let (re, im) = v.pycomplex_parts();
+    Ok(PyObject::from_bool(re != 0.0 || im != 0.0))
+}
+
+// a part of the repr, where integral values drop their ".0"
+fn part_repr(f: f64) -> String {
+    let s = float_repr(f);
+    if s.ends_with(".0") { s[..s.len() - 2].to_string() } else { s }
+}
+
+fn pycomplex_repr(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    let (re, im) = v.pycomplex_parts();
+    let repr = if re == 0.0 && !re.is_sign_negative() {
+        format!("{}j", part_repr(im))
+    } else {
+        let sign = if im.is_sign_negative() && !im.is_nan() { "-" } else { "+" };
+        format!("({}{}{}j)", part_repr(re), sign, part_repr(im.abs()))
+    };
+    Ok(PyObject::from_string(repr))
+}
+
+fn pycomplex_getattro(v: Rc<PyObject>, key: Rc<PyObject>) -> PyRes<Option<Rc<PyObject>>> {
+    let (re, im) = v.pycomplex_parts();
+    match &pyobj_to_string(Rc::clone(&key))?[..] {
+        "real" => Ok(Some(PyObject::from_f64(re))),
+        "imag" => Ok(Some(PyObject::from_f64(im))),
+        _ => pyobj_generic_get_attro(v, key),
+    }
+}
+
+fn pycomplex_conjugate(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    let (re, im) = v.pycomplex_parts();
+    Ok(PyObject::from_complex(re, -im))
+}
+
+fn malformed_string<T>() -> PyRes<T> {
+    pyerr_set_string(PY_VALUEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                     "complex() arg is a malformed string");
+    Err(())
+}
+
+// "re", "imj" or "re+imj", optionally in parentheses
+fn parse_complex(s: &str) -> PyRes<Complex> {
+    let s = s.trim();
+    let s = if s.starts_with('(') && s.ends_with(')') { s[1..s.len() - 1].trim() } else { s };
+    if !s.ends_with('j') && !s.ends_with('J') {
+        return s.parse::<f64>().map(|re| (re, 0.0)).or_else(|_| malformed_string());
+    }
+    let body = &s[..s.len() - 1];
+    // the sign of the imaginary part, skipping that of the real part and exponents
+    let split = body.char_indices()
+        .filter(|&(i, ch)| (ch == '+' || ch == '-') && i > 0 && !body[..i].ends_with(|c| c == 'e' || c == 'E'))
+        .map(|(i, _)| i)
+        .last();
+    let (re, im) = match split {
+        Some(i) => (&body[..i], &body[i..]),
+        None => ("", body),
+    };
+    let re = if re.is_empty() { 0.0 } else { re.parse::<f64>().or_else(|_| malformed_string())? };
+    let im = match im {
+        "" | "+" => 1.0,
+        "-" => -1.0,
+        im => im.parse::<f64>().or_else(|_| malformed_string())?,
+    };
+    Ok((re, im))
+}
+
+fn pycomplex_new(_typ: Rc<PyObject>, args: &Vec<Rc<PyObject>>, kwargs: Option<Rc<PyObject>>) -> PyRes<Rc<PyObject>> {
+    check_no_kwargs("complex", &kwargs)?;
+    check_arg_count("complex", args, 0, 2)?;
+    let mut parts = vec![];
+    for (i, arg) in args.iter().enumerate() {
+        if let Some(part) = arg.pycomplex_as_parts()? {
+            parts.push(part);
+        } else if i == 0 && args.len() == 1 && arg.pystr_check() {
+            parts.push(parse_complex(&pyobj_to_string(Rc::clone(arg))?)?);
+        } else {
+            pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                             "complex() argument must be a string or a number");
+            return Err(());
+        }
+    }
+    let (re, im) = match (parts.get(0), parts.get(1)) {
+        (Some(&(a, b)), Some(&(c, d))) => (a - d, b + c),
+        (Some(&part), None) => part,
+        _ => (0.0, 0.0),
+    };
+    Ok(PyObject::from_complex(re, im))
+}
+
+thread_local! (
+    pub static PY_COMPLEX_TYPE: Rc<PyObject> = {
+        let tp_methods = vec![
+            PyObject::pyrustfun_method("conjugate", PyRustFun::MethNoArgs(Rc::new(pycomplex_conjugate))),
+        ];
+        let complextp = PyTypeObject {
+            tp_name: "complex".to_string(),
+            tp_hash: Some(Rc::new(pycomplex_hash)),
+            tp_bool: Some(Rc::new(pycomplex_bool)),
+            tp_repr: Some(Rc::new(pycomplex_repr)),
+            tp_fun_eq: Some(Rc::new(pycomplex_eq)),
+            tp_fun_ne: Some(Rc::new(pycomplex_ne)),
+            tp_fun_add: Some(Rc::new(pycomplex_add)),
+            tp_fun_sub: Some(Rc::new(pycomplex_sub)),
+            tp_fun_mul: Some(Rc::new(pycomplex_mul)),
+            tp_fun_truediv: Some(Rc::new(pycomplex_truediv)),
+            tp_fun_floordiv: Some(Rc::new(pycomplex_floor)),
+            tp_fun_mod: Some(Rc::new(pycomplex_floor)),
+            tp_fun_pow: Some(Rc::new(pycomplex_pow)),
+            tp_fun_neg: Some(Rc::new(pycomplex_neg)),
+            tp_fun_pos: Some(Rc::new(pycomplex_pos)),
+            tp_getattro: Some(Rc::new(pycomplex_getattro)),
+            tp_new: Some(Rc::new(pycomplex_new)),
+            tp_methods: Some(tp_methods),
+            ..Default::default()
+        };
+        Rc::new(PyObject {
+            ob_type: PY_TYPE_TYPE.with(|tp| { Some(Rc::clone(tp)) }),
+            ob_dict: None,
+            inner: PyInnerObject::TypeObj(Rc::new(RefCell::new(complextp))),
+        })
+    }
+);
+
+pub struct PyComplexObject {
+    pub re: f64,
+    pub im: f64,
+}
+
+impl PyObject {
+    pub fn from_complex(re: f64, im: f64) -> Rc<PyObject> {
+        PY_COMPLEX_TYPE.with(|tp| {
+            Rc::new(PyObject {
+                ob_type: Some(Rc::clone(&tp)),
+                ob_dict: None,
+                inner: PyInnerObject::ComplexObj(Rc::new(PyComplexObject { re: re, im: im }))
+            })
+        })
+    }
+
+    pub fn pycomplex_check(&self) -> bool {
+        match self.inner {
+            PyInnerObject::ComplexObj(_) => true,
+            _ => false,
+        }
+    }
+
+    pub fn pycomplex_parts(&self) -> Complex {
+        match self.inner {
+            PyInnerObject::ComplexObj(ref obj) => (obj.re, obj.im),
+            _ => panic!("Type Error: pycomplex_parts")
+        }
+    }
+
+    // ints and floats are promoted with a zero imaginary part
+    pub fn pycomplex_as_parts(&self) -> PyRes<Option<Complex>> {
+        match self.inner {
+            PyInnerObject::ComplexObj(ref obj) => Ok(Some((obj.re, obj.im))),
+            _ => Ok(self.pyfloat_as_f64()?.map(|re| (re, 0.0))),
+        }
+    }
+}
@@ -3,19 +3,44 @@ use std::rc::Rc;
 
 use error::*;
 use eval::PyRes;
+use syntax::CompOp;
 use object::{PyObject, PyInnerObject};
 use object::excobj::*;
 use object::generic::*;
+use object::rustfunobj::*;
 use object::typeobj::{PyTypeObject, PY_TYPE_TYPE};
 use object::pyhashmap::PyHashMap;
 
 
 thread_local! (
     pub static PY_DICT_TYPE: Rc<PyObject> = {
+        let tp_methods = vec![
+            PyObject::pyrustfun_method("keys", PyRustFun::MethNoArgs(Rc::new(PyObject::pydict_keys))),
+            PyObject::pyrustfun_method("values", PyRustFun::MethNoArgs(Rc::new(PyObject::pydict_values))),
+            PyObject::pyrustfun_method("items", PyRustFun::MethNoArgs(Rc::new(PyObject::pydict_items))),
+            PyObject::pyrustfun_method("get", PyRustFun::MethVarArgs(Rc::new(PyObject::pydict_get))),
+            PyObject::pyrustfun_method("pop", PyRustFun::MethVarArgs(Rc::new(PyObject::pydict_pop))),
+            PyObject::pyrustfun_method("popitem", PyRustFun::MethNoArgs(Rc::new(PyObject::pydict_popitem))),
+            PyObject::pyrustfun_method("setdefault", PyRustFun::MethVarArgs(Rc::new(PyObject::pydict_setdefault))),
+            PyObject::pyrustfun_method("update", PyRustFun::MethKeywords(Rc::new(PyObject::pydict_update_method))),
+            PyObject::pyrustfun_method("clear", PyRustFun::MethNoArgs(Rc::new(PyObject::pydict_clear))),
+            PyObject::pyrustfun_method("copy", PyRustFun::MethNoArgs(Rc::new(PyObject::pydict_copy))),
+        ];
         let dicttp = PyTypeObject {
             tp_name: "dict".to_string(),
+            tp_hash: Some(Rc::new(pyobj_unhashable)),
             tp_len: Some(Rc::new(PyObject::pydict_len)),
+            tp_repr: Some(Rc::new(PyObject::pydict_repr)),
+            tp_contains: Some(Rc::new(PyObject::pydict_contains)),
+            tp_getitem: Some(Rc::new(PyObject::pydict_subscript)),
+            tp_setitem: Some(Rc::new(PyObject::pydict_ass_subscript)),
+            tp_delitem: Some(Rc::new(PyObject::pydict_del_subscript)),
+            tp_iter: Some(Rc::new(PyObject::pydict_iter)),
+            tp_fun_eq: Some(Rc::new(pydict_eq)),
+            tp_fun_ne: Some(Rc::new(pydict_ne)),
             tp_getattro: Some(Rc::new(pyobj_generic_get_attro)),
+            tp_methods: Some(tp_methods),
+            tp_new: Some(Rc::new(dict_new)),
             ..Default::default()
         };
         Rc::new(PyObject {
@@ -26,10 +51,48 @@ thread_local! (
     }
 );
 
+fn dict_new(_typ: Rc<PyObject>, args: &Vec<Rc<PyObject>>, kwargs: Option<Rc<PyObject>>) -> PyRes<Rc<PyObject>> {
+    let dictobj = PyObject::pydict_new();
+    Rc::clone(&dictobj).pydict_update_method(args, kwargs)?;
+    Ok(dictobj)
+}
+
 pub struct PyDictObject {
     pub dict: RefCell<PyHashMap>,
 }
 
+// Dicts are equal when they have the same keys mapped to equal values.
+fn pydict_equal(lv: &Rc<PyObject>, rv: &Rc<PyObject>) -> PyRes<bool> {
+    if lv.pydict_size() != rv.pydict_size() {
+        return Ok(false);
+    }
+    for (key, value) in lv.pydict_entries() {
+        match rv.pydict_lookup(key)? {
+            Some(other) => {
+                if !(value == other || pyobj_to_bool(pyobj_richcompare(value, other, CompOp::EqEq)?)?) {
+                    return Ok(false);
+                }
+            },
+            None => return Ok(false),
+        }
+    }
+    Ok(true)
+}
+
+fn pydict_eq(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    if !(lv.pydict_check() && rv.pydict_check()) {
+        return Ok(PyObject::notimpl_obj());
+    }
+    Ok(PyObject::from_bool(pydict_equal(&lv, &rv)?))
+}
+
+fn pydict_ne(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    if !(lv.pydict_check() && rv.pydict_check()) {
+        return Ok(PyObject::notimpl_obj());
+    }
+    Ok(PyObject::from_bool(!pydict_equal(&lv, &rv)?))
+}
+
 impl PyObject {
     pub fn pydict_new() -> Rc<PyObject> {
         let inner =  PyDictObject {
@@ -42,9 +105,9 @@ impl PyObject {
         })
     }
 
-    fn pydict_len(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
-        match v.inner {
-            PyInnerObject::DictObj(ref obj) => Ok(obj.dict.borrow().len()),
+    fn pydict_map(&self) -> PyRes<&RefCell<PyHashMap>> {
+        match self.inner {
+            PyInnerObject::DictObj(ref obj) => Ok(&obj.dict),
             _ => {
                 pyerr_set_string(
                     PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
@@ -54,59 +117,387 @@ impl PyObject {
         }
     }
 
+    pub fn pydict_key_error<T>(key: Rc<PyObject>) -> PyRes<T> {
+        let err = call_func(PY_KEYERROR_TYPE.with(|tp| Rc::clone(tp)), &vec![key])?;
+        pyerr_set(err);
+        Err(())
+    }
+
+    fn pydict_len(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+        Ok(PyObject::from_i32(v.pydict_map()?.borrow().len() as i32))
+    }
+
+    fn pydict_repr(self: Rc<Self>) -> PyRes<Rc<PyObject>> {
+        let items = self.pydict_map()?.borrow().items();
+        let mut reprs = vec![];
+        for (key, value) in items {
+            reprs.push(format!("{}: {}", pyobj_to_string(pyobj_repr(key)?)?,
+                               pyobj_to_string(pyobj_repr(value)?)?));
+        }
+        Ok(PyObject::from_string(format!("{{{}}}", reprs.join(", "))))
+    }
+
+    fn pydict_contains(self: Rc<Self>, key: Rc<PyObject>) -> PyRes<bool> {
+        Ok(self.pydict_lookup(key)?.is_some())
+    }
+
+    fn pydict_subscript(self: Rc<Self>, key: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+        self.pydict_getitem(key)
+    }
+
+    fn pydict_ass_subscript(self: Rc<Self>, key: Rc<PyObject>, value: Rc<PyObject>) -> PyRes<()> {
+        self.pydict_update(key, value)
+    }
+
+    fn pydict_del_subscript(self: Rc<Self>, key: Rc<PyObject>) -> PyRes<()> {
+        self.pydict_delitem(key)
+    }
+
+    pub fn pydict_size(&self) -> usize {
+        match self.inner {
+            PyInnerObject::DictObj(ref obj) => obj.dict.borrow().len(),
+            _ => panic!("Type Error: pydict_size"),
+        }
+    }
+
+    /// The key/value pairs in insertion order.
+    pub fn pydict_entries(&self) -> Vec<(Rc<PyObject>, Rc<PyObject>)> {
+        match self.inner {
+            PyInnerObject::DictObj(ref obj) => obj.dict.borrow().items(),
+            _ => panic!("Type Error: pydict_entries"),
+        }
+    }
+
     pub fn pydict_check(&self) -> bool {
         PY_DICT_TYPE.with(|tp| { (&self.ob_type).as_ref() == Some(tp) })
     }
 
     pub fn pydict_lookup(&self, key: Rc<PyObject>) -> PyRes<Option<Rc<PyObject>>> {
-        let ob_type = key.ob_type();
-        let hash = match ob_type.pytype_typeobj_borrow().tp_hash {
-            Some(ref tp_hash) => tp_hash(Rc::clone(&key))?,
-            None => {
-                pyerr_set_string(
-                    PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
-                    "unhasshable dict key");
+        let hash = pyobj_hash(Rc::clone(&key))?;
+        PyHashMap::get(self.pydict_map()?, hash, &key)
+    }
+
+    pub fn pydict_getitem(&self, key: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+        match self.pydict_lookup(Rc::clone(&key))? {
+            Some(value) => Ok(value),
+            None => PyObject::pydict_key_error(key),
+        }
+    }
+
+    pub fn pydict_update(&self, key: Rc<PyObject>, value: Rc<PyObject>) -> PyRes<()> {
+        let hash = pyobj_hash(Rc::clone(&key))?;
+        PyHashMap::insert(self.pydict_map()?, hash, key, value)
+    }
+
+    pub fn pydict_delitem(&self, key: Rc<PyObject>) -> PyRes<()> {
+        let hash = pyobj_hash(Rc::clone(&key))?;
+        let removed = PyHashMap::remove(self.pydict_map()?, hash, &key)?;
+        match removed {
+            Some(_) => Ok(()),
+            None => PyObject::pydict_key_error(key),
+        }
+    }
+
+    /// Adds the entries of a dict, or of an iterable of key/value pairs.
+    pub fn pydict_merge(&self, other: Rc<PyObject>) -> PyRes<()> {
+        if other.pydict_check() {
+            let items = other.pydict_map()?.borrow().items();
+            for (key, value) in items {
+                self.pydict_update(key, value)?;
+            }
+            return Ok(());
+        }
+
+        let iter = pyobj_get_iter(other)?;
+        let mut i = 0;
+        while let Some(item) = pyobj_iter_next(Rc::clone(&iter))? {
+            let pair_iter = pyobj_get_iter(item)?;
+            let mut pair = vec![];
+            while let Some(elm) = pyobj_iter_next(Rc::clone(&pair_iter))? {
+                pair.push(elm);
+            }
+            if pair.len() != 2 {
+                let msg = format!("dictionary update sequence element #{} has length {}; 2 is required",
+                                  i, pair.len());
+                pyerr_set_string(PY_VALUEERROR_TYPE.with(|tp| Rc::clone(tp)), &msg);
                 return Err(());
             }
+            self.pydict_update(Rc::clone(&pair[0]), Rc::clone(&pair[1]))?;
+            i += 1;
+        }
+        Ok(())
+    }
+
+    fn pydict_keys(self: Rc<Self>) -> PyRes<Rc<PyObject>> {
+        PyObject::pydictview_new(self, DictViewKind::Keys)
+    }
+
+    fn pydict_values(self: Rc<Self>) -> PyRes<Rc<PyObject>> {
+        PyObject::pydictview_new(self, DictViewKind::Values)
+    }
+
+    fn pydict_items(self: Rc<Self>) -> PyRes<Rc<PyObject>> {
+        PyObject::pydictview_new(self, DictViewKind::Items)
+    }
+
+    fn pydict_iter(self: Rc<Self>) -> PyRes<Rc<PyObject>> {
+        PyObject::pydictiter_new(self, DictViewKind::Keys)
+    }
+
+    fn pydict_get(self: Rc<Self>, args: &Vec<Rc<PyObject>>) -> PyRes<Rc<PyObject>> {
+        check_arg_count("get", args, 1, 2)?;
+        match self.pydict_lookup(Rc::clone(&args[0]))? {
+            Some(value) => Ok(value),
+            None => Ok(args.get(1).map_or_else(PyObject::none_obj, Rc::clone)),
+        }
+    }
+
+    fn pydict_pop(self: Rc<Self>, args: &Vec<Rc<PyObject>>) -> PyRes<Rc<PyObject>> {
+        check_arg_count("pop", args, 1, 2)?;
+        let key = Rc::clone(&args[0]);
+        let hash = pyobj_hash(Rc::clone(&key))?;
+        let removed = PyHashMap::remove(self.pydict_map()?, hash, &key)?;
+        match (removed, args.get(1)) {
+            (Some(value), _) => Ok(value),
+            (None, Some(default)) => Ok(Rc::clone(default)),
+            (None, None) => PyObject::pydict_key_error(key),
+        }
+    }
+
+    fn pydict_popitem(self: Rc<Self>) -> PyRes<Rc<PyObject>> {
+        let popped = self.pydict_map()?.borrow_mut().pop_last();
+        match popped {
+            Some((key, value)) => Ok(dict_pair(key, value)),
+            None => {
+                pyerr_set_string(PY_KEYERROR_TYPE.with(|tp| Rc::clone(tp)),
+                                 "popitem(): dictionary is empty");
+                Err(())
+            }
+        }
+    }
+
+    fn pydict_setdefault(self: Rc<Self>, args: &Vec<Rc<PyObject>>) -> PyRes<Rc<PyObject>> {
+        check_arg_count("setdefault", args, 1, 2)?;
+        if let Some(value) = self.pydict_lookup(Rc::clone(&args[0]))? {
+            return Ok(value);
+        }
+        let default = args.get(1).map_or_else(PyObject::none_obj, Rc::clone);
+        self.pydict_update(Rc::clone(&args[0]), Rc::clone(&default))?;
+        Ok(default)
+    }
+
+    fn pydict_update_method(self: Rc<Self>, args: &Vec<Rc<PyObject>>, kwargs: Option<Rc<PyObject>>)
+                            -> PyRes<Rc<PyObject>> {
+        check_arg_count("update", args, 0, 1)?;
+        if let Some(other) = args.get(0) {
+            self.pydict_merge(Rc::clone(other))?;
+        }
+        if let Some(kwargs) = kwargs {
+            self.pydict_merge(kwargs)?;
+        }
+        Ok(PyObject::none_obj())
+    }
+
+    fn pydict_clear(self: Rc<Self>) -> PyRes<Rc<PyObject>> {
+        self.pydict_map()?.borrow_mut().clear();
+        Ok(PyObject::none_obj())
+    }
+
+    fn pydict_copy(self: Rc<Self>) -> PyRes<Rc<PyObject>> {
+        let map = self.pydict_map()?.borrow().clone();
+        Ok(Rc::new(PyObject {
+            ob_type: PY_DICT_TYPE.with(|tp| { Some(Rc::clone(tp)) }),
+            ob_dict: None,
+            inner: PyInnerObject::DictObj(Rc::new(PyDictObject { dict: RefCell::new(map) }))
+        }))
+    }
+}
+
+fn dict_pair(key: Rc<PyObject>, value: Rc<PyObject>) -> Rc<PyObject> {
+    PyObject::pytuple_from_vec(vec![key, value])
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum DictViewKind {
+    Keys,
+    Values,
+    Items,
+}
+
+/// `dict.keys()`, `dict.values()` and `dict.items()`; they read through to
+/// the dict so later updates are visible.
+pub struct PyDictViewObject {
+    dv_dict: Rc<PyObject>,
+    dv_kind: DictViewKind,
+}
+
+macro_rules! dictview_type {
+    ($name:expr, $contains:expr) => {
+        {
+            let viewtp = PyTypeObject {
+                tp_name: $name.to_string(),
+                tp_len: Some(Rc::new(PyObject::pydictview_len)),
+                tp_repr: Some(Rc::new(PyObject::pydictview_repr)),
+                tp_iter: Some(Rc::new(PyObject::pydictview_iter)),
+                tp_contains: $contains,
+                ..Default::default()
+            };
+            Rc::new(PyObject {
+                ob_type: PY_TYPE_TYPE.with(|tp| { Some(Rc::clone(tp)) }),
+                ob_dict: None,
+                inner: PyInnerObject::TypeObj(Rc::new(RefCell::new(viewtp))),
+            })
+        }
+    }
+}
+
+thread_local! (
+    pub static PY_DICTKEYS_TYPE: Rc<PyObject> =
+        dictview_type!("dict_keys", Some(Rc::new(PyObject::pydictview_contains)));
+    pub static PY_DICTVALUES_TYPE: Rc<PyObject> = dictview_type!("dict_values", None);
+    pub static PY_DICTITEMS_TYPE: Rc<PyObject> =
+        dictview_type!("dict_items", Some(Rc::new(PyObject::pydictview_contains)));
+);
+
+impl PyObject {
+    fn pydictview_new(dict: Rc<PyObject>, kind: DictViewKind) -> PyRes<Rc<PyObject>> {
+        dict.pydict_map()?;
+        let typ = match kind {
+            DictViewKind::Keys => PY_DICTKEYS_TYPE.with(|tp| Rc::clone(tp)),
+            DictViewKind::Values => PY_DICTVALUES_TYPE.with(|tp| Rc::clone(tp)),
+            DictViewKind::Items => PY_DICTITEMS_TYPE.with(|tp| Rc::clone(tp)),
         };
+        Ok(Rc::new(PyObject {
+            ob_type: Some(typ),
+            ob_dict: None,
+            inner: PyInnerObject::DictViewObj(Rc::new(PyDictViewObject {
+                dv_dict: dict,
+                dv_kind: kind,
+            }))
+        }))
+    }
 
+    fn pydictview_inner(&self) -> PyRes<&PyDictViewObject> {
         match self.inner {
-            PyInnerObject::DictObj(ref obj) => {
-                match obj.dict.borrow().get(hash) {
-                    Some(v) => Ok(Some(Rc::clone(v))),
-                    None => Ok(None)
-                }
-            },
+            PyInnerObject::DictViewObj(ref view) => Ok(view),
             _ => {
                 pyerr_set_string(
                     PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
-                    "__getitem__ expects dict object");
+                    "dict view object expected");
                 Err(())
             }
         }
     }
 
-    pub fn pydict_update(&self, key: Rc<PyObject>, value: Rc<PyObject>) -> PyRes<()> {
-        let ob_type = key.ob_type();
-        let hash = match ob_type.pytype_typeobj_borrow().tp_hash {
-            Some(ref tp_hash) => tp_hash(Rc::clone(&key))?,
-            None => {
-                pyerr_set_string(
-                    PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
-                    "unhasshable dict key");
-                return Err(());
-            }
+    fn pydictview_len(self: Rc<Self>) -> PyRes<Rc<PyObject>> {
+        PyObject::pydict_len(Rc::clone(&self.pydictview_inner()?.dv_dict))
+    }
+
+    fn pydictview_iter(self: Rc<Self>) -> PyRes<Rc<PyObject>> {
+        let view = self.pydictview_inner()?;
+        PyObject::pydictiter_new(Rc::clone(&view.dv_dict), view.dv_kind)
+    }
+
+    fn pydictview_contains(self: Rc<Self>, item: Rc<PyObject>) -> PyRes<bool> {
+        let view = self.pydictview_inner()?;
+        if view.dv_kind == DictViewKind::Keys {
+            return Ok(view.dv_dict.pydict_lookup(item)?.is_some());
+        }
+
+        let pair = match item.inner {
+            PyInnerObject::TupleObj(_) if item.pytuple_size() == 2 => item.pytuple_items(),
+            _ => return Ok(false),
+        };
+        match view.dv_dict.pydict_lookup(Rc::clone(&pair[0]))? {
+            Some(value) => {
+                Ok(value == pair[1] ||
+                   pyobj_to_bool(pyobj_richcompare(value, Rc::clone(&pair[1]), CompOp::EqEq)?)?)
+            },
+            None => Ok(false),
+        }
+    }
+
+    fn pydictview_repr(self: Rc<Self>) -> PyRes<Rc<PyObject>> {
+        let name = self.ob_type().pytype_typeobj_borrow().tp_name.clone();
+        let iter = Rc::clone(&self).pydictview_iter()?;
+        let mut reprs = vec![];
+        while let Some(elm) = pyobj_iter_next(Rc::clone(&iter))? {
+            reprs.push(pyobj_to_string(pyobj_repr(elm)?)?);
+        }
+        Ok(PyObject::from_string(format!("{}([{}])", name, reprs.join(", "))))
+    }
+}
+
+pub struct PyDictIterObject {
+    it_dict: Rc<PyObject>,
+    it_pos: usize,
+    it_kind: DictViewKind,
+    // size when iteration started, None once a change has been reported
+    it_used: Option<usize>,
+}
+
+thread_local! (
+    pub static PY_DICTITER_TYPE: Rc<PyObject> = {
+        let itertp = PyTypeObject {
+            tp_name: "dict_iterator".to_string(),
+            tp_iternext: Some(Rc::new(PyObject::pydictiter_next)),
+            ..Default::default()
         };
+        Rc::new(PyObject {
+            ob_type: PY_TYPE_TYPE.with(|tp| { Some(Rc::clone(tp)) }),
+            ob_dict: None,
+            inner: PyInnerObject::TypeObj(Rc::new(RefCell::new(itertp))),
+        })
+    }
+);
 
+impl PyObject {
+    fn pydictiter_new(dict: Rc<PyObject>, kind: DictViewKind) -> PyRes<Rc<PyObject>> {
+        let used = dict.pydict_map()?.borrow().len();
+        Ok(Rc::new(PyObject {
+            ob_type: PY_DICTITER_TYPE.with(|tp| { Some(Rc::clone(tp)) }),
+            ob_dict: None,
+            inner: PyInnerObject::DictIterObj(Rc::new(RefCell::new(
+                PyDictIterObject {
+                    it_dict: dict,
+                    it_pos: 0,
+                    it_kind: kind,
+                    it_used: Some(used),
+                }
+            )))
+        }))
+    }
+
+    fn pydictiter_next(self: Rc<PyObject>) -> PyRes<Option<Rc<PyObject>>> {
         match self.inner {
-            PyInnerObject::DictObj(ref obj) => {
-                obj.dict.borrow_mut().insert(hash, key, value);
-                Ok(())
+            PyInnerObject::DictIterObj(ref it) => {
+                let mut it = it.borrow_mut();
+                let map = Rc::clone(&it.it_dict);
+                let map = map.pydict_map()?.borrow();
+                if it.it_used != Some(map.len()) {
+                    it.it_used = None;
+                    pyerr_set_string(PY_RUNTIMEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                                     "dictionary changed size during iteration");
+                    return Err(());
+                }
+                let next = map.next_entry(it.it_pos);
+                match next {
+                    Some((pos, key, value)) => {
+                        it.it_pos = pos;
+                        Ok(Some(match it.it_kind {
+                            DictViewKind::Keys => key,
+                            DictViewKind::Values => value,
+                            DictViewKind::Items => dict_pair(key, value),
+                        }))
+                    },
+                    None => Ok(None),
+                }
             },
             _ => {
                 pyerr_set_string(
                     PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
-                    "__setitem__ expects dict object");
+                    "__next__ expects dict_iterator object"
+                );
                 Err(())
             }
         }
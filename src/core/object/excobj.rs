@@ -1,16 +1,40 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
+use error::*;
 use eval::PyRes;
 use object::{PyObject, PyInnerObject};
 use object::generic::*;
 use object::typeobj::*;
 
+// Exception types that only differ from their base in name
+macro_rules! exception_type {
+    ($name:expr, $base:ident) => {
+        {
+            let exctp = PyTypeObject {
+                tp_name: $name.to_string(),
+                tp_base: $base.with(|tp| { Some(Rc::clone(&tp)) }),
+                tp_hash: Some(Rc::new(default_hash)),
+                ..Default::default()
+            };
+            Rc::new(PyObject {
+                ob_type: PY_TYPE_TYPE.with(|tp| { Some(Rc::clone(&tp)) }),
+                ob_dict: None,
+                inner: PyInnerObject::TypeObj(Rc::new(RefCell::new(exctp))),
+            })
+        }
+    }
+}
+
 thread_local! (
     pub static PY_BASEEXC_TYPE: Rc<PyObject> = {
         let bexctp = PyTypeObject {
             tp_name: "BaseException".to_string(),
             tp_hash: Some(Rc::new(default_hash)),
+            tp_repr: Some(Rc::new(pybaseexc_repr)),
+            tp_str: Some(Rc::new(pybaseexc_str)),
+            tp_getattro: Some(Rc::new(pybaseexc_getattro)),
+            tp_setattro: Some(Rc::new(pybaseexc_setattro)),
             tp_new: Some(Rc::new(pybaseexc_new)),
             ..Default::default()
         };
@@ -35,61 +59,54 @@ thread_local! (
         })
     };
 
-    pub static PY_TYPEERROR_TYPE: Rc<PyObject> = {
-        let exctp = PyTypeObject {
-            tp_name: "TypeError".to_string(),
-            tp_base: PY_EXC_TYPE.with(|tp| { Some(Rc::clone(&tp)) }),
-            tp_hash: Some(Rc::new(default_hash)),
-            ..Default::default()
-        };
-        Rc::new(PyObject {
-            ob_type: PY_TYPE_TYPE.with(|tp| { Some(Rc::clone(&tp)) }),
-            ob_dict: None,
-            inner: PyInnerObject::TypeObj(Rc::new(RefCell::new(exctp))),
-        })
-    };
-
     pub static PY_STOPITERATION_TYPE: Rc<PyObject> = {
-        let exctp = PyTypeObject {
+        let stoptp = PyTypeObject {
             tp_name: "StopIteration".to_string(),
             tp_base: PY_EXC_TYPE.with(|tp| { Some(Rc::clone(&tp)) }),
             tp_hash: Some(Rc::new(default_hash)),
+            tp_getattro: Some(Rc::new(pystopiteration_getattro)),
             ..Default::default()
         };
         Rc::new(PyObject {
             ob_type: PY_TYPE_TYPE.with(|tp| { Some(Rc::clone(&tp)) }),
             ob_dict: None,
-            inner: PyInnerObject::TypeObj(Rc::new(RefCell::new(exctp))),
+            inner: PyInnerObject::TypeObj(Rc::new(RefCell::new(stoptp))),
         })
     };
 
-    pub static PY_ATTRIBUTEERROR_TYPE: Rc<PyObject> = {
-        let exctp = PyTypeObject {
-            tp_name: "AttributeError".to_string(),
-            tp_base: PY_EXC_TYPE.with(|tp| { Some(Rc::clone(&tp)) }),
+    pub static PY_GENERATOREXIT_TYPE: Rc<PyObject> = exception_type!("GeneratorExit", PY_BASEEXC_TYPE);
+    pub static PY_TYPEERROR_TYPE: Rc<PyObject> = exception_type!("TypeError", PY_EXC_TYPE);
+    pub static PY_ATTRIBUTEERROR_TYPE: Rc<PyObject> = exception_type!("AttributeError", PY_EXC_TYPE);
+    pub static PY_ARITHMETICERROR_TYPE: Rc<PyObject> = exception_type!("ArithmeticError", PY_EXC_TYPE);
+    pub static PY_ZERODIVERROR_TYPE: Rc<PyObject> = exception_type!("ZeroDivisionError", PY_ARITHMETICERROR_TYPE);
+    pub static PY_OVERFLOWERROR_TYPE: Rc<PyObject> = exception_type!("OverflowError", PY_ARITHMETICERROR_TYPE);
+    pub static PY_LOOKUPERROR_TYPE: Rc<PyObject> = exception_type!("LookupError", PY_EXC_TYPE);
+    pub static PY_KEYERROR_TYPE: Rc<PyObject> = {
+        let keyerrtp = PyTypeObject {
+            tp_name: "KeyError".to_string(),
+            tp_base: PY_LOOKUPERROR_TYPE.with(|tp| { Some(Rc::clone(&tp)) }),
             tp_hash: Some(Rc::new(default_hash)),
+            tp_str: Some(Rc::new(pykeyerror_str)),
             ..Default::default()
         };
         Rc::new(PyObject {
             ob_type: PY_TYPE_TYPE.with(|tp| { Some(Rc::clone(&tp)) }),
             ob_dict: None,
-            inner: PyInnerObject::TypeObj(Rc::new(RefCell::new(exctp))),
-        })
-    };
-
-    pub static PY_INDEXERROR_TYPE: Rc<PyObject> = {
-        let exctp = PyTypeObject {
-            tp_name: "IndexError".to_string(),
-            tp_base: PY_EXC_TYPE.with(|tp| { Some(Rc::clone(&tp)) }),
-            tp_hash: Some(Rc::new(default_hash)),
-            ..Default::default()
-        };
-        Rc::new(PyObject {
-            ob_type: PY_TYPE_TYPE.with(|tp| { Some(Rc::clone(&tp)) }),
-            ob_dict: None,
-            inner: PyInnerObject::TypeObj(Rc::new(RefCell::new(exctp))),
+            inner: PyInnerObject::TypeObj(Rc::new(RefCell::new(keyerrtp))),
         })
     };
+    pub static PY_INDEXERROR_TYPE: Rc<PyObject> = exception_type!("IndexError", PY_LOOKUPERROR_TYPE);
+    pub static PY_VALUEERROR_TYPE: Rc<PyObject> = exception_type!("ValueError", PY_EXC_TYPE);
+    pub static PY_NAMEERROR_TYPE: Rc<PyObject> = exception_type!("NameError", PY_EXC_TYPE);
+    pub static PY_UNBOUNDLOCALERROR_TYPE: Rc<PyObject> = exception_type!("UnboundLocalError", PY_NAMEERROR_TYPE);
+    pub static PY_RUNTIMEERROR_TYPE: Rc<PyObject> = exception_type!("RuntimeError", PY_EXC_TYPE);
+    pub static PY_RECURSIONERROR_TYPE: Rc<PyObject> = exception_type!("RecursionError", PY_RUNTIMEERROR_TYPE);
+    pub static PY_NOTIMPLEMENTEDERROR_TYPE: Rc<PyObject> = exception_type!("NotImplementedError", PY_RUNTIMEERROR_TYPE);
+    pub static PY_SYNTAXERROR_TYPE: Rc<PyObject> = exception_type!("SyntaxError", PY_EXC_TYPE);
+    pub static PY_INDENTATIONERROR_TYPE: Rc<PyObject> = exception_type!("IndentationError", PY_SYNTAXERROR_TYPE);
+    pub static PY_TABERROR_TYPE: Rc<PyObject> = exception_type!("TabError", PY_INDENTATIONERROR_TYPE);
+    pub static PY_MEMORYERROR_TYPE: Rc<PyObject> = exception_type!("MemoryError", PY_EXC_TYPE);
+    pub static PY_ASSERTIONERROR_TYPE: Rc<PyObject> = exception_type!("AssertionError", PY_EXC_TYPE)
 );
 
 impl PyObject {
@@ -104,12 +121,192 @@ impl PyObject {
 
 pub struct PyExcObject {
     args: Rc<PyObject>,
+    traceback: RefCell<Option<Rc<PyObject>>>,
+    cause: RefCell<Option<Rc<PyObject>>>,
+    context: RefCell<Option<Rc<PyObject>>>,
+    suppress_context: Cell<bool>,
 }
 
-fn pybaseexc_new(exception: Rc<PyObject>, args: &Vec<Rc<PyObject>>) -> PyRes<Rc<PyObject>> {
+fn pybaseexc_new(exception: Rc<PyObject>, args: &Vec<Rc<PyObject>>, _kwargs: Option<Rc<PyObject>>) -> PyRes<Rc<PyObject>> {
     Ok(Rc::new(PyObject {
         ob_type: Some(exception),
-        ob_dict: None,
-        inner: PyInnerObject::ExcObj(Rc::new(PyExcObject { args: PyObject::pylist_from_vec(args) })),
+        ob_dict: Some(PyObject::pydict_new()),
+        inner: PyInnerObject::ExcObj(Rc::new(PyExcObject {
+            args: PyObject::pytuple_from_vec(args.clone()),
+            traceback: RefCell::new(None),
+            cause: RefCell::new(None),
+            context: RefCell::new(None),
+            suppress_context: Cell::new(false),
+        })),
     }))
 }
+
+fn pyexc_args(exc: &Rc<PyObject>) -> Vec<Rc<PyObject>> {
+    match exc.inner {
+        PyInnerObject::ExcObj(ref obj) => obj.args.pytuple_items(),
+        _ => panic!("Type Error: pyexc_args"),
+    }
+}
+
+// the str of the only argument, or of the argument tuple
+fn pybaseexc_str(exc: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    let args = pyexc_args(&exc);
+    match args.len() {
+        0 => Ok(PyObject::from_str("")),
+        1 => pyobj_str(Rc::clone(&args[0])),
+        _ => pyobj_str(PyObject::pytuple_from_vec(args)),
+    }
+}
+
+fn pybaseexc_repr(exc: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    let args = pyexc_args(&exc);
+    let name = exc.ob_type().pytype_typeobj_borrow().tp_name.clone();
+    let args_repr = match args.len() {
+        1 => format!("({})", pyobj_to_string(pyobj_repr(Rc::clone(&args[0]))?)?),
+        _ => pyobj_to_string(pyobj_repr(PyObject::pytuple_from_vec(args))?)?,
+    };
+    Ok(PyObject::from_string(format!("{}{}", name, args_repr)))
+}
+
+// a missing key is shown by its repr, so that KeyError('') is not blank
+fn pykeyerror_str(exc: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    let args = pyexc_args(&exc);
+    if args.len() == 1 {
+        return pyobj_repr(Rc::clone(&args[0]));
+    }
+    pybaseexc_str(exc)
+}
+
+fn none_or(v: Option<Rc<PyObject>>) -> Rc<PyObject> {
+    v.unwrap_or_else(PyObject::none_obj)
+}
+
+fn pybaseexc_getattro(exc: Rc<PyObject>, key: Rc<PyObject>) -> PyRes<Option<Rc<PyObject>>> {
+    let res = match &pyobj_to_string(Rc::clone(&key))?[..] {
+        "args" => match exc.inner {
+            PyInnerObject::ExcObj(ref obj) => Rc::clone(&obj.args),
+            _ => panic!("Type Error: pybaseexc_getattro"),
+        },
+        "__traceback__" => none_or(exc.pyexc_traceback()),
+        "__cause__" => none_or(exc.pyexc_cause()),
+        "__context__" => none_or(exc.pyexc_context()),
+        "__suppress_context__" => PyObject::from_bool(exc.pyexc_suppress_context()),
+        _ => return pyobj_generic_get_attro(exc, key),
+    };
+    Ok(Some(res))
+}
+
+fn pystopiteration_getattro(exc: Rc<PyObject>, key: Rc<PyObject>) -> PyRes<Option<Rc<PyObject>>> {
+    if pyobj_to_string(Rc::clone(&key))? == "value" {
+        return Ok(Some(exc.pystopiteration_value()));
+    }
+    pybaseexc_getattro(exc, key)
+}
+
+// None or an exception instance, as accepted by __cause__ and __context__
+fn exc_or_none(v: Rc<PyObject>, msg: &str) -> PyRes<Option<Rc<PyObject>>> {
+    if v.pynone_check() {
+        Ok(None)
+    } else if PyObject::pyexc_is_exc_instance(Rc::clone(&v)) {
+        Ok(Some(v))
+    } else {
+        pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)), msg);
+        Err(())
+    }
+}
+
+fn pybaseexc_setattro(exc: Rc<PyObject>, key: Rc<PyObject>, rvalue: Rc<PyObject>) -> PyRes<()> {
+    match &pyobj_to_string(Rc::clone(&key))?[..] {
+        "__traceback__" => {
+            if !rvalue.pynone_check() && !rvalue.pytraceback_check() {
+                pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                                 "__traceback__ must be a traceback or None");
+                return Err(());
+            }
+            exc.pyexc_set_traceback(if rvalue.pynone_check() { None } else { Some(rvalue) });
+        },
+        "__cause__" => {
+            let cause = exc_or_none(rvalue, "exception cause must be None or derive from BaseException")?;
+            exc.pyexc_set_cause(cause);
+        },
+        "__context__" => {
+            let context = exc_or_none(rvalue, "exception context must be None or derive from BaseException")?;
+            exc.pyexc_set_context(context);
+        },
+        "__suppress_context__" => {
+            let suppress = pyobj_to_bool(rvalue)?;
+            if let PyInnerObject::ExcObj(ref obj) = exc.inner {
+                obj.suppress_context.set(suppress);
+            }
+        },
+        _ => return pyobj_generic_set_attro(exc, key, rvalue),
+    }
+    Ok(())
+}
+
+impl PyObject {
+    pub fn pyexc_traceback(&self) -> Option<Rc<PyObject>> {
+        match self.inner {
+            PyInnerObject::ExcObj(ref obj) => obj.traceback.borrow().clone(),
+            _ => None,
+        }
+    }
+
+    pub fn pyexc_set_traceback(&self, tb: Option<Rc<PyObject>>) {
+        if let PyInnerObject::ExcObj(ref obj) = self.inner {
+            obj.traceback.replace(tb);
+        }
+    }
+
+    pub fn pyexc_cause(&self) -> Option<Rc<PyObject>> {
+        match self.inner {
+            PyInnerObject::ExcObj(ref obj) => obj.cause.borrow().clone(),
+            _ => None,
+        }
+    }
+
+    /// Setting the cause, even to None, hides the context when printed.
+    pub fn pyexc_set_cause(&self, cause: Option<Rc<PyObject>>) {
+        if let PyInnerObject::ExcObj(ref obj) = self.inner {
+            obj.cause.replace(cause);
+            obj.suppress_context.set(true);
+        }
+    }
+
+    pub fn pyexc_context(&self) -> Option<Rc<PyObject>> {
+        match self.inner {
+            PyInnerObject::ExcObj(ref obj) => obj.context.borrow().clone(),
+            _ => None,
+        }
+    }
+
+    pub fn pyexc_set_context(&self, context: Option<Rc<PyObject>>) {
+        if let PyInnerObject::ExcObj(ref obj) = self.inner {
+            obj.context.replace(context);
+        }
+    }
+
+    pub fn pyexc_suppress_context(&self) -> bool {
+        match self.inner {
+            PyInnerObject::ExcObj(ref obj) => obj.suppress_context.get(),
+            _ => false,
+        }
+    }
+
+    /// What an iterator that raised this StopIteration returned: its first
+    /// argument, or None.
+    pub fn pystopiteration_value(&self) -> Rc<PyObject> {
+        match self.inner {
+            PyInnerObject::ExcObj(ref obj) => {
+                obj.args.pytuple_items().into_iter().next().unwrap_or_else(PyObject::none_obj)
+            },
+            _ => PyObject::none_obj(),
+        }
+    }
+
+    /// The text after the type name in a traceback, which is the str of the
+    /// exception.
+    pub fn pyexc_message(self: Rc<Self>) -> PyRes<String> {
+        pyobj_to_string(pyobj_str(self)?)
+    }
+}
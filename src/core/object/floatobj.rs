@@ -0,0 +1,431 @@
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+use error::*;
+use eval::PyRes;
+use object::{PyObject, PyInnerObject};
+use object::bigint::{BigInt, HASH_MODULUS};
+use object::complexobj::{pycomplex_binop_by_name, pycomplex_pow};
+use object::excobj::*;
+use object::formatter::format_float;
+use object::generic::{check_no_kwargs, pyobj_to_string};
+use object::typeobj::*;
+
+
+thread_local! (
+    pub static PY_FLOAT_TYPE: Rc<PyObject> = {
+        let floattp = PyTypeObject {
+            tp_name: "float".to_string(),
+            tp_hash: Some(Rc::new(pyfloat_hash)),
+            tp_bool: Some(Rc::new(pyfloat_bool)),
+            tp_repr: Some(Rc::new(pyfloat_repr)),
+            tp_format: Some(Rc::new(pyfloat_format)),
+            tp_fun_eq: Some(Rc::new(pyfloat_eq)),
+            tp_fun_ne: Some(Rc::new(pyfloat_ne)),
+            tp_fun_lt: Some(Rc::new(pyfloat_lt)),
+            tp_fun_le: Some(Rc::new(pyfloat_le)),
+            tp_fun_gt: Some(Rc::new(pyfloat_gt)),
+            tp_fun_ge: Some(Rc::new(pyfloat_ge)),
+            tp_fun_add: Some(Rc::new(pyfloat_add)),
+            tp_fun_sub: Some(Rc::new(pyfloat_sub)),
+            tp_fun_mul: Some(Rc::new(pyfloat_mul)),
+            tp_fun_truediv: Some(Rc::new(pyfloat_truediv)),
+            tp_fun_floordiv: Some(Rc::new(pyfloat_floordiv)),
+            tp_fun_mod: Some(Rc::new(pyfloat_mod)),
+            tp_fun_pow: Some(Rc::new(pyfloat_pow)),
+            tp_fun_neg: Some(Rc::new(pyfloat_neg)),
+            tp_fun_pos: Some(Rc::new(pyfloat_pos)),
+            tp_new: Some(Rc::new(pyfloat_new)),
+            ..Default::default()
+        };
+        Rc::new(PyObject {
+            ob_type: PY_TYPE_TYPE.with(|tp| { Some(Rc::clone(&tp)) }),
+            ob_dict: None,
+            inner: PyInnerObject::TypeObj(Rc::new(RefCell::new(floattp))),
+        })
+    }
+);
+
+pub struct PyFloatObject {
+    pub n: f64,
+}
+
+impl PyObject {
+    pub fn from_f64(raw_f64: f64) -> Rc<PyObject> {
+        PY_FLOAT_TYPE.with(|tp| {
+            let inner = PyFloatObject { n: raw_f64 };
+            Rc::new(PyObject {
+                ob_type: Some(Rc::clone(&tp)),
+                ob_dict: None,
+                inner: PyInnerObject::FloatObj(Rc::new(inner))
+            })
+        })
+    }
+
+    pub fn pyfloat_check(&self) -> bool {
+        match self.inner {
+            PyInnerObject::FloatObj(_) => true,
+            _ => false,
+        }
+    }
+
+    // ints are promoted so that mixed arithmetic can be done in f64
+    pub fn pyfloat_as_f64(&self) -> PyRes<Option<f64>> {
+        match self.inner {
+            PyInnerObject::FloatObj(ref obj) => Ok(Some(obj.n)),
+            PyInnerObject::LongObj(ref obj) => Ok(Some(long_to_f64(&obj.n)?)),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Rounds an int to the nearest float, raising OverflowError rather than
+/// giving an infinity.
+pub fn long_to_f64(n: &BigInt) -> PyRes<f64> {
+    let f = n.to_f64();
+    if f.is_infinite() {
+        pyerr_set_string(PY_OVERFLOWERROR_TYPE.with(|tp| Rc::clone(tp)),
+                         "int too large to convert to float");
+        return Err(());
+    }
+    Ok(f)
+}
+
+fn pyfloat_binop<F>(lv: Rc<PyObject>, rv: Rc<PyObject>, name: &str, f: F) -> PyRes<Rc<PyObject>>
+where F: Fn(f64, f64) -> PyRes<f64> {
+    if rv.pycomplex_check() {
+        return pycomplex_binop_by_name(lv, rv, name);
+    }
+    match (lv.pyfloat_as_f64()?, rv.pyfloat_as_f64()?) {
+        (Some(l), Some(r)) => Ok(PyObject::from_f64(f(l, r)?)),
+        _ => {
+            pyerr_set_string(
+                PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                &format!("{} expects float objects", name)
+            );
+            Err(())
+        }
+    }
+}
+
+// Compares an int with a float exactly, as rounding the int to a float
+// would make distinct values equal. None when the float is nan.
+fn long_float_cmp(n: &BigInt, f: f64) -> Option<Ordering> {
+    if f.is_nan() {
+        None
+    } else if f.is_infinite() {
+        Some(if f > 0.0 { Ordering::Less } else { Ordering::Greater })
+    } else if f == f.floor() {
+        Some(n.cmp(&BigInt::from_f64(f)))
+    } else if *n <= BigInt::from_f64(f.floor()) {
+        Some(Ordering::Less)
+    } else {
+        Some(Ordering::Greater)
+    }
+}
+
+// either side may be an int, as ints leave mixed comparisons to floats
+fn pyfloat_richcmp<F>(lv: Rc<PyObject>, rv: Rc<PyObject>, f: F) -> PyRes<Rc<PyObject>>
+where F: Fn(Option<Ordering>) -> bool {
+    let ord = match (&lv.inner, &rv.inner) {
+        (&PyInnerObject::FloatObj(ref l), &PyInnerObject::FloatObj(ref r)) => l.n.partial_cmp(&r.n),
+        (&PyInnerObject::LongObj(ref l), &PyInnerObject::FloatObj(ref r)) => long_float_cmp(&l.n, r.n),
+        (&PyInnerObject::FloatObj(ref l), &PyInnerObject::LongObj(ref r)) => {
+            long_float_cmp(&r.n, l.n).map(Ordering::reverse)
+        },
+        _ => return Ok(PyObject::notimpl_obj()),
+    };
+    Ok(PyObject::from_bool(f(ord)))
+}
+
+fn zero_division(msg: &str) -> PyRes<f64> {
+    pyerr_set_string(PY_ZERODIVERROR_TYPE.with(|tp| Rc::clone(tp)), msg);
+    Err(())
+}
+
+// Python takes the sign of the divisor for the remainder
+fn float_mod(l: f64, r: f64) -> f64 {
+    let m = l % r;
+    if m != 0.0 && ((m < 0.0) != (r < 0.0)) { m + r } else { m }
+}
+
+pub fn pyfloat_add(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pyfloat_binop(lv, rv, "__add__", |l, r| Ok(l + r))
+}
+
+pub fn pyfloat_sub(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pyfloat_binop(lv, rv, "__sub__", |l, r| Ok(l - r))
+}
+
+pub fn pyfloat_mul(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pyfloat_binop(lv, rv, "__mul__", |l, r| Ok(l * r))
+}
+
+pub fn pyfloat_truediv(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pyfloat_binop(lv, rv, "__truediv__", |l, r| {
+        if r == 0.0 { return zero_division("division by zero") }
+        Ok(l / r)
+    })
+}
+
+pub fn pyfloat_floordiv(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pyfloat_binop(lv, rv, "__floordiv__", |l, r| {
+        if r == 0.0 { return zero_division("float floor division by zero") }
+        Ok(((l - float_mod(l, r)) / r).round())
+    })
+}
+
+pub fn pyfloat_mod(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pyfloat_binop(lv, rv, "__mod__", |l, r| {
+        if r == 0.0 { return zero_division("float modulo") }
+        Ok(float_mod(l, r))
+    })
+}
+
+pub fn pyfloat_pow(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    // a negative base to a fractional power has a complex result
+    if let (Some(l), Some(r)) = (lv.pyfloat_as_f64()?, rv.pyfloat_as_f64()?) {
+        if l < 0.0 && l.is_finite() && r.is_finite() && r.fract() != 0.0 {
+            return pycomplex_pow(lv, rv);
+        }
+    }
+    pyfloat_binop(lv, rv, "__pow__", |l, r| {
+        // powf already follows C99 for nan and infinite operands, where
+        // 0.0 ** -inf is inf rather than an error
+        if l == 0.0 && r < 0.0 && r.is_finite() {
+            return zero_division("0.0 cannot be raised to a negative power");
+        }
+        Ok(l.powf(r))
+    })
+}
+
+fn pyfloat_neg(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    match v.inner {
+        PyInnerObject::FloatObj(ref obj) => Ok(PyObject::from_f64(-obj.n)),
+        _ => {
+            pyerr_set_string(
+                PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                "__neg__ expects float objects"
+            );
+            Err(())
+        }
+    }
+}
+
+fn pyfloat_pos(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    match v.inner {
+        PyInnerObject::FloatObj(ref obj) => Ok(PyObject::from_f64(obj.n)),
+        _ => {
+            pyerr_set_string(
+                PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                "__pos__ expects float objects"
+            );
+            Err(())
+        }
+    }
+}
+
+fn pyfloat_eq(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pyfloat_richcmp(lv, rv, |ord| ord == Some(Ordering::Equal))
+}
+
+fn pyfloat_ne(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pyfloat_richcmp(lv, rv, |ord| ord != Some(Ordering::Equal))
+}
+
+fn pyfloat_lt(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pyfloat_richcmp(lv, rv, |ord| ord == Some(Ordering::Less))
+}
+
+fn pyfloat_le(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pyfloat_richcmp(lv, rv, |ord| ord == Some(Ordering::Less) || ord == Some(Ordering::Equal))
+}
+
+fn pyfloat_gt(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pyfloat_richcmp(lv, rv, |ord| ord == Some(Ordering::Greater))
+}
+
+fn pyfloat_ge(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pyfloat_richcmp(lv, rv, |ord| ord == Some(Ordering::Greater) || ord == Some(Ordering::Equal))
+}
+
+// frexp(3): mantissa in [0.5, 1) and a power of two
+fn frexp(x: f64) -> (f64, i32) {
+    if x == 0.0 || !x.is_finite() {
+        return (x, 0);
+    }
+    let bits = x.to_bits();
+    let exp = ((bits >> 52) & 0x7ff) as i32;
+    if exp == 0 {
+        // subnormal, scale into the normal range first
+        let (m, e) = frexp(x * (1u64 << 54) as f64);
+        return (m, e - 54);
+    }
+    let m = f64::from_bits((bits & !(0x7ff << 52)) | (1022 << 52));
+    (m, exp - 1022)
+}
+
+/// CPython's hash of a float: the value reduced modulo 2**61 - 1, so that
+/// it agrees with `BigInt::hash` whenever the float is integral.
+pub fn hash_f64(x: f64) -> i64 {
+    if x.is_nan() {
+        return 0;
+    }
+    if x.is_infinite() {
+        return if x > 0.0 { 314159 } else { -314159 };
+    }
+
+    let (mut m, mut e) = frexp(x);
+    let sign = if m < 0.0 { m = -m; -1 } else { 1 };
+    let mut acc = 0u64;
+    while m != 0.0 {
+        acc = ((acc << 28) & HASH_MODULUS) | acc >> (61 - 28);
+        m *= 268435456.0;
+        e -= 28;
+        let y = m as u64;
+        m -= y as f64;
+        acc += y;
+        if acc >= HASH_MODULUS {
+            acc -= HASH_MODULUS;
+        }
+    }
+    let e = if e >= 0 { e % 61 } else { 61 - 1 - ((-1 - e) % 61) } as u64;
+    acc = ((acc << e) & HASH_MODULUS) | acc >> (61 - e);
+    let h = acc as i64 * sign;
+    if h == -1 { -2 } else { h }
+}
+
+fn pyfloat_hash(v: Rc<PyObject>) -> PyRes<u64> {
+    match v.inner {
+        PyInnerObject::FloatObj(ref obj) => Ok(hash_f64(obj.n) as u64),
+        _ => {
+            pyerr_set_string(
+                PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                "__hash__ expects float objects"
+            );
+            Err(())
+        }
+    }
+}
+
+fn pyfloat_bool(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    match v.inner {
+        PyInnerObject::FloatObj(ref obj) => Ok(PyObject::from_bool(obj.n != 0.0)),
+        _ => {
+            pyerr_set_string(
+                PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                "__bool__ expects float objects"
+            );
+            Err(())
+        }
+    }
+}
+
+fn pyfloat_repr(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    match v.inner {
+        PyInnerObject::FloatObj(ref obj) => Ok(PyObject::from_string(float_repr(obj.n))),
+        _ => {
+            pyerr_set_string(
+                PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                "__repr__ expects float objects"
+            );
+            Err(())
+        }
+    }
+}
+
+fn pyfloat_format(v: Rc<PyObject>, spec: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    let spec = pyobj_to_string(spec)?;
+    match v.inner {
+        PyInnerObject::FloatObj(ref obj) => Ok(PyObject::from_string(format_float(obj.n, &spec)?)),
+        _ => {
+            pyerr_set_string(
+                PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                "__format__ expects float objects"
+            );
+            Err(())
+        }
+    }
+}
+
+/// Formats `n` the way CPython's `repr` does: the shortest digit string that
+/// round-trips, written positionally for exponents in [-4, 16) and in
+/// scientific notation otherwise.
+pub fn float_repr(n: f64) -> String {
+    if n.is_nan() {
+        return "nan".to_string();
+    }
+    if n.is_infinite() {
+        return if n > 0.0 { "inf".to_string() } else { "-inf".to_string() };
+    }
+
+    // `{:e}` yields the shortest round-tripping digits, e.g. "-1.2345e3"
+    let sci = format!("{:e}", n);
+    let (mantissa, exp) = sci.split_at(sci.find('e').expect("Never fails"));
+    let exp: i32 = exp[1..].parse().expect("Never fails");
+    let (sign, mantissa) = if mantissa.starts_with('-') {
+        ("-", &mantissa[1..])
+    } else {
+        ("", mantissa)
+    };
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+
+    if -4 <= exp && exp < 16 {
+        let point = exp + 1;
+        let body = if point <= 0 {
+            format!("0.{}{}", "0".repeat((-point) as usize), digits)
+        } else if point as usize >= digits.len() {
+            format!("{}{}.0", digits, "0".repeat(point as usize - digits.len()))
+        } else {
+            let (int_part, frac_part) = digits.split_at(point as usize);
+            format!("{}.{}", int_part, frac_part)
+        };
+        format!("{}{}", sign, body)
+    } else {
+        let (first, rest) = digits.split_at(1);
+        let mantissa = if rest.is_empty() {
+            first.to_string()
+        } else {
+            format!("{}.{}", first, rest)
+        };
+        let exp_sign = if exp < 0 { "-" } else { "+" };
+        format!("{}{}e{}{:02}", sign, mantissa, exp_sign, exp.abs())
+    }
+}
+
+fn pyfloat_new(_typ: Rc<PyObject>, args: &Vec<Rc<PyObject>>, kwargs: Option<Rc<PyObject>>) -> PyRes<Rc<PyObject>> {
+    check_no_kwargs("float", &kwargs)?;
+    if args.len() == 0 {
+        return Ok(PyObject::from_f64(0.0));
+    }
+    if args.len() != 1 {
+        pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                         "float expected at most 1 argument");
+        return Err(());
+    }
+
+    let arg = Rc::clone(&args[0]);
+    if let Some(n) = arg.pyfloat_as_f64()? {
+        return Ok(PyObject::from_f64(n));
+    }
+    match arg.inner {
+        PyInnerObject::StrObj(ref obj) => {
+            match obj.s.trim().parse::<f64>() {
+                Ok(n) => Ok(PyObject::from_f64(n)),
+                Err(_) => {
+                    pyerr_set_string(
+                        PY_VALUEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                        &format!("could not convert string to float: '{}'", obj.s));
+                    Err(())
+                }
+            }
+        },
+        _ => {
+            pyerr_set_string(
+                PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                "float() argument must be a string or a number");
+            Err(())
+        }
+    }
+}
@@ -0,0 +1,352 @@
+use std::char;
+use std::iter;
+use std::rc::Rc;
+
+use error::*;
+use eval::PyRes;
+use object::bigint::BigInt;
+use object::excobj::*;
+use object::floatobj::{float_repr, long_to_f64};
+
+/// The format spec mini-language shared by `format()`, `str.format` and
+/// f-strings:
+///
+/// `[[fill]align][sign]['#']['0'][width][grouping]['.' precision][type]`
+pub struct FormatSpec {
+    fill: char,
+    align: Option<char>,
+    sign: Option<char>,
+    alternate: bool,
+    width: usize,
+    grouping: Option<char>,
+    precision: Option<usize>,
+    typ: Option<char>,
+}
+
+fn value_error<T>(msg: &str) -> PyRes<T> {
+    pyerr_set_string(PY_VALUEERROR_TYPE.with(|tp| Rc::clone(tp)), msg);
+    Err(())
+}
+
+fn unknown_code<T>(typ: char, tp_name: &str) -> PyRes<T> {
+    value_error(&format!("Unknown format code '{}' for object of type '{}'", typ, tp_name))
+}
+
+// Makes room for `additional` more bytes, raising MemoryError where growing
+// the string would abort. None stands for a size that overflows.
+fn reserve(s: &mut String, additional: Option<usize>) -> PyRes<()> {
+    match additional {
+        Some(n) if s.try_reserve_exact(n).is_ok() => Ok(()),
+        _ => {
+            pyerr_no_memory();
+            Err(())
+        }
+    }
+}
+
+fn is_align(ch: char) -> bool {
+    ch == '<' || ch == '>' || ch == '=' || ch == '^'
+}
+
+// a width or precision, which like CPython's must fit in a Py_ssize_t
+fn digits(chars: &[char], i: &mut usize) -> PyRes<Option<usize>> {
+    let start = *i;
+    while *i < chars.len() && chars[*i].is_ascii_digit() {
+        *i += 1;
+    }
+    if *i == start {
+        return Ok(None);
+    }
+    match chars[start..*i].iter().collect::<String>().parse::<isize>() {
+        Ok(n) => Ok(Some(n as usize)),
+        Err(_) => value_error("Too many decimal digits in format string"),
+    }
+}
+
+impl FormatSpec {
+    pub fn parse(spec: &str, tp_name: &str) -> PyRes<FormatSpec> {
+        let chars: Vec<char> = spec.chars().collect();
+        let mut res = FormatSpec { fill: ' ', align: None, sign: None, alternate: false, width: 0,
+                                   grouping: None, precision: None, typ: None };
+        let mut i = 0;
+        if chars.len() >= 2 && is_align(chars[1]) {
+            res.fill = chars[0];
+            res.align = Some(chars[1]);
+            i = 2;
+        } else if chars.len() >= 1 && is_align(chars[0]) {
+            res.align = Some(chars[0]);
+            i = 1;
+        }
+        if i < chars.len() && (chars[i] == '+' || chars[i] == '-' || chars[i] == ' ') {
+            res.sign = Some(chars[i]);
+            i += 1;
+        }
+        if i < chars.len() && chars[i] == '#' {
+            res.alternate = true;
+            i += 1;
+        }
+        // a leading zero pads between the sign and the digits
+        if i < chars.len() && chars[i] == '0' {
+            if res.align.is_none() {
+                res.fill = '0';
+                res.align = Some('=');
+            }
+            i += 1;
+        }
+        res.width = digits(&chars, &mut i)?.unwrap_or(0);
+        if i < chars.len() && (chars[i] == ',' || chars[i] == '_') {
+            res.grouping = Some(chars[i]);
+            i += 1;
+        }
+        if i < chars.len() && chars[i] == '.' {
+            i += 1;
+            match digits(&chars, &mut i)? {
+                Some(precision) => res.precision = Some(precision),
+                None => return value_error("Format specifier missing precision"),
+            }
+        }
+        if chars.len() - i > 1 {
+            return value_error(&format!("Invalid format specifier '{}' for object of type '{}'",
+                                        spec, tp_name));
+        }
+        if i < chars.len() {
+            res.typ = Some(chars[i]);
+        }
+        Ok(res)
+    }
+
+    // Pads `sign` followed by `body` to the width, with '=' putting the
+    // padding between them.
+    fn pad(&self, sign: &str, body: &str, default_align: char) -> PyRes<String> {
+        let len = sign.chars().count() + body.chars().count();
+        if len >= self.width {
+            return Ok(format!("{}{}", sign, body));
+        }
+        let padding = self.width - len;
+        let mut res = String::new();
+        reserve(&mut res, padding.checked_mul(self.fill.len_utf8())
+                .and_then(|n| n.checked_add(sign.len() + body.len())))?;
+        let fill = iter::repeat(self.fill);
+        let (before, after) = match self.align.unwrap_or(default_align) {
+            '<' => (0, padding),
+            '^' => (padding / 2, padding - padding / 2),
+            '=' => {
+                res.push_str(sign);
+                res.extend(fill.take(padding));
+                res.push_str(body);
+                return Ok(res);
+            },
+            _ => (padding, 0),
+        };
+        res.extend(fill.clone().take(before));
+        res.push_str(sign);
+        res.push_str(body);
+        res.extend(fill.take(after));
+        Ok(res)
+    }
+
+    fn sign(&self, negative: bool) -> &'static str {
+        match (negative, self.sign) {
+            (true, _) => "-",
+            (false, Some('+')) => "+",
+            (false, Some(' ')) => " ",
+            _ => "",
+        }
+    }
+}
+
+// separates the digits in groups of `size` from the right
+fn group_digits(digits: &str, sep: char, size: usize) -> String {
+    let chars: Vec<char> = digits.chars().collect();
+    let mut res = String::new();
+    for (i, ch) in chars.iter().enumerate() {
+        if i > 0 && (chars.len() - i) % size == 0 {
+            res.push(sep);
+        }
+        res.push(*ch);
+    }
+    res
+}
+
+pub fn format_str(s: &str, spec: &str) -> PyRes<String> {
+    let spec = FormatSpec::parse(spec, "str")?;
+    match spec.typ {
+        None | Some('s') => (),
+        Some(typ) => return unknown_code(typ, "str"),
+    }
+    if spec.sign.is_some() {
+        return value_error("Sign not allowed in string format specifier");
+    }
+    if spec.alternate {
+        return value_error("Alternate form (#) not allowed in string format specifier");
+    }
+    if spec.align == Some('=') {
+        return value_error("'=' alignment not allowed in string format specifier");
+    }
+    if let Some(sep) = spec.grouping {
+        return value_error(&format!("Cannot specify '{}' with 's'.", sep));
+    }
+    let body: String = match spec.precision {
+        Some(precision) => s.chars().take(precision).collect(),
+        None => s.to_string(),
+    };
+    spec.pad("", &body, '<')
+}
+
+pub fn format_long(n: &BigInt, spec: &str) -> PyRes<String> {
+    let spec = FormatSpec::parse(spec, "int")?;
+    let (radix, prefix) = match spec.typ {
+        None | Some('d') | Some('n') => (10, ""),
+        Some('b') => (2, "0b"),
+        Some('o') => (8, "0o"),
+        Some('x') => (16, "0x"),
+        Some('X') => (16, "0X"),
+        Some('c') => {
+            if spec.sign.is_some() {
+                return value_error("Sign not allowed with integer format specifier 'c'");
+            }
+            let ch = n.to_i64().and_then(|n| if n < 0 { None } else { char::from_u32(n as u32) });
+            return match ch {
+                Some(ch) => spec.pad("", &ch.to_string(), '<'),
+                None => {
+                    pyerr_set_string(PY_OVERFLOWERROR_TYPE.with(|tp| Rc::clone(tp)),
+                                     "%c arg not in range(0x110000)");
+                    Err(())
+                }
+            };
+        },
+        Some('e') | Some('E') | Some('f') | Some('F') | Some('g') | Some('G') | Some('%') => {
+            return format_float_spec(long_to_f64(n)?, &spec);
+        },
+        Some(typ) => return unknown_code(typ, "int"),
+    };
+    if spec.precision.is_some() {
+        return value_error("Precision not allowed in integer format specifier");
+    }
+
+    let negative = n.is_negative();
+    let magnitude = if negative { n.neg() } else { n.clone() };
+    let mut body = magnitude.to_str_radix(radix);
+    if spec.typ == Some('X') {
+        body = body.to_uppercase();
+    }
+    match spec.grouping {
+        Some(',') if radix != 10 => {
+            return value_error(&format!("Cannot specify ',' with '{}'.", spec.typ.expect("Never fails")));
+        },
+        Some(sep) => body = group_digits(&body, sep, if radix == 10 { 3 } else { 4 }),
+        None => (),
+    }
+    let sign = format!("{}{}", spec.sign(negative), if spec.alternate { prefix } else { "" });
+    spec.pad(&sign, &body, '>')
+}
+
+pub fn format_float(f: f64, spec: &str) -> PyRes<String> {
+    let spec = FormatSpec::parse(spec, "float")?;
+    format_float_spec(f, &spec)
+}
+
+// Rust formats with precisions up to u16::MAX only. No double has more than
+// 1074 digits after the point, so beyond that the digits are all zeros and
+// longer precisions are written with this one and padded with zeros.
+const MAX_EXACT_PRECISION: usize = 1100;
+
+fn fixed(f: f64, precision: usize) -> PyRes<String> {
+    let exact = precision.min(MAX_EXACT_PRECISION);
+    let mut s = format!("{:.*}", exact, f);
+    // one more byte for the '%' of that type
+    reserve(&mut s, (precision - exact).checked_add(1))?;
+    s.extend(iter::repeat('0').take(precision - exact));
+    Ok(s)
+}
+
+// scientific notation with an exponent of at least two digits, like C's
+fn exponential(f: f64, precision: usize) -> PyRes<String> {
+    let exact = precision.min(MAX_EXACT_PRECISION);
+    let s = format!("{:.*e}", exact, f);
+    let (mantissa, exp) = s.split_at(s.find('e').expect("Never fails"));
+    let exp: i32 = exp[1..].parse().expect("Never fails");
+    let exp = format!("e{}{:02}", if exp < 0 { '-' } else { '+' }, exp.abs());
+    let mut res = mantissa.to_string();
+    reserve(&mut res, (precision - exact).checked_add(exp.len()))?;
+    res.extend(iter::repeat('0').take(precision - exact));
+    res.push_str(&exp);
+    Ok(res)
+}
+
+fn strip_zeros(s: &str) -> String {
+    match s.find('e') {
+        Some(i) => format!("{}{}", strip_zeros(&s[..i]), &s[i..]),
+        None if s.contains('.') => s.trim_end_matches('0').trim_end_matches('.').to_string(),
+        None => s.to_string(),
+    }
+}
+
+// 'g': fixed or scientific notation depending on the exponent, without
+// trailing zeros unless `alternate`
+fn general(f: f64, precision: usize, alternate: bool) -> PyRes<String> {
+    let precision = if precision == 0 { 1 } else { precision };
+    let exp = if f == 0.0 {
+        0
+    } else {
+        // padding zeros do not change the rounded exponent
+        let s = exponential(f, (precision - 1).min(MAX_EXACT_PRECISION))?;
+        s[s.find('e').expect("Never fails") + 1..].parse::<i32>().expect("Never fails")
+    };
+    let s = if -4 <= exp && (exp as i64) < precision as i64 {
+        fixed(f, (precision as i64 - 1 - exp as i64) as usize)?
+    } else {
+        exponential(f, precision - 1)?
+    };
+    Ok(if alternate { s } else { strip_zeros(&s) })
+}
+
+fn format_float_spec(f: f64, spec: &FormatSpec) -> PyRes<String> {
+    match spec.typ {
+        None | Some('f') | Some('F') | Some('e') | Some('E') |
+        Some('g') | Some('G') | Some('n') | Some('%') => (),
+        Some(typ) => return unknown_code(typ, "float"),
+    }
+    let negative = f.is_sign_negative() && !f.is_nan();
+    let magnitude = f.abs();
+    let precision = spec.precision.unwrap_or(6);
+    // CPython keeps float precisions in a C int
+    if precision > i32::max_value() as usize {
+        return value_error("precision too big");
+    }
+    let mut body = if f.is_nan() {
+        "nan".to_string()
+    } else if f.is_infinite() {
+        "inf".to_string()
+    } else {
+        match spec.typ {
+            Some('f') | Some('F') => fixed(magnitude, precision)?,
+            Some('e') | Some('E') => exponential(magnitude, precision)?,
+            Some('%') => {
+                let mut s = fixed(magnitude * 100.0, precision)?;
+                s.push('%');
+                s
+            },
+            None => match spec.precision {
+                // like 'g', but keeping a digit after the point
+                Some(precision) => {
+                    let s = general(magnitude, precision, spec.alternate)?;
+                    if s.contains('.') || s.contains('e') { s } else { format!("{}.0", s) }
+                },
+                None => float_repr(magnitude),
+            },
+            _ => general(magnitude, precision, spec.alternate)?,
+        }
+    };
+    match spec.typ {
+        Some('E') | Some('F') | Some('G') => body = body.to_uppercase(),
+        _ => (),
+    }
+    if let Some(sep) = spec.grouping {
+        let end = body.find(|ch: char| !ch.is_ascii_digit()).unwrap_or(body.len());
+        if end > 0 {
+            body = format!("{}{}", group_digits(&body[..end], sep, 3), &body[end..]);
+        }
+    }
+    spec.pad(spec.sign(negative), &body, '>')
+}
@@ -2,14 +2,25 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 use env::Env;
+use error::*;
+use eval::PyRes;
 use object::*;
+use object::excobj::*;
+use object::generic::*;
 use object::typeobj::*;
 use syntax::Id;
 use opcode::Code;
 
 pub struct PyFunObject {
+    /// the environment of the module the function was defined in
     pub env: Rc<Env>,
     pub codeobj: Rc<PyObject>,
+    /// default values of the last positional parameters
+    pub defaults: Vec<Rc<PyObject>>,
+    /// dict of the default values of keyword-only parameters
+    pub kwdefaults: Option<Rc<PyObject>>,
+    /// the cells of the code's free variables
+    pub closure: Vec<Rc<PyObject>>,
 }
 
 thread_local! (
@@ -17,6 +28,7 @@ thread_local! (
         let funtp =  PyTypeObject {
             tp_name: "function".to_string(),
             tp_hash: Some(Rc::new(default_hash)),
+            tp_getattro: Some(Rc::new(pyfun_getattro)),
             ..Default::default()
         };
         Rc::new(PyObject {
@@ -27,14 +39,35 @@ thread_local! (
     }
 );
 
+fn pyfun_getattro(fun: Rc<PyObject>, key: Rc<PyObject>) -> PyRes<Option<Rc<PyObject>>> {
+    let obj = match fun.inner {
+        PyInnerObject::FunObj(ref obj) => obj,
+        _ => panic!("Type Error: pyfun_getattro")
+    };
+    match &pyobj_to_string(Rc::clone(&key))?[..] {
+        "__code__" => Ok(Some(Rc::clone(&obj.codeobj))),
+        "__name__" => Ok(Some(PyObject::from_string(obj.codeobj.pycode_name()))),
+        "__defaults__" if obj.defaults.is_empty() => Ok(Some(PyObject::none_obj())),
+        "__defaults__" => Ok(Some(PyObject::pytuple_from_vec(obj.defaults.clone()))),
+        "__kwdefaults__" => Ok(Some(obj.kwdefaults.clone().unwrap_or_else(PyObject::none_obj))),
+        "__closure__" if obj.closure.is_empty() => Ok(Some(PyObject::none_obj())),
+        "__closure__" => Ok(Some(PyObject::pytuple_from_vec(obj.closure.clone()))),
+        _ => pyobj_generic_get_attro(fun, key),
+    }
+}
+
 impl PyObject {
-    pub fn pyfun_new(env: &Rc<Env>, codeobj: Rc<PyObject>) -> Rc<PyObject> {
+    pub fn pyfun_new(env: &Rc<Env>, codeobj: Rc<PyObject>, defaults: Vec<Rc<PyObject>>,
+                     kwdefaults: Option<Rc<PyObject>>, closure: Vec<Rc<PyObject>>) -> Rc<PyObject> {
         Rc::new(PyObject {
             ob_type: PY_FUN_TYPE.with(|tp| { Some(Rc::clone(&tp)) }),
             ob_dict: None,
             inner: PyInnerObject::FunObj(Rc::new(PyFunObject {
                 env: Rc::clone(env),
                 codeobj: codeobj,
+                defaults: defaults,
+                kwdefaults: kwdefaults,
+                closure: closure,
             }))
         })
     }
@@ -48,8 +81,151 @@ impl PyObject {
 
     pub fn pyfun_argnames(self: Rc<Self>) -> Vec<Id> {
         match self.inner {
-            PyInnerObject::FunObj(ref obj) => obj.codeobj.pycode_argnames(),
+            PyInnerObject::FunObj(ref obj) => obj.codeobj.pycode_argspec().args.clone(),
             _ => panic!("Type Error: pyfun_code")
         }
     }
 }
+
+fn type_error<T>(msg: &str) -> PyRes<T> {
+    pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)), msg);
+    Err(())
+}
+
+fn plural(n: usize, word: &str) -> String {
+    if n == 1 { format!("{} {}", n, word) } else { format!("{} {}s", n, word) }
+}
+
+// 'a', 'a' and 'b', 'a', 'b', and 'c'
+fn name_list(names: &[&Id]) -> String {
+    let quoted: Vec<String> = names.iter().map(|name| format!("'{}'", name)).collect();
+    match quoted.len() {
+        1 => quoted[0].clone(),
+        2 => format!("{} and {}", quoted[0], quoted[1]),
+        n => format!("{}, and {}", quoted[..n - 1].join(", "), quoted[n - 1]),
+    }
+}
+
+impl PyFunObject {
+    /// Makes the environment a call runs in, with the locals it starts with.
+    /// The cell variables get fresh cells, holding the value of the
+    /// parameter when there is one of the same name, and the free variables
+    /// get the cells of the closure.
+    pub fn new_env(&self, names: &Vec<Id>, vals: &Vec<Rc<PyObject>>) -> Rc<Env> {
+        let env = Rc::new(Env::new_child(&self.env, names, vals));
+        for name in self.codeobj.pycode_cellvars() {
+            env.set_cell(name.clone(), PyObject::pycell_new(env.get_local(name)));
+        }
+        for (name, cell) in self.codeobj.pycode_freevars().iter().zip(self.closure.iter()) {
+            env.set_cell(name.clone(), Rc::clone(cell));
+        }
+        env
+    }
+
+    /// Matches the arguments of a call to the parameters, giving the names
+    /// and values of the locals the body starts with.
+    pub fn bind_args(&self, args: &Vec<Rc<PyObject>>, kwargs: Option<Rc<PyObject>>)
+                     -> PyRes<(Vec<Id>, Vec<Rc<PyObject>>)> {
+        let spec = self.codeobj.pycode_argspec();
+        let name = self.codeobj.pycode_name();
+        let nargs = spec.args.len();
+
+        // one slot for each positional parameter, then each keyword-only one
+        let mut slots: Vec<Option<Rc<PyObject>>> = vec![None; nargs + spec.kwonly.len()];
+        for (slot, arg) in slots[..nargs].iter_mut().zip(args.iter()) {
+            *slot = Some(Rc::clone(arg));
+        }
+        let extra = if args.len() > nargs { args[nargs..].to_vec() } else { vec![] };
+        let kwdict = if spec.kwarg.is_some() { Some(PyObject::pydict_new()) } else { None };
+        let mut posonly_passed = vec![];
+        if let Some(ref kwargs) = kwargs {
+            for (key, value) in kwargs.pydict_entries() {
+                let key_name = pyobj_to_string(Rc::clone(&key))?;
+                let pos = spec.args.iter().position(|arg| *arg == key_name)
+                    .filter(|&i| i >= spec.posonly)
+                    .or_else(|| spec.kwonly.iter().position(|arg| *arg == key_name).map(|i| nargs + i));
+                match pos {
+                    Some(i) => {
+                        if slots[i].is_some() {
+                            return type_error(&format!("{}() got multiple values for argument '{}'",
+                                                       name, key_name));
+                        }
+                        slots[i] = Some(value);
+                    },
+                    None => match kwdict {
+                        Some(ref kwdict) => kwdict.pydict_update(key, value)?,
+                        None if spec.args[..spec.posonly].contains(&key_name) => {
+                            posonly_passed.push(key_name);
+                        },
+                        None => {
+                            return type_error(&format!("{}() got an unexpected keyword argument '{}'",
+                                                       name, key_name));
+                        }
+                    }
+                }
+            }
+        }
+        if !posonly_passed.is_empty() {
+            return type_error(&format!("{}() got some positional-only arguments passed as keyword arguments: '{}'",
+                                       name, posonly_passed.join(", ")));
+        }
+        if !extra.is_empty() && spec.vararg.is_none() {
+            let takes = if self.defaults.is_empty() {
+                format!("{}", plural(nargs, "positional argument"))
+            } else {
+                format!("from {} to {} positional arguments", nargs - self.defaults.len(), nargs)
+            };
+            let kwonly_given = slots[nargs..].iter().filter(|slot| slot.is_some()).count();
+            let given = if kwonly_given > 0 {
+                format!("{} (and {}) were", plural(args.len(), "positional argument"),
+                        plural(kwonly_given, "keyword-only argument"))
+            } else if args.len() == 1 {
+                "1 was".to_string()
+            } else {
+                format!("{} were", args.len())
+            };
+            return type_error(&format!("{}() takes {} but {} given", name, takes, given));
+        }
+
+        // fill in the defaults, then complain about what is still missing
+        let first_default = nargs - self.defaults.len();
+        for (i, default) in self.defaults.iter().enumerate() {
+            if slots[first_default + i].is_none() {
+                slots[first_default + i] = Some(Rc::clone(default));
+            }
+        }
+        let missing: Vec<&Id> = spec.args.iter().zip(slots.iter())
+            .filter(|&(_, slot)| slot.is_none()).map(|(arg, _)| arg).collect();
+        if !missing.is_empty() {
+            return type_error(&format!("{}() missing {}: {}", name,
+                                       plural(missing.len(), "required positional argument"),
+                                       name_list(&missing)));
+        }
+        for (i, kwarg) in spec.kwonly.iter().enumerate() {
+            if slots[nargs + i].is_none() {
+                if let Some(ref kwdefaults) = self.kwdefaults {
+                    slots[nargs + i] = kwdefaults.pydict_lookup(PyObject::from_str(kwarg))?;
+                }
+            }
+        }
+        let missing: Vec<&Id> = spec.kwonly.iter().zip(slots[nargs..].iter())
+            .filter(|&(_, slot)| slot.is_none()).map(|(arg, _)| arg).collect();
+        if !missing.is_empty() {
+            return type_error(&format!("{}() missing {}: {}", name,
+                                       plural(missing.len(), "required keyword-only argument"),
+                                       name_list(&missing)));
+        }
+
+        let mut names: Vec<Id> = spec.args.iter().chain(spec.kwonly.iter()).cloned().collect();
+        let mut vals: Vec<Rc<PyObject>> = slots.into_iter().map(|slot| slot.unwrap()).collect();
+        if let Some(ref vararg) = spec.vararg {
+            names.push(vararg.clone());
+            vals.push(PyObject::pytuple_from_vec(extra));
+        }
+        if let (Some(kwarg), Some(kwdict)) = (spec.kwarg.clone(), kwdict) {
+            names.push(kwarg);
+            vals.push(kwdict);
+        }
+        Ok((names, vals))
+    }
+}
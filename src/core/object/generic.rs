@@ -1,8 +1,8 @@
 use std::rc::Rc;
 
 use error::*;
-use env::*;
 use eval::{PyRes, eval};
+use syntax::CompOp;
 use object::*;
 use object::boolobj::*;
 use object::excobj::*;
@@ -29,19 +29,365 @@ pub fn pyobj_to_bool(v: Rc<PyObject>) -> PyRes<bool> {
             }
         },
         None => match typ.tp_len.as_ref() {
-            Some(ref fun) => Ok(pyobj_to_i32(fun(Rc::clone(&v))?)? > 0),
-            None => {
+            Some(ref fun) => Ok(len_result(fun(Rc::clone(&v))?)? > 0),
+            None => Ok(true),
+        }
+    }
+}
+
+// checks what __len__ returned, which has to be a non-negative int that
+// fits in a Py_ssize_t
+fn len_result(res: Rc<PyObject>) -> PyRes<usize> {
+    let n = match res.inner {
+        PyInnerObject::LongObj(ref obj) => obj.n.clone(),
+        _ => {
+            let msg = format!("'{}' object cannot be interpreted as an integer",
+                              res.ob_type().pytype_typeobj_borrow().tp_name);
+            pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)), &msg);
+            return Err(());
+        }
+    };
+    if n.is_negative() {
+        pyerr_set_string(PY_VALUEERROR_TYPE.with(|tp| Rc::clone(tp)), "__len__() should return >= 0");
+        return Err(());
+    }
+    match n.to_i64() {
+        Some(n) if n <= isize::max_value() as i64 => Ok(n as usize),
+        _ => {
+            let msg = format!("cannot fit '{}' into an index-sized integer",
+                              res.ob_type().pytype_typeobj_borrow().tp_name);
+            pyerr_set_string(PY_OVERFLOWERROR_TYPE.with(|tp| Rc::clone(tp)), &msg);
+            Err(())
+        }
+    }
+}
+
+/// The length of `v` as given by its __len__.
+pub fn pyobj_len(v: Rc<PyObject>) -> PyRes<usize> {
+    let len = v.ob_type().pytype_typeobj_borrow().tp_len.clone();
+    match len {
+        Some(ref fun) => len_result(fun(Rc::clone(&v))?),
+        None => {
+            let msg = format!("object of type '{}' has no len()", v.ob_type().pytype_typeobj_borrow().tp_name);
+            pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)), &msg);
+            Err(())
+        }
+    }
+}
+
+pub fn pyobj_repr(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    let ob_type = v.ob_type();
+    let repr = ob_type.pytype_typeobj_borrow().tp_repr.clone();
+    match repr {
+        Some(ref fun) => {
+            let res = fun(v)?;
+            if !res.pystr_check() {
                 pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
-                                 "boolean object expected");
-                Err(())
+                                 "__repr__ returned non-string");
+                return Err(());
+            }
+            Ok(res)
+        },
+        None => {
+            let name = ob_type.pytype_typeobj_borrow().tp_name.clone();
+            Ok(PyObject::from_string(format!("<{} object>", name)))
+        }
+    }
+}
+
+// `__str__` falls back to `__repr__`
+pub fn pyobj_str(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    let ob_type = v.ob_type();
+    let str_fun = ob_type.pytype_typeobj_borrow().tp_str.clone();
+    match str_fun {
+        Some(ref fun) => {
+            let res = fun(v)?;
+            if !res.pystr_check() {
+                pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                                 "__str__ returned non-string");
+                return Err(());
+            }
+            Ok(res)
+        },
+        None => pyobj_repr(v),
+    }
+}
+
+// like `pyobj_repr`, with non-ASCII characters escaped
+pub fn pyobj_ascii(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    let repr = pyobj_to_string(pyobj_repr(v)?)?;
+    let mut res = String::new();
+    for ch in repr.chars() {
+        match ch as u32 {
+            0 ..= 0x7f => res.push(ch),
+            n @ 0x80 ..= 0xff => res.push_str(&format!("\\x{:02x}", n)),
+            n @ 0x100 ..= 0xffff => res.push_str(&format!("\\u{:04x}", n)),
+            n => res.push_str(&format!("\\U{:08x}", n)),
+        }
+    }
+    Ok(PyObject::from_string(res))
+}
+
+// Objects without `__format__` only take an empty format spec, which
+// gives their `__str__`.
+pub fn pyobj_format(v: Rc<PyObject>, spec: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    let ob_type = v.ob_type();
+    let format = ob_type.pytype_typeobj_borrow().tp_format.clone();
+    match format {
+        Some(ref fun) => {
+            let res = fun(v, spec)?;
+            if !res.pystr_check() {
+                let msg = format!("__format__ must return a str, not {}",
+                                  res.ob_type().pytype_typeobj_borrow().tp_name);
+                pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)), &msg);
+                return Err(());
+            }
+            Ok(res)
+        },
+        None => {
+            if pyobj_to_string(spec)?.is_empty() {
+                return pyobj_str(v);
             }
+            let msg = format!("unsupported format string passed to {}.__format__",
+                              ob_type.pytype_typeobj_borrow().tp_name);
+            pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)), &msg);
+            Err(())
+        }
+    }
+}
+
+/// Applies a binary operator, falling back to the right operand's reflected
+/// method when the left one is missing or returns NotImplemented.
+pub fn pyobj_binop<F, G>(lv: Rc<PyObject>, rv: Rc<PyObject>, symbol: &str, slot: F, rslot: G) -> PyRes<Rc<PyObject>>
+where F: Fn(&PyTypeObject) -> Option<Rc<BinaryOp>>, G: Fn(&PyTypeObject) -> Option<Rc<BinaryOp>> {
+    let (l_type, r_type) = (lv.ob_type(), rv.ob_type());
+    let fun = slot(&l_type.pytype_typeobj_borrow());
+    if let Some(ref fun) = fun {
+        let res = fun(Rc::clone(&lv), Rc::clone(&rv))?;
+        if !res.pynotimpl_check() {
+            return Ok(res);
+        }
+    }
+    if !Rc::ptr_eq(&l_type, &r_type) {
+        let rfun = rslot(&r_type.pytype_typeobj_borrow());
+        if let Some(ref rfun) = rfun {
+            let res = rfun(Rc::clone(&rv), Rc::clone(&lv))?;
+            if !res.pynotimpl_check() {
+                return Ok(res);
+            }
+        }
+    }
+
+    let msg = format!("unsupported operand type(s) for {}: '{}' and '{}'", symbol,
+                      l_type.pytype_typeobj_borrow().tp_name,
+                      r_type.pytype_typeobj_borrow().tp_name);
+    pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)), &msg);
+    Err(())
+}
+
+pub fn pyobj_unaryop<F>(v: Rc<PyObject>, name: &str, slot: F) -> PyRes<Rc<PyObject>>
+where F: Fn(&PyTypeObject) -> Option<Rc<UnaryOp>> {
+    let fun = slot(&v.ob_type().pytype_typeobj_borrow());
+    match fun {
+        Some(ref fun) => fun(v),
+        None => {
+            pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                             &format!("no {} operation", name));
+            Err(())
+        }
+    }
+}
+
+fn richcmp_slot(typ: &PyTypeObject, op: CompOp) -> Option<Rc<BinaryOp>> {
+    match op {
+        CompOp::Lt => typ.tp_fun_lt.clone(),
+        CompOp::LtE => typ.tp_fun_le.clone(),
+        CompOp::Gt => typ.tp_fun_gt.clone(),
+        CompOp::GtE => typ.tp_fun_ge.clone(),
+        CompOp::EqEq => typ.tp_fun_eq.clone(),
+        CompOp::NotEq => typ.tp_fun_ne.clone(),
+        _ => panic!("Implementation Error: richcmp_slot"),
+    }
+}
+
+fn richcmp_swapped(op: CompOp) -> CompOp {
+    match op {
+        CompOp::Lt => CompOp::Gt,
+        CompOp::LtE => CompOp::GtE,
+        CompOp::Gt => CompOp::Lt,
+        CompOp::GtE => CompOp::LtE,
+        CompOp::EqEq => CompOp::EqEq,
+        CompOp::NotEq => CompOp::NotEq,
+        _ => panic!("Implementation Error: richcmp_swapped"),
+    }
+}
+
+fn richcmp_symbol(op: CompOp) -> &'static str {
+    match op {
+        CompOp::Lt => "<",
+        CompOp::LtE => "<=",
+        CompOp::Gt => ">",
+        CompOp::GtE => ">=",
+        CompOp::EqEq => "==",
+        CompOp::NotEq => "!=",
+        _ => panic!("Implementation Error: richcmp_symbol"),
+    }
+}
+
+// types without an mro are builtins that have not been readied, and none
+// of them derives from another type
+fn is_proper_subtype(typ: &Rc<PyObject>, base: &Rc<PyObject>) -> bool {
+    if Rc::ptr_eq(typ, base) {
+        return false;
+    }
+    match typ.pytype_tp_mro() {
+        Some(ref mro) => (0..mro.pylist_size()).any(|i| mro.pylist_getitem(i).ok().as_ref() == Some(base)),
+        None => false,
+    }
+}
+
+// Ok(None) means that the operation is not implemented for the pair
+fn try_richcompare(lv: Rc<PyObject>, rv: Rc<PyObject>, op: CompOp) -> PyRes<Option<Rc<PyObject>>> {
+    let ob_type = lv.ob_type();
+    let (fun, eq_fun) = {
+        let typ = ob_type.pytype_typeobj_borrow();
+        (richcmp_slot(&typ, op), typ.tp_fun_eq.clone())
+    };
+
+    if let Some(ref fun) = fun {
+        let res = fun(lv, rv)?;
+        return Ok(if res.pynotimpl_check() { None } else { Some(res) });
+    }
+
+    // __ne__ defaults to the inverse of __eq__
+    if op == CompOp::NotEq {
+        if let Some(ref eq_fun) = eq_fun {
+            let res = eq_fun(lv, rv)?;
+            if res.pynotimpl_check() { return Ok(None) }
+            return Ok(Some(PyObject::from_bool(!pyobj_to_bool(res)?)));
         }
     }
+    Ok(None)
+}
+
+pub fn pyobj_richcompare(lv: Rc<PyObject>, rv: Rc<PyObject>, op: CompOp) -> PyRes<Rc<PyObject>> {
+    // a subclass on the right goes first, so that it can override its base
+    let (l_type, r_type) = (lv.ob_type(), rv.ob_type());
+    let reflected_first = is_proper_subtype(&r_type, &l_type)
+        && richcmp_slot(&r_type.pytype_typeobj_borrow(), richcmp_swapped(op)).is_some();
+    if reflected_first {
+        if let Some(res) = try_richcompare(Rc::clone(&rv), Rc::clone(&lv), richcmp_swapped(op))? {
+            return Ok(res);
+        }
+    }
+    if let Some(res) = try_richcompare(Rc::clone(&lv), Rc::clone(&rv), op)? {
+        return Ok(res);
+    }
+    if !reflected_first {
+        if let Some(res) = try_richcompare(Rc::clone(&rv), Rc::clone(&lv), richcmp_swapped(op))? {
+            return Ok(res);
+        }
+    }
+
+    match op {
+        CompOp::EqEq => Ok(PyObject::from_bool(lv == rv)),
+        CompOp::NotEq => Ok(PyObject::from_bool(lv != rv)),
+        _ => {
+            let msg = format!("'{}' not supported between instances of '{}' and '{}'",
+                              richcmp_symbol(op),
+                              lv.ob_type().pytype_typeobj_borrow().tp_name,
+                              rv.ob_type().pytype_typeobj_borrow().tp_name);
+            pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)), &msg);
+            Err(())
+        }
+    }
+}
+
+pub fn pyobj_get_iter(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    match v.ob_type().pytype_tp_iter() {
+        Some(ref iterfun) => iterfun(v),
+        None => {
+            let msg = format!("'{}' object is not iterable",
+                              v.ob_type().pytype_typeobj_borrow().tp_name);
+            pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)), &msg);
+            Err(())
+        }
+    }
+}
+
+/// Advances an iterator. The StopIteration an exhausted iterator may leave
+/// set, such as a generator reporting its return value, is cleared.
+pub fn pyobj_iter_next(it: Rc<PyObject>) -> PyRes<Option<Rc<PyObject>>> {
+    match it.ob_type().pytype_tp_iternext() {
+        Some(ref nextfun) => {
+            let next = nextfun(it)?;
+            if next.is_none() && pyerr_check(PY_STOPITERATION_TYPE.with(|tp| Rc::clone(tp))) {
+                pyerr_clear();
+            }
+            Ok(next)
+        },
+        None => {
+            let msg = format!("'{}' object is not an iterator",
+                              it.ob_type().pytype_typeobj_borrow().tp_name);
+            pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)), &msg);
+            Err(())
+        }
+    }
+}
+
+/// Collects the items of an iterable.
+pub fn pyobj_to_vec(v: Rc<PyObject>) -> PyRes<Vec<Rc<PyObject>>> {
+    let it = pyobj_get_iter(v)?;
+    let mut items = vec![];
+    while let Some(item) = pyobj_iter_next(Rc::clone(&it))? {
+        items.push(item);
+    }
+    Ok(items)
+}
+
+pub fn pyobj_contains(container: Rc<PyObject>, item: Rc<PyObject>) -> PyRes<bool> {
+    let contains = container.ob_type().pytype_typeobj_borrow().tp_contains.clone();
+    if let Some(ref contains) = contains {
+        return contains(container, item);
+    }
+
+    let it = pyobj_get_iter(container)?;
+    while let Some(elm) = pyobj_iter_next(Rc::clone(&it))? {
+        if elm == item || pyobj_to_bool(pyobj_richcompare(elm, Rc::clone(&item), CompOp::EqEq)?)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+pub fn pyobj_hash(v: Rc<PyObject>) -> PyRes<u64> {
+    let hashfun = v.ob_type().pytype_typeobj_borrow().tp_hash.clone();
+    match hashfun {
+        Some(ref fun) => fun(v),
+        None => pyobj_unhashable(v),
+    }
+}
+
+pub fn pyobj_unhashable(v: Rc<PyObject>) -> PyRes<u64> {
+    let msg = format!("unhashable type: '{}'", v.ob_type().pytype_typeobj_borrow().tp_name);
+    pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)), &msg);
+    Err(())
 }
 
 pub fn pyobj_to_i32(v: Rc<PyObject>) -> PyRes<i32> {
     match v.inner {
-        PyInnerObject::LongObj(ref obj) => Ok(obj.n),
+        PyInnerObject::LongObj(ref obj) => {
+            match obj.n.to_i64() {
+                Some(n) if n >= i32::min_value() as i64 && n <= i32::max_value() as i64 => {
+                    Ok(n as i32)
+                },
+                _ => {
+                    pyerr_set_string(PY_OVERFLOWERROR_TYPE.with(|tp| Rc::clone(tp)),
+                                     "Python int too large to convert to C int");
+                    Err(())
+                }
+            }
+        },
         _ => {
             pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
                              "int object expected");
@@ -50,6 +396,81 @@ pub fn pyobj_to_i32(v: Rc<PyObject>) -> PyRes<i32> {
     }
 }
 
+/// An int as an index, saturating at the bounds of `isize`, or `None` for
+/// any other object.
+pub fn pyobj_to_index(v: Rc<PyObject>) -> Option<isize> {
+    match v.inner {
+        PyInnerObject::LongObj(ref obj) => {
+            match obj.n.to_i64() {
+                Some(n) if n >= isize::min_value() as i64 && n <= isize::max_value() as i64 => Some(n as isize),
+                _ if obj.n.is_negative() => Some(isize::min_value()),
+                _ => Some(isize::max_value()),
+            }
+        },
+        _ => None,
+    }
+}
+
+// Resolves an int subscript of a sequence of `len` items, counting a
+// negative index from the end. `msg` is the IndexError when it is out of
+// range.
+pub fn pyobj_seq_index(key: Rc<PyObject>, len: usize, name: &str, msg: &str) -> PyRes<usize> {
+    let index = match pyobj_to_index(Rc::clone(&key)) {
+        Some(index) => index,
+        None => {
+            let msg = format!("{} indices must be integers or slices, not {}",
+                              name, key.ob_type().pytype_typeobj_borrow().tp_name);
+            pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)), &msg);
+            return Err(());
+        }
+    };
+    let index = if index < 0 { index.saturating_add(len as isize) } else { index };
+    if index < 0 || index as usize >= len {
+        pyerr_set_string(PY_INDEXERROR_TYPE.with(|tp| Rc::clone(tp)), msg);
+        return Err(());
+    }
+    Ok(index as usize)
+}
+
+pub fn pyobj_getitem(v: Rc<PyObject>, key: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    let getitem = v.ob_type().pytype_typeobj_borrow().tp_getitem.clone();
+    match getitem {
+        Some(ref fun) => fun(v, key),
+        None => {
+            let msg = format!("'{}' object is not subscriptable",
+                              v.ob_type().pytype_typeobj_borrow().tp_name);
+            pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)), &msg);
+            Err(())
+        }
+    }
+}
+
+pub fn pyobj_setitem(v: Rc<PyObject>, key: Rc<PyObject>, rvalue: Rc<PyObject>) -> PyRes<()> {
+    let setitem = v.ob_type().pytype_typeobj_borrow().tp_setitem.clone();
+    match setitem {
+        Some(ref fun) => fun(v, key, rvalue),
+        None => {
+            let msg = format!("'{}' object does not support item assignment",
+                              v.ob_type().pytype_typeobj_borrow().tp_name);
+            pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)), &msg);
+            Err(())
+        }
+    }
+}
+
+pub fn pyobj_delitem(v: Rc<PyObject>, key: Rc<PyObject>) -> PyRes<()> {
+    let delitem = v.ob_type().pytype_typeobj_borrow().tp_delitem.clone();
+    match delitem {
+        Some(ref fun) => fun(v, key),
+        None => {
+            let msg = format!("'{}' object doesn't support item deletion",
+                              v.ob_type().pytype_typeobj_borrow().tp_name);
+            pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)), &msg);
+            Err(())
+        }
+    }
+}
+
 pub fn pyobj_to_string(v: Rc<PyObject>) -> PyRes<String> {
     match v.inner {
         PyInnerObject::StrObj(ref obj) => Ok(obj.s.clone()),
@@ -61,6 +482,21 @@ pub fn pyobj_to_string(v: Rc<PyObject>) -> PyRes<String> {
     }
 }
 
+pub fn check_arg_count(name: &str, args: &Vec<Rc<PyObject>>, min: usize, max: usize) -> PyRes<()> {
+    if args.len() < min || args.len() > max {
+        let msg = if min == max {
+            format!("{}() takes exactly {} argument(s) ({} given)", name, min, args.len())
+        } else if args.len() < min {
+            format!("{}() takes at least {} argument(s) ({} given)", name, min, args.len())
+        } else {
+            format!("{}() takes at most {} argument(s) ({} given)", name, max, args.len())
+        };
+        pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)), &msg);
+        return Err(());
+    }
+    Ok(())
+}
+
 pub fn pyobj_issubclass(v: Rc<PyObject>, typ: Rc<PyObject>) -> bool {
     if !PyObject::pytype_check(&v) { return false }
 
@@ -86,45 +522,76 @@ pub fn pyobj_isinstance(v: Rc<PyObject>, typ: Rc<PyObject>) -> bool {
             };
             false
         },
-        None => panic!("Implementation Error: pyobj_issubclass")
+        None => {
+            // builtin types that were never readied only have tp_base
+            let mut base = Some(v_type);
+            while let Some(tp) = base {
+                if tp == typ { return true; }
+                base = tp.pytype_tp_base();
+            }
+            false
+        }
     }
 }
 
 pub fn call_func(funv: Rc<PyObject>, args: &Vec<Rc<PyObject>>) -> PyRes<Rc<PyObject>> {
+    call_func_kw(funv, args, None)
+}
+
+/// Calls an object with positional arguments and a dict of keyword ones.
+pub fn call_func_kw(funv: Rc<PyObject>, args: &Vec<Rc<PyObject>>, kwargs: Option<Rc<PyObject>>)
+                    -> PyRes<Rc<PyObject>> {
+    let kwargs = kwargs.filter(|kwargs| kwargs.pydict_size() > 0);
     match funv.inner {
         PyInnerObject::FunObj(ref fun) => {
-            eval(&fun.codeobj.pycode_code(),
-                 Rc::new(Env::new_child(&fun.env, &fun.codeobj.pycode_argnames(), args)))
+            let (names, vals) = fun.bind_args(args, kwargs)?;
+            let env = fun.new_env(&names, &vals);
+            if fun.codeobj.pycode_is_generator() {
+                return Ok(PyObject::pygen_new(Rc::clone(&fun.codeobj), env));
+            }
+            eval(&fun.codeobj, env)
         },
         PyInnerObject::MethodObj(ref method) => {
             let mut vals = vec![Rc::clone(&method.ob_self)];
-            let mut args = args.clone();
-            vals.append(&mut args);
-            eval(&method.codeobj.pycode_code(),
-                 Rc::new(Env::new_child(&method.env, &method.codeobj.pycode_argnames(), &vals)))
+            vals.extend(args.iter().cloned());
+            call_func_kw(Rc::clone(&method.func), &vals, kwargs)
         },
         PyInnerObject::RustFunObj(ref obj) => {
+            // Probably, slf cannot be None after module is implemented
+            let slf = match obj.ob_self {
+                Some(ref slf) => Rc::clone(slf),
+                None => PY_NONE_OBJECT.with(|ob| { Rc::clone(ob) })
+            };
             match obj.rust_fun {
+                PyRustFun::MethKeywords(_) => (),
+                _ => check_no_kwargs(&obj.name, &kwargs)?,
+            }
+            match obj.rust_fun {
+                PyRustFun::MethNoArgs(ref fun) => {
+                    if args.len() != 0 {
+                        pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                                         &format!("{}() takes no arguments", obj.name));
+                        return Err(())
+                    }
+                    (*fun)(slf)
+                },
                 PyRustFun::MethO(ref fun) => {
                     if args.len() != 1 {
                         pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
                                          "1 argument expected");
                         return Err(())
                     }
-                    // Probably, slf cannot be None after module is implemented
-                    let slf = match obj.ob_self {
-                        Some(ref slf) => Rc::clone(slf),
-                        None => PY_NONE_OBJECT.with(|ob| { Rc::clone(ob) })
-                    };
                     (*fun)(slf, Rc::clone(&args[0]))
-                }
+                },
+                PyRustFun::MethVarArgs(ref fun) => (*fun)(slf, args),
+                PyRustFun::MethKeywords(ref fun) => (*fun)(slf, args, kwargs),
             }
         },
         _ => {
             let ob_type = funv.ob_type();
-            let typ = ob_type.pytype_typeobj_borrow();
-            match typ.tp_call {
-                Some(ref tp_call) => tp_call(Rc::clone(&funv), args),
+            let tp_call = ob_type.pytype_typeobj_borrow().tp_call.clone();
+            match tp_call {
+                Some(ref tp_call) => tp_call(Rc::clone(&funv), args, kwargs),
                 None => {
                     pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
                                      "callable object expected");
@@ -135,17 +602,38 @@ pub fn call_func(funv: Rc<PyObject>, args: &Vec<Rc<PyObject>>) -> PyRes<Rc<PyObj
     }
 }
 
+pub fn check_no_kwargs(name: &str, kwargs: &Option<Rc<PyObject>>) -> PyRes<()> {
+    match *kwargs {
+        Some(ref kwargs) if kwargs.pydict_size() > 0 => {
+            pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                             &format!("{}() takes no keyword arguments", name));
+            Err(())
+        },
+        _ => Ok(()),
+    }
+}
+
+/// The name a call error gives for a callable.
+pub fn pyobj_func_name(funv: &Rc<PyObject>) -> String {
+    match funv.inner {
+        PyInnerObject::FunObj(ref fun) => fun.codeobj.pycode_name(),
+        PyInnerObject::MethodObj(ref method) => pyobj_func_name(&method.func),
+        PyInnerObject::RustFunObj(ref obj) => obj.name.clone(),
+        PyInnerObject::TypeObj(ref typ) => typ.borrow().tp_name.clone(),
+        _ => format!("{} object", funv.ob_type().pytype_typeobj_borrow().tp_name),
+    }
+}
+
 pub fn bind_self(value: &Rc<PyObject>, slf: Rc<PyObject>) -> Rc<PyObject> {
     match value.inner {
-        PyInnerObject::FunObj(ref fun) => {
+        PyInnerObject::FunObj(_) => {
             Rc::new(PyObject {
                 ob_type: PY_METHOD_TYPE.with(|tp| { Some(Rc::clone(tp)) }),
                 ob_dict: None,
                 inner: PyInnerObject::MethodObj(Rc::new(
                     PyMethodObject {
                         ob_self: Rc::clone(&slf),
-                        env: Rc::clone(&fun.env),
-                        codeobj: Rc::clone(&fun.codeobj),
+                        func: Rc::clone(value),
                     }))
             })
         },
@@ -169,15 +657,15 @@ pub fn pyobj_get_attr(value: Rc<PyObject>, key: Rc<PyObject>) -> PyRes<Rc<PyObje
     let mut res = None;
 
     if let Some(ref tp_getattro) = ob_type.pytype_typeobj_borrow().tp_getattro {
-        res = tp_getattro(value, key)?;
+        res = tp_getattro(value, Rc::clone(&key))?;
     };
 
     match res {
         Some(res) => Ok(res),
         None => {
-            pyerr_set_string(
-                PY_ATTRIBUTEERROR_TYPE.with(|tp| Rc::clone(tp)),
-                "failed to find attribute");
+            let msg = format!("'{}' object has no attribute '{}'",
+                              ob_type.pytype_typeobj_borrow().tp_name, pyobj_to_string(key)?);
+            pyerr_set_string(PY_ATTRIBUTEERROR_TYPE.with(|tp| Rc::clone(tp)), &msg);
             Err(())
         }
     }
@@ -0,0 +1,236 @@
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+use env::Env;
+use error::*;
+use eval::{new_exception, resume, resume_throw, PyRes, Resumed, StackMachine};
+use object::{PyObject, PyInnerObject};
+use object::excobj::*;
+use object::generic::*;
+use object::rustfunobj::*;
+use object::typeobj::*;
+
+thread_local! (
+    pub static PY_GEN_TYPE: Rc<PyObject> = {
+        let tp_methods = vec![
+            PyObject::pyrustfun_method("send", PyRustFun::MethO(Rc::new(PyObject::pygen_send))),
+            PyObject::pyrustfun_method("throw", PyRustFun::MethVarArgs(Rc::new(PyObject::pygen_throw))),
+            PyObject::pyrustfun_method("close", PyRustFun::MethNoArgs(Rc::new(PyObject::pygen_close))),
+        ];
+        let gentp = PyTypeObject {
+            tp_name: "generator".to_string(),
+            tp_hash: Some(Rc::new(default_hash)),
+            tp_iter: Some(Rc::new(pygen_iter)),
+            tp_iternext: Some(Rc::new(pygen_iternext)),
+            tp_getattro: Some(Rc::new(pyobj_generic_get_attro)),
+            tp_methods: Some(tp_methods),
+            ..Default::default()
+        };
+        Rc::new(PyObject {
+            ob_type: PY_TYPE_TYPE.with(|tp| { Some(Rc::clone(tp)) }),
+            ob_dict: None,
+            inner: PyInnerObject::TypeObj(Rc::new(RefCell::new(gentp))),
+        })
+    }
+);
+
+/// A suspended run of a generator's code, which each `next` continues up
+/// to the following yield.
+pub struct PyGenObject {
+    gi_code: Rc<PyObject>,
+    gi_env: Rc<Env>,
+    // None once the generator has finished
+    gi_frame: RefCell<Option<StackMachine>>,
+    gi_running: Cell<bool>,
+    // the exception the generator's own except clauses are handling, kept
+    // apart from its caller's while it is suspended
+    gi_exc_info: RefCell<Option<Rc<PyObject>>>,
+}
+
+fn pygen_iter(gen: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    Ok(gen)
+}
+
+// A return value other than None is left in a StopIteration, as CPython does.
+fn pygen_iternext(gen: Rc<PyObject>) -> PyRes<Option<Rc<PyObject>>> {
+    match gen.pygen_send_ex(PyObject::none_obj())? {
+        Resumed::Yield(v) => Ok(Some(v)),
+        Resumed::Return(v) => {
+            if !v.pynone_check() {
+                pyerr_set_stop_iteration(v);
+            }
+            Ok(None)
+        },
+    }
+}
+
+/// Sends `value` on to the iterator a `yield from` delegates to. Iterators
+/// other than generators are advanced when the value is None and have their
+/// `send` method called otherwise.
+pub fn pyiter_send(iter: Rc<PyObject>, value: Rc<PyObject>) -> PyRes<Resumed> {
+    if iter.pygen_check() {
+        return iter.pygen_send_ex(value);
+    }
+    if value.pynone_check() {
+        return match pyobj_iter_next(iter)? {
+            Some(v) => Ok(Resumed::Yield(v)),
+            None => Ok(Resumed::Return(PyObject::none_obj())),
+        };
+    }
+    let send = pyobj_get_attr(iter, PyObject::from_str("send"))?;
+    match call_func(send, &vec![value]) {
+        Ok(v) => Ok(Resumed::Yield(v)),
+        Err(()) if pyerr_check(PY_STOPITERATION_TYPE.with(|tp| Rc::clone(tp))) => {
+            let exc = pyerr_fetch().expect("Never fails");
+            Ok(Resumed::Return(exc.pystopiteration_value()))
+        },
+        Err(()) => Err(()),
+    }
+}
+
+impl PyObject {
+    pub fn pygen_new(codeobj: Rc<PyObject>, env: Rc<Env>) -> Rc<PyObject> {
+        Rc::new(PyObject {
+            ob_type: PY_GEN_TYPE.with(|tp| { Some(Rc::clone(tp)) }),
+            ob_dict: None,
+            inner: PyInnerObject::GenObj(Rc::new(PyGenObject {
+                gi_code: codeobj,
+                gi_env: env,
+                gi_frame: RefCell::new(Some(StackMachine::new())),
+                gi_running: Cell::new(false),
+                gi_exc_info: RefCell::new(None),
+            }))
+        })
+    }
+
+    pub fn pygen_check(&self) -> bool {
+        match self.inner {
+            PyInnerObject::GenObj(_) => true,
+            _ => false,
+        }
+    }
+
+    fn pygen_inner(&self) -> &PyGenObject {
+        match self.inner {
+            PyInnerObject::GenObj(ref gen) => gen,
+            _ => panic!("Type Error: pygen_inner"),
+        }
+    }
+
+    // Runs `f` on the generator's frame with the generator's exception state
+    // in place of the caller's. None means the generator has finished.
+    fn pygen_run<F>(&self, f: F) -> Option<PyRes<Resumed>>
+    where F: FnOnce(&mut StackMachine, &Rc<PyObject>, Rc<Env>) -> PyRes<Resumed> {
+        let gen = self.pygen_inner();
+        if gen.gi_running.get() {
+            pyerr_set_string(PY_VALUEERROR_TYPE.with(|tp| Rc::clone(tp)), "generator already executing");
+            return Some(Err(()));
+        }
+        // the frame is taken out while it runs and put back if it yields
+        let mut frame = match gen.gi_frame.borrow_mut().take() {
+            Some(frame) => frame,
+            None => return None,
+        };
+        let caller_exc_info = pyerr_get_exc_info();
+        pyerr_set_exc_info(gen.gi_exc_info.borrow_mut().take());
+        gen.gi_running.set(true);
+        let res = f(&mut frame, &gen.gi_code, Rc::clone(&gen.gi_env));
+        gen.gi_running.set(false);
+        *gen.gi_exc_info.borrow_mut() = pyerr_get_exc_info();
+        pyerr_set_exc_info(caller_exc_info);
+
+        match res {
+            Ok(Resumed::Yield(_)) => *gen.gi_frame.borrow_mut() = Some(frame),
+            // a StopIteration escaping the code would look like its end
+            Err(()) if pyerr_check(PY_STOPITERATION_TYPE.with(|tp| Rc::clone(tp))) => {
+                let stop = pyerr_fetch().expect("Never fails");
+                pyerr_set_string(PY_RUNTIMEERROR_TYPE.with(|tp| Rc::clone(tp)), "generator raised StopIteration");
+                if let Some(err) = pyerr_fetch() {
+                    err.pyexc_set_cause(Some(stop));
+                    pyerr_restore(err);
+                }
+            },
+            _ => {},
+        }
+        Some(res)
+    }
+
+    /// Runs the generator up to its next yield, with `value` as the result
+    /// of the yield it is suspended at.
+    pub fn pygen_send_ex(&self, value: Rc<PyObject>) -> PyRes<Resumed> {
+        let started = match *self.pygen_inner().gi_frame.borrow() {
+            Some(ref frame) => frame.started(),
+            None => true,
+        };
+        if !started && !value.pynone_check() {
+            pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                             "can't send non-None value to a just-started generator");
+            return Err(());
+        }
+        let value = if started { Some(value) } else { None };
+        let res = self.pygen_run(|frame, codeobj, env| resume(frame, codeobj, env, value));
+        res.unwrap_or_else(|| Ok(Resumed::Return(PyObject::none_obj())))
+    }
+
+    /// Raises the pending exception inside the generator where it stopped.
+    /// A finished generator leaves it to its caller.
+    pub fn pygen_throw_ex(&self) -> PyRes<Resumed> {
+        self.pygen_run(resume_throw).unwrap_or(Err(()))
+    }
+
+    fn pygen_send(self: Rc<Self>, value: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+        match self.pygen_send_ex(value)? {
+            Resumed::Yield(v) => Ok(v),
+            Resumed::Return(v) => {
+                pyerr_set_stop_iteration(v);
+                Err(())
+            },
+        }
+    }
+
+    // throw(type[, value]) or throw(exception)
+    fn pygen_throw(self: Rc<Self>, args: &Vec<Rc<PyObject>>) -> PyRes<Rc<PyObject>> {
+        check_arg_count("throw", args, 1, 2)?;
+        let typ = Rc::clone(&args[0]);
+        let exc = match args.get(1) {
+            Some(value) if !value.pynone_check() && PyObject::pyexc_is_exc_subclass(Rc::clone(&typ)) => {
+                if pyobj_isinstance(Rc::clone(value), Rc::clone(&typ)) {
+                    Rc::clone(value)
+                } else {
+                    call_func(typ, &vec![Rc::clone(value)])?
+                }
+            },
+            _ => new_exception(typ, "exceptions must be classes or instances deriving from BaseException")?,
+        };
+        pyerr_restore(exc);
+        match self.pygen_throw_ex()? {
+            Resumed::Yield(v) => Ok(v),
+            Resumed::Return(v) => {
+                pyerr_set_stop_iteration(v);
+                Err(())
+            },
+        }
+    }
+
+    // Raises GeneratorExit where the generator stopped, which it may only
+    // answer by finishing.
+    fn pygen_close(self: Rc<Self>) -> PyRes<Rc<PyObject>> {
+        let exit = call_func(PY_GENERATOREXIT_TYPE.with(|tp| Rc::clone(tp)), &vec![])?;
+        pyerr_restore(exit);
+        match self.pygen_throw_ex() {
+            Ok(Resumed::Yield(_)) => {
+                pyerr_set_string(PY_RUNTIMEERROR_TYPE.with(|tp| Rc::clone(tp)), "generator ignored GeneratorExit");
+                Err(())
+            },
+            Ok(Resumed::Return(_)) => Ok(PyObject::none_obj()),
+            Err(()) => {
+                if pyerr_check(PY_GENERATOREXIT_TYPE.with(|tp| Rc::clone(tp))) ||
+                    pyerr_check(PY_STOPITERATION_TYPE.with(|tp| Rc::clone(tp))) {
+                    pyerr_clear();
+                    return Ok(PyObject::none_obj());
+                }
+                Err(())
+            },
+        }
+    }
+}
@@ -3,8 +3,10 @@ use std::rc::Rc;
 
 use error::*;
 use eval::PyRes;
+use syntax::CompOp;
 use object::{PyObject, PyInnerObject};
 use object::excobj::*;
+use object::generic::*;
 use object::noneobj::*;
 use object::rustfunobj::*;
 use object::typeobj::{PyTypeObject, PY_TYPE_TYPE};
@@ -12,21 +14,26 @@ use object::typeobj::{PyTypeObject, PY_TYPE_TYPE};
 
 thread_local! (
     pub static PY_LIST_TYPE: Rc<PyObject> = {
-        let mut tp_methods = vec![];
-        tp_methods.push(Rc::new(PyObject {
-            ob_type: PY_RUSTFUN_TYPE.with(|tp| { Some(Rc::clone(tp)) }),
-            ob_dict: None,
-            inner: PyInnerObject::RustFunObj(Rc::new(PyRustFunObject {
-                name: "append".to_string(),
-                ob_self: None,
-                rust_fun: PyRustFun::MethO(Rc::new(PyObject::pylist_append)),
-            })),
-        }));
+        let tp_methods = vec![
+            PyObject::pyrustfun_method("append", PyRustFun::MethO(Rc::new(PyObject::pylist_append))),
+        ];
         let listtp = PyTypeObject {
             tp_name: "list".to_string(),
+            tp_hash: Some(Rc::new(pyobj_unhashable)),
             tp_bool: Some(Rc::new(PyObject::pylist_bool)),
             tp_len: Some(Rc::new(PyObject::pylist_len)),
+            tp_repr: Some(Rc::new(PyObject::pylist_repr)),
             tp_iter: Some(Rc::new(PyObject::pylist_iter)),
+            tp_contains: Some(Rc::new(PyObject::pylist_contains)),
+            tp_getitem: Some(Rc::new(PyObject::pylist_subscript)),
+            tp_setitem: Some(Rc::new(PyObject::pylist_ass_subscript)),
+            tp_delitem: Some(Rc::new(PyObject::pylist_del_subscript)),
+            tp_fun_eq: Some(Rc::new(pylist_eq)),
+            tp_fun_ne: Some(Rc::new(pylist_ne)),
+            tp_fun_lt: Some(Rc::new(pylist_lt)),
+            tp_fun_le: Some(Rc::new(pylist_le)),
+            tp_fun_gt: Some(Rc::new(pylist_gt)),
+            tp_fun_ge: Some(Rc::new(pylist_ge)),
             tp_methods: Some(tp_methods),
             ..Default::default()
         };
@@ -42,6 +49,70 @@ pub struct PyListObject {
     list: RefCell<Vec<Rc<PyObject>>>,
 }
 
+// Lists compare like tuples. Items are fetched one at a time since an
+// item's comparison may change either list.
+fn pylist_richcmp(lv: Rc<PyObject>, rv: Rc<PyObject>, op: CompOp) -> PyRes<Rc<PyObject>> {
+    match (&lv.inner, &rv.inner) {
+        (&PyInnerObject::ListObj(_), &PyInnerObject::ListObj(_)) => {},
+        _ => return Ok(PyObject::notimpl_obj()),
+    }
+    if lv.pylist_size() != rv.pylist_size() {
+        match op {
+            CompOp::EqEq => return Ok(PyObject::from_bool(false)),
+            CompOp::NotEq => return Ok(PyObject::from_bool(true)),
+            _ => {},
+        }
+    }
+    let mut i = 0;
+    while i < lv.pylist_size() && i < rv.pylist_size() {
+        let (l_item, r_item) = (lv.pylist_getitem(i)?, rv.pylist_getitem(i)?);
+        let equal = l_item == r_item
+            || pyobj_to_bool(pyobj_richcompare(Rc::clone(&l_item), Rc::clone(&r_item), CompOp::EqEq)?)?;
+        if !equal {
+            return match op {
+                CompOp::EqEq => Ok(PyObject::from_bool(false)),
+                CompOp::NotEq => Ok(PyObject::from_bool(true)),
+                _ => pyobj_richcompare(l_item, r_item, op),
+            };
+        }
+        i += 1;
+    }
+    let (l_len, r_len) = (lv.pylist_size(), rv.pylist_size());
+    let res = match op {
+        CompOp::Lt => l_len < r_len,
+        CompOp::LtE => l_len <= r_len,
+        CompOp::Gt => l_len > r_len,
+        CompOp::GtE => l_len >= r_len,
+        CompOp::EqEq => l_len == r_len,
+        _ => l_len != r_len,
+    };
+    Ok(PyObject::from_bool(res))
+}
+
+fn pylist_eq(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pylist_richcmp(lv, rv, CompOp::EqEq)
+}
+
+fn pylist_ne(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pylist_richcmp(lv, rv, CompOp::NotEq)
+}
+
+fn pylist_lt(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pylist_richcmp(lv, rv, CompOp::Lt)
+}
+
+fn pylist_le(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pylist_richcmp(lv, rv, CompOp::LtE)
+}
+
+fn pylist_gt(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pylist_richcmp(lv, rv, CompOp::Gt)
+}
+
+fn pylist_ge(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pylist_richcmp(lv, rv, CompOp::GtE)
+}
+
 impl PyObject {
     pub fn pylist_from_vec(v: &Vec<Rc<PyObject>>) -> Rc<PyObject> {
         PY_LIST_TYPE.with(|tp| {
@@ -73,6 +144,14 @@ impl PyObject {
         }
     }
 
+    fn pylist_repr(self: Rc<Self>) -> PyRes<Rc<PyObject>> {
+        let mut reprs = vec![];
+        for elm in self.pylist_clone() {
+            reprs.push(pyobj_to_string(pyobj_repr(elm)?)?);
+        }
+        Ok(PyObject::from_string(format!("[{}]", reprs.join(", "))))
+    }
+
     fn pylist_bool(self: Rc<Self>) -> PyRes<Rc<PyObject>> {
         match self.inner {
             PyInnerObject::ListObj(ref obj) => Ok(PyObject::from_bool(!(obj.list.borrow().is_empty()))),
@@ -94,7 +173,7 @@ impl PyObject {
                     None => {
                         pyerr_set_string(
                             PY_INDEXERROR_TYPE.with(|tp| Rc::clone(tp)),
-                            "len expects list object"
+                            "list index out of range"
                         );
                         Err(())
                     }
@@ -144,6 +223,122 @@ impl PyObject {
         }
     }
 
+    pub fn pylist_setitem(&self, index: usize, item: Rc<PyObject>) -> PyRes<()> {
+        match self.inner {
+            PyInnerObject::ListObj(ref obj) => {
+                let mut list = obj.list.borrow_mut();
+                if index >= list.len() {
+                    pyerr_set_string(
+                        PY_INDEXERROR_TYPE.with(|tp| Rc::clone(tp)),
+                        "list assignment index out of range"
+                    );
+                    return Err(());
+                }
+                list[index] = item;
+                Ok(())
+            },
+            _ => {
+                pyerr_set_string(
+                    PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                    "__setitem__ expects list object"
+                );
+                Err(())
+            }
+        }
+    }
+
+    pub fn pylist_delitem(&self, index: usize) -> PyRes<()> {
+        match self.inner {
+            PyInnerObject::ListObj(ref obj) => {
+                let mut list = obj.list.borrow_mut();
+                if index >= list.len() {
+                    pyerr_set_string(
+                        PY_INDEXERROR_TYPE.with(|tp| Rc::clone(tp)),
+                        "list assignment index out of range"
+                    );
+                    return Err(());
+                }
+                list.remove(index);
+                Ok(())
+            },
+            _ => {
+                pyerr_set_string(
+                    PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                    "__delitem__ expects list object"
+                );
+                Err(())
+            }
+        }
+    }
+
+    fn pylist_subscript(self: Rc<Self>, key: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+        let list = self.pylist_clone();
+        if key.pyslice_check() {
+            let items: Vec<_> = key.pyslice_positions(list.len())?.into_iter()
+                .map(|i| Rc::clone(&list[i])).collect();
+            return Ok(PyObject::pylist_from_vec(&items));
+        }
+        let index = pyobj_seq_index(key, list.len(), "list", "list index out of range")?;
+        Ok(Rc::clone(&list[index]))
+    }
+
+    // A slice with no step is replaced by any number of items; an extended
+    // slice only by as many as it picks.
+    fn pylist_ass_subscript(self: Rc<Self>, key: Rc<PyObject>, value: Rc<PyObject>) -> PyRes<()> {
+        let len = self.pylist_size();
+        if !key.pyslice_check() {
+            let index = pyobj_seq_index(key, len, "list", "list assignment index out of range")?;
+            return self.pylist_setitem(index, value);
+        }
+        let items = pyobj_to_vec(value)?;
+        let (start, stop, step) = key.pyslice_indices(len)?;
+        let obj = match self.inner {
+            PyInnerObject::ListObj(ref obj) => obj,
+            _ => panic!("Type Error: pylist_ass_subscript"),
+        };
+        if step == 1 {
+            let start = start as usize;
+            let stop = if stop < start as isize { start } else { stop as usize };
+            obj.list.borrow_mut().splice(start..stop, items);
+            return Ok(());
+        }
+        let positions = key.pyslice_positions(len)?;
+        if positions.len() != items.len() {
+            let msg = format!("attempt to assign sequence of size {} to extended slice of size {}",
+                              items.len(), positions.len());
+            pyerr_set_string(PY_VALUEERROR_TYPE.with(|tp| Rc::clone(tp)), &msg);
+            return Err(());
+        }
+        let mut list = obj.list.borrow_mut();
+        for (i, item) in positions.into_iter().zip(items) {
+            list[i] = item;
+        }
+        Ok(())
+    }
+
+    fn pylist_del_subscript(self: Rc<Self>, key: Rc<PyObject>) -> PyRes<()> {
+        let len = self.pylist_size();
+        if !key.pyslice_check() {
+            let index = pyobj_seq_index(key, len, "list", "list assignment index out of range")?;
+            return self.pylist_delitem(index);
+        }
+        let mut positions = key.pyslice_positions(len)?;
+        positions.sort();
+        for i in positions.into_iter().rev() {
+            self.pylist_delitem(i)?;
+        }
+        Ok(())
+    }
+
+    fn pylist_contains(self: Rc<Self>, item: Rc<PyObject>) -> PyRes<bool> {
+        for elm in self.pylist_clone() {
+            if elm == item || pyobj_to_bool(pyobj_richcompare(elm, Rc::clone(&item), CompOp::EqEq)?)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
     pub fn pylist_iter(self: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
         if !self.pylist_check() {
             pyerr_set_string(
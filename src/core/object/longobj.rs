@@ -1,12 +1,14 @@
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
 use std::cell::RefCell;
 use std::rc::Rc;
 
 use error::*;
 use eval::PyRes;
 use object::{PyObject, PyInnerObject};
+use object::bigint::BigInt;
 use object::excobj::*;
+use object::floatobj::*;
+use object::formatter::format_long;
+use object::generic::{check_no_kwargs, pyobj_str, pyobj_to_i32, pyobj_to_string};
 use object::typeobj::*;
 
 
@@ -16,9 +18,24 @@ thread_local! (
             tp_name: "int".to_string(),
             tp_hash: Some(Rc::new(PyObject::pylong_hash)),
             tp_bool: Some(Rc::new(PyObject::pylong_bool)),
+            tp_repr: Some(Rc::new(PyObject::pylong_repr)),
+            tp_format: Some(Rc::new(pylong_format)),
+            tp_new: Some(Rc::new(pylong_new)),
             tp_fun_eq: Some(Rc::new(PyObject::pylong_eq)),
             tp_fun_add: Some(Rc::new(PyObject::pylong_add)),
+            tp_fun_sub: Some(Rc::new(PyObject::pylong_sub)),
+            tp_fun_mul: Some(Rc::new(PyObject::pylong_mul)),
+            tp_fun_truediv: Some(Rc::new(PyObject::pylong_truediv)),
+            tp_fun_floordiv: Some(Rc::new(PyObject::pylong_floordiv)),
+            tp_fun_mod: Some(Rc::new(PyObject::pylong_mod)),
+            tp_fun_pow: Some(Rc::new(PyObject::pylong_pow)),
+            tp_fun_neg: Some(Rc::new(PyObject::pylong_neg)),
+            tp_fun_pos: Some(Rc::new(PyObject::pylong_pos)),
+            tp_fun_ne: Some(Rc::new(PyObject::pylong_ne)),
             tp_fun_lt: Some(Rc::new(PyObject::pylong_lt)),
+            tp_fun_le: Some(Rc::new(PyObject::pylong_le)),
+            tp_fun_gt: Some(Rc::new(PyObject::pylong_gt)),
+            tp_fun_ge: Some(Rc::new(PyObject::pylong_ge)),
             ..Default::default()
         };
         Rc::new(PyObject {
@@ -29,14 +46,103 @@ thread_local! (
     }
 );
 
+fn zero_division_error() -> PyRes<Rc<PyObject>> {
+    pyerr_set_string(PY_ZERODIVERROR_TYPE.with(|tp| Rc::clone(tp)),
+                     "integer division or modulo by zero");
+    Err(())
+}
+
+// an empty spec gives the str, which differs for bool
+fn pylong_format(v: Rc<PyObject>, spec: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    let spec = pyobj_to_string(spec)?;
+    if spec.is_empty() {
+        return pyobj_str(v);
+    }
+    match v.inner {
+        PyInnerObject::LongObj(ref obj) => Ok(PyObject::from_string(format_long(&obj.n, &spec)?)),
+        _ => {
+            pyerr_set_string(
+                PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                "__format__ expects int objects"
+            );
+            Err(())
+        }
+    }
+}
+
+fn pylong_new(_typ: Rc<PyObject>, args: &Vec<Rc<PyObject>>, kwargs: Option<Rc<PyObject>>) -> PyRes<Rc<PyObject>> {
+    check_no_kwargs("int", &kwargs)?;
+    if args.len() > 2 {
+        pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                         "int() takes at most 2 arguments");
+        return Err(());
+    }
+    if args.len() == 2 {
+        let base = pyobj_to_i32(Rc::clone(&args[1]))?;
+        if base < 2 || base > 36 {
+            pyerr_set_string(PY_VALUEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                             "int() base must be >= 2 and <= 36");
+            return Err(());
+        }
+        return match args[0].inner {
+            PyInnerObject::StrObj(ref obj) => pylong_from_str(&obj.s, base as u32),
+            _ => {
+                pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                                 "int() can't convert non-string with explicit base");
+                Err(())
+            }
+        };
+    }
+    match args.get(0).map(|v| &v.inner) {
+        None => Ok(PyObject::from_i32(0)),
+        Some(&PyInnerObject::LongObj(ref obj)) => Ok(PyObject::from_bigint(obj.n.clone())),
+        Some(&PyInnerObject::FloatObj(ref obj)) => {
+            if obj.n.is_nan() {
+                pyerr_set_string(PY_VALUEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                                 "cannot convert float NaN to integer");
+                return Err(());
+            }
+            if obj.n.is_infinite() {
+                pyerr_set_string(PY_OVERFLOWERROR_TYPE.with(|tp| Rc::clone(tp)),
+                                 "cannot convert float infinity to integer");
+                return Err(());
+            }
+            Ok(PyObject::from_bigint(BigInt::from_f64(obj.n)))
+        },
+        Some(&PyInnerObject::StrObj(ref obj)) => pylong_from_str(&obj.s, 10),
+        _ => {
+            pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                             "int() argument must be a string or a number");
+            Err(())
+        }
+    }
+}
+
+fn pylong_from_str(s: &str, base: u32) -> PyRes<Rc<PyObject>> {
+    match BigInt::parse(s.trim(), base) {
+        Some(n) => Ok(PyObject::from_bigint(n)),
+        None => {
+            pyerr_set_string(
+                PY_VALUEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                &format!("invalid literal for int() with base {}: '{}'", base, s)
+            );
+            Err(())
+        }
+    }
+}
+
 pub struct PyLongObject {
-    pub n: i32,
+    pub n: BigInt,
 }
 
 impl PyObject {
     pub fn from_i32(raw_i32: i32) -> Rc<PyObject> {
+        PyObject::from_bigint(BigInt::from(raw_i32))
+    }
+
+    pub fn from_bigint(n: BigInt) -> Rc<PyObject> {
         PY_LONG_TYPE.with(|tp| {
-            let inner = PyLongObject { n: raw_i32 };
+            let inner = PyLongObject { n: n };
             Rc::new(PyObject {
                 ob_type: Some(Rc::clone(&tp)),
                 ob_dict: None,
@@ -45,81 +151,190 @@ impl PyObject {
         })
     }
 
+    fn pylong_richcmp<F>(self: Rc<Self>, rv: Rc<PyObject>, f: F) -> PyRes<Rc<PyObject>>
+    where F: Fn(&BigInt, &BigInt) -> bool {
+        match (&self.inner, &rv.inner) {
+            (&PyInnerObject::LongObj(ref l_obj), &PyInnerObject::LongObj(ref r_obj)) => {
+                Ok(PyObject::from_bool(f(&l_obj.n, &r_obj.n)))
+            },
+            _ => Ok(PyObject::notimpl_obj()),
+        }
+    }
+
     fn pylong_eq(self: Rc<Self>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
-        match self.inner {
-            PyInnerObject::LongObj(ref l_obj) => {
-                match rv.inner {
-                    PyInnerObject::LongObj(ref r_obj) => {
-                        return Ok(PyObject::from_bool(l_obj.n == r_obj.n));
-                    },
-                    _ => {}
-                }
+        self.pylong_richcmp(rv, |l, r| l == r)
+    }
+
+    fn pylong_ne(self: Rc<Self>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+        self.pylong_richcmp(rv, |l, r| l != r)
+    }
+
+    fn pylong_lt(self: Rc<Self>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+        self.pylong_richcmp(rv, |l, r| l < r)
+    }
+
+    fn pylong_le(self: Rc<Self>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+        self.pylong_richcmp(rv, |l, r| l <= r)
+    }
+
+    fn pylong_gt(self: Rc<Self>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+        self.pylong_richcmp(rv, |l, r| l > r)
+    }
+
+    fn pylong_ge(self: Rc<Self>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+        self.pylong_richcmp(rv, |l, r| l >= r)
+    }
+
+    // an int operand paired with a float is handed over to the float slot
+    fn pylong_binop<F>(self: Rc<Self>, rv: Rc<PyObject>, name: &str, f: F,
+                       float_fun: fn(Rc<PyObject>, Rc<PyObject>) -> PyRes<Rc<PyObject>>)
+                       -> PyRes<Rc<PyObject>>
+    where F: Fn(&BigInt, &BigInt) -> PyRes<Rc<PyObject>> {
+        match (&self.inner, &rv.inner) {
+            (&PyInnerObject::LongObj(ref l_obj), &PyInnerObject::LongObj(ref r_obj)) => {
+                return f(&l_obj.n, &r_obj.n);
+            },
+            (&PyInnerObject::LongObj(_), &PyInnerObject::FloatObj(_)) |
+            (&PyInnerObject::LongObj(_), &PyInnerObject::ComplexObj(_)) => {
+                return float_fun(Rc::clone(&self), rv);
             },
             _ => {}
         }
         pyerr_set_string(
             PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
-            "__eq__ expects int objects"
+            &format!("{} expects int objects", name)
         );
         Err(())
     }
 
     fn pylong_add(self: Rc<Self>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
-        match self.inner {
-            PyInnerObject::LongObj(ref l_obj) => {
-                match rv.inner {
-                    PyInnerObject::LongObj(ref r_obj) => {
-                        return Ok(PyObject::from_i32(l_obj.n + r_obj.n));
-                    },
-                    _ => {}
+        self.pylong_binop(rv, "__add__", |l, r| Ok(PyObject::from_bigint(l.add(r))), pyfloat_add)
+    }
+
+    fn pylong_sub(self: Rc<Self>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+        self.pylong_binop(rv, "__sub__", |l, r| Ok(PyObject::from_bigint(l.sub(r))), pyfloat_sub)
+    }
+
+    fn pylong_mul(self: Rc<Self>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+        self.pylong_binop(rv, "__mul__", |l, r| Ok(PyObject::from_bigint(l.mul(r))), pyfloat_mul)
+    }
+
+    fn pylong_truediv(self: Rc<Self>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+        self.pylong_binop(rv, "__truediv__", |l, r| {
+            match l.true_div(r) {
+                None => {
+                    pyerr_set_string(PY_ZERODIVERROR_TYPE.with(|tp| Rc::clone(tp)), "division by zero");
+                    Err(())
+                },
+                Some(f) if f.is_infinite() => {
+                    pyerr_set_string(PY_OVERFLOWERROR_TYPE.with(|tp| Rc::clone(tp)),
+                                     "integer division result too large for a float");
+                    Err(())
+                },
+                Some(f) => Ok(PyObject::from_f64(f)),
+            }
+        }, pyfloat_truediv)
+    }
+
+    fn pylong_floordiv(self: Rc<Self>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+        self.pylong_binop(rv, "__floordiv__", |l, r| {
+            match l.divmod(r) {
+                Some((q, _)) => Ok(PyObject::from_bigint(q)),
+                None => zero_division_error(),
+            }
+        }, pyfloat_floordiv)
+    }
+
+    fn pylong_mod(self: Rc<Self>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+        self.pylong_binop(rv, "__mod__", |l, r| {
+            match l.divmod(r) {
+                Some((_, m)) => Ok(PyObject::from_bigint(m)),
+                None => zero_division_error(),
+            }
+        }, pyfloat_mod)
+    }
+
+    fn pylong_pow(self: Rc<Self>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+        self.pylong_binop(rv, "__pow__", |l, r| {
+            if r.is_negative() {
+                return pyfloat_pow(PyObject::from_f64(long_to_f64(l)?), PyObject::from_f64(long_to_f64(r)?));
+            }
+            // 0, 1 and -1 stay small for any exponent, and only its parity
+            // matters once it is positive
+            if l.to_i64().map_or(false, |n| n >= -1 && n <= 1) {
+                let odd = r.divmod(&BigInt::from(2)).expect("Never fails").1 == BigInt::from(1);
+                let exp = if r.is_zero() { 0 } else if odd { 1 } else { 2 };
+                return Ok(PyObject::from_bigint(l.pow(exp)));
+            }
+            match r.to_i64() {
+                Some(exp) if exp <= u32::max_value() as i64 => {
+                    Ok(PyObject::from_bigint(l.pow(exp as u64)))
+                },
+                _ => {
+                    pyerr_set_string(PY_OVERFLOWERROR_TYPE.with(|tp| Rc::clone(tp)),
+                                     "exponent too large");
+                    Err(())
                 }
-            },
-            _ => {}
+            }
+        }, pyfloat_pow)
+    }
+
+    fn pylong_neg(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+        match v.inner {
+            PyInnerObject::LongObj(ref obj) => Ok(PyObject::from_bigint(obj.n.neg())),
+            _ => {
+                pyerr_set_string(
+                    PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                    "__neg__ expects int objects"
+                );
+                Err(())
+            }
         }
-        pyerr_set_string(
-            PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
-            "__add__ expects int objects"
-        );
-        Err(())
     }
 
-    fn pylong_lt(self: Rc<Self>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
-        match self.inner {
-            PyInnerObject::LongObj(ref l_obj) => {
-                match rv.inner {
-                    PyInnerObject::LongObj(ref r_obj) => {
-                        return Ok(PyObject::from_bool(l_obj.n < r_obj.n));
-                    },
-                    _ => {}
-                }
-            },
-            _ => {}
+    fn pylong_pos(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+        match v.inner {
+            PyInnerObject::LongObj(ref obj) => Ok(PyObject::from_bigint(obj.n.clone())),
+            _ => {
+                pyerr_set_string(
+                    PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                    "__pos__ expects int objects"
+                );
+                Err(())
+            }
         }
-        pyerr_set_string(
-            PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
-            "__lt__ expects int objects"
-        );
-        Err(())
     }
 
+    // equal ints and floats must hash alike, see pyfloat_hash
     fn pylong_hash(obj: Rc<PyObject>) -> PyRes<u64> {
-        let mut hasher = DefaultHasher::new();
         match obj.inner {
-            PyInnerObject::LongObj(ref obj) => obj.n.hash(&mut hasher),
+            PyInnerObject::LongObj(ref obj) => Ok(obj.n.hash() as u64),
             _ => {
                 pyerr_set_string(
                     PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
                     "__hash__ expects int objects"
                 );
-                return Err(());
+                Err(())
             }
-        };
-        Ok(hasher.finish())
+        }
+    }
+
+    fn pylong_repr(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+        match v.inner {
+            PyInnerObject::LongObj(ref obj) => Ok(PyObject::from_string(obj.n.to_string())),
+            _ => {
+                pyerr_set_string(
+                    PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                    "__repr__ expects int objects"
+                );
+                Err(())
+            }
+        }
     }
 
     fn pylong_bool(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
         match v.inner {
-            PyInnerObject::LongObj(ref obj) => Ok(PyObject::from_bool(obj.n > 0)),
+            PyInnerObject::LongObj(ref obj) => Ok(PyObject::from_bool(!obj.n.is_zero())),
             _ => {
                 pyerr_set_string(
                     PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
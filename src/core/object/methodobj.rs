@@ -1,14 +1,13 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
-use env::Env;
 use object::{PyObject, PyInnerObject};
 use object::typeobj::*;
 
+/// A function bound to the object it was looked up on.
 pub struct PyMethodObject {
     pub ob_self: Rc<PyObject>,
-    pub env: Rc<Env>,
-    pub codeobj: Rc<PyObject>,
+    pub func: Rc<PyObject>,
 }
 
 thread_local! (
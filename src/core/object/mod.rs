@@ -1,46 +1,81 @@
+pub mod bigint;
 pub mod boolobj;
+pub mod bytesobj;
+pub mod cellobj;
 pub mod codeobj;
+pub mod complexobj;
 pub mod dictobj;
 pub mod excobj;
+pub mod floatobj;
+pub mod formatter;
 pub mod funobj;
+pub mod genobj;
 pub mod generic;
 pub mod listobj;
 pub mod longobj;
 pub mod methodobj;
 pub mod noneobj;
+pub mod notimplobj;
 pub mod pyhashmap;
 pub mod rustfunobj;
+pub mod setobj;
+pub mod sliceobj;
 pub mod strobj;
+pub mod tracebackobj;
+pub mod tupleobj;
 pub mod typeobj;
 
 use std::cell::RefCell;
 use std::rc::Rc;
 
+use self::bytesobj::PyBytesObject;
+use self::cellobj::PyCellObject;
 use self::codeobj::PyCodeObject;
-use self::dictobj::PyDictObject;
+use self::complexobj::PyComplexObject;
+use self::dictobj::{PyDictObject, PyDictIterObject, PyDictViewObject};
 use self::excobj::PyExcObject;
+use self::floatobj::PyFloatObject;
 use self::funobj::PyFunObject;
+use self::genobj::PyGenObject;
 use self::listobj::{PyListObject, PyListIterObject};
 use self::longobj::PyLongObject;
 use self::methodobj::PyMethodObject;
 use self::rustfunobj::PyRustFunObject;
+use self::setobj::{PySetObject, PySetIterObject};
+use self::sliceobj::PySliceObject;
 use self::strobj::PyStringObject;
+use self::tracebackobj::PyTracebackObject;
+use self::tupleobj::{PyTupleObject, PyTupleIterObject};
 use self::typeobj::PyTypeObject;
 
 pub enum PyInnerObject {
     BaseObj,
+    BytesObj(Rc<PyBytesObject>),
+    CellObj(Rc<PyCellObject>),
     CodeObj(Rc<PyCodeObject>),
+    ComplexObj(Rc<PyComplexObject>),
     DictObj(Rc<PyDictObject>),
+    DictIterObj(Rc<RefCell<PyDictIterObject>>),
+    DictViewObj(Rc<PyDictViewObject>),
     ExcObj(Rc<PyExcObject>),
+    FloatObj(Rc<PyFloatObject>),
     FunObj(Rc<PyFunObject>),
+    GenObj(Rc<PyGenObject>),
     InstObj,
     ListObj(Rc<PyListObject>),
     ListIterObj(Rc<RefCell<PyListIterObject>>),
     LongObj(Rc<PyLongObject>),
     MethodObj(Rc<PyMethodObject>),
     NoneObj,
+    NotImplObj,
     RustFunObj(Rc<PyRustFunObject>),
+    SetObj(Rc<PySetObject>),
+    SetIterObj(Rc<RefCell<PySetIterObject>>),
+    SliceObj(Rc<PySliceObject>),
     StrObj(Rc<PyStringObject>),
+    TracebackObj(Rc<PyTracebackObject>),
+    TupleObj(Rc<PyTupleObject>),
+    TupleIterObj(Rc<RefCell<PyTupleIterObject>>),
     TypeObj(Rc<RefCell<PyTypeObject>>),
 }
 
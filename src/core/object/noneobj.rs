@@ -1,14 +1,25 @@
 use std::cell::RefCell;
 use std::rc::Rc;
 
+use eval::PyRes;
 use object::{PyObject, PyInnerObject};
 use object::typeobj::*;
 
+fn none_repr(_v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    Ok(PyObject::from_str("None"))
+}
+
+fn none_bool(_v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    Ok(PyObject::from_bool(false))
+}
+
 thread_local! (
     pub static PY_NONE_TYPE: Rc<PyObject> = {
         let nonetp = PyTypeObject {
             tp_name: "None".to_string(),
             tp_hash: Some(Rc::new(default_hash)),
+            tp_bool: Some(Rc::new(none_bool)),
+            tp_repr: Some(Rc::new(none_repr)),
             ..Default::default()
         };
         Rc::new(PyObject {
@@ -33,4 +44,11 @@ impl PyObject {
     pub fn none_obj() -> Rc<PyObject> {
         PY_NONE_OBJECT.with(|obj| { Rc::clone(&obj) })
     }
+
+    pub fn pynone_check(&self) -> bool {
+        match self.inner {
+            PyInnerObject::NoneObj => true,
+            _ => false,
+        }
+    }
 }
@@ -0,0 +1,43 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use object::{PyObject, PyInnerObject};
+use object::typeobj::*;
+
+thread_local! (
+    pub static PY_NOTIMPL_TYPE: Rc<PyObject> = {
+        let notimpltp = PyTypeObject {
+            tp_name: "NotImplementedType".to_string(),
+            tp_hash: Some(Rc::new(default_hash)),
+            ..Default::default()
+        };
+        Rc::new(PyObject {
+            ob_type: PY_TYPE_TYPE.with(|tp| { Some(Rc::clone(tp)) }),
+            ob_dict: None,
+            inner: PyInnerObject::TypeObj(Rc::new(RefCell::new(notimpltp))),
+        })
+    };
+
+    pub static PY_NOTIMPL_OBJECT: Rc<PyObject> = {
+        PY_NOTIMPL_TYPE.with(|tp| {
+            Rc::new(PyObject {
+                ob_type: Some(Rc::clone(&tp)),
+                ob_dict: None,
+                inner: PyInnerObject::NotImplObj
+            })
+        })
+    }
+);
+
+impl PyObject {
+    pub fn notimpl_obj() -> Rc<PyObject> {
+        PY_NOTIMPL_OBJECT.with(|obj| { Rc::clone(&obj) })
+    }
+
+    pub fn pynotimpl_check(&self) -> bool {
+        match self.inner {
+            PyInnerObject::NotImplObj => true,
+            _ => false,
+        }
+    }
+}
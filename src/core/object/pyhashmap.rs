@@ -1,36 +1,217 @@
+use std::cell::RefCell;
 use std::rc::Rc;
 
+use eval::PyRes;
+use syntax::CompOp;
 use object::PyObject;
+use object::generic::*;
 
+const MIN_SIZE: usize = 8;
+const PERTURB_SHIFT: u32 = 5;
+
+#[derive(Clone, Copy)]
+enum Slot {
+    Empty,
+    Dummy,
+    Index(usize),
+}
+
+#[derive(Clone)]
+struct Entry {
+    hash: u64,
+    key: Rc<PyObject>,
+    value: Rc<PyObject>,
+}
+
+/// Insertion ordered hash table in the style of CPython's compact dict.
+///
+/// `entries` keeps key/value pairs in insertion order and `indices` is the
+/// open addressing table pointing into it. A deleted entry becomes `None`
+/// and its slot a `Dummy`, both reclaimed on the next resize.
+///
+/// Operations that compare keys take the table's `RefCell` rather than a
+/// borrow of it, because a key's `__eq__` may read or modify the table.
+#[derive(Clone)]
 pub struct PyHashMap {
-    table: Vec<(u64, Rc<PyObject>, Rc<PyObject>)>,
+    indices: Vec<Slot>,
+    entries: Vec<Option<Entry>>,
+    used: usize,
+}
+
+fn keys_equal(lv: &Rc<PyObject>, rv: &Rc<PyObject>) -> PyRes<bool> {
+    if Rc::ptr_eq(lv, rv) {
+        return Ok(true);
+    }
+    pyobj_to_bool(pyobj_richcompare(Rc::clone(lv), Rc::clone(rv), CompOp::EqEq)?)
+}
+
+fn next_slot(i: usize, perturb: &mut u64, mask: usize) -> usize {
+    *perturb >>= PERTURB_SHIFT;
+    (i.wrapping_mul(5).wrapping_add(*perturb as usize).wrapping_add(1)) & mask
 }
 
 impl PyHashMap {
     pub fn new() -> PyHashMap {
-        PyHashMap { table: vec![] }
+        PyHashMap { indices: vec![Slot::Empty; MIN_SIZE], entries: vec![], used: 0 }
     }
 
-    pub fn get(&self, hash: u64) -> Option<&Rc<PyObject>> {
-        self.table.iter().find_map(|tuple| {
-            if tuple.0 == hash {
-                Some(&tuple.2)
-            } else {
-                None
+    /// Finds the slot and entry index of a key equal to `key`.
+    ///
+    /// The borrow is released while the keys are compared. If the table or
+    /// the entry changed meanwhile the search starts over, as CPython does.
+    fn lookup(map: &RefCell<PyHashMap>, hash: u64, key: &Rc<PyObject>) -> PyRes<Option<(usize, usize)>> {
+        'restart: loop {
+            let size = map.borrow().indices.len();
+            let mask = size - 1;
+            let mut perturb = hash;
+            let mut i = hash as usize & mask;
+            loop {
+                let candidate = {
+                    let table = map.borrow();
+                    match table.indices[i] {
+                        Slot::Empty => return Ok(None),
+                        Slot::Dummy => None,
+                        Slot::Index(ix) => {
+                            let entry = table.entries[ix].as_ref().expect("Implementation Error: PyHashMap");
+                            if entry.hash == hash { Some((ix, Rc::clone(&entry.key))) } else { None }
+                        },
+                    }
+                };
+                if let Some((ix, startkey)) = candidate {
+                    let equal = keys_equal(&startkey, key)?;
+                    let table = map.borrow();
+                    let unchanged = table.indices.len() == size && match (table.indices[i], table.entries.get(ix)) {
+                        (Slot::Index(j), Some(&Some(ref entry))) => j == ix && Rc::ptr_eq(&entry.key, &startkey),
+                        _ => false,
+                    };
+                    if !unchanged {
+                        continue 'restart;
+                    }
+                    if equal {
+                        return Ok(Some((i, ix)));
+                    }
+                }
+                i = next_slot(i, &mut perturb, mask);
             }
-        })
+        }
+    }
+
+    // the first slot on the probe sequence of a key known to be absent
+    fn find_empty_slot(&self, hash: u64) -> usize {
+        let mask = self.indices.len() - 1;
+        let mut perturb = hash;
+        let mut i = hash as usize & mask;
+        while let Slot::Index(_) = self.indices[i] {
+            i = next_slot(i, &mut perturb, mask);
+        }
+        i
+    }
+
+    // rebuilds the index table, dropping deleted entries and dummy slots
+    fn resize(&mut self) {
+        let mut size = MIN_SIZE;
+        while size * 2 < (self.used + 1) * 3 {
+            size *= 2;
+        }
+        let entries: Vec<Option<Entry>> = self.entries.drain(..).filter(|e| e.is_some()).collect();
+        self.indices = vec![Slot::Empty; size];
+        for (ix, entry) in entries.iter().enumerate() {
+            let i = self.find_empty_slot(entry.as_ref().expect("Never fails").hash);
+            self.indices[i] = Slot::Index(ix);
+        }
+        self.entries = entries;
+    }
+
+    pub fn get(map: &RefCell<PyHashMap>, hash: u64, key: &Rc<PyObject>) -> PyRes<Option<Rc<PyObject>>> {
+        match PyHashMap::lookup(map, hash, key)? {
+            Some((_, ix)) => Ok(map.borrow().entries[ix].as_ref().map(|entry| Rc::clone(&entry.value))),
+            None => Ok(None),
+        }
     }
 
-    pub fn insert(&mut self, hash: u64, key: Rc<PyObject>, value: Rc<PyObject>) {
-        let new_entry = [(hash, Rc::clone(&key), Rc::clone(&value))];
-        let i = self.table.iter().position(|ref tuple| tuple.0 == hash);
-        match i {
-            Some(i) => { self.table.splice(i..i, new_entry.iter().cloned()); },
-            None => { self.table.push((hash, key, value)) },
+    /// Replaces the value if an equal key is present, keeping the original
+    /// key object and its position.
+    pub fn insert(map: &RefCell<PyHashMap>, hash: u64, key: Rc<PyObject>, value: Rc<PyObject>) -> PyRes<()> {
+        let found = PyHashMap::lookup(map, hash, &key)?;
+        let mut table = map.borrow_mut();
+        if let Some((_, ix)) = found {
+            table.entries[ix].as_mut().expect("Never fails").value = value;
+            return Ok(());
+        }
+
+        // keep at least a third of the slots empty so that probing ends
+        if (table.entries.len() + 1) * 3 > table.indices.len() * 2 {
+            table.resize();
+        }
+        let i = table.find_empty_slot(hash);
+        table.indices[i] = Slot::Index(table.entries.len());
+        table.entries.push(Some(Entry { hash: hash, key: key, value: value }));
+        table.used += 1;
+        Ok(())
+    }
+
+    pub fn remove(map: &RefCell<PyHashMap>, hash: u64, key: &Rc<PyObject>) -> PyRes<Option<Rc<PyObject>>> {
+        match PyHashMap::lookup(map, hash, key)? {
+            Some((i, ix)) => Ok(map.borrow_mut().remove_at(i, ix)),
+            None => Ok(None),
+        }
+    }
+
+    fn remove_at(&mut self, i: usize, ix: usize) -> Option<Rc<PyObject>> {
+        self.indices[i] = Slot::Dummy;
+        self.used -= 1;
+        self.entries[ix].take().map(|entry| entry.value)
+    }
+
+    /// Removes the most recently inserted entry.
+    pub fn pop_last(&mut self) -> Option<(Rc<PyObject>, Rc<PyObject>)> {
+        // trailing deleted entries are not referenced from the index table
+        while let Some(&None) = self.entries.last() {
+            self.entries.pop();
+        }
+        let (hash, key) = match self.entries.last() {
+            Some(&Some(ref entry)) => (entry.hash, Rc::clone(&entry.key)),
+            _ => return None,
         };
+        // the slot is found by position, so no keys are compared
+        let ix = self.entries.len() - 1;
+        let mask = self.indices.len() - 1;
+        let mut perturb = hash;
+        let mut i = hash as usize & mask;
+        loop {
+            if let Slot::Index(j) = self.indices[i] {
+                if j == ix {
+                    break;
+                }
+            }
+            i = next_slot(i, &mut perturb, mask);
+        }
+        let value = self.remove_at(i, ix).expect("Never fails");
+        Some((key, value))
+    }
+
+    /// Returns the first live entry at or after `pos` together with the
+    /// position to resume from.
+    pub fn next_entry(&self, pos: usize) -> Option<(usize, Rc<PyObject>, Rc<PyObject>)> {
+        for ix in pos..self.entries.len() {
+            if let Some(ref entry) = self.entries[ix] {
+                return Some((ix + 1, Rc::clone(&entry.key), Rc::clone(&entry.value)));
+            }
+        }
+        None
+    }
+
+    pub fn clear(&mut self) {
+        *self = PyHashMap::new();
+    }
+
+    pub fn items(&self) -> Vec<(Rc<PyObject>, Rc<PyObject>)> {
+        self.entries.iter()
+            .filter_map(|e| e.as_ref().map(|entry| (Rc::clone(&entry.key), Rc::clone(&entry.value))))
+            .collect()
     }
 
-    pub fn len(&self) -> Rc<PyObject> {
-        PyObject::from_i32(self.table.len() as i32)
+    pub fn len(&self) -> usize {
+        self.used
     }
 }
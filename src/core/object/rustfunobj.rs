@@ -13,7 +13,10 @@ pub struct PyRustFunObject {
 
 #[derive(Clone)]
 pub enum PyRustFun {
+    MethNoArgs(Rc<dyn Fn(Rc<PyObject>) -> PyRes<Rc<PyObject>>>),
     MethO(Rc<dyn Fn(Rc<PyObject>, Rc<PyObject>) -> PyRes<Rc<PyObject>>>),
+    MethVarArgs(Rc<dyn Fn(Rc<PyObject>, &Vec<Rc<PyObject>>) -> PyRes<Rc<PyObject>>>),
+    MethKeywords(Rc<dyn Fn(Rc<PyObject>, &Vec<Rc<PyObject>>, Option<Rc<PyObject>>) -> PyRes<Rc<PyObject>>>),
 }
 
 thread_local! (
@@ -32,6 +35,19 @@ thread_local! (
 );
 
 impl PyObject {
+    /// Unbound method entry for `tp_methods`.
+    pub fn pyrustfun_method(name: &str, rust_fun: PyRustFun) -> Rc<PyObject> {
+        Rc::new(PyObject {
+            ob_type: PY_RUSTFUN_TYPE.with(|tp| { Some(Rc::clone(tp)) }),
+            ob_dict: None,
+            inner: PyInnerObject::RustFunObj(Rc::new(PyRustFunObject {
+                name: name.to_string(),
+                ob_self: None,
+                rust_fun: rust_fun,
+            })),
+        })
+    }
+
     pub fn pyrustfun_name(self: Rc<PyObject>) -> String {
         match self.inner {
             PyInnerObject::RustFunObj(ref obj) => obj.name.clone(),
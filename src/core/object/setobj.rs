@@ -0,0 +1,260 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use error::*;
+use eval::PyRes;
+use object::{PyObject, PyInnerObject};
+use object::excobj::*;
+use object::generic::*;
+use object::rustfunobj::*;
+use object::typeobj::{PyTypeObject, PY_TYPE_TYPE};
+use object::pyhashmap::PyHashMap;
+
+thread_local! (
+    pub static PY_SET_TYPE: Rc<PyObject> = {
+        let tp_methods = vec![
+            PyObject::pyrustfun_method("add", PyRustFun::MethO(Rc::new(PyObject::pyset_add_method))),
+            PyObject::pyrustfun_method("discard", PyRustFun::MethO(Rc::new(PyObject::pyset_discard))),
+            PyObject::pyrustfun_method("remove", PyRustFun::MethO(Rc::new(PyObject::pyset_remove))),
+            PyObject::pyrustfun_method("clear", PyRustFun::MethNoArgs(Rc::new(PyObject::pyset_clear))),
+            PyObject::pyrustfun_method("copy", PyRustFun::MethNoArgs(Rc::new(PyObject::pyset_copy))),
+        ];
+        let settp = PyTypeObject {
+            tp_name: "set".to_string(),
+            tp_hash: Some(Rc::new(pyobj_unhashable)),
+            tp_fun_eq: Some(Rc::new(pyset_eq)),
+            tp_fun_ne: Some(Rc::new(pyset_ne)),
+            tp_len: Some(Rc::new(PyObject::pyset_len)),
+            tp_repr: Some(Rc::new(PyObject::pyset_repr)),
+            tp_contains: Some(Rc::new(PyObject::pyset_contains)),
+            tp_iter: Some(Rc::new(PyObject::pyset_iter)),
+            tp_getattro: Some(Rc::new(pyobj_generic_get_attro)),
+            tp_methods: Some(tp_methods),
+            tp_new: Some(Rc::new(set_new)),
+            ..Default::default()
+        };
+        Rc::new(PyObject {
+            ob_type: PY_TYPE_TYPE.with(|tp| { Some(Rc::clone(tp)) }),
+            ob_dict: None,
+            inner: PyInnerObject::TypeObj(Rc::new(RefCell::new(settp))),
+        })
+    }
+);
+
+fn set_new(_typ: Rc<PyObject>, args: &Vec<Rc<PyObject>>, kwargs: Option<Rc<PyObject>>) -> PyRes<Rc<PyObject>> {
+    check_no_kwargs("set", &kwargs)?;
+    check_arg_count("set", args, 0, 1)?;
+    let setobj = PyObject::pyset_new();
+    if let Some(iterable) = args.get(0) {
+        for item in pyobj_to_vec(Rc::clone(iterable))? {
+            setobj.pyset_add(item)?;
+        }
+    }
+    Ok(setobj)
+}
+
+// Sets are equal when they have the same size and one holds every item of
+// the other.
+fn pyset_richcmp(lv: Rc<PyObject>, rv: Rc<PyObject>, negate: bool) -> PyRes<Rc<PyObject>> {
+    if !lv.pyset_check() || !rv.pyset_check() {
+        return Ok(PyObject::notimpl_obj());
+    }
+    let mut eq = lv.pyset_size() == rv.pyset_size();
+    if eq {
+        for item in lv.pyset_items() {
+            if !rv.pyset_has(item)? {
+                eq = false;
+                break;
+            }
+        }
+    }
+    Ok(PyObject::from_bool(eq != negate))
+}
+
+fn pyset_eq(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pyset_richcmp(lv, rv, false)
+}
+
+fn pyset_ne(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pyset_richcmp(lv, rv, true)
+}
+
+/// An unordered collection of distinct hashable items, kept in a hash map
+/// whose values are all `None`.
+pub struct PySetObject {
+    set: RefCell<PyHashMap>,
+}
+
+impl PyObject {
+    pub fn pyset_new() -> Rc<PyObject> {
+        Rc::new(PyObject {
+            ob_type: PY_SET_TYPE.with(|tp| { Some(Rc::clone(tp)) }),
+            ob_dict: None,
+            inner: PyInnerObject::SetObj(Rc::new(PySetObject { set: RefCell::new(PyHashMap::new()) }))
+        })
+    }
+
+    pub fn pyset_check(&self) -> bool {
+        match self.inner {
+            PyInnerObject::SetObj(_) => true,
+            _ => false,
+        }
+    }
+
+    fn pyset_map(&self) -> PyRes<&RefCell<PyHashMap>> {
+        match self.inner {
+            PyInnerObject::SetObj(ref obj) => Ok(&obj.set),
+            _ => {
+                pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)), "set object expected");
+                Err(())
+            }
+        }
+    }
+
+    pub fn pyset_size(&self) -> usize {
+        match self.inner {
+            PyInnerObject::SetObj(ref obj) => obj.set.borrow().len(),
+            _ => panic!("Type Error: pyset_size"),
+        }
+    }
+
+    pub fn pyset_items(&self) -> Vec<Rc<PyObject>> {
+        match self.inner {
+            PyInnerObject::SetObj(ref obj) => {
+                obj.set.borrow().items().into_iter().map(|(key, _)| key).collect()
+            },
+            _ => panic!("Type Error: pyset_items"),
+        }
+    }
+
+    pub fn pyset_add(&self, item: Rc<PyObject>) -> PyRes<()> {
+        let hash = pyobj_hash(Rc::clone(&item))?;
+        PyHashMap::insert(self.pyset_map()?, hash, item, PyObject::none_obj())
+    }
+
+    pub fn pyset_has(&self, item: Rc<PyObject>) -> PyRes<bool> {
+        let hash = pyobj_hash(Rc::clone(&item))?;
+        Ok(PyHashMap::get(self.pyset_map()?, hash, &item)?.is_some())
+    }
+
+    fn pyset_len(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+        Ok(PyObject::from_i32(v.pyset_map()?.borrow().len() as i32))
+    }
+
+    fn pyset_repr(self: Rc<Self>) -> PyRes<Rc<PyObject>> {
+        let items = self.pyset_items();
+        if items.is_empty() {
+            return Ok(PyObject::from_str("set()"));
+        }
+        let mut reprs = vec![];
+        for item in items {
+            reprs.push(pyobj_to_string(pyobj_repr(item)?)?);
+        }
+        Ok(PyObject::from_string(format!("{{{}}}", reprs.join(", "))))
+    }
+
+    fn pyset_contains(self: Rc<Self>, item: Rc<PyObject>) -> PyRes<bool> {
+        self.pyset_has(item)
+    }
+
+    fn pyset_iter(self: Rc<Self>) -> PyRes<Rc<PyObject>> {
+        let used = self.pyset_map()?.borrow().len();
+        Ok(Rc::new(PyObject {
+            ob_type: PY_SETITER_TYPE.with(|tp| { Some(Rc::clone(tp)) }),
+            ob_dict: None,
+            inner: PyInnerObject::SetIterObj(Rc::new(RefCell::new(
+                PySetIterObject {
+                    it_set: self,
+                    it_pos: 0,
+                    it_used: Some(used),
+                }
+            )))
+        }))
+    }
+
+    fn pyset_add_method(self: Rc<Self>, item: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+        self.pyset_add(item)?;
+        Ok(PyObject::none_obj())
+    }
+
+    fn pyset_discard(self: Rc<Self>, item: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+        let hash = pyobj_hash(Rc::clone(&item))?;
+        PyHashMap::remove(self.pyset_map()?, hash, &item)?;
+        Ok(PyObject::none_obj())
+    }
+
+    fn pyset_remove(self: Rc<Self>, item: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+        let hash = pyobj_hash(Rc::clone(&item))?;
+        let removed = PyHashMap::remove(self.pyset_map()?, hash, &item)?;
+        match removed {
+            Some(_) => Ok(PyObject::none_obj()),
+            None => PyObject::pydict_key_error(item),
+        }
+    }
+
+    fn pyset_clear(self: Rc<Self>) -> PyRes<Rc<PyObject>> {
+        self.pyset_map()?.borrow_mut().clear();
+        Ok(PyObject::none_obj())
+    }
+
+    fn pyset_copy(self: Rc<Self>) -> PyRes<Rc<PyObject>> {
+        let map = self.pyset_map()?.borrow().clone();
+        Ok(Rc::new(PyObject {
+            ob_type: PY_SET_TYPE.with(|tp| { Some(Rc::clone(tp)) }),
+            ob_dict: None,
+            inner: PyInnerObject::SetObj(Rc::new(PySetObject { set: RefCell::new(map) }))
+        }))
+    }
+}
+
+pub struct PySetIterObject {
+    it_set: Rc<PyObject>,
+    it_pos: usize,
+    // size when iteration started, None once a change has been reported
+    it_used: Option<usize>,
+}
+
+thread_local! (
+    pub static PY_SETITER_TYPE: Rc<PyObject> = {
+        let itertp = PyTypeObject {
+            tp_name: "set_iterator".to_string(),
+            tp_iternext: Some(Rc::new(PyObject::pysetiter_next)),
+            ..Default::default()
+        };
+        Rc::new(PyObject {
+            ob_type: PY_TYPE_TYPE.with(|tp| { Some(Rc::clone(tp)) }),
+            ob_dict: None,
+            inner: PyInnerObject::TypeObj(Rc::new(RefCell::new(itertp))),
+        })
+    }
+);
+
+impl PyObject {
+    fn pysetiter_next(self: Rc<PyObject>) -> PyRes<Option<Rc<PyObject>>> {
+        match self.inner {
+            PyInnerObject::SetIterObj(ref it) => {
+                let mut it = it.borrow_mut();
+                let set = Rc::clone(&it.it_set);
+                let map = set.pyset_map()?.borrow();
+                if it.it_used != Some(map.len()) {
+                    it.it_used = None;
+                    pyerr_set_string(PY_RUNTIMEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                                     "Set changed size during iteration");
+                    return Err(());
+                }
+                match map.next_entry(it.it_pos) {
+                    Some((pos, key, _)) => {
+                        it.it_pos = pos;
+                        Ok(Some(key))
+                    },
+                    None => Ok(None),
+                }
+            },
+            _ => {
+                pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                                 "__next__ expects set_iterator object");
+                Err(())
+            }
+        }
+    }
+}
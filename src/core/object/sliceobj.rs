@@ -0,0 +1,204 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use error::*;
+use eval::PyRes;
+use syntax::CompOp;
+use object::{PyObject, PyInnerObject};
+use object::bigint::BigInt;
+use object::excobj::*;
+use object::generic::*;
+use object::rustfunobj::*;
+use object::typeobj::*;
+
+fn pyslice_new(_typ: Rc<PyObject>, args: &Vec<Rc<PyObject>>, kwargs: Option<Rc<PyObject>>) -> PyRes<Rc<PyObject>> {
+    check_no_kwargs("slice", &kwargs)?;
+    check_arg_count("slice", args, 1, 3)?;
+    let none = PyObject::none_obj();
+    match args.len() {
+        1 => Ok(PyObject::pyslice_new(Rc::clone(&none), Rc::clone(&args[0]), none)),
+        2 => Ok(PyObject::pyslice_new(Rc::clone(&args[0]), Rc::clone(&args[1]), none)),
+        _ => Ok(PyObject::pyslice_new(Rc::clone(&args[0]), Rc::clone(&args[1]), Rc::clone(&args[2]))),
+    }
+}
+
+// Slices compare like the tuples of their start, stop and step.
+fn pyslice_richcmp(lv: Rc<PyObject>, rv: Rc<PyObject>, negate: bool) -> PyRes<Rc<PyObject>> {
+    let (l, r) = match (&lv.inner, &rv.inner) {
+        (&PyInnerObject::SliceObj(ref l), &PyInnerObject::SliceObj(ref r)) => (l, r),
+        _ => return Ok(PyObject::notimpl_obj()),
+    };
+    let lt = PyObject::pytuple_from_vec(vec![Rc::clone(&l.start), Rc::clone(&l.stop), Rc::clone(&l.step)]);
+    let rt = PyObject::pytuple_from_vec(vec![Rc::clone(&r.start), Rc::clone(&r.stop), Rc::clone(&r.step)]);
+    let eq = pyobj_to_bool(pyobj_richcompare(lt, rt, CompOp::EqEq)?)?;
+    Ok(PyObject::from_bool(eq != negate))
+}
+
+fn pyslice_eq(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pyslice_richcmp(lv, rv, false)
+}
+
+fn pyslice_ne(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pyslice_richcmp(lv, rv, true)
+}
+
+fn pyslice_repr(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    let (start, stop, step) = v.pyslice_parts();
+    let repr = format!("slice({}, {}, {})",
+                       pyobj_to_string(pyobj_repr(start)?)?,
+                       pyobj_to_string(pyobj_repr(stop)?)?,
+                       pyobj_to_string(pyobj_repr(step)?)?);
+    Ok(PyObject::from_string(repr))
+}
+
+fn pyslice_getattro(v: Rc<PyObject>, key: Rc<PyObject>) -> PyRes<Option<Rc<PyObject>>> {
+    let (start, stop, step) = v.pyslice_parts();
+    match &pyobj_to_string(Rc::clone(&key))?[..] {
+        "start" => Ok(Some(start)),
+        "stop" => Ok(Some(stop)),
+        "step" => Ok(Some(step)),
+        _ => pyobj_generic_get_attro(v, key),
+    }
+}
+
+fn pyslice_indices_method(v: Rc<PyObject>, len: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    let len = match pyobj_to_index(len) {
+        Some(len) if len >= 0 => len as usize,
+        Some(_) => {
+            pyerr_set_string(PY_VALUEERROR_TYPE.with(|tp| Rc::clone(tp)), "length should not be negative");
+            return Err(());
+        },
+        None => {
+            pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                             "'slice' object cannot be interpreted as an integer");
+            return Err(());
+        }
+    };
+    let (start, stop, step) = v.pyslice_indices(len)?;
+    let from_isize = |n: isize| PyObject::from_bigint(BigInt::from(n as i64));
+    Ok(PyObject::pytuple_from_vec(vec![from_isize(start), from_isize(stop), from_isize(step)]))
+}
+
+thread_local! (
+    pub static PY_SLICE_TYPE: Rc<PyObject> = {
+        let tp_methods = vec![
+            PyObject::pyrustfun_method("indices", PyRustFun::MethO(Rc::new(pyslice_indices_method))),
+        ];
+        let slicetp = PyTypeObject {
+            tp_name: "slice".to_string(),
+            tp_hash: Some(Rc::new(pyobj_unhashable)),
+            tp_fun_eq: Some(Rc::new(pyslice_eq)),
+            tp_fun_ne: Some(Rc::new(pyslice_ne)),
+            tp_repr: Some(Rc::new(pyslice_repr)),
+            tp_getattro: Some(Rc::new(pyslice_getattro)),
+            tp_new: Some(Rc::new(pyslice_new)),
+            tp_methods: Some(tp_methods),
+            ..Default::default()
+        };
+        Rc::new(PyObject {
+            ob_type: PY_TYPE_TYPE.with(|tp| { Some(Rc::clone(tp)) }),
+            ob_dict: None,
+            inner: PyInnerObject::TypeObj(Rc::new(RefCell::new(slicetp))),
+        })
+    }
+);
+
+/// `start:stop:step` in a subscript, with `None` for a missing part.
+pub struct PySliceObject {
+    start: Rc<PyObject>,
+    stop: Rc<PyObject>,
+    step: Rc<PyObject>,
+}
+
+// a part of a slice as an integer, or `None` when it is missing
+fn slice_part(v: Rc<PyObject>) -> PyRes<Option<isize>> {
+    if v.pynone_check() {
+        return Ok(None);
+    }
+    match pyobj_to_index(v) {
+        Some(n) => Ok(Some(n)),
+        None => {
+            pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                             "slice indices must be integers or None or have an __index__ method");
+            Err(())
+        }
+    }
+}
+
+// Clamps a bound to the sequence, counting a negative one from the end.
+// `lower` and `upper` are where an out of range bound ends up.
+fn adjust(bound: isize, len: isize, lower: isize, upper: isize) -> isize {
+    if bound < 0 {
+        let bound = bound + len;
+        if bound < 0 { lower } else { bound }
+    } else if bound >= len {
+        upper
+    } else {
+        bound
+    }
+}
+
+impl PyObject {
+    pub fn pyslice_new(start: Rc<PyObject>, stop: Rc<PyObject>, step: Rc<PyObject>) -> Rc<PyObject> {
+        PY_SLICE_TYPE.with(|tp| {
+            Rc::new(PyObject {
+                ob_type: Some(Rc::clone(&tp)),
+                ob_dict: None,
+                inner: PyInnerObject::SliceObj(Rc::new(PySliceObject { start: start, stop: stop, step: step }))
+            })
+        })
+    }
+
+    pub fn pyslice_check(&self) -> bool {
+        match self.inner {
+            PyInnerObject::SliceObj(_) => true,
+            _ => false,
+        }
+    }
+
+    pub fn pyslice_parts(&self) -> (Rc<PyObject>, Rc<PyObject>, Rc<PyObject>) {
+        match self.inner {
+            PyInnerObject::SliceObj(ref obj) => {
+                (Rc::clone(&obj.start), Rc::clone(&obj.stop), Rc::clone(&obj.step))
+            },
+            _ => panic!("Type Error: pyslice_parts")
+        }
+    }
+
+    /// The start, stop and step the slice takes from a sequence of `len`
+    /// items, with missing and negative bounds resolved.
+    pub fn pyslice_indices(&self, len: usize) -> PyRes<(isize, isize, isize)> {
+        let (start, stop, step) = self.pyslice_parts();
+        let step = slice_part(step)?.unwrap_or(1);
+        if step == 0 {
+            pyerr_set_string(PY_VALUEERROR_TYPE.with(|tp| Rc::clone(tp)), "slice step cannot be zero");
+            return Err(());
+        }
+        let len = len as isize;
+        let (lower, upper) = if step < 0 { (-1, len - 1) } else { (0, len) };
+        let start = match slice_part(start)? {
+            Some(start) => adjust(start, len, lower, upper),
+            None => if step < 0 { upper } else { lower },
+        };
+        let stop = match slice_part(stop)? {
+            Some(stop) => adjust(stop, len, lower, upper),
+            None => if step < 0 { lower } else { upper },
+        };
+        Ok((start, stop, step))
+    }
+
+    /// The positions the slice picks from a sequence of `len` items, in order.
+    pub fn pyslice_positions(&self, len: usize) -> PyRes<Vec<usize>> {
+        let (start, stop, step) = self.pyslice_indices(len)?;
+        let mut positions = vec![];
+        let mut i = start;
+        while (step > 0 && i < stop) || (step < 0 && i > stop) {
+            positions.push(i as usize);
+            i = match i.checked_add(step) {
+                Some(next) => next,
+                None => break,
+            };
+        }
+        Ok(positions)
+    }
+}
@@ -7,6 +7,9 @@ use error::*;
 use eval::PyRes;
 use object::{PyObject, PyInnerObject};
 use object::excobj::*;
+use object::formatter::format_str;
+use object::generic::*;
+use object::rustfunobj::*;
 use object::typeobj::*;
 
 fn pystr_add(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
@@ -24,31 +27,44 @@ fn pystr_add(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
 
     pyerr_set_string(
         PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
-        "__eq__ expects str objects"
+        "__add__ expects str objects"
     );
     Err(())
 }
 
-fn pystr_eq(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
-    match lv.inner {
-        PyInnerObject::StrObj(ref l_obj) => {
-            match rv.inner {
-                PyInnerObject::StrObj(ref r_obj) => {
-                    return Ok(PyObject::from_bool(l_obj.s == r_obj.s));
-                },
-                _ => {}
-            }
+fn pystr_richcmp<F>(lv: Rc<PyObject>, rv: Rc<PyObject>, f: F) -> PyRes<Rc<PyObject>>
+where F: Fn(&String, &String) -> bool {
+    match (&lv.inner, &rv.inner) {
+        (&PyInnerObject::StrObj(ref l_obj), &PyInnerObject::StrObj(ref r_obj)) => {
+            Ok(PyObject::from_bool(f(&l_obj.s, &r_obj.s)))
         },
-        _ => {}
+        _ => Ok(PyObject::notimpl_obj()),
     }
+}
 
-    pyerr_set_string(
-        PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
-        "__eq__ expects str objects"
-    );
-    Err(())
+fn pystr_eq(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pystr_richcmp(lv, rv, |l, r| l == r)
+}
+
+fn pystr_ne(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pystr_richcmp(lv, rv, |l, r| l != r)
+}
+
+fn pystr_lt(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pystr_richcmp(lv, rv, |l, r| l < r)
 }
 
+fn pystr_le(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pystr_richcmp(lv, rv, |l, r| l <= r)
+}
+
+fn pystr_gt(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pystr_richcmp(lv, rv, |l, r| l > r)
+}
+
+fn pystr_ge(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pystr_richcmp(lv, rv, |l, r| l >= r)
+}
 
 fn pystr_hash(obj: Rc<PyObject>) -> PyRes<u64> {
     let mut hasher = DefaultHasher::new();
@@ -67,7 +83,7 @@ fn pystr_hash(obj: Rc<PyObject>) -> PyRes<u64> {
 
 fn pystr_len(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
     match v.inner {
-        PyInnerObject::StrObj(ref obj) => Ok(PyObject::from_i32(obj.s.len() as i32)),
+        PyInnerObject::StrObj(ref obj) => Ok(PyObject::from_i32(obj.s.chars().count() as i32)),
         _ => {
             pyerr_set_string(
                 PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
@@ -78,14 +94,283 @@ fn pystr_len(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
     }
 }
 
+fn pystr_subscript(v: Rc<PyObject>, key: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    let chars: Vec<char> = pyobj_to_string(v)?.chars().collect();
+    if key.pyslice_check() {
+        let s = key.pyslice_positions(chars.len())?.into_iter().map(|i| chars[i]).collect();
+        return Ok(PyObject::from_string(s));
+    }
+    let index = pyobj_seq_index(key, chars.len(), "string", "string index out of range")?;
+    Ok(PyObject::from_string(chars[index].to_string()))
+}
+
+fn pystr_repr(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    match v.inner {
+        PyInnerObject::StrObj(ref obj) => {
+            let quote = if obj.s.contains('\'') && !obj.s.contains('"') { '"' } else { '\'' };
+            let mut repr = String::new();
+            repr.push(quote);
+            for ch in obj.s.chars() {
+                match ch {
+                    '\\' => repr.push_str("\\\\"),
+                    '\n' => repr.push_str("\\n"),
+                    '\r' => repr.push_str("\\r"),
+                    '\t' => repr.push_str("\\t"),
+                    ch if ch == quote => { repr.push('\\'); repr.push(ch) },
+                    ch if (ch as u32) < 0x20 || ch as u32 == 0x7f => {
+                        repr.push_str(&format!("\\x{:02x}", ch as u32))
+                    },
+                    ch => repr.push(ch),
+                }
+            }
+            repr.push(quote);
+            Ok(PyObject::from_string(repr))
+        },
+        _ => {
+            pyerr_set_string(
+                PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                "__repr__ expects str objects"
+            );
+            Err(())
+        }
+    }
+}
+
+fn pystr_contains(v: Rc<PyObject>, item: Rc<PyObject>) -> PyRes<bool> {
+    match (&v.inner, &item.inner) {
+        (&PyInnerObject::StrObj(ref obj), &PyInnerObject::StrObj(ref sub)) => {
+            Ok(obj.s.contains(&sub.s[..]))
+        },
+        _ => {
+            pyerr_set_string(
+                PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                "'in <string>' requires string as left operand"
+            );
+            Err(())
+        }
+    }
+}
+
+fn pystr_str(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    Ok(v)
+}
+
+fn pystr_format(v: Rc<PyObject>, spec: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    let s = pyobj_to_string(v)?;
+    Ok(PyObject::from_string(format_str(&s, &pyobj_to_string(spec)?)?))
+}
+
+fn pystr_new(_typ: Rc<PyObject>, args: &Vec<Rc<PyObject>>, kwargs: Option<Rc<PyObject>>) -> PyRes<Rc<PyObject>> {
+    check_no_kwargs("str", &kwargs)?;
+    check_arg_count("str", args, 0, 1)?;
+    match args.first() {
+        Some(arg) => pyobj_str(Rc::clone(arg)),
+        None => Ok(PyObject::from_str("")),
+    }
+}
+
+fn format_error<T>(msg: &str) -> PyRes<T> {
+    pyerr_set_string(PY_VALUEERROR_TYPE.with(|tp| Rc::clone(tp)), msg);
+    Err(())
+}
+
+// fields of a template are numbered either all automatically or all by hand
+struct FieldNumbering {
+    auto: Option<bool>,
+    next: usize,
+}
+
+fn subscript(v: Rc<PyObject>, key: &str) -> PyRes<Rc<PyObject>> {
+    if v.pylist_check() {
+        match key.parse::<usize>() {
+            Ok(index) => v.pylist_getitem(index),
+            Err(_) => {
+                pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                                 "list indices must be integers");
+                Err(())
+            }
+        }
+    } else if v.pytuple_check() {
+        match key.parse::<usize>() {
+            Ok(index) => v.pytuple_getitem(index),
+            Err(_) => {
+                pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                                 "tuple indices must be integers");
+                Err(())
+            }
+        }
+    } else if v.pydict_check() {
+        match key.parse::<i32>() {
+            Ok(index) => v.pydict_getitem(PyObject::from_i32(index)),
+            Err(_) => v.pydict_getitem(PyObject::from_str(key)),
+        }
+    } else {
+        let msg = format!("'{}' object is not subscriptable", v.ob_type().pytype_typeobj_borrow().tp_name);
+        pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)), &msg);
+        Err(())
+    }
+}
+
+// The value of a field name: an argument index, possibly left out, or a
+// keyword argument name, followed by attribute lookups `.name` and
+// subscripts `[key]`.
+fn field_value(name: &str, args: &Vec<Rc<PyObject>>, kwargs: &Option<Rc<PyObject>>,
+               numbering: &mut FieldNumbering)
+               -> PyRes<Rc<PyObject>> {
+    let end = name.find(|ch| ch == '.' || ch == '[').unwrap_or(name.len());
+    let (first, rest) = name.split_at(end);
+    if !first.is_empty() && first.parse::<usize>().is_err() {
+        let value = match *kwargs {
+            Some(ref kwargs) => kwargs.pydict_lookup(PyObject::from_str(first))?,
+            None => None,
+        };
+        return match value {
+            Some(value) => field_lookups(value, rest),
+            None => PyObject::pydict_key_error(PyObject::from_str(first)),
+        };
+    }
+    let index = if first.is_empty() {
+        if numbering.auto == Some(false) {
+            return format_error("cannot switch from manual field specification to automatic field numbering");
+        }
+        numbering.auto = Some(true);
+        numbering.next += 1;
+        numbering.next - 1
+    } else {
+        if numbering.auto == Some(true) {
+            return format_error("cannot switch from automatic field numbering to manual field specification");
+        }
+        numbering.auto = Some(false);
+        first.parse::<usize>().unwrap()
+    };
+    let value = match args.get(index) {
+        Some(arg) => Rc::clone(arg),
+        None => {
+            pyerr_set_string(PY_INDEXERROR_TYPE.with(|tp| Rc::clone(tp)),
+                             &format!("Replacement index {} out of range for positional args tuple", index));
+            return Err(());
+        }
+    };
+    field_lookups(value, rest)
+}
+
+// the `.name` and `[key]` lookups after the first name of a field
+fn field_lookups(mut value: Rc<PyObject>, mut rest: &str) -> PyRes<Rc<PyObject>> {
+    while !rest.is_empty() {
+        if rest.starts_with('.') {
+            let end = rest[1..].find(|ch| ch == '.' || ch == '[').map_or(rest.len(), |i| i + 1);
+            if end == 1 {
+                return format_error("Empty attribute in format string");
+            }
+            value = pyobj_get_attr(value, PyObject::from_str(&rest[1..end]))?;
+            rest = &rest[end..];
+        } else if rest.starts_with('[') {
+            let end = match rest.find(']') {
+                Some(end) => end,
+                None => return format_error("Missing ']' in format string"),
+            };
+            value = subscript(value, &rest[1..end])?;
+            rest = &rest[end + 1..];
+        } else {
+            return format_error("Only '.' or '[' may follow ']' in format field specifier");
+        }
+    }
+    Ok(value)
+}
+
+// name ('!' conversion)? (':' spec)?, where the spec may hold fields itself
+fn format_field(field: &str, args: &Vec<Rc<PyObject>>, kwargs: &Option<Rc<PyObject>>,
+                numbering: &mut FieldNumbering)
+                -> PyRes<String> {
+    let end = field.find(|ch| ch == '!' || ch == ':').unwrap_or(field.len());
+    let (name, mut rest) = field.split_at(end);
+    let mut conversion = None;
+    if rest.starts_with('!') {
+        let mut chars = rest[1..].chars();
+        conversion = match chars.next() {
+            Some(ch) => Some(ch),
+            None => return format_error("end of string while looking for conversion specifier"),
+        };
+        rest = chars.as_str();
+        if !rest.is_empty() && !rest.starts_with(':') {
+            return format_error("expected ':' after conversion specifier");
+        }
+    }
+    let spec = if rest.is_empty() { "" } else { &rest[1..] };
+    let spec = format_template(spec, args, kwargs, numbering)?;
+
+    let value = field_value(name, args, kwargs, numbering)?;
+    let value = match conversion {
+        None => value,
+        Some('s') => pyobj_str(value)?,
+        Some('r') => pyobj_repr(value)?,
+        Some('a') => pyobj_ascii(value)?,
+        Some(ch) => return format_error(&format!("Unknown conversion specifier {}", ch)),
+    };
+    pyobj_to_string(pyobj_format(value, PyObject::from_string(spec))?)
+}
+
+fn format_template(template: &str, args: &Vec<Rc<PyObject>>, kwargs: &Option<Rc<PyObject>>,
+                   numbering: &mut FieldNumbering)
+                   -> PyRes<String> {
+    let mut res = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '{' if chars.peek() == Some(&'{') => { chars.next(); res.push('{') },
+            '}' if chars.peek() == Some(&'}') => { chars.next(); res.push('}') },
+            '}' => return format_error("Single '}' encountered in format string"),
+            '{' => {
+                // up to the matching '}', as the spec may hold fields
+                let mut field = String::new();
+                let mut depth = 1;
+                loop {
+                    match chars.next() {
+                        Some('{') => { depth += 1; field.push('{') },
+                        Some('}') if depth == 1 => break,
+                        Some('}') => { depth -= 1; field.push('}') },
+                        Some(ch) => field.push(ch),
+                        None => return format_error("expected '}' before end of string"),
+                    }
+                }
+                res.push_str(&format_field(&field, args, kwargs, numbering)?);
+            },
+            ch => res.push(ch),
+        }
+    }
+    Ok(res)
+}
+
+fn pystr_format_method(v: Rc<PyObject>, args: &Vec<Rc<PyObject>>, kwargs: Option<Rc<PyObject>>)
+                       -> PyRes<Rc<PyObject>> {
+    let template = pyobj_to_string(v)?;
+    let mut numbering = FieldNumbering { auto: None, next: 0 };
+    Ok(PyObject::from_string(format_template(&template, args, &kwargs, &mut numbering)?))
+}
+
 thread_local! (
     pub static PY_STRING_TYPE: Rc<PyObject> = {
+        let tp_methods = vec![
+            PyObject::pyrustfun_method("format", PyRustFun::MethKeywords(Rc::new(pystr_format_method))),
+        ];
         let strtp = PyTypeObject {
             tp_name: "str".to_string(),
             tp_hash: Some(Rc::new(pystr_hash)),
             tp_fun_eq: Some(Rc::new(pystr_eq)),
+            tp_fun_ne: Some(Rc::new(pystr_ne)),
+            tp_fun_lt: Some(Rc::new(pystr_lt)),
+            tp_fun_le: Some(Rc::new(pystr_le)),
+            tp_fun_gt: Some(Rc::new(pystr_gt)),
+            tp_fun_ge: Some(Rc::new(pystr_ge)),
             tp_fun_add: Some(Rc::new(pystr_add)),
             tp_len: Some(Rc::new(pystr_len)),
+            tp_repr: Some(Rc::new(pystr_repr)),
+            tp_str: Some(Rc::new(pystr_str)),
+            tp_format: Some(Rc::new(pystr_format)),
+            tp_contains: Some(Rc::new(pystr_contains)),
+            tp_getitem: Some(Rc::new(pystr_subscript)),
+            tp_new: Some(Rc::new(pystr_new)),
+            tp_methods: Some(tp_methods),
             ..Default::default()
         };
         Rc::new(PyObject {
@@ -105,6 +390,13 @@ impl PyObject {
         PyObject::from_string(s.to_string())
     }
 
+    pub fn pystr_check(&self) -> bool {
+        match self.inner {
+            PyInnerObject::StrObj(_) => true,
+            _ => false,
+        }
+    }
+
     pub fn from_string(raw_string: String) -> Rc<PyObject> {
         PY_STRING_TYPE.with(|tp| {
             let inner = PyStringObject { s: raw_string };
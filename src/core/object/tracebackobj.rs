@@ -0,0 +1,96 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use error::*;
+use eval::PyRes;
+use syntax::Line;
+use object::{PyObject, PyInnerObject};
+use object::generic::*;
+use object::typeobj::*;
+
+thread_local! (
+    pub static PY_TRACEBACK_TYPE: Rc<PyObject> = {
+        let tbtp = PyTypeObject {
+            tp_name: "traceback".to_string(),
+            tp_hash: Some(Rc::new(default_hash)),
+            tp_getattro: Some(Rc::new(pytraceback_getattro)),
+            ..Default::default()
+        };
+        Rc::new(PyObject {
+            ob_type: PY_TYPE_TYPE.with(|tp| { Some(Rc::clone(tp)) }),
+            ob_dict: None,
+            inner: PyInnerObject::TypeObj(Rc::new(RefCell::new(tbtp))),
+        })
+    }
+);
+
+/// One frame of a traceback. The chain starts at the outermost frame and
+/// `tb_next` leads towards the frame where the exception was raised.
+pub struct PyTracebackObject {
+    pub tb_next: Option<Rc<PyObject>>,
+    pub tb_code: Rc<PyObject>,
+    pub tb_lineno: Line,
+}
+
+fn pytraceback_getattro(tb: Rc<PyObject>, key: Rc<PyObject>) -> PyRes<Option<Rc<PyObject>>> {
+    let obj = match tb.inner {
+        PyInnerObject::TracebackObj(ref obj) => obj,
+        _ => panic!("Type Error: pytraceback_getattro")
+    };
+    match &pyobj_to_string(Rc::clone(&key))?[..] {
+        "tb_next" => Ok(Some(obj.tb_next.clone().unwrap_or_else(PyObject::none_obj))),
+        "tb_lineno" => Ok(Some(PyObject::from_i32(obj.tb_lineno as i32))),
+        _ => Ok(None),
+    }
+}
+
+impl PyObject {
+    pub fn pytraceback_new(next: Option<Rc<PyObject>>, codeobj: Rc<PyObject>, lineno: Line) -> Rc<PyObject> {
+        Rc::new(PyObject {
+            ob_type: PY_TRACEBACK_TYPE.with(|tp| { Some(Rc::clone(tp)) }),
+            ob_dict: None,
+            inner: PyInnerObject::TracebackObj(Rc::new(PyTracebackObject {
+                tb_next: next,
+                tb_code: codeobj,
+                tb_lineno: lineno,
+            }))
+        })
+    }
+
+    pub fn pytraceback_check(&self) -> bool {
+        PY_TRACEBACK_TYPE.with(|tp| { (&self.ob_type).as_ref() == Some(tp) })
+    }
+}
+
+/// Records the frame running `codeobj` at `lineno` on the traceback of the
+/// pending exception, as the exception leaves that frame.
+pub fn pytraceback_here(codeobj: &Rc<PyObject>, lineno: Line) {
+    if let Some(exc) = EXC_INDICATOR.with(|ind| ind.borrow().clone()) {
+        let tb = PyObject::pytraceback_new(exc.pyexc_traceback(), Rc::clone(codeobj), lineno);
+        exc.pyexc_set_traceback(Some(tb));
+    }
+}
+
+/// Formats the frames of a traceback, outermost first. `source` gives the
+/// lines of `filename` so that its frames can quote the line they stopped at.
+pub fn pytraceback_format(tb: &Rc<PyObject>, filename: &str, source: &str) -> String {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut res = String::from("Traceback (most recent call last):\n");
+    let mut cur = Some(Rc::clone(tb));
+    while let Some(tb) = cur {
+        let obj = match tb.inner {
+            PyInnerObject::TracebackObj(ref obj) => obj,
+            _ => panic!("Type Error: pytraceback_format")
+        };
+        let co_filename = obj.tb_code.pycode_filename();
+        res.push_str(&format!("  File \"{}\", line {}, in {}\n",
+                              co_filename, obj.tb_lineno, obj.tb_code.pycode_name()));
+        if co_filename == filename {
+            if let Some(line) = obj.tb_lineno.checked_sub(1).and_then(|i| lines.get(i)) {
+                res.push_str(&format!("    {}\n", line.trim()));
+            }
+        }
+        cur = obj.tb_next.clone();
+    }
+    res
+}
@@ -0,0 +1,301 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use error::*;
+use eval::PyRes;
+use syntax::CompOp;
+use object::{PyObject, PyInnerObject};
+use object::excobj::*;
+use object::generic::*;
+use object::typeobj::*;
+
+fn pytuple_new(_typ: Rc<PyObject>, args: &Vec<Rc<PyObject>>, kwargs: Option<Rc<PyObject>>) -> PyRes<Rc<PyObject>> {
+    check_no_kwargs("tuple", &kwargs)?;
+    check_arg_count("tuple", args, 0, 1)?;
+    match args.get(0) {
+        Some(arg) if arg.pytuple_check() => Ok(Rc::clone(arg)),
+        Some(arg) => Ok(PyObject::pytuple_from_vec(pyobj_to_vec(Rc::clone(arg))?)),
+        None => Ok(PyObject::pytuple_from_vec(vec![])),
+    }
+}
+
+fn item_eq(l: &Rc<PyObject>, r: &Rc<PyObject>) -> PyRes<bool> {
+    Ok(l == r || pyobj_to_bool(pyobj_richcompare(Rc::clone(l), Rc::clone(r), CompOp::EqEq)?)?)
+}
+
+// Tuples compare by their first differing items, or by length when one is
+// a prefix of the other.
+fn pytuple_richcmp(lv: Rc<PyObject>, rv: Rc<PyObject>, op: CompOp) -> PyRes<Rc<PyObject>> {
+    let (l, r) = match (&lv.inner, &rv.inner) {
+        (&PyInnerObject::TupleObj(ref l), &PyInnerObject::TupleObj(ref r)) => (l, r),
+        _ => return Ok(PyObject::notimpl_obj()),
+    };
+    for (l_item, r_item) in l.items.iter().zip(r.items.iter()) {
+        if item_eq(l_item, r_item)? {
+            continue;
+        }
+        return match op {
+            CompOp::EqEq => Ok(PyObject::from_bool(false)),
+            CompOp::NotEq => Ok(PyObject::from_bool(true)),
+            _ => pyobj_richcompare(Rc::clone(l_item), Rc::clone(r_item), op),
+        };
+    }
+    let (l_len, r_len) = (l.items.len(), r.items.len());
+    let res = match op {
+        CompOp::Lt => l_len < r_len,
+        CompOp::LtE => l_len <= r_len,
+        CompOp::Gt => l_len > r_len,
+        CompOp::GtE => l_len >= r_len,
+        CompOp::EqEq => l_len == r_len,
+        _ => l_len != r_len,
+    };
+    Ok(PyObject::from_bool(res))
+}
+
+fn pytuple_eq(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pytuple_richcmp(lv, rv, CompOp::EqEq)
+}
+
+fn pytuple_ne(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pytuple_richcmp(lv, rv, CompOp::NotEq)
+}
+
+fn pytuple_lt(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pytuple_richcmp(lv, rv, CompOp::Lt)
+}
+
+fn pytuple_le(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pytuple_richcmp(lv, rv, CompOp::LtE)
+}
+
+fn pytuple_gt(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pytuple_richcmp(lv, rv, CompOp::Gt)
+}
+
+fn pytuple_ge(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    pytuple_richcmp(lv, rv, CompOp::GtE)
+}
+
+fn pytuple_add(lv: Rc<PyObject>, rv: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    match (&lv.inner, &rv.inner) {
+        (&PyInnerObject::TupleObj(ref l), &PyInnerObject::TupleObj(ref r)) => {
+            let mut items = l.items.clone();
+            items.extend(r.items.iter().cloned());
+            Ok(PyObject::pytuple_from_vec(items))
+        },
+        _ => {
+            let msg = format!("can only concatenate tuple (not \"{}\") to tuple",
+                              rv.ob_type().pytype_typeobj_borrow().tp_name);
+            pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)), &msg);
+            Err(())
+        }
+    }
+}
+
+// the xxHash based combination CPython uses, so that a tuple hashes the same
+// as it does there whenever its items do
+fn pytuple_hash(v: Rc<PyObject>) -> PyRes<u64> {
+    const PRIME_1: u64 = 11400714785074694791;
+    const PRIME_2: u64 = 14029467366897019727;
+    const PRIME_5: u64 = 2870177450012600261;
+    let items = v.pytuple_items();
+    let mut acc = PRIME_5;
+    for item in &items {
+        let lane = pyobj_hash(Rc::clone(item))?;
+        acc = acc.wrapping_add(lane.wrapping_mul(PRIME_2));
+        acc = acc.rotate_left(31);
+        acc = acc.wrapping_mul(PRIME_1);
+    }
+    acc = acc.wrapping_add(items.len() as u64 ^ (PRIME_5 ^ 3527539));
+    if acc == u64::max_value() {
+        return Ok(1546275796);
+    }
+    Ok(acc)
+}
+
+fn pytuple_len(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    Ok(PyObject::from_i32(v.pytuple_size() as i32))
+}
+
+fn pytuple_bool(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    Ok(PyObject::from_bool(v.pytuple_size() > 0))
+}
+
+// a one-item tuple keeps its trailing comma
+fn pytuple_repr(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    let mut reprs = vec![];
+    for item in v.pytuple_items() {
+        reprs.push(pyobj_to_string(pyobj_repr(item)?)?);
+    }
+    let repr = if reprs.len() == 1 {
+        format!("({},)", reprs[0])
+    } else {
+        format!("({})", reprs.join(", "))
+    };
+    Ok(PyObject::from_string(repr))
+}
+
+fn pytuple_contains(v: Rc<PyObject>, item: Rc<PyObject>) -> PyRes<bool> {
+    for elm in v.pytuple_items() {
+        if item_eq(&elm, &item)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn pytuple_subscript(v: Rc<PyObject>, key: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    let items = v.pytuple_items();
+    if key.pyslice_check() {
+        let items = key.pyslice_positions(items.len())?.into_iter()
+            .map(|i| Rc::clone(&items[i])).collect();
+        return Ok(PyObject::pytuple_from_vec(items));
+    }
+    let index = pyobj_seq_index(key, items.len(), "tuple", "tuple index out of range")?;
+    Ok(Rc::clone(&items[index]))
+}
+
+fn pytuple_iter(v: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    Ok(Rc::new(PyObject {
+        ob_type: PY_TUPLEITER_TYPE.with(|tp| { Some(Rc::clone(tp)) }),
+        ob_dict: None,
+        inner: PyInnerObject::TupleIterObj(Rc::new(RefCell::new(
+            PyTupleIterObject {
+                it_index: 0,
+                it_seq: v,
+            }
+        )))
+    }))
+}
+
+thread_local! (
+    pub static PY_TUPLE_TYPE: Rc<PyObject> = {
+        let tupletp = PyTypeObject {
+            tp_name: "tuple".to_string(),
+            tp_hash: Some(Rc::new(pytuple_hash)),
+            tp_bool: Some(Rc::new(pytuple_bool)),
+            tp_fun_eq: Some(Rc::new(pytuple_eq)),
+            tp_fun_ne: Some(Rc::new(pytuple_ne)),
+            tp_fun_lt: Some(Rc::new(pytuple_lt)),
+            tp_fun_le: Some(Rc::new(pytuple_le)),
+            tp_fun_gt: Some(Rc::new(pytuple_gt)),
+            tp_fun_ge: Some(Rc::new(pytuple_ge)),
+            tp_fun_add: Some(Rc::new(pytuple_add)),
+            tp_len: Some(Rc::new(pytuple_len)),
+            tp_repr: Some(Rc::new(pytuple_repr)),
+            tp_iter: Some(Rc::new(pytuple_iter)),
+            tp_contains: Some(Rc::new(pytuple_contains)),
+            tp_getitem: Some(Rc::new(pytuple_subscript)),
+            tp_new: Some(Rc::new(pytuple_new)),
+            ..Default::default()
+        };
+        Rc::new(PyObject {
+            ob_type: PY_TYPE_TYPE.with(|tp| { Some(Rc::clone(tp)) }),
+            ob_dict: None,
+            inner: PyInnerObject::TypeObj(Rc::new(RefCell::new(tupletp))),
+        })
+    }
+);
+
+/// An immutable sequence, hashable when its items are.
+pub struct PyTupleObject {
+    items: Vec<Rc<PyObject>>,
+}
+
+impl PyObject {
+    pub fn pytuple_from_vec(items: Vec<Rc<PyObject>>) -> Rc<PyObject> {
+        PY_TUPLE_TYPE.with(|tp| {
+            Rc::new(PyObject {
+                ob_type: Some(Rc::clone(&tp)),
+                ob_dict: None,
+                inner: PyInnerObject::TupleObj(Rc::new(PyTupleObject { items: items }))
+            })
+        })
+    }
+
+    pub fn pytuple_check(&self) -> bool {
+        match self.inner {
+            PyInnerObject::TupleObj(_) => true,
+            _ => false,
+        }
+    }
+
+    pub fn pytuple_size(&self) -> usize {
+        match self.inner {
+            PyInnerObject::TupleObj(ref obj) => obj.items.len(),
+            _ => panic!("Type Error: pytuple_size")
+        }
+    }
+
+    pub fn pytuple_items(&self) -> Vec<Rc<PyObject>> {
+        match self.inner {
+            PyInnerObject::TupleObj(ref obj) => obj.items.clone(),
+            _ => panic!("Type Error: pytuple_items")
+        }
+    }
+
+    pub fn pytuple_getitem(&self, index: usize) -> PyRes<Rc<PyObject>> {
+        match self.inner {
+            PyInnerObject::TupleObj(ref obj) => {
+                match obj.items.get(index) {
+                    Some(item) => Ok(Rc::clone(item)),
+                    None => {
+                        pyerr_set_string(
+                            PY_INDEXERROR_TYPE.with(|tp| Rc::clone(tp)),
+                            "tuple index out of range"
+                        );
+                        Err(())
+                    }
+                }
+            },
+            _ => {
+                pyerr_set_string(
+                    PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                    "__getitem__ expects tuple object"
+                );
+                Err(())
+            }
+        }
+    }
+}
+
+pub struct PyTupleIterObject {
+    it_index: usize,
+    it_seq: Rc<PyObject>,
+}
+
+fn pytupleiter_next(v: Rc<PyObject>) -> PyRes<Option<Rc<PyObject>>> {
+    match v.inner {
+        PyInnerObject::TupleIterObj(ref it) => {
+            let mut it = it.borrow_mut();
+            if it.it_index >= it.it_seq.pytuple_size() {
+                return Ok(None);
+            }
+            let res = it.it_seq.pytuple_getitem(it.it_index)?;
+            it.it_index += 1;
+            Ok(Some(res))
+        },
+        _ => {
+            pyerr_set_string(
+                PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                "__next__ expects tuple_iterator object"
+            );
+            Err(())
+        }
+    }
+}
+
+thread_local! (
+    pub static PY_TUPLEITER_TYPE: Rc<PyObject> = {
+        let itertp = PyTypeObject {
+            tp_name: "tuple_iterator".to_string(),
+            tp_iternext: Some(Rc::new(pytupleiter_next)),
+            ..Default::default()
+        };
+        Rc::new(PyObject {
+            ob_type: PY_TYPE_TYPE.with(|tp| { Some(Rc::clone(tp)) }),
+            ob_dict: None,
+            inner: PyInnerObject::TypeObj(Rc::new(RefCell::new(itertp))),
+        })
+    }
+);
@@ -13,11 +13,15 @@ use object::generic::*;
 pub type HashFun = dyn Fn(Rc<PyObject>) -> PyRes<u64>;
 pub type UnaryOp = dyn Fn(Rc<PyObject>) -> PyRes<Rc<PyObject>>;
 pub type BinaryOp = dyn Fn(Rc<PyObject>, Rc<PyObject>) -> PyRes<Rc<PyObject>>;
-pub type VarArgFun = dyn Fn(Rc<PyObject>, &Vec<Rc<PyObject>>) -> PyRes<Rc<PyObject>>;
+// the positional arguments and, when there are any, a dict of the keyword ones
+pub type VarArgFun = dyn Fn(Rc<PyObject>, &Vec<Rc<PyObject>>, Option<Rc<PyObject>>) -> PyRes<Rc<PyObject>>;
 pub type GetAttroFun = dyn Fn(Rc<PyObject>, Rc<PyObject>) -> PyRes<Option<Rc<PyObject>>>;
 pub type SetAttroFun = dyn Fn(Rc<PyObject>, Rc<PyObject>, Rc<PyObject>) -> PyRes<()>;
 pub type GetIterFun = dyn Fn(Rc<PyObject>) -> PyRes<Rc<PyObject>>;
 pub type IterNextFun = dyn Fn(Rc<PyObject>) -> PyRes<Option<Rc<PyObject>>>;
+pub type ContainsFun = dyn Fn(Rc<PyObject>, Rc<PyObject>) -> PyRes<bool>;
+pub type SetItemFun = dyn Fn(Rc<PyObject>, Rc<PyObject>, Rc<PyObject>) -> PyRes<()>;
+pub type DelItemFun = dyn Fn(Rc<PyObject>, Rc<PyObject>) -> PyRes<()>;
 
 #[derive(Default)]
 pub struct PyTypeObject {
@@ -25,10 +29,36 @@ pub struct PyTypeObject {
     pub tp_base: Option<Rc<PyObject>>,
     pub tp_hash: Option<Rc<HashFun>>,
     pub tp_bool: Option<Rc<UnaryOp>>,
+    pub tp_repr: Option<Rc<UnaryOp>>,
+    pub tp_str: Option<Rc<UnaryOp>>,
+    pub tp_format: Option<Rc<BinaryOp>>,
     pub tp_fun_eq: Option<Rc<BinaryOp>>,
     pub tp_fun_add: Option<Rc<BinaryOp>>,
+    pub tp_fun_sub: Option<Rc<BinaryOp>>,
+    pub tp_fun_mul: Option<Rc<BinaryOp>>,
+    pub tp_fun_truediv: Option<Rc<BinaryOp>>,
+    pub tp_fun_floordiv: Option<Rc<BinaryOp>>,
+    pub tp_fun_mod: Option<Rc<BinaryOp>>,
+    pub tp_fun_pow: Option<Rc<BinaryOp>>,
+    pub tp_fun_radd: Option<Rc<BinaryOp>>,
+    pub tp_fun_rsub: Option<Rc<BinaryOp>>,
+    pub tp_fun_rmul: Option<Rc<BinaryOp>>,
+    pub tp_fun_rtruediv: Option<Rc<BinaryOp>>,
+    pub tp_fun_rfloordiv: Option<Rc<BinaryOp>>,
+    pub tp_fun_rmod: Option<Rc<BinaryOp>>,
+    pub tp_fun_rpow: Option<Rc<BinaryOp>>,
+    pub tp_fun_neg: Option<Rc<UnaryOp>>,
+    pub tp_fun_pos: Option<Rc<UnaryOp>>,
+    pub tp_fun_ne: Option<Rc<BinaryOp>>,
     pub tp_fun_lt: Option<Rc<BinaryOp>>,
+    pub tp_fun_le: Option<Rc<BinaryOp>>,
+    pub tp_fun_gt: Option<Rc<BinaryOp>>,
+    pub tp_fun_ge: Option<Rc<BinaryOp>>,
     pub tp_len: Option<Rc<UnaryOp>>,
+    pub tp_contains: Option<Rc<ContainsFun>>,
+    pub tp_getitem: Option<Rc<BinaryOp>>,
+    pub tp_setitem: Option<Rc<SetItemFun>>,
+    pub tp_delitem: Option<Rc<DelItemFun>>,
     pub tp_call: Option<Rc<VarArgFun>>,
     pub tp_getattro: Option<Rc<GetAttroFun>>,
     pub tp_setattro: Option<Rc<SetAttroFun>>,
@@ -49,6 +79,7 @@ thread_local! (
             tp_name: "type".to_string(),
             tp_hash: Some(Rc::new(default_hash)),
             tp_fun_eq: Some(Rc::new(type_eq)),
+            tp_repr: Some(Rc::new(type_repr)),
             tp_call: Some(Rc::new(type_call)),
             tp_getattro: Some(Rc::new(type_getattro)),
             tp_setattro: Some(Rc::new(type_setattro)),
@@ -67,6 +98,11 @@ fn type_eq(slf: Rc<PyObject>, other: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
     Ok(PyObject::from_bool(slf == other))
 }
 
+fn type_repr(slf: Rc<PyObject>) -> PyRes<Rc<PyObject>> {
+    let name = slf.pytype_typeobj_borrow().tp_name.clone();
+    Ok(PyObject::from_string(format!("<class '{}'>", name)))
+}
+
 impl PartialEq for PyTypeObject {
     fn eq(&self, other: &PyTypeObject) -> bool {
         self as *const _ == other as *const _
@@ -186,7 +222,7 @@ impl PyTypeObject {
     }
 }
 
-pub fn type_call(typ: Rc<PyObject>, args: &Vec<Rc<PyObject>>) -> PyRes<Rc<PyObject>> {
+pub fn type_call(typ: Rc<PyObject>, args: &Vec<Rc<PyObject>>, kwargs: Option<Rc<PyObject>>) -> PyRes<Rc<PyObject>> {
 
     if PY_TYPE_TYPE.with(|tp| { tp == &typ }) {
         if args.len() == 1 {
@@ -210,19 +246,19 @@ pub fn type_call(typ: Rc<PyObject>, args: &Vec<Rc<PyObject>>) -> PyRes<Rc<PyObje
         return Err(())
     }
 
-    let obj = tp_new.unwrap()(Rc::clone(&typ), args)?;
+    let obj = tp_new.unwrap()(Rc::clone(&typ), args, kwargs.clone())?;
 
     if PY_TYPE_TYPE.with(|tp| { tp == &typ }) {
         return Ok(obj);
     }
 
     if let Some(tp_init) = obj.ob_type().pytype_tp_init() {
-        tp_init(Rc::clone(&obj), args)?;
+        tp_init(Rc::clone(&obj), args, kwargs)?;
     }
     Ok(obj)
 }
 
-pub fn type_new(meta: Rc<PyObject>, args: &Vec<Rc<PyObject>>) -> PyRes<Rc<PyObject>> {
+pub fn type_new(meta: Rc<PyObject>, args: &Vec<Rc<PyObject>>, _kwargs: Option<Rc<PyObject>>) -> PyRes<Rc<PyObject>> {
     let nameobj = Rc::clone(&args[0]);
     let bases = Rc::clone(&args[1]);
     let dictobj = Rc::clone(&args[2]);
@@ -233,6 +269,10 @@ pub fn type_new(meta: Rc<PyObject>, args: &Vec<Rc<PyObject>>) -> PyRes<Rc<PyObje
         typ.tp_dict = Some(Rc::clone(&dictobj));
         typ.tp_name = pyobj_to_string(nameobj)?;
         typ.tp_bases = Some(Rc::clone(&bases));
+        // the first base provides the instance layout, as in CPython
+        if bases.pylist_size() > 0 {
+            typ.tp_base = Some(bases.pylist_getitem(0)?);
+        }
     }
 
     for i in 0..bases.pylist_size() {
@@ -350,11 +390,82 @@ fn get_wrapped_binop(dict: Rc<PyObject>, s: &str) -> PyRes<Option<Rc<BinaryOp>>>
             .map(|opt| opt.map(binop_from_pyobj))
     }
 
+fn containsfun_from_pyobj(obj: Rc<PyObject>) -> Rc<ContainsFun> {
+        Rc::new(move |x, y| pyobj_to_bool(call_func(Rc::clone(&obj), &mut vec![x, y])?))
+    }
+
+fn get_wrapped_containsfun(dict: Rc<PyObject>, s: &str) -> PyRes<Option<Rc<ContainsFun>>> {
+        dict.pydict_lookup(PyObject::from_str(s))
+            .map(|opt| opt.map(containsfun_from_pyobj))
+    }
+
+fn setitemfun_from_pyobj(obj: Rc<PyObject>) -> Rc<SetItemFun> {
+        Rc::new(move |x, key, value| call_func(Rc::clone(&obj), &mut vec![x, key, value]).map(|_| ()))
+    }
+
+fn get_wrapped_setitemfun(dict: Rc<PyObject>, s: &str) -> PyRes<Option<Rc<SetItemFun>>> {
+        dict.pydict_lookup(PyObject::from_str(s))
+            .map(|opt| opt.map(setitemfun_from_pyobj))
+    }
+
+fn delitemfun_from_pyobj(obj: Rc<PyObject>) -> Rc<DelItemFun> {
+        Rc::new(move |x, key| call_func(Rc::clone(&obj), &mut vec![x, key]).map(|_| ()))
+    }
+
+fn get_wrapped_delitemfun(dict: Rc<PyObject>, s: &str) -> PyRes<Option<Rc<DelItemFun>>> {
+        dict.pydict_lookup(PyObject::from_str(s))
+            .map(|opt| opt.map(delitemfun_from_pyobj))
+    }
+
+fn iternextfun_from_pyobj(obj: Rc<PyObject>) -> Rc<IterNextFun> {
+        Rc::new(move |x| {
+            match call_func(Rc::clone(&obj), &mut vec![x]) {
+                Ok(next) => Ok(Some(next)),
+                Err(()) => {
+                    if pyerr_check(PY_STOPITERATION_TYPE.with(|tp| Rc::clone(tp))) {
+                        pyerr_clear();
+                        Ok(None)
+                    } else {
+                        Err(())
+                    }
+                }
+            }
+        })
+    }
+
+fn get_wrapped_iternextfun(dict: Rc<PyObject>, s: &str) -> PyRes<Option<Rc<IterNextFun>>> {
+        dict.pydict_lookup(PyObject::from_str(s))
+            .map(|opt| opt.map(iternextfun_from_pyobj))
+    }
+
+// `__hash__ = None` marks a class unhashable
+fn hashfun_from_pyobj(obj: Rc<PyObject>) -> Rc<HashFun> {
+        if obj.pynone_check() {
+            return Rc::new(pyobj_unhashable);
+        }
+        Rc::new(move |x| {
+            let res = call_func(Rc::clone(&obj), &mut vec![x])?;
+            match res.inner {
+                PyInnerObject::LongObj(ref obj) => Ok(obj.n.hash() as u64),
+                _ => {
+                    pyerr_set_string(PY_TYPEERROR_TYPE.with(|tp| Rc::clone(tp)),
+                                     "__hash__ method should return an integer");
+                    Err(())
+                }
+            }
+        })
+    }
+
+fn get_wrapped_hashfun(dict: Rc<PyObject>, s: &str) -> PyRes<Option<Rc<HashFun>>> {
+        dict.pydict_lookup(PyObject::from_str(s))
+            .map(|opt| opt.map(hashfun_from_pyobj))
+    }
+
 fn varargfun_from_pyobj(obj: Rc<PyObject>) -> Rc<VarArgFun> {
-        Rc::new(move |x, vs| {
+        Rc::new(move |x, vs, kwargs| {
             let mut args = vec![x];
             for v in vs.iter() { args.push(Rc::clone(v)) };
-            call_func(Rc::clone(&obj), &mut args)
+            call_func_kw(Rc::clone(&obj), &args, kwargs)
         })
     }
 
@@ -372,6 +483,18 @@ fn inherit_method(typ: &mut PyTypeObject, base: &PyTypeObject) {
         typ.tp_bool = base.tp_bool.clone();
     }
 
+    if typ.tp_repr.is_none() && base.tp_repr.is_some() {
+        typ.tp_repr = base.tp_repr.clone();
+    }
+
+    if typ.tp_str.is_none() && base.tp_str.is_some() {
+        typ.tp_str = base.tp_str.clone();
+    }
+
+    if typ.tp_format.is_none() && base.tp_format.is_some() {
+        typ.tp_format = base.tp_format.clone();
+    }
+
     if typ.tp_fun_eq.is_none() && base.tp_fun_eq.is_some() {
         typ.tp_fun_eq = base.tp_fun_eq.clone();
     }
@@ -380,14 +503,122 @@ fn inherit_method(typ: &mut PyTypeObject, base: &PyTypeObject) {
         typ.tp_fun_add = base.tp_fun_add.clone();
     }
 
+    if typ.tp_fun_sub.is_none() && base.tp_fun_sub.is_some() {
+        typ.tp_fun_sub = base.tp_fun_sub.clone();
+    }
+
+    if typ.tp_fun_mul.is_none() && base.tp_fun_mul.is_some() {
+        typ.tp_fun_mul = base.tp_fun_mul.clone();
+    }
+
+    if typ.tp_fun_truediv.is_none() && base.tp_fun_truediv.is_some() {
+        typ.tp_fun_truediv = base.tp_fun_truediv.clone();
+    }
+
+    if typ.tp_fun_floordiv.is_none() && base.tp_fun_floordiv.is_some() {
+        typ.tp_fun_floordiv = base.tp_fun_floordiv.clone();
+    }
+
+    if typ.tp_fun_mod.is_none() && base.tp_fun_mod.is_some() {
+        typ.tp_fun_mod = base.tp_fun_mod.clone();
+    }
+
+    if typ.tp_fun_pow.is_none() && base.tp_fun_pow.is_some() {
+        typ.tp_fun_pow = base.tp_fun_pow.clone();
+    }
+
+    if typ.tp_fun_radd.is_none() && base.tp_fun_radd.is_some() {
+        typ.tp_fun_radd = base.tp_fun_radd.clone();
+    }
+
+    if typ.tp_fun_rsub.is_none() && base.tp_fun_rsub.is_some() {
+        typ.tp_fun_rsub = base.tp_fun_rsub.clone();
+    }
+
+    if typ.tp_fun_rmul.is_none() && base.tp_fun_rmul.is_some() {
+        typ.tp_fun_rmul = base.tp_fun_rmul.clone();
+    }
+
+    if typ.tp_fun_rtruediv.is_none() && base.tp_fun_rtruediv.is_some() {
+        typ.tp_fun_rtruediv = base.tp_fun_rtruediv.clone();
+    }
+
+    if typ.tp_fun_rfloordiv.is_none() && base.tp_fun_rfloordiv.is_some() {
+        typ.tp_fun_rfloordiv = base.tp_fun_rfloordiv.clone();
+    }
+
+    if typ.tp_fun_rmod.is_none() && base.tp_fun_rmod.is_some() {
+        typ.tp_fun_rmod = base.tp_fun_rmod.clone();
+    }
+
+    if typ.tp_fun_rpow.is_none() && base.tp_fun_rpow.is_some() {
+        typ.tp_fun_rpow = base.tp_fun_rpow.clone();
+    }
+
+    if typ.tp_fun_neg.is_none() && base.tp_fun_neg.is_some() {
+        typ.tp_fun_neg = base.tp_fun_neg.clone();
+    }
+
+    if typ.tp_fun_pos.is_none() && base.tp_fun_pos.is_some() {
+        typ.tp_fun_pos = base.tp_fun_pos.clone();
+    }
+
     if typ.tp_fun_lt.is_none() && base.tp_fun_lt.is_some() {
         typ.tp_fun_lt = base.tp_fun_lt.clone();
     }
 
+    if typ.tp_fun_ne.is_none() && base.tp_fun_ne.is_some() {
+        typ.tp_fun_ne = base.tp_fun_ne.clone();
+    }
+
+    if typ.tp_fun_le.is_none() && base.tp_fun_le.is_some() {
+        typ.tp_fun_le = base.tp_fun_le.clone();
+    }
+
+    if typ.tp_fun_gt.is_none() && base.tp_fun_gt.is_some() {
+        typ.tp_fun_gt = base.tp_fun_gt.clone();
+    }
+
+    if typ.tp_fun_ge.is_none() && base.tp_fun_ge.is_some() {
+        typ.tp_fun_ge = base.tp_fun_ge.clone();
+    }
+
     if typ.tp_len.is_none() && base.tp_len.is_some() {
         typ.tp_len = base.tp_len.clone();
     }
 
+    if typ.tp_contains.is_none() && base.tp_contains.is_some() {
+        typ.tp_contains = base.tp_contains.clone();
+    }
+
+    if typ.tp_getitem.is_none() && base.tp_getitem.is_some() {
+        typ.tp_getitem = base.tp_getitem.clone();
+    }
+
+    if typ.tp_setitem.is_none() && base.tp_setitem.is_some() {
+        typ.tp_setitem = base.tp_setitem.clone();
+    }
+
+    if typ.tp_delitem.is_none() && base.tp_delitem.is_some() {
+        typ.tp_delitem = base.tp_delitem.clone();
+    }
+
+    if typ.tp_iter.is_none() && base.tp_iter.is_some() {
+        typ.tp_iter = base.tp_iter.clone();
+    }
+
+    if typ.tp_iternext.is_none() && base.tp_iternext.is_some() {
+        typ.tp_iternext = base.tp_iternext.clone();
+    }
+
+    if typ.tp_getattro.is_none() && base.tp_getattro.is_some() {
+        typ.tp_getattro = base.tp_getattro.clone();
+    }
+
+    if typ.tp_setattro.is_none() && base.tp_setattro.is_some() {
+        typ.tp_setattro = base.tp_setattro.clone();
+    }
+
     if typ.tp_new.is_none() && base.tp_new.is_some() {
         typ.tp_new = base.tp_new.clone();
     }
@@ -416,14 +647,72 @@ pub fn update_slot(value: Rc<PyObject>, key: Id, rvalue: Rc<PyObject>) {
         let mut  typ = value.pytype_typeobj_borrow_mut();
         if key == "__add__".to_string() {
             typ.tp_fun_add = Some(binop_from_pyobj(Rc::clone(&rvalue)));
+        } else if key == "__sub__".to_string() {
+            typ.tp_fun_sub = Some(binop_from_pyobj(Rc::clone(&rvalue)));
+        } else if key == "__mul__".to_string() {
+            typ.tp_fun_mul = Some(binop_from_pyobj(Rc::clone(&rvalue)));
+        } else if key == "__truediv__".to_string() {
+            typ.tp_fun_truediv = Some(binop_from_pyobj(Rc::clone(&rvalue)));
+        } else if key == "__floordiv__".to_string() {
+            typ.tp_fun_floordiv = Some(binop_from_pyobj(Rc::clone(&rvalue)));
+        } else if key == "__mod__".to_string() {
+            typ.tp_fun_mod = Some(binop_from_pyobj(Rc::clone(&rvalue)));
+        } else if key == "__pow__".to_string() {
+            typ.tp_fun_pow = Some(binop_from_pyobj(Rc::clone(&rvalue)));
+        } else if key == "__radd__".to_string() {
+            typ.tp_fun_radd = Some(binop_from_pyobj(Rc::clone(&rvalue)));
+        } else if key == "__rsub__".to_string() {
+            typ.tp_fun_rsub = Some(binop_from_pyobj(Rc::clone(&rvalue)));
+        } else if key == "__rmul__".to_string() {
+            typ.tp_fun_rmul = Some(binop_from_pyobj(Rc::clone(&rvalue)));
+        } else if key == "__rtruediv__".to_string() {
+            typ.tp_fun_rtruediv = Some(binop_from_pyobj(Rc::clone(&rvalue)));
+        } else if key == "__rfloordiv__".to_string() {
+            typ.tp_fun_rfloordiv = Some(binop_from_pyobj(Rc::clone(&rvalue)));
+        } else if key == "__rmod__".to_string() {
+            typ.tp_fun_rmod = Some(binop_from_pyobj(Rc::clone(&rvalue)));
+        } else if key == "__rpow__".to_string() {
+            typ.tp_fun_rpow = Some(binop_from_pyobj(Rc::clone(&rvalue)));
+        } else if key == "__neg__".to_string() {
+            typ.tp_fun_neg = Some(unaryop_from_pyobj(Rc::clone(&rvalue)));
+        } else if key == "__pos__".to_string() {
+            typ.tp_fun_pos = Some(unaryop_from_pyobj(Rc::clone(&rvalue)));
+        } else if key == "__hash__".to_string() {
+            typ.tp_hash = Some(hashfun_from_pyobj(Rc::clone(&rvalue)));
         } else if key == "__bool__".to_string() {
             typ.tp_bool = Some(unaryop_from_pyobj(Rc::clone(&rvalue)));
+        } else if key == "__repr__".to_string() {
+            typ.tp_repr = Some(unaryop_from_pyobj(Rc::clone(&rvalue)));
+        } else if key == "__str__".to_string() {
+            typ.tp_str = Some(unaryop_from_pyobj(Rc::clone(&rvalue)));
+        } else if key == "__format__".to_string() {
+            typ.tp_format = Some(binop_from_pyobj(Rc::clone(&rvalue)));
         } else if key == "__lt__".to_string() {
             typ.tp_fun_lt = Some(binop_from_pyobj(Rc::clone(&rvalue)));
         } else if key == "__eq__".to_string() {
             typ.tp_fun_eq = Some(binop_from_pyobj(Rc::clone(&rvalue)));
+        } else if key == "__ne__".to_string() {
+            typ.tp_fun_ne = Some(binop_from_pyobj(Rc::clone(&rvalue)));
+        } else if key == "__le__".to_string() {
+            typ.tp_fun_le = Some(binop_from_pyobj(Rc::clone(&rvalue)));
+        } else if key == "__gt__".to_string() {
+            typ.tp_fun_gt = Some(binop_from_pyobj(Rc::clone(&rvalue)));
+        } else if key == "__ge__".to_string() {
+            typ.tp_fun_ge = Some(binop_from_pyobj(Rc::clone(&rvalue)));
         } else if key == "__len__".to_string() {
             typ.tp_len = Some(unaryop_from_pyobj(Rc::clone(&rvalue)));
+        } else if key == "__contains__".to_string() {
+            typ.tp_contains = Some(containsfun_from_pyobj(Rc::clone(&rvalue)));
+        } else if key == "__getitem__".to_string() {
+            typ.tp_getitem = Some(binop_from_pyobj(Rc::clone(&rvalue)));
+        } else if key == "__setitem__".to_string() {
+            typ.tp_setitem = Some(setitemfun_from_pyobj(Rc::clone(&rvalue)));
+        } else if key == "__delitem__".to_string() {
+            typ.tp_delitem = Some(delitemfun_from_pyobj(Rc::clone(&rvalue)));
+        } else if key == "__iter__".to_string() {
+            typ.tp_iter = Some(unaryop_from_pyobj(Rc::clone(&rvalue)));
+        } else if key == "__next__".to_string() {
+            typ.tp_iternext = Some(iternextfun_from_pyobj(Rc::clone(&rvalue)));
         } else if key == "__init__".to_string() {
             typ.tp_init = Some(varargfun_from_pyobj(Rc::clone(&rvalue)));
         } else if key == "__new__".to_string() {
@@ -435,16 +724,17 @@ pub fn update_slot(value: Rc<PyObject>, key: Id, rvalue: Rc<PyObject>) {
 
 pub fn pytype_ready(obj: Rc<PyObject>) -> PyRes<()> {
     if obj.pytype_tp_dict().is_none() {
-        let mut typ = obj.pytype_typeobj_borrow_mut();
         let dictobj = PyObject::pydict_new();
 
-        if let Some(ref tp_methods) = typ.tp_methods {
+        // hashing the method names borrows the str type, which may be `obj`
+        let tp_methods = obj.pytype_typeobj_borrow().tp_methods.clone();
+        if let Some(ref tp_methods) = tp_methods {
             for meth in tp_methods {
                 dictobj.pydict_update(PyObject::from_string(Rc::clone(meth).pyrustfun_name()),
                                       Rc::clone(&meth))?
             }
         }
-        typ.tp_dict = Some(dictobj);
+        obj.pytype_typeobj_borrow_mut().tp_dict = Some(dictobj);
     }
 
     {
@@ -486,28 +776,120 @@ pub fn pytype_ready(obj: Rc<PyObject>) -> PyRes<()> {
     let mro_obj = PyObject::pylist_from_vec(&mro);
     obj.pytype_typeobj_borrow_mut().tp_mro = Some(Rc::clone(&mro_obj));
 
+    // the type is borrowed only to store each slot, as looking up the names
+    // borrows the str type, which may be `obj`
     if let Some(ref dictobj) = obj.pytype_tp_dict() {
-        let mut typ = obj.pytype_typeobj_borrow_mut();
+        let typ = || obj.pytype_typeobj_borrow_mut();
+        if let Some(fun) = get_wrapped_hashfun(Rc::clone(&dictobj), "__hash__")? {
+            typ().tp_hash = Some(fun);
+        } else if dictobj.pydict_lookup(PyObject::from_str("__eq__"))?.is_some() {
+            // overriding __eq__ alone drops the inherited identity hash
+            typ().tp_hash = Some(Rc::new(pyobj_unhashable));
+        }
         if let Some(fun) = get_wrapped_unaryop(Rc::clone(&dictobj), "__bool__")? {
-            typ.tp_bool = Some(fun);
+            typ().tp_bool = Some(fun);
+        }
+        if let Some(fun) = get_wrapped_unaryop(Rc::clone(&dictobj), "__repr__")? {
+            typ().tp_repr = Some(fun);
+        }
+        if let Some(fun) = get_wrapped_unaryop(Rc::clone(&dictobj), "__str__")? {
+            typ().tp_str = Some(fun);
+        }
+        if let Some(fun) = get_wrapped_binop(Rc::clone(&dictobj), "__format__")? {
+            typ().tp_format = Some(fun);
         }
         if let Some(fun) = get_wrapped_binop(Rc::clone(&dictobj), "__add__")? {
-            typ.tp_fun_add = Some(fun);
+            typ().tp_fun_add = Some(fun);
+        }
+        if let Some(fun) = get_wrapped_binop(Rc::clone(&dictobj), "__sub__")? {
+            typ().tp_fun_sub = Some(fun);
+        }
+        if let Some(fun) = get_wrapped_binop(Rc::clone(&dictobj), "__mul__")? {
+            typ().tp_fun_mul = Some(fun);
+        }
+        if let Some(fun) = get_wrapped_binop(Rc::clone(&dictobj), "__truediv__")? {
+            typ().tp_fun_truediv = Some(fun);
+        }
+        if let Some(fun) = get_wrapped_binop(Rc::clone(&dictobj), "__floordiv__")? {
+            typ().tp_fun_floordiv = Some(fun);
+        }
+        if let Some(fun) = get_wrapped_binop(Rc::clone(&dictobj), "__mod__")? {
+            typ().tp_fun_mod = Some(fun);
+        }
+        if let Some(fun) = get_wrapped_binop(Rc::clone(&dictobj), "__pow__")? {
+            typ().tp_fun_pow = Some(fun);
+        }
+        if let Some(fun) = get_wrapped_binop(Rc::clone(&dictobj), "__radd__")? {
+            typ().tp_fun_radd = Some(fun);
+        }
+        if let Some(fun) = get_wrapped_binop(Rc::clone(&dictobj), "__rsub__")? {
+            typ().tp_fun_rsub = Some(fun);
+        }
+        if let Some(fun) = get_wrapped_binop(Rc::clone(&dictobj), "__rmul__")? {
+            typ().tp_fun_rmul = Some(fun);
+        }
+        if let Some(fun) = get_wrapped_binop(Rc::clone(&dictobj), "__rtruediv__")? {
+            typ().tp_fun_rtruediv = Some(fun);
+        }
+        if let Some(fun) = get_wrapped_binop(Rc::clone(&dictobj), "__rfloordiv__")? {
+            typ().tp_fun_rfloordiv = Some(fun);
+        }
+        if let Some(fun) = get_wrapped_binop(Rc::clone(&dictobj), "__rmod__")? {
+            typ().tp_fun_rmod = Some(fun);
+        }
+        if let Some(fun) = get_wrapped_binop(Rc::clone(&dictobj), "__rpow__")? {
+            typ().tp_fun_rpow = Some(fun);
+        }
+        if let Some(fun) = get_wrapped_unaryop(Rc::clone(&dictobj), "__neg__")? {
+            typ().tp_fun_neg = Some(fun);
+        }
+        if let Some(fun) = get_wrapped_unaryop(Rc::clone(&dictobj), "__pos__")? {
+            typ().tp_fun_pos = Some(fun);
         }
         if let Some(fun) = get_wrapped_binop(Rc::clone(&dictobj), "__eq__")? {
-            typ.tp_fun_eq = Some(fun);
+            typ().tp_fun_eq = Some(fun);
         }
         if let Some(fun) = get_wrapped_binop(Rc::clone(&dictobj), "__lt__")? {
-            typ.tp_fun_lt = Some(fun);
+            typ().tp_fun_lt = Some(fun);
+        }
+        if let Some(fun) = get_wrapped_binop(Rc::clone(&dictobj), "__ne__")? {
+            typ().tp_fun_ne = Some(fun);
+        }
+        if let Some(fun) = get_wrapped_binop(Rc::clone(&dictobj), "__le__")? {
+            typ().tp_fun_le = Some(fun);
+        }
+        if let Some(fun) = get_wrapped_binop(Rc::clone(&dictobj), "__gt__")? {
+            typ().tp_fun_gt = Some(fun);
+        }
+        if let Some(fun) = get_wrapped_binop(Rc::clone(&dictobj), "__ge__")? {
+            typ().tp_fun_ge = Some(fun);
         }
         if let Some(fun) = get_wrapped_unaryop(Rc::clone(&dictobj), "__len__")? {
-            typ.tp_len = Some(fun);
+            typ().tp_len = Some(fun);
+        }
+        if let Some(fun) = get_wrapped_containsfun(Rc::clone(&dictobj), "__contains__")? {
+            typ().tp_contains = Some(fun);
+        }
+        if let Some(fun) = get_wrapped_binop(Rc::clone(&dictobj), "__getitem__")? {
+            typ().tp_getitem = Some(fun);
+        }
+        if let Some(fun) = get_wrapped_setitemfun(Rc::clone(&dictobj), "__setitem__")? {
+            typ().tp_setitem = Some(fun);
+        }
+        if let Some(fun) = get_wrapped_delitemfun(Rc::clone(&dictobj), "__delitem__")? {
+            typ().tp_delitem = Some(fun);
+        }
+        if let Some(fun) = get_wrapped_unaryop(Rc::clone(&dictobj), "__iter__")? {
+            typ().tp_iter = Some(fun);
+        }
+        if let Some(fun) = get_wrapped_iternextfun(Rc::clone(&dictobj), "__next__")? {
+            typ().tp_iternext = Some(fun);
         }
         if let Some(fun) = get_wrapped_varargfun(Rc::clone(&dictobj), "__init__")? {
-            typ.tp_init = Some(fun);
+            typ().tp_init = Some(fun);
         }
         if let Some(fun) = get_wrapped_varargfun(Rc::clone(&dictobj), "__new__")? {
-            typ.tp_new = Some(fun);
+            typ().tp_new = Some(fun);
         }
     }
 
@@ -553,7 +935,7 @@ thread_local! (
     }
 );
 
-fn object_new(typ: Rc<PyObject>, args: &Vec<Rc<PyObject>>) -> PyRes<Rc<PyObject>> {
+fn object_new(typ: Rc<PyObject>, args: &Vec<Rc<PyObject>>, _kwargs: Option<Rc<PyObject>>) -> PyRes<Rc<PyObject>> {
     Ok(Rc::new(PyObject {
         ob_type: Some(Rc::clone(&typ)),
         ob_dict: Some(PyObject::pydict_new()),
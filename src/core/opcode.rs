@@ -1,7 +1,7 @@
 use std::fmt;
 use std::rc::Rc;
 
-use syntax::Id;
+use syntax::{Conversion, Id, Line};
 use object::PyObject;
 
 pub type Addr = usize;
@@ -10,30 +10,86 @@ pub type Code = Vec<Opcode>;
 
 #[derive(Clone)]
 pub enum Opcode {
+    SetLineno(Line),
     PopTop,
+    RotTwo,
+    RotThree,
+    DupTop,
     LoadConst(Rc<PyObject>),
     LoadName(Id),
     StoreName(Id),
+    LoadFast(Id),
+    StoreFast(Id),
+    LoadGlobal(Id),
+    StoreGlobal(Id),
+    LoadDeref(Id),
+    StoreDeref(Id),
+    LoadClosure(Id),
+    DeleteName(Id),
+    DeleteFast(Id),
+    DeleteGlobal(Id),
+    DeleteDeref(Id),
     BinaryAdd,
+    BinarySub,
+    BinaryMul,
+    BinaryTrueDiv,
+    BinaryFloorDiv,
+    BinaryMod,
+    BinaryPow,
+    UnaryNeg,
+    UnaryPos,
+    UnaryNot,
     BinaryEq,
+    BinaryNotEq,
     BinaryLt,
-    MakeFunction,
+    BinaryLtE,
+    BinaryGt,
+    BinaryGtE,
+    BinaryIn,
+    BinaryNotIn,
+    BinaryIs,
+    BinaryIsNot,
+    MakeFunction(bool, bool, bool),
     CallFunction(usize),
+    CallFunctionKw(usize),
+    CallFunctionEx(bool),
     ReturnValue,
+    YieldValue,
+    YieldFrom,
     LoadAttr(Id),
     StoreAttr(Id),
     BinarySubScr,
     StoreSubScr,
+    DeleteSubScr,
+    BuildSlice(usize),
     BuildList(usize),
+    BuildTuple(usize),
+    ListAppend(usize),
+    ListExtend,
+    ListToTuple,
+    UnpackSequence(usize),
+    UnpackEx(usize, usize),
+    BuildSet(usize),
+    SetAdd(usize),
     BuildMap(usize),
+    MapAdd(usize),
+    DictMerge,
+    BuildString(usize),
+    FormatValue(Option<Conversion>, bool),
     PopJumpIfTrue(Addr),
     PopJumpIfFalse(Addr),
+    JumpIfFalseOrPop(Addr),
+    JumpIfTrueOrPop(Addr),
     JumpAbsolute(Addr),
     SetupLoop(Offset),
     BreakLoop,
     ContinueLoop(Addr),
     SetupExcept(Offset),
-    Raise,
+    SetupFinally(Offset),
+    ExcMatch,
+    PopExcept,
+    EndFinally,
+    Raise(usize),
     GetIter,
     ForIter(Addr),
     PopBlock,
@@ -43,24 +99,80 @@ pub enum Opcode {
 impl fmt::Display for Opcode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
+            &Opcode::SetLineno(line) => write!(f, "{} {}", "SetLineno", line),
             &Opcode::PopTop => write!(f, "{}", "PopTop"),
+            &Opcode::RotTwo => write!(f, "{}", "RotTwo"),
+            &Opcode::RotThree => write!(f, "{}", "RotThree"),
+            &Opcode::DupTop => write!(f, "{}", "DupTop"),
             &Opcode::LoadConst(ref _obj) => write!(f, "{}", "LoadConst"),
             &Opcode::LoadName(ref id) => write!(f, "{} {}", "LoadName", id),
             &Opcode::StoreName(ref id) => write!(f, "{} {}", "StoreName", id),
+            &Opcode::LoadFast(ref id) => write!(f, "{} {}", "LoadFast", id),
+            &Opcode::StoreFast(ref id) => write!(f, "{} {}", "StoreFast", id),
+            &Opcode::LoadGlobal(ref id) => write!(f, "{} {}", "LoadGlobal", id),
+            &Opcode::StoreGlobal(ref id) => write!(f, "{} {}", "StoreGlobal", id),
+            &Opcode::LoadDeref(ref id) => write!(f, "{} {}", "LoadDeref", id),
+            &Opcode::StoreDeref(ref id) => write!(f, "{} {}", "StoreDeref", id),
+            &Opcode::LoadClosure(ref id) => write!(f, "{} {}", "LoadClosure", id),
+            &Opcode::DeleteName(ref id) => write!(f, "{} {}", "DeleteName", id),
+            &Opcode::DeleteFast(ref id) => write!(f, "{} {}", "DeleteFast", id),
+            &Opcode::DeleteGlobal(ref id) => write!(f, "{} {}", "DeleteGlobal", id),
+            &Opcode::DeleteDeref(ref id) => write!(f, "{} {}", "DeleteDeref", id),
             &Opcode::BinaryAdd => write!(f, "{}", "BinaryAdd"),
+            &Opcode::BinarySub => write!(f, "{}", "BinarySub"),
+            &Opcode::BinaryMul => write!(f, "{}", "BinaryMul"),
+            &Opcode::BinaryTrueDiv => write!(f, "{}", "BinaryTrueDiv"),
+            &Opcode::BinaryFloorDiv => write!(f, "{}", "BinaryFloorDiv"),
+            &Opcode::BinaryMod => write!(f, "{}", "BinaryMod"),
+            &Opcode::BinaryPow => write!(f, "{}", "BinaryPow"),
+            &Opcode::UnaryNeg => write!(f, "{}", "UnaryNeg"),
+            &Opcode::UnaryPos => write!(f, "{}", "UnaryPos"),
+            &Opcode::UnaryNot => write!(f, "{}", "UnaryNot"),
             &Opcode::BinaryLt => write!(f, "{}", "BinaryLt"),
+            &Opcode::BinaryLtE => write!(f, "{}", "BinaryLtE"),
+            &Opcode::BinaryGt => write!(f, "{}", "BinaryGt"),
+            &Opcode::BinaryGtE => write!(f, "{}", "BinaryGtE"),
             &Opcode::BinaryEq => write!(f, "{}", "BinaryEq"),
-            &Opcode::MakeFunction => write!(f, "{}", "MakeFunction"),
+            &Opcode::BinaryNotEq => write!(f, "{}", "BinaryNotEq"),
+            &Opcode::BinaryIn => write!(f, "{}", "BinaryIn"),
+            &Opcode::BinaryNotIn => write!(f, "{}", "BinaryNotIn"),
+            &Opcode::BinaryIs => write!(f, "{}", "BinaryIs"),
+            &Opcode::BinaryIsNot => write!(f, "{}", "BinaryIsNot"),
+            &Opcode::MakeFunction(has_defaults, has_kwdefaults, has_closure) => {
+                write!(f, "{} {} {} {}", "MakeFunction", has_defaults, has_kwdefaults, has_closure)
+            },
             &Opcode::CallFunction(argcnt) => write!(f, "{} {}", "CallFunction", argcnt),
+            &Opcode::CallFunctionKw(argcnt) => write!(f, "{} {}", "CallFunctionKw", argcnt),
+            &Opcode::CallFunctionEx(has_kwargs) => write!(f, "{} {}", "CallFunctionEx", has_kwargs),
             &Opcode::ReturnValue => write!(f, "{}", "ReturnValue"),
+            &Opcode::YieldValue => write!(f, "{}", "YieldValue"),
+            &Opcode::YieldFrom => write!(f, "{}", "YieldFrom"),
             &Opcode::LoadAttr(ref id) => write!(f, "{} {}", "LoadAttr", id),
             &Opcode::StoreAttr(ref id) => write!(f, "{} {}", "StoreAttr", id),
             &Opcode::BinarySubScr => write!(f, "{}", "BinarySubscr"),
             &Opcode::StoreSubScr => write!(f, "{}", "StoreSubscr"),
+            &Opcode::DeleteSubScr => write!(f, "{}", "DeleteSubscr"),
+            &Opcode::BuildSlice(argc) => write!(f, "{} {}", "BuildSlice", argc),
             &Opcode::BuildList(len) => write!(f, "{} {}", "BuildList", len),
+            &Opcode::BuildTuple(len) => write!(f, "{} {}", "BuildTuple", len),
+            &Opcode::ListAppend(depth) => write!(f, "{} {}", "ListAppend", depth),
+            &Opcode::ListExtend => write!(f, "{}", "ListExtend"),
+            &Opcode::ListToTuple => write!(f, "{}", "ListToTuple"),
+            &Opcode::UnpackSequence(count) => write!(f, "{} {}", "UnpackSequence", count),
+            &Opcode::UnpackEx(before, after) => write!(f, "{} {} {}", "UnpackEx", before, after),
+            &Opcode::BuildSet(len) => write!(f, "{} {}", "BuildSet", len),
+            &Opcode::SetAdd(depth) => write!(f, "{} {}", "SetAdd", depth),
             &Opcode::BuildMap(len) => write!(f, "{} {}", "BuildMap", len),
+            &Opcode::MapAdd(depth) => write!(f, "{} {}", "MapAdd", depth),
+            &Opcode::DictMerge => write!(f, "{}", "DictMerge"),
+            &Opcode::BuildString(len) => write!(f, "{} {}", "BuildString", len),
+            &Opcode::FormatValue(conversion, has_spec) => {
+                write!(f, "{} {:?} {}", "FormatValue", conversion, has_spec)
+            },
             &Opcode::PopJumpIfTrue(addr) => write!(f, "{} {}", "PopJumpIfTrue", addr),
             &Opcode::PopJumpIfFalse(addr) => write!(f, "{} {}", "PopJumpIfFalse", addr),
+            &Opcode::JumpIfFalseOrPop(addr) => write!(f, "{} {}", "JumpIfFalseOrPop", addr),
+            &Opcode::JumpIfTrueOrPop(addr) => write!(f, "{} {}", "JumpIfTrueOrPop", addr),
             &Opcode::JumpAbsolute(addr) => write!(f, "{} {}", "JumpAbsolute", addr),
             &Opcode::SetupLoop(offset) => write!(f, "{} {}", "SetupLoop", offset),
             &Opcode::BreakLoop => write!(f, "{}", "BreakLoop"),
@@ -68,7 +180,11 @@ impl fmt::Display for Opcode {
             &Opcode::GetIter => write!(f, "{}", "GetIter"),
             &Opcode::ForIter(addr) => write!(f, "{} {}", "ForIter", addr),
             &Opcode::SetupExcept(offset) => write!(f, "{} {}", "SetupExcept", offset),
-            &Opcode::Raise => write!(f, "{}", "Raise"),
+            &Opcode::SetupFinally(offset) => write!(f, "{} {}", "SetupFinally", offset),
+            &Opcode::ExcMatch => write!(f, "{}", "ExcMatch"),
+            &Opcode::PopExcept => write!(f, "{}", "PopExcept"),
+            &Opcode::EndFinally => write!(f, "{}", "EndFinally"),
+            &Opcode::Raise(argc) => write!(f, "{} {}", "Raise", argc),
             &Opcode::PopBlock => write!(f, "{}", "PopBlock"),
             &Opcode::MakeClass(nbases)=> write!(f, "{} {}", "MakeClass", nbases),
         }
@@ -1,196 +1,627 @@
 use std::iter::Peekable;
+use std::mem;
+use lexer::tokenize;
+use object::bigint::BigInt;
 use syntax::*;
-use token::Token;
+use token::{FStrPiece, Token};
 
 /*
 program -> statement* EOF
 
-block -> Indent statement+ Dedent | e
+block -> Indent statement+ Dedent
+
+suite -> NewLine block
+       | simple_stmt NewLine
 
 statement -> simple_stmt NewLine
            | compound_stmt
 
 simple_stmt ->
-  | expr
-  | target = expr
-  | Return expr?
+  | expr_list
+  | yield_expr
+  | target_list = (expr_list | yield_expr)
+  | Return expr_list?
   | Continue
   | Break
-  | Raise expr
+  | Pass
+  | Raise (expr (From expr)?)?
   | Assert expr
+  | Global Ident(s) (Comma Ident(s))*
+  | Nonlocal Ident(s) (Comma Ident(s))*
+
+target_list -> target (Comma target)* Comma?
 
 target ->
   | Ident
   | cexpr Dot Ident
   | cexpr LBrace expr RBrace
+  | LParen target_list RParen
+  | LBracket target_list RBracket
+  | Star target
 
 compound_stmt ->
-  | If expr Colon NewLine block Else Colon NewLine block
-  | While expr Colon NewLine block
-  | For target In Expr Colon NewLine block
-  | Try Colon NewLine block Except Colon NewLine block
-  | Def Ident(s) LParen parm_list RParen Colon NewLine block
-  | Class Ident(s) LParen (expr, ..., expr) RParen Colon NewLine block
-
-parm_list ->
-  | Ident(s)
-  | Ident(s) Comma parm_list
-  | e
+  | If if_rest
+  | While expr Colon suite
+  | For target_list In expr_list Colon suite
+  | Try Colon suite except_clause+ (Else Colon suite)? (Finally Colon suite)?
+  | Try Colon suite Finally Colon suite
+  | Def Ident(s) LParen parm_list RParen Colon suite
+  | Class Ident(s) LParen (expr, ..., expr) RParen Colon suite
+
+if_rest -> expr Colon suite (Elif if_rest | Else Colon suite)?
+
+except_clause -> Except (expr (As Ident)?)? Colon suite
+
+parm_list -> (parm Comma)* parm?
+
+parm -> Ident(s) (Eq expr)?
+      | Slash
+      | Star Ident(s)?
+      | DoubleStar Ident(s)
+
+expr_list -> star_expr (Comma star_expr)* Comma?
+
+yield_expr -> Yield (From expr | expr_list)?
 
-expr -> eexpr Lt eexpr
-      | eexpr
+star_expr -> Star pexpr
+           | expr
 
-eexpr -> pexpr EqEq pexpr
-       | pexpr
+expr -> or_test (If or_test Else expr)?
+      | Lambda parm_list Colon expr
 
-pexpr -> cexpr Plus pexpr
-       | cexpr
+or_test -> and_test (Or and_test)*
+
+and_test -> not_test (And not_test)*
+
+not_test -> Not not_test
+          | comparison
+
+comparison -> pexpr (comp_op pexpr)*
+
+comp_op -> Lt | LtE | Gt | GtE | EqEq | NotEq
+         | In | Not In | Is | Is Not
+
+pexpr -> mexpr ((Plus | Minus) mexpr)*
+
+mexpr -> uexpr ((Star | Slash | DoubleSlash | Percent) uexpr)*
+
+uexpr -> (Minus | Plus) uexpr
+       | powexpr
+
+powexpr -> cexpr DoubleStar uexpr
+         | cexpr
 
 cexpr -> aexpr successor*
        | aexpr
 
-successor -> LParen comma_list RParen
+successor -> LParen arg_list RParen
            | Dot Ident
-           | LBrace expr RBrace
+           | LBracket subscript_list RBracket
+
+arg_list -> (argument Comma)* argument?
+
+argument -> expr
+          | expr comp_for
+          | Ident(s) Eq expr
+          | Star expr
+          | DoubleStar expr
+
+subscript_list -> subscript (Comma subscript)* Comma?
+
+subscript -> expr? Colon expr? (Colon expr?)?
+           | star_expr
 
 aexpr -> LParen expr RParen
-       | LBracket (expr (Comma expr)*)? RBracket
-       | LBrace (expr Colon expr)* RBrace
+       | LParen yield_expr RParen
+       | LParen (star_expr Comma)+ star_expr? RParen
+       | LParen RParen
+       | LParen expr comp_for RParen
+       | LBracket (star_expr (Comma star_expr)* Comma?)? RBracket
+       | LBracket expr comp_for RBracket
+       | LBrace (expr Colon expr (Comma expr Colon expr)* Comma?)? RBrace
+       | LBrace expr Colon expr comp_for RBrace
+       | LBrace expr (Comma expr)* Comma? RBrace
+       | LBrace expr comp_for RBrace
        | True
        | False
        | Ident
        | Int
-       | Str
+       | Float
+       | Imag
+       | (Str | FStr)+
+       | Bytes+
        | None
 
+comp_for -> (For target_list In or_test (If or_test)*)+
+
 comma_list ->
   | expr
   | expr Comma comma_list
+  | expr Comma
   | e
  */
 
+pub type ParseRes<T> = Result<T, SyntaxError>;
+
 pub trait TokenStream {
-    fn parse(&mut self) -> Program;
+    fn parse(&mut self) -> ParseRes<Program>;
+    fn parse_all(&mut self) -> Result<Program, Vec<SyntaxError>>;
     fn program(&mut self) -> Program;
-    fn block(&mut self) -> Program;
-    fn statement(&mut self) -> Stmt;
-    fn simple_stmt(&mut self) -> SimpleStmt;
+    fn block(&mut self) -> ParseRes<Program>;
+    fn suite(&mut self) -> ParseRes<Program>;
+    fn statement(&mut self) -> ParseRes<Stmt>;
+    fn simple_stmt(&mut self) -> ParseRes<SimpleStmt>;
+    fn name_list(&mut self) -> ParseRes<Vec<Id>>;
     fn is_compound(&mut self) -> bool;
-    fn compound_stmt(&mut self) -> CompoundStmt;
-    fn parm_list(&mut self) -> Vec<Id>;
-    fn expr(&mut self) -> Expr;
-    fn eexpr(&mut self) -> Expr;
-    fn pexpr(&mut self) -> Expr;
-    fn cexpr(&mut self) -> Expr;
-    fn comma_list(&mut self) -> Vec<Expr>;
-    fn pair_list(&mut self) -> Vec<(Expr, Expr)>;
+    fn compound_stmt(&mut self) -> ParseRes<CompoundStmt>;
+    fn if_rest(&mut self) -> ParseRes<CompoundStmt>;
+    fn except_clause(&mut self) -> ParseRes<ExceptHandler>;
+    fn parm_list(&mut self) -> ParseRes<Parameters>;
+    fn expr_list(&mut self) -> ParseRes<Expr>;
+    fn yield_expr(&mut self) -> ParseRes<Expr>;
+    fn target_list(&mut self) -> ParseRes<Expr>;
+    fn star_expr(&mut self) -> ParseRes<Expr>;
+    fn expr(&mut self) -> ParseRes<Expr>;
+    fn or_test(&mut self) -> ParseRes<Expr>;
+    fn and_test(&mut self) -> ParseRes<Expr>;
+    fn not_test(&mut self) -> ParseRes<Expr>;
+    fn comparison(&mut self) -> ParseRes<Expr>;
+    fn comp_op(&mut self) -> ParseRes<Option<CompOp>>;
+    fn pexpr(&mut self) -> ParseRes<Expr>;
+    fn mexpr(&mut self) -> ParseRes<Expr>;
+    fn uexpr(&mut self) -> ParseRes<Expr>;
+    fn powexpr(&mut self) -> ParseRes<Expr>;
+    fn cexpr(&mut self) -> ParseRes<Expr>;
+    fn arg_list(&mut self) -> ParseRes<(Vec<Expr>, Vec<Keyword>)>;
+    fn subscript_list(&mut self) -> ParseRes<Expr>;
+    fn subscript(&mut self) -> ParseRes<Expr>;
+    fn comma_list(&mut self) -> ParseRes<Vec<Expr>>;
+    fn brace_display(&mut self) -> ParseRes<ExprKind>;
+    fn comp_for(&mut self) -> ParseRes<Vec<Comprehension>>;
+    fn pair_list(&mut self) -> ParseRes<Vec<(Expr, Expr)>>;
     fn is_expr(&mut self) -> bool;
-    fn aexpr(&mut self) -> Expr;
+    fn aexpr(&mut self) -> ParseRes<Expr>;
     fn match_token(&mut self, token: Token) -> bool;
-    fn consume(&mut self, token: Token) -> ();
-    fn consume_ident(&mut self) -> String;
-    fn consume_int(&mut self) -> i32;
-    fn consume_str(&mut self) -> String;
+    fn consume(&mut self, token: Token) -> ParseRes<()>;
+    fn consume_ident(&mut self) -> ParseRes<String>;
+    fn consume_int(&mut self) -> ParseRes<BigInt>;
+    fn consume_float(&mut self) -> ParseRes<f64>;
+    fn consume_imag(&mut self) -> ParseRes<f64>;
+    fn consume_str(&mut self) -> ParseRes<String>;
+    fn consume_bytes(&mut self) -> ParseRes<Vec<u8>>;
 }
 
-impl<I: Iterator<Item = Token>> TokenStream for Peekable<I> {
-    fn parse(&mut self) -> Program {
-        self.program()
+// Parses the expression of an f-string replacement field starting at
+// `pos`. Like CPython it is parenthesized, so that it may span lines.
+fn replacement_field(expr: &str, pos: Pos) -> ParseRes<Expr> {
+    let shift = |p: Pos| {
+        if p.line == 1 {
+            Pos { line: pos.line, col: pos.col + p.col - 2 }
+        } else {
+            Pos { line: pos.line + p.line - 1, col: p.col }
+        }
+    };
+    let tokens = tokenize(format!("({})", expr)).map_err(|mut err| {
+        err.pos = shift(err.pos);
+        err
+    })?;
+    let mut field = Tokens::new(tokens.into_iter().map(|(token, span)| {
+        (token, Span::new(shift(span.start), shift(span.end)))
+    }));
+    let value = field.expr()?;
+    if !field.match_token(Token::NewLine) {
+        return Err(field.unexpected("'}'"));
     }
+    Ok(value)
+}
 
-    fn program(&mut self) -> Program {
-        let mut prog: Program = vec![];
+/// Tokens paired with their source spans.
+///
+/// A statement that fails to parse is recorded in `errors` and skipped, so
+/// that one pass reports every error the parser can resynchronize after.
+pub struct Tokens<I: Iterator<Item = (Token, Span)>> {
+    it: Peekable<I>,
+    prev_end: Pos,
+    depth: usize,
+    errors: Vec<SyntaxError>,
+}
+
+impl<I: Iterator<Item = (Token, Span)>> Tokens<I> {
+    pub fn new(it: I) -> Tokens<I> {
+        Tokens { it: it.peekable(), prev_end: Pos { line: 1, col: 1 }, depth: 0, errors: vec![] }
+    }
+
+    fn peek(&mut self) -> Option<&Token> {
+        self.it.peek().map(|&(ref token, _)| token)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let next = self.it.next();
+        next.map(|(token, span)| {
+            self.prev_end = span.end;
+            match token {
+                Token::Indent => self.depth += 1,
+                Token::Dedent => self.depth -= 1,
+                _ => (),
+            }
+            token
+        })
+    }
+
+    // start of the token to be consumed next
+    fn pos(&mut self) -> Pos {
+        let prev_end = self.prev_end;
+        self.it.peek().map_or(prev_end, |&(_, span)| span.start)
+    }
+
+    // span from `start` to the end of the last consumed token
+    fn span_from(&self, start: Pos) -> Span {
+        Span::new(start, self.prev_end)
+    }
+
+    fn finish(&self, kind: ExprKind, start: Pos) -> Expr {
+        Expr { kind: kind, span: self.span_from(start) }
+    }
+
+    fn unexpected(&mut self, expected: &str) -> SyntaxError {
+        if self.match_token(Token::Indent) {
+            return SyntaxError::indentation("unexpected indent", self.pos());
+        }
+        let found = self.peek().map_or("end of file".to_string(), |token| token.to_string());
+        SyntaxError::mismatch(expected, &found, self.pos())
+    }
+
+    // Adjacent string literals are concatenated into one, which is joined
+    // at runtime if any of them is an f-string.
+    fn string_literal(&mut self) -> ParseRes<ExprKind> {
+        let start = self.pos();
+        let bytes = match self.peek() {
+            Some(&Token::Bytes(_)) => true,
+            _ => false,
+        };
+        let mut s = String::new();
+        let mut b = vec![];
+        let mut values = vec![];
+        let mut joined = false;
         loop {
             match self.peek() {
-                Some(&Token::EOF) => break,
-                Some(_) => prog.push(self.statement()),
-                None => panic!("Parse Error: program"),
+                Some(&Token::Str(_)) if !bytes => s.push_str(&self.consume_str()?),
+                Some(&Token::FStr(_)) if !bytes => {
+                    let pieces = match self.next() {
+                        Some(Token::FStr(pieces)) => pieces,
+                        _ => panic!("Never fails"),
+                    };
+                    self.fstring(pieces, start, &mut s, &mut values)?;
+                    joined = true;
+                },
+                Some(&Token::Bytes(_)) if bytes => b.extend(self.consume_bytes()?),
+                Some(&Token::Str(_)) | Some(&Token::FStr(_)) | Some(&Token::Bytes(_)) => {
+                    return Err(SyntaxError::new("cannot mix bytes and nonbytes literals", self.pos()));
+                },
+                _ => break,
             }
-        };
-        prog
+        }
+        if bytes {
+            Ok(ExprKind::BytesExpr(b))
+        } else if joined {
+            if !s.is_empty() {
+                values.push(self.finish(ExprKind::StrExpr(s), start));
+            }
+            Ok(ExprKind::JoinedStr(values))
+        } else {
+            Ok(ExprKind::StrExpr(s))
+        }
     }
 
-    fn block(&mut self) -> Program {
-        let mut prog: Program = vec![];
-        match self.peek() {
-            Some(&Token::Indent) => {
-                self.consume(Token::Indent);
-                prog.push(self.statement());
-                loop {
-                    match self.peek() {
-                        Some(&Token::Dedent) => {
-                            self.consume(Token::Dedent);
-                            break
-                        },
-                        Some(_) => prog.push(self.statement()),
-                        _ => panic!("Parse Error: block")
+    // Appends the pieces of an f-string to `values`. Literal text is
+    // collected in `s` until a replacement field comes.
+    fn fstring(&mut self, pieces: Vec<FStrPiece>, start: Pos, s: &mut String, values: &mut Vec<Expr>)
+               -> ParseRes<()> {
+        for piece in pieces {
+            match piece {
+                FStrPiece::Lit(lit) => s.push_str(&lit),
+                FStrPiece::Field { expr, pos, conversion, spec } => {
+                    if !s.is_empty() {
+                        let lit = mem::replace(s, String::new());
+                        values.push(self.finish(ExprKind::StrExpr(lit), start));
                     }
+                    let value = replacement_field(&expr, pos)?;
+                    let conversion = match conversion {
+                        Some('s') => Some(Conversion::Str),
+                        Some('r') => Some(Conversion::Repr),
+                        Some('a') => Some(Conversion::Ascii),
+                        _ => None,
+                    };
+                    let spec = match spec {
+                        Some(spec) => {
+                            let mut spec_s = String::new();
+                            let mut spec_values = vec![];
+                            self.fstring(spec, start, &mut spec_s, &mut spec_values)?;
+                            if !spec_s.is_empty() || spec_values.is_empty() {
+                                spec_values.push(self.finish(ExprKind::StrExpr(spec_s), start));
+                            }
+                            Some(Box::new(self.finish(ExprKind::JoinedStr(spec_values), start)))
+                        },
+                        None => None,
+                    };
+                    let span = value.span;
+                    values.push(Expr { kind: ExprKind::FormattedValue(Box::new(value), conversion, spec),
+                                       span: span });
+                },
+            }
+        }
+        Ok(())
+    }
+
+    fn target(&self, expr: Expr) -> ParseRes<Target> {
+        match expr.kind {
+            ExprKind::VarExpr(id) => Ok(Target::IdentTarget(id)),
+            ExprKind::AttrExpr(expr, id) => Ok(Target::AttrTarget(expr, id)),
+            ExprKind::SubscrExpr(expr1, expr2) => Ok(Target::SubscrTarget(expr1, expr2)),
+            ExprKind::TupleExpr(elts) | ExprKind::ListExpr(elts) => {
+                let is_starred = |elt: &&Expr| match elt.kind {
+                    ExprKind::StarredExpr(_) => true,
+                    _ => false,
+                };
+                if elts.iter().filter(is_starred).count() > 1 {
+                    return Err(SyntaxError::new("multiple starred expressions in assignment",
+                                                expr.span.start));
+                }
+                let mut targets = vec![];
+                for elt in elts {
+                    targets.push(match elt.kind {
+                        ExprKind::StarredExpr(inner) => Target::StarredTarget(Box::new(self.target(*inner)?)),
+                        _ => self.target(elt)?,
+                    });
                 }
+                Ok(Target::TupleTarget(targets))
             },
-            _ => (),
-        };
+            ExprKind::StarredExpr(_) => {
+                Err(SyntaxError::new("starred assignment target must be in a list or tuple",
+                                     expr.span.start))
+            },
+            _ => Err(SyntaxError::new("cannot assign to expression", expr.span.start)),
+        }
+    }
+
+    // a starred expression is only allowed as an item of a display
+    fn comprehension_elt(&self, expr: Expr) -> ParseRes<Expr> {
+        match expr.kind {
+            ExprKind::StarredExpr(_) => {
+                Err(SyntaxError::new("iterable unpacking cannot be used in comprehension", expr.span.start))
+            },
+            _ => Ok(expr),
+        }
+    }
+
+    fn unstarred(&self, expr: Expr) -> ParseRes<Expr> {
+        match expr.kind {
+            ExprKind::StarredExpr(_) => {
+                Err(SyntaxError::new("can't use starred expression here", expr.span.start))
+            },
+            _ => Ok(expr),
+        }
+    }
+
+    // Comma separated items up to the first token that cannot start one,
+    // and whether there was a comma.
+    fn items<F>(&mut self, item: F) -> ParseRes<(Vec<Expr>, bool)>
+    where F: Fn(&mut Self) -> ParseRes<Expr> {
+        let mut items = vec![];
+        let mut comma = false;
+        while self.is_expr() || self.match_token(Token::Star) {
+            items.push(item(self)?);
+            if !self.match_token(Token::Comma) {
+                break;
+            }
+            self.consume(Token::Comma)?;
+            comma = true;
+        }
+        Ok((items, comma))
+    }
+
+    // a single item, or a tuple of them if there is a comma
+    fn tuple_or_item(&mut self, start: Pos, mut items: Vec<Expr>, comma: bool) -> ParseRes<Expr> {
+        if items.is_empty() {
+            return Err(self.unexpected("expression"));
+        }
+        if comma {
+            Ok(self.finish(ExprKind::TupleExpr(items), start))
+        } else {
+            Ok(items.pop().expect("Never fails"))
+        }
+    }
+
+    // skips the rest of the failed statement, stopping at the start of the
+    // next statement of the block at `depth` or at the end of that block
+    fn synchronize(&mut self, depth: usize) {
+        loop {
+            match self.peek() {
+                Some(&Token::EOF) | None => return,
+                _ => (),
+            }
+            let token = self.next();
+            if self.depth < depth {
+                return;
+            }
+            let at_boundary = token == Some(Token::NewLine) || token == Some(Token::Dedent);
+            if self.depth == depth && at_boundary && !self.match_token(Token::Indent) {
+                return;
+            }
+        }
+    }
+
+    // parses statements up to `end` into `prog`, recovering from errors
+    fn statements(&mut self, prog: &mut Program, end: Token) {
+        let depth = self.depth;
+        while !self.match_token(end.clone()) && self.depth == depth {
+            if let Some(&Token::EOF) = self.peek() {
+                break;
+            }
+            match self.statement() {
+                Ok(stmt) => prog.push(stmt),
+                Err(err) => {
+                    self.errors.push(err);
+                    self.synchronize(depth);
+                }
+            }
+        }
+    }
+}
+
+impl<I: Iterator<Item = (Token, Span)>> TokenStream for Tokens<I> {
+    fn parse(&mut self) -> ParseRes<Program> {
+        self.parse_all().map_err(|mut errors| errors.remove(0))
+    }
+
+    fn parse_all(&mut self) -> Result<Program, Vec<SyntaxError>> {
+        let prog = self.program();
+        if self.errors.is_empty() {
+            Ok(prog)
+        } else {
+            Err(self.errors.drain(..).collect())
+        }
+    }
+
+    fn program(&mut self) -> Program {
+        let mut prog: Program = vec![];
+        self.statements(&mut prog, Token::EOF);
         prog
     }
 
-    fn statement(&mut self) -> Stmt {
+    fn block(&mut self) -> ParseRes<Program> {
+        let mut prog: Program = vec![];
+        if !self.match_token(Token::Indent) {
+            return Err(SyntaxError::indentation("expected an indented block", self.pos()));
+        }
+        self.consume(Token::Indent)?;
+        let depth = self.depth;
+        self.statements(&mut prog, Token::Dedent);
+        // a failed statement may have skipped the closing dedent
+        if self.depth == depth {
+            self.consume(Token::Dedent)?;
+        }
+        Ok(prog)
+    }
+
+    // the body of a compound statement, either indented on the following
+    // lines or a simple statement on the line of the header
+    fn suite(&mut self) -> ParseRes<Program> {
+        if self.match_token(Token::NewLine) {
+            self.consume(Token::NewLine)?;
+            return self.block();
+        }
+        if self.is_compound() {
+            return Err(self.unexpected("simple statement"));
+        }
+        Ok(vec![self.statement()?])
+    }
+
+    fn statement(&mut self) -> ParseRes<Stmt> {
+        let start = self.pos();
         if self.is_compound() {
-            Stmt::StmtCompound(self.compound_stmt())
+            let stmt = self.compound_stmt()?;
+            Ok(Stmt::StmtCompound(stmt, self.span_from(start)))
         } else {
-            let stmt = Stmt::StmtSimple(self.simple_stmt());
-            self.consume(Token::NewLine);
-            stmt
+            let stmt = self.simple_stmt()?;
+            let stmt = Stmt::StmtSimple(stmt, self.span_from(start));
+            self.consume(Token::NewLine)?;
+            Ok(stmt)
         }
     }
 
-    fn simple_stmt(&mut self) -> SimpleStmt {
-        match self.peek() {
+    fn simple_stmt(&mut self) -> ParseRes<SimpleStmt> {
+        let stmt = match self.peek() {
             Some(&Token::Break) => {
-                self.consume(Token::Break);
+                self.consume(Token::Break)?;
                 SimpleStmt::BreakStmt
             },
             Some(&Token::Continue) => {
-                self.consume(Token::Continue);
+                self.consume(Token::Continue)?;
                 SimpleStmt::ContinueStmt
             },
+            Some(&Token::Pass) => {
+                self.consume(Token::Pass)?;
+                SimpleStmt::PassStmt
+            },
             Some(&Token::Raise) => {
-                self.consume(Token::Raise);
-                let expr = self.expr();
-                SimpleStmt::RaiseStmt(expr)
+                self.consume(Token::Raise)?;
+                let mut expr = None;
+                let mut cause = None;
+                if !self.match_token(Token::NewLine) {
+                    expr = Some(self.expr()?);
+                    if self.match_token(Token::From) {
+                        self.consume(Token::From)?;
+                        cause = Some(self.expr()?);
+                    }
+                }
+                SimpleStmt::RaiseStmt(expr, cause)
             },
             Some(&Token::Return) => {
-                self.consume(Token::Return);
-                let mut expr = Expr::NoneExpr;
+                self.consume(Token::Return)?;
+                let start = self.pos();
+                let mut expr = self.finish(ExprKind::NoneExpr, start);
                 if !self.match_token(Token::NewLine) {
-                    expr = self.expr();
+                    let value = self.expr_list()?;
+                    expr = self.unstarred(value)?;
                 }
                 SimpleStmt::ReturnStmt(expr)
             },
             Some(&Token::Assert) => {
-                self.consume(Token::Assert);
-                let expr = self.expr();
+                self.consume(Token::Assert)?;
+                let expr = self.expr()?;
                 SimpleStmt::AssertStmt(expr)
             },
+            Some(&Token::Del) => {
+                self.consume(Token::Del)?;
+                let expr = self.expr()?;
+                let target = match expr.kind {
+                    ExprKind::SubscrExpr(expr1, expr2) => Target::SubscrTarget(expr1, expr2),
+                    _ => return Err(SyntaxError::new("cannot delete expression", expr.span.start)),
+                };
+                SimpleStmt::DelStmt(target)
+            },
+            Some(&Token::Global) => {
+                self.consume(Token::Global)?;
+                SimpleStmt::GlobalStmt(self.name_list()?)
+            },
+            Some(&Token::Nonlocal) => {
+                self.consume(Token::Nonlocal)?;
+                SimpleStmt::NonlocalStmt(self.name_list()?)
+            },
+            Some(&Token::Yield) => {
+                SimpleStmt::ExprStmt(self.yield_expr()?)
+            },
             _ => {
-                let expr = self.expr();
+                let expr = self.expr_list()?;
                 match self.peek() {
                     Some(&Token::Eq) => {
-                        let target = match expr {
-                            Expr::VarExpr(id) => Target::IdentTarget(id),
-                            Expr::AttrExpr(expr, id) => Target::AttrTarget(expr, id),
-                            Expr::SubscrExpr(expr1, expr2) => Target::SubscrTarget(expr1, expr2),
-                            _ => panic!("Parse Error: Assign Target")
+                        let target = self.target(expr)?;
+                        self.consume(Token::Eq)?;
+                        let value = if self.match_token(Token::Yield) {
+                            self.yield_expr()?
+                        } else {
+                            self.expr_list()?
                         };
-                        self.consume(Token::Eq);
-                        let expr = self.expr();
-                        SimpleStmt::AssignStmt(target, expr)
+                        SimpleStmt::AssignStmt(target, self.unstarred(value)?)
                     },
                     Some(&Token::NewLine) => {
-                        SimpleStmt::ExprStmt(expr)
+                        SimpleStmt::ExprStmt(self.unstarred(expr)?)
                     },
-                    _ => panic!("Parse Error: AssignStmt")
+                    _ => return Err(self.unexpected("newline")),
                 }
             },
+        };
+        Ok(stmt)
+    }
+
+    fn name_list(&mut self) -> ParseRes<Vec<Id>> {
+        let mut names = vec![self.consume_ident()?];
+        while self.match_token(Token::Comma) {
+            self.consume(Token::Comma)?;
+            names.push(self.consume_ident()?);
         }
+        Ok(names)
     }
 
     fn is_compound(&mut self) -> bool {
@@ -205,214 +636,642 @@ impl<I: Iterator<Item = Token>> TokenStream for Peekable<I> {
         }
     }
 
-    fn compound_stmt(&mut self) -> CompoundStmt {
-        match self.peek() {
+    fn compound_stmt(&mut self) -> ParseRes<CompoundStmt> {
+        let stmt = match self.peek() {
             Some(&Token::If) => {
-                self.consume(Token::If);
-                let expr = self.expr();
-                self.consume(Token::Colon);
-                self.consume(Token::NewLine);
-                let prog_then = self.block();
-                self.consume(Token::Else);
-                self.consume(Token::Colon);
-                self.consume(Token::NewLine);
-                let prog_else = self.block();
-                CompoundStmt::IfStmt(expr, prog_then, prog_else)
+                self.consume(Token::If)?;
+                self.if_rest()?
             },
             Some(&Token::While) => {
-                self.consume(Token::While);
-                let expr = self.expr();
-                self.consume(Token::Colon);
-                self.consume(Token::NewLine);
-                let prog = self.block();
+                self.consume(Token::While)?;
+                let expr = self.expr()?;
+                self.consume(Token::Colon)?;
+                let prog = self.suite()?;
                 CompoundStmt::WhileStmt(expr, prog)
             },
             Some(&Token::For) => {
-                self.consume(Token::For);
-                let target = match self.expr() {
-                    Expr::VarExpr(id) => Target::IdentTarget(id),
-                    Expr::AttrExpr(expr, id) => Target::AttrTarget(expr, id),
-                    Expr::SubscrExpr(expr1, expr2) => Target::SubscrTarget(expr1, expr2),
-                    _ => panic!("Parse Error: Assign Target")
-                };
-                self.consume(Token::In);
-                let expr = self.expr();
-                self.consume(Token::Colon);
-                self.consume(Token::NewLine);
-                let prog = self.block();
+                self.consume(Token::For)?;
+                let expr = self.target_list()?;
+                let target = self.target(expr)?;
+                self.consume(Token::In)?;
+                let iter = self.expr_list()?;
+                let expr = self.unstarred(iter)?;
+                self.consume(Token::Colon)?;
+                let prog = self.suite()?;
                 CompoundStmt::ForStmt(target, expr, prog)
             },
             Some(&Token::Try) => {
-                self.consume(Token::Try);
-                self.consume(Token::Colon);
-                self.consume(Token::NewLine);
-                let prog_try = self.block();
-                self.consume(Token::Except);
-                self.consume(Token::Colon);
-                self.consume(Token::NewLine);
-                let prog_except = self.block();
-                CompoundStmt::TryStmt(prog_try, prog_except)
+                self.consume(Token::Try)?;
+                self.consume(Token::Colon)?;
+                let prog_try = self.suite()?;
+
+                let mut handlers = vec![];
+                while self.match_token(Token::Except) {
+                    if let Some(handler) = handlers.last().filter(|h: &&ExceptHandler| h.typ.is_none()) {
+                        return Err(SyntaxError::new("default 'except:' must be last", handler.span.start));
+                    }
+                    handlers.push(self.except_clause()?);
+                }
+
+                let mut prog_else = vec![];
+                if !handlers.is_empty() && self.match_token(Token::Else) {
+                    self.consume(Token::Else)?;
+                    self.consume(Token::Colon)?;
+                    prog_else = self.suite()?;
+                }
+
+                let mut prog_finally = None;
+                if self.match_token(Token::Finally) {
+                    self.consume(Token::Finally)?;
+                    self.consume(Token::Colon)?;
+                    prog_finally = Some(self.suite()?);
+                }
+
+                if handlers.is_empty() && prog_finally.is_none() {
+                    return Err(self.unexpected("'except' or 'finally'"));
+                }
+                CompoundStmt::TryStmt(prog_try, handlers, prog_else, prog_finally)
             },
             Some(&Token::Def) => {
-                self.consume(Token::Def);
-                let fun_name = self.consume_ident();
-                self.consume(Token::LParen);
-                let parm_list = self.parm_list();
-                self.consume(Token::RParen);
-                self.consume(Token::Colon);
-                self.consume(Token::NewLine);
-                let prog = self.block();
+                self.consume(Token::Def)?;
+                let fun_name = self.consume_ident()?;
+                self.consume(Token::LParen)?;
+                let parm_list = self.parm_list()?;
+                self.consume(Token::RParen)?;
+                self.consume(Token::Colon)?;
+                let prog = self.suite()?;
                 CompoundStmt::DefStmt(fun_name, parm_list, prog)
             },
             Some(&Token::Class) => {
                 let mut bases = vec![];
-                self.consume(Token::Class);
-                let class_name = self.consume_ident();
+                self.consume(Token::Class)?;
+                let class_name = self.consume_ident()?;
 
                 if self.match_token(Token::LParen) {
-                    self.consume(Token::LParen);
-                    bases = self.comma_list();
-                    self.consume(Token::RParen);
+                    self.consume(Token::LParen)?;
+                    bases = self.comma_list()?;
+                    self.consume(Token::RParen)?;
                 }
 
-                self.consume(Token::Colon);
-                self.consume(Token::NewLine);
-                let prog = self.block();
+                self.consume(Token::Colon)?;
+                let prog = self.suite()?;
                 CompoundStmt::ClassStmt(class_name, bases, prog)
             },
-            _ => panic!("Parse Error: compound_stmt"),
-        }
+            _ => return Err(self.unexpected("compound statement")),
+        };
+        Ok(stmt)
     }
 
-    fn parm_list(&mut self) -> Vec<Id> {
-        let mut pl: Vec<Id>  = vec![];
-        match self.peek() {
-            Some(&Token::Ident(_)) => pl.push(self.consume_ident()),
-            Some(_) => return pl,
-            _ => panic!("Parse Error: parm_list"),
+    // an elif continues as an if statement nested in the else branch
+    fn if_rest(&mut self) -> ParseRes<CompoundStmt> {
+        let expr = self.expr()?;
+        self.consume(Token::Colon)?;
+        let prog_then = self.suite()?;
+        let prog_else = match self.peek() {
+            Some(&Token::Elif) => {
+                let start = self.pos();
+                self.consume(Token::Elif)?;
+                let stmt = self.if_rest()?;
+                vec![Stmt::StmtCompound(stmt, self.span_from(start))]
+            },
+            Some(&Token::Else) => {
+                self.consume(Token::Else)?;
+                self.consume(Token::Colon)?;
+                self.suite()?
+            },
+            _ => vec![],
         };
+        Ok(CompoundStmt::IfStmt(expr, prog_then, prog_else))
+    }
+
+    fn except_clause(&mut self) -> ParseRes<ExceptHandler> {
+        let start = self.pos();
+        self.consume(Token::Except)?;
+        let mut typ = None;
+        let mut name = None;
+        if !self.match_token(Token::Colon) {
+            typ = Some(self.expr()?);
+            if self.match_token(Token::As) {
+                self.consume(Token::As)?;
+                name = Some(self.consume_ident()?);
+            }
+        }
+        self.consume(Token::Colon)?;
+        let body = self.suite()?;
+        Ok(ExceptHandler { typ: typ, name: name, body: body, span: self.span_from(start) })
+    }
+
+    fn parm_list(&mut self) -> ParseRes<Parameters> {
+        let mut parms = Parameters::default();
+        let mut names: Vec<Id> = vec![];
+        let mut slash = false;
+        let mut bare_star = None;
         loop {
+            let start = self.pos();
             match self.peek() {
-                Some(&Token::Comma) => {
-                    self.consume(Token::Comma);
-                    pl.push(self.consume_ident());
+                Some(&Token::Ident(_)) | Some(&Token::Slash) |
+                Some(&Token::Star) | Some(&Token::DoubleStar) => {},
+                _ => break,
+            }
+            if parms.kwarg.is_some() {
+                return Err(SyntaxError::new("arguments cannot follow var-keyword argument", start));
+            }
+            let name = match self.peek() {
+                Some(&Token::Slash) => {
+                    self.consume(Token::Slash)?;
+                    if slash {
+                        return Err(SyntaxError::new("/ may appear only once", start));
+                    } else if bare_star.is_some() || parms.vararg.is_some() {
+                        return Err(SyntaxError::new("/ must be ahead of *", start));
+                    } else if parms.args.is_empty() {
+                        return Err(SyntaxError::new("at least one argument must precede /", start));
+                    }
+                    slash = true;
+                    parms.posonly = parms.args.len();
+                    None
+                },
+                Some(&Token::Star) => {
+                    self.consume(Token::Star)?;
+                    if bare_star.is_some() || parms.vararg.is_some() {
+                        return Err(SyntaxError::new("* argument may appear only once", start));
+                    }
+                    match self.peek() {
+                        Some(&Token::Ident(_)) => {
+                            let name = self.consume_ident()?;
+                            parms.vararg = Some(name.clone());
+                            Some(name)
+                        },
+                        _ => {
+                            bare_star = Some(start);
+                            None
+                        },
+                    }
+                },
+                Some(&Token::DoubleStar) => {
+                    self.consume(Token::DoubleStar)?;
+                    let name = self.consume_ident()?;
+                    parms.kwarg = Some(name.clone());
+                    Some(name)
+                },
+                _ => {
+                    let name = self.consume_ident()?;
+                    let default = if self.match_token(Token::Eq) {
+                        self.consume(Token::Eq)?;
+                        Some(self.expr()?)
+                    } else {
+                        None
+                    };
+                    if bare_star.is_some() || parms.vararg.is_some() {
+                        parms.kwonly.push((name.clone(), default));
+                    } else {
+                        match default {
+                            Some(default) => parms.defaults.push(default),
+                            None if !parms.defaults.is_empty() => {
+                                return Err(SyntaxError::new("non-default argument follows default argument",
+                                                            start));
+                            },
+                            None => {},
+                        }
+                        parms.args.push(name.clone());
+                    }
+                    Some(name)
                 },
-                Some(_) => break,
-                _ => panic!("Parse Error: parm_list"),
+            };
+            if let Some(name) = name {
+                if names.contains(&name) {
+                    let msg = format!("duplicate argument '{}' in function definition", name);
+                    return Err(SyntaxError::new(&msg, start));
+                }
+                names.push(name);
             }
-        };
-        pl
+            if !self.match_token(Token::Comma) {
+                break;
+            }
+            self.consume(Token::Comma)?;
+        }
+        match bare_star {
+            Some(pos) if parms.kwonly.is_empty() => {
+                Err(SyntaxError::new("named arguments must follow bare *", pos))
+            },
+            _ => Ok(parms),
+        }
     }
 
-    fn expr(&mut self) -> Expr {
-        let expr1 = self.eexpr();
-        match self.peek() {
-            Some(&Token::Lt) => {
-                self.consume(Token::Lt);
-                let expr2 = self.eexpr();
-                Expr::LtExpr(Box::new(expr1), Box::new(expr2))
-            },
-            Some(_) => expr1,
-            None => panic!("Parse Error: expr"),
+    fn expr_list(&mut self) -> ParseRes<Expr> {
+        let start = self.pos();
+        let (items, comma) = self.items(|s| s.star_expr())?;
+        self.tuple_or_item(start, items, comma)
+    }
+
+    fn yield_expr(&mut self) -> ParseRes<Expr> {
+        let start = self.pos();
+        self.consume(Token::Yield)?;
+        if self.match_token(Token::From) {
+            self.consume(Token::From)?;
+            let expr = self.expr()?;
+            return Ok(self.finish(ExprKind::YieldFrom(Box::new(expr)), start));
+        }
+        let mut value = None;
+        if self.is_expr() || self.match_token(Token::Star) {
+            let expr = self.expr_list()?;
+            value = Some(Box::new(self.unstarred(expr)?));
         }
+        Ok(self.finish(ExprKind::YieldExpr(value), start))
     }
 
-    fn eexpr(&mut self) -> Expr {
-        let expr1 = self.pexpr();
-        match self.peek() {
-            Some(&Token::EqEq) => {
-                self.consume(Token::EqEq);
-                let expr2 = self.pexpr();
-                Expr::EqEqExpr(Box::new(expr1), Box::new(expr2))
+    // the targets of a for loop, which stop short of a comparison so that
+    // `in` is left to the loop
+    fn target_list(&mut self) -> ParseRes<Expr> {
+        let start = self.pos();
+        let (items, comma) = self.items(|s| {
+            let start = s.pos();
+            if s.match_token(Token::Star) {
+                s.consume(Token::Star)?;
+                let expr = s.pexpr()?;
+                Ok(s.finish(ExprKind::StarredExpr(Box::new(expr)), start))
+            } else {
+                s.pexpr()
+            }
+        })?;
+        self.tuple_or_item(start, items, comma)
+    }
+
+    fn star_expr(&mut self) -> ParseRes<Expr> {
+        let start = self.pos();
+        if self.match_token(Token::Star) {
+            self.consume(Token::Star)?;
+            let expr = self.pexpr()?;
+            Ok(self.finish(ExprKind::StarredExpr(Box::new(expr)), start))
+        } else {
+            self.expr()
+        }
+    }
+
+    fn expr(&mut self) -> ParseRes<Expr> {
+        let start = self.pos();
+        if self.match_token(Token::Lambda) {
+            self.consume(Token::Lambda)?;
+            let parms = self.parm_list()?;
+            self.consume(Token::Colon)?;
+            let body = self.expr()?;
+            return Ok(self.finish(ExprKind::LambdaExpr(Box::new(parms), Box::new(body)), start));
+        }
+        let expr = self.or_test()?;
+        if !self.match_token(Token::If) {
+            return Ok(expr);
+        }
+        self.consume(Token::If)?;
+        let cond = self.or_test()?;
+        self.consume(Token::Else)?;
+        let expr2 = self.expr()?;
+        Ok(self.finish(ExprKind::CondExpr(Box::new(cond), Box::new(expr), Box::new(expr2)), start))
+    }
+
+    fn or_test(&mut self) -> ParseRes<Expr> {
+        let start = self.pos();
+        let mut expr = self.and_test()?;
+        while self.match_token(Token::Or) {
+            self.consume(Token::Or)?;
+            let expr2 = self.and_test()?;
+            expr = self.finish(ExprKind::OrExpr(Box::new(expr), Box::new(expr2)), start)
+        }
+        Ok(expr)
+    }
+
+    fn and_test(&mut self) -> ParseRes<Expr> {
+        let start = self.pos();
+        let mut expr = self.not_test()?;
+        while self.match_token(Token::And) {
+            self.consume(Token::And)?;
+            let expr2 = self.not_test()?;
+            expr = self.finish(ExprKind::AndExpr(Box::new(expr), Box::new(expr2)), start)
+        }
+        Ok(expr)
+    }
+
+    fn not_test(&mut self) -> ParseRes<Expr> {
+        let start = self.pos();
+        if self.match_token(Token::Not) {
+            self.consume(Token::Not)?;
+            let expr = self.not_test()?;
+            Ok(self.finish(ExprKind::NotExpr(Box::new(expr)), start))
+        } else {
+            self.comparison()
+        }
+    }
+
+    fn comparison(&mut self) -> ParseRes<Expr> {
+        let start = self.pos();
+        let expr1 = self.pexpr()?;
+        let mut comps = vec![];
+        while let Some(op) = self.comp_op()? {
+            comps.push((op, self.pexpr()?));
+        }
+        if comps.is_empty() {
+            Ok(expr1)
+        } else {
+            Ok(self.finish(ExprKind::CompareExpr(Box::new(expr1), comps), start))
+        }
+    }
+
+    fn comp_op(&mut self) -> ParseRes<Option<CompOp>> {
+        let op = match self.peek() {
+            Some(&Token::Lt) => CompOp::Lt,
+            Some(&Token::LtE) => CompOp::LtE,
+            Some(&Token::Gt) => CompOp::Gt,
+            Some(&Token::GtE) => CompOp::GtE,
+            Some(&Token::EqEq) => CompOp::EqEq,
+            Some(&Token::NotEq) => CompOp::NotEq,
+            Some(&Token::In) => CompOp::In,
+            Some(&Token::Not) => {
+                self.consume(Token::Not)?;
+                self.consume(Token::In)?;
+                return Ok(Some(CompOp::NotIn));
             },
-            Some(_) => expr1,
-            None => panic!("Parse Error: expr"),
+            Some(&Token::Is) => {
+                self.consume(Token::Is)?;
+                if self.match_token(Token::Not) {
+                    self.consume(Token::Not)?;
+                    return Ok(Some(CompOp::IsNot));
+                }
+                return Ok(Some(CompOp::Is));
+            },
+            _ => return Ok(None),
+        };
+        self.next();
+        Ok(Some(op))
+    }
+
+    fn pexpr(&mut self) -> ParseRes<Expr> {
+        let start = self.pos();
+        let mut expr = self.mexpr()?;
+        loop {
+            match self.peek() {
+                Some(&Token::Plus) => {
+                    self.consume(Token::Plus)?;
+                    let expr2 = self.mexpr()?;
+                    expr = self.finish(ExprKind::AddExpr(Box::new(expr), Box::new(expr2)), start)
+                },
+                Some(&Token::Minus) => {
+                    self.consume(Token::Minus)?;
+                    let expr2 = self.mexpr()?;
+                    expr = self.finish(ExprKind::SubExpr(Box::new(expr), Box::new(expr2)), start)
+                },
+                _ => return Ok(expr),
+            }
         }
     }
 
-    fn pexpr(&mut self) -> Expr {
-        let expr1 = self.cexpr();
+    fn mexpr(&mut self) -> ParseRes<Expr> {
+        let start = self.pos();
+        let mut expr = self.uexpr()?;
+        loop {
+            match self.peek() {
+                Some(&Token::Star) => {
+                    self.consume(Token::Star)?;
+                    let expr2 = self.uexpr()?;
+                    expr = self.finish(ExprKind::MulExpr(Box::new(expr), Box::new(expr2)), start)
+                },
+                Some(&Token::Slash) => {
+                    self.consume(Token::Slash)?;
+                    let expr2 = self.uexpr()?;
+                    expr = self.finish(ExprKind::TrueDivExpr(Box::new(expr), Box::new(expr2)), start)
+                },
+                Some(&Token::DoubleSlash) => {
+                    self.consume(Token::DoubleSlash)?;
+                    let expr2 = self.uexpr()?;
+                    expr = self.finish(ExprKind::FloorDivExpr(Box::new(expr), Box::new(expr2)), start)
+                },
+                Some(&Token::Percent) => {
+                    self.consume(Token::Percent)?;
+                    let expr2 = self.uexpr()?;
+                    expr = self.finish(ExprKind::ModExpr(Box::new(expr), Box::new(expr2)), start)
+                },
+                _ => return Ok(expr),
+            }
+        }
+    }
+
+    fn uexpr(&mut self) -> ParseRes<Expr> {
+        let start = self.pos();
         match self.peek() {
+            Some(&Token::Minus) => {
+                self.consume(Token::Minus)?;
+                let expr = self.uexpr()?;
+                Ok(self.finish(ExprKind::NegExpr(Box::new(expr)), start))
+            },
             Some(&Token::Plus) => {
-                self.consume(Token::Plus);
-                let expr2 = self.pexpr();
-                Expr::AddExpr(Box::new(expr1), Box::new(expr2))
+                self.consume(Token::Plus)?;
+                let expr = self.uexpr()?;
+                Ok(self.finish(ExprKind::PosExpr(Box::new(expr)), start))
             },
-            Some(_) => expr1,
-            None => panic!("Parse Error: pexpr"),
+            _ => self.powexpr(),
+        }
+    }
+
+    fn powexpr(&mut self) -> ParseRes<Expr> {
+        let start = self.pos();
+        let expr1 = self.cexpr()?;
+        if self.match_token(Token::DoubleStar) {
+            self.consume(Token::DoubleStar)?;
+            let expr2 = self.uexpr()?;
+            Ok(self.finish(ExprKind::PowExpr(Box::new(expr1), Box::new(expr2)), start))
+        } else {
+            Ok(expr1)
         }
     }
 
-    fn cexpr(&mut self) -> Expr {
-        let mut expr = self.aexpr();
+    fn cexpr(&mut self) -> ParseRes<Expr> {
+        let start = self.pos();
+        let mut expr = self.aexpr()?;
         loop {
             match self.peek() {
                 Some(&Token::LParen) => {
-                    self.consume(Token::LParen);
-                    let comma_list = self.comma_list();
-                    self.consume(Token::RParen);
-                    expr = Expr::CallExpr(Box::new(expr), comma_list)
+                    self.consume(Token::LParen)?;
+                    let (args, keywords) = self.arg_list()?;
+                    self.consume(Token::RParen)?;
+                    expr = self.finish(ExprKind::CallExpr(Box::new(expr), args, keywords), start)
                 },
                 Some(&Token::Dot) => {
-                    self.consume(Token::Dot);
-                    let ident = self.consume_ident();
-                    expr = Expr::AttrExpr(Box::new(expr), ident)
+                    self.consume(Token::Dot)?;
+                    let ident = self.consume_ident()?;
+                    expr = self.finish(ExprKind::AttrExpr(Box::new(expr), ident), start)
                 },
                 Some(&Token::LBracket) => {
-                    self.consume(Token::LBracket);
-                    let key_expr = self.expr();
-                    self.consume(Token::RBracket);
-                    expr = Expr::SubscrExpr(Box::new(expr), Box::new(key_expr))
+                    self.consume(Token::LBracket)?;
+                    let key = self.subscript_list()?;
+                    let key_expr = self.unstarred(key)?;
+                    self.consume(Token::RBracket)?;
+                    expr = self.finish(ExprKind::SubscrExpr(Box::new(expr), Box::new(key_expr)), start)
                 },
-                Some(_) => return expr,
-                None => panic!("Parse Error: cexpr"),
+                _ => return Ok(expr),
             }
         }
     }
 
-    fn aexpr(&mut self) -> Expr {
-        match self.peek().unwrap() {
-            &Token::LParen => {
-                self.consume(Token::LParen);
-                let expr = self.expr();
-                self.consume(Token::RParen);
-                expr
-            },
-            &Token::LBracket => {
-                self.consume(Token::LBracket);
-                let cl = self.comma_list();
-                self.consume(Token::RBracket);
-                Expr::ListExpr(cl)
+    fn arg_list(&mut self) -> ParseRes<(Vec<Expr>, Vec<Keyword>)> {
+        let mut args = vec![];
+        let mut keywords: Vec<Keyword> = vec![];
+        while !self.match_token(Token::RParen) {
+            let start = self.pos();
+            let unpacking = keywords.iter().any(|kw| kw.arg.is_none());
+            if self.match_token(Token::DoubleStar) {
+                self.consume(Token::DoubleStar)?;
+                let value = self.expr()?;
+                keywords.push(Keyword { arg: None, value: value });
+            } else if self.match_token(Token::Star) {
+                self.consume(Token::Star)?;
+                let value = self.expr()?;
+                if unpacking {
+                    return Err(SyntaxError::new("iterable argument unpacking follows keyword argument unpacking",
+                                                start));
+                }
+                args.push(self.finish(ExprKind::StarredExpr(Box::new(value)), start));
+            } else {
+                let expr = self.expr()?;
+                if self.match_token(Token::For) {
+                    let comps = self.comp_for()?;
+                    let genexp = self.finish(ExprKind::GeneratorExp(Box::new(expr), comps), start);
+                    if !args.is_empty() || !keywords.is_empty() || !self.match_token(Token::RParen) {
+                        return Err(SyntaxError::new("Generator expression must be parenthesized", start));
+                    }
+                    args.push(genexp);
+                    break;
+                }
+                if self.match_token(Token::Eq) {
+                    let arg = match expr.kind {
+                        ExprKind::VarExpr(ref id) => id.clone(),
+                        _ => return Err(SyntaxError::new(
+                            "expression cannot contain assignment, perhaps you meant \"==\"?", start)),
+                    };
+                    self.consume(Token::Eq)?;
+                    let value = self.expr()?;
+                    if keywords.iter().any(|kw| kw.arg.as_ref() == Some(&arg)) {
+                        return Err(SyntaxError::new(&format!("keyword argument repeated: {}", arg), start));
+                    }
+                    keywords.push(Keyword { arg: Some(arg), value: value });
+                } else if unpacking {
+                    return Err(SyntaxError::new("positional argument follows keyword argument unpacking", start));
+                } else if !keywords.is_empty() {
+                    return Err(SyntaxError::new("positional argument follows keyword argument", start));
+                } else {
+                    args.push(expr);
+                }
+            }
+            if !self.match_token(Token::Comma) {
+                break;
+            }
+            self.consume(Token::Comma)?;
+        }
+        Ok((args, keywords))
+    }
+
+    fn subscript_list(&mut self) -> ParseRes<Expr> {
+        let start = self.pos();
+        let mut items = vec![self.subscript()?];
+        let mut comma = false;
+        while self.match_token(Token::Comma) {
+            self.consume(Token::Comma)?;
+            comma = true;
+            if !(self.is_expr() || self.match_token(Token::Colon) || self.match_token(Token::Star)) {
+                break;
+            }
+            items.push(self.subscript()?);
+        }
+        self.tuple_or_item(start, items, comma)
+    }
 
+    fn subscript(&mut self) -> ParseRes<Expr> {
+        let start = self.pos();
+        let lower = if self.match_token(Token::Colon) {
+            None
+        } else {
+            let expr = self.star_expr()?;
+            if !self.match_token(Token::Colon) {
+                return Ok(expr);
+            }
+            Some(Box::new(self.unstarred(expr)?))
+        };
+        self.consume(Token::Colon)?;
+        let upper = if self.is_expr() { Some(Box::new(self.expr()?)) } else { None };
+        let mut step = None;
+        if self.match_token(Token::Colon) {
+            self.consume(Token::Colon)?;
+            if self.is_expr() {
+                step = Some(Box::new(self.expr()?));
+            }
+        }
+        Ok(self.finish(ExprKind::SliceExpr(lower, upper, step), start))
+    }
+
+    fn aexpr(&mut self) -> ParseRes<Expr> {
+        let start = self.pos();
+        let kind = match self.peek() {
+            Some(&Token::LParen) => {
+                self.consume(Token::LParen)?;
+                if self.match_token(Token::Yield) {
+                    let expr = self.yield_expr()?;
+                    self.consume(Token::RParen)?;
+                    return Ok(expr);
+                }
+                let (mut items, comma) = self.items(|s| s.star_expr())?;
+                if !comma && items.len() == 1 && self.match_token(Token::For) {
+                    let elt = self.comprehension_elt(items.pop().expect("Never fails"))?;
+                    let comps = self.comp_for()?;
+                    self.consume(Token::RParen)?;
+                    return Ok(self.finish(ExprKind::GeneratorExp(Box::new(elt), comps), start));
+                }
+                self.consume(Token::RParen)?;
+                if comma || items.is_empty() {
+                    ExprKind::TupleExpr(items)
+                } else {
+                    let expr = items.pop().expect("Never fails");
+                    if let ExprKind::StarredExpr(_) = expr.kind {
+                        return Err(SyntaxError::new("cannot use starred expression here", expr.span.start));
+                    }
+                    return Ok(expr);
+                }
             },
-            &Token::LBrace => {
-                self.consume(Token::LBrace);
-                let pl = self.pair_list();
-                self.consume(Token::RBrace);
-                Expr::DictExpr(pl)
+            Some(&Token::LBracket) => {
+                self.consume(Token::LBracket)?;
+                let (mut items, comma) = self.items(|s| s.star_expr())?;
+                let kind = if !comma && items.len() == 1 && self.match_token(Token::For) {
+                    let elt = self.comprehension_elt(items.pop().expect("Never fails"))?;
+                    ExprKind::ListComp(Box::new(elt), self.comp_for()?)
+                } else {
+                    ExprKind::ListExpr(items)
+                };
+                self.consume(Token::RBracket)?;
+                kind
             },
-            &Token::True => {
-                self.consume(Token::True);
-                Expr::BoolExpr(true)
+            Some(&Token::LBrace) => {
+                self.consume(Token::LBrace)?;
+                let kind = self.brace_display()?;
+                self.consume(Token::RBrace)?;
+                kind
             },
-            &Token::False => {
-                self.consume(Token::False);
-                Expr::BoolExpr(false)
+            Some(&Token::True) => {
+                self.consume(Token::True)?;
+                ExprKind::BoolExpr(true)
             },
-            &Token::Ident(_) => {
-                let ident = self.consume_ident();
-                Expr::VarExpr(ident)
+            Some(&Token::False) => {
+                self.consume(Token::False)?;
+                ExprKind::BoolExpr(false)
             },
-            &Token::Int(_) => {
-                let i = self.consume_int();
-                Expr::IntExpr(i)
+            Some(&Token::None) => {
+                self.consume(Token::None)?;
+                ExprKind::NoneExpr
             },
-            &Token::Str(_) => {
-                let s = self.consume_str();
-                Expr::StrExpr(s)
+            Some(&Token::Ident(_)) => ExprKind::VarExpr(self.consume_ident()?),
+            Some(&Token::Int(_)) => ExprKind::IntExpr(self.consume_int()?),
+            Some(&Token::Float(_)) => ExprKind::FloatExpr(self.consume_float()?),
+            Some(&Token::Imag(_)) => ExprKind::ImagExpr(self.consume_imag()?),
+            Some(&Token::Str(_)) | Some(&Token::FStr(_)) | Some(&Token::Bytes(_)) => {
+                self.string_literal()?
             },
-            _ => panic!("Parse Error: aexpr"),
-        }
+            _ => return Err(self.unexpected("expression")),
+        };
+        Ok(self.finish(kind, start))
     }
 
     fn is_expr(&mut self) -> bool {
@@ -424,60 +1283,111 @@ impl<I: Iterator<Item = Token>> TokenStream for Peekable<I> {
             Some(&Token::False) => true,
             Some(&Token::Ident(_)) => true,
             Some(&Token::Int(_)) => true,
+            Some(&Token::Float(_)) => true,
+            Some(&Token::Imag(_)) => true,
             Some(&Token::Str(_)) => true,
+            Some(&Token::Bytes(_)) => true,
+            Some(&Token::FStr(_)) => true,
             Some(&Token::None) => true,
+            Some(&Token::Minus) => true,
+            Some(&Token::Plus) => true,
+            Some(&Token::Not) => true,
+            Some(&Token::Lambda) => true,
             _ => false,
         }
     }
 
-    fn comma_list(&mut self) -> Vec<Expr> {
+    fn comma_list(&mut self) -> ParseRes<Vec<Expr>> {
         let mut al: Vec<Expr>  = vec![];
 
         if self.is_expr() {
-            al.push(self.expr());
+            al.push(self.expr()?);
         } else {
-            return al;
+            return Ok(al);
         }
 
-        loop {
-            match self.peek() {
-                Some(&Token::Comma) => {
-                    self.consume(Token::Comma);
-                    al.push(self.expr());
-                },
-                Some(_) => break,
-                _ => panic!("Parse Error: parm_list"),
+        while self.match_token(Token::Comma) {
+            self.consume(Token::Comma)?;
+            if !self.is_expr() {
+                break;
             }
-        };
-        al
+            al.push(self.expr()?);
+        }
+        Ok(al)
     }
 
-    fn pair_list(&mut self) -> Vec<(Expr, Expr)> {
+    // what follows `{`: a dict or a set, either as a display or as a
+    // comprehension
+    fn brace_display(&mut self) -> ParseRes<ExprKind> {
+        if !self.is_expr() {
+            return Ok(ExprKind::DictExpr(vec![]));
+        }
+        let first = self.expr()?;
+        if self.match_token(Token::Colon) {
+            self.consume(Token::Colon)?;
+            let value = self.expr()?;
+            if self.match_token(Token::For) {
+                return Ok(ExprKind::DictComp(Box::new(first), Box::new(value), self.comp_for()?));
+            }
+            let mut pl = vec![(first, value)];
+            if self.match_token(Token::Comma) {
+                self.consume(Token::Comma)?;
+                pl.append(&mut self.pair_list()?);
+            }
+            return Ok(ExprKind::DictExpr(pl));
+        }
+        if self.match_token(Token::For) {
+            return Ok(ExprKind::SetComp(Box::new(first), self.comp_for()?));
+        }
+        let mut items = vec![first];
+        if self.match_token(Token::Comma) {
+            self.consume(Token::Comma)?;
+            items.append(&mut self.comma_list()?);
+        }
+        Ok(ExprKind::SetExpr(items))
+    }
+
+    fn comp_for(&mut self) -> ParseRes<Vec<Comprehension>> {
+        let mut comps = vec![];
+        while self.match_token(Token::For) {
+            self.consume(Token::For)?;
+            let expr = self.target_list()?;
+            let target = self.target(expr)?;
+            self.consume(Token::In)?;
+            let iter = self.or_test()?;
+            let mut ifs = vec![];
+            while self.match_token(Token::If) {
+                self.consume(Token::If)?;
+                ifs.push(self.or_test()?);
+            }
+            comps.push(Comprehension { target: target, iter: iter, ifs: ifs });
+        }
+        Ok(comps)
+    }
+
+    fn pair_list(&mut self) -> ParseRes<Vec<(Expr, Expr)>> {
         let mut pl: Vec<(Expr, Expr)>  = vec![];
 
         if self.is_expr() {
-            let e1 = self.expr();
-            self.consume(Token::Colon);
-            let e2 = self.expr();
+            let e1 = self.expr()?;
+            self.consume(Token::Colon)?;
+            let e2 = self.expr()?;
             pl.push((e1, e2))
         } else {
-            return pl;
+            return Ok(pl);
         }
 
-        loop {
-            match self.peek() {
-                Some(&Token::Comma) => {
-                    self.consume(Token::Comma);
-                    let e1 = self.expr();
-                    self.consume(Token::Colon);
-                    let e2 = self.expr();
-                    pl.push((e1, e2))
-                },
-                Some(_) => break,
-                _ => panic!("Parse Error: pair_list"),
+        while self.match_token(Token::Comma) {
+            self.consume(Token::Comma)?;
+            if !self.is_expr() {
+                break;
             }
-        };
-        pl
+            let e1 = self.expr()?;
+            self.consume(Token::Colon)?;
+            let e2 = self.expr()?;
+            pl.push((e1, e2))
+        }
+        Ok(pl)
     }
 
     fn match_token(&mut self, token: Token) -> bool {
@@ -487,32 +1397,78 @@ impl<I: Iterator<Item = Token>> TokenStream for Peekable<I> {
         }
     }
 
-    fn consume(&mut self, token: Token) -> () {
+    fn consume(&mut self, token: Token) -> ParseRes<()> {
         if self.match_token(token.clone()) {
             self.next();
+            Ok(())
         } else {
-            panic!("Parse Error: {:?} expected", token);
+            Err(self.unexpected(&token.to_string()))
+        }
+    }
+
+    fn consume_ident(&mut self) -> ParseRes<String> {
+        match self.peek() {
+            Some(&Token::Ident(_)) => (),
+            _ => return Err(self.unexpected("identifier")),
+        }
+        match self.next() {
+            Some(Token::Ident(s)) => Ok(s),
+            _ => panic!("Never fails"),
         }
     }
 
-    fn consume_ident(&mut self) -> String {
+    fn consume_int(&mut self) -> ParseRes<BigInt> {
+        match self.peek() {
+            Some(&Token::Int(_)) => (),
+            _ => return Err(self.unexpected("number")),
+        }
         match self.next() {
-            Some(Token::Ident(ref s)) => s.clone(),
-            _ => panic!("Parse Error: Ident expected"),
+            Some(Token::Int(i)) => Ok(i),
+            _ => panic!("Never fails"),
         }
     }
 
-    fn consume_int(&mut self) -> i32 {
+    fn consume_float(&mut self) -> ParseRes<f64> {
+        match self.peek() {
+            Some(&Token::Float(_)) => (),
+            _ => return Err(self.unexpected("number")),
+        }
         match self.next() {
-            Some(Token::Int(ref i)) => i.clone(),
-            _ => panic!("Parse Error: int expected"),
+            Some(Token::Float(f)) => Ok(f),
+            _ => panic!("Never fails"),
         }
     }
 
-    fn consume_str(&mut self) -> String {
+    fn consume_imag(&mut self) -> ParseRes<f64> {
+        match self.peek() {
+            Some(&Token::Imag(_)) => (),
+            _ => return Err(self.unexpected("number")),
+        }
+        match self.next() {
+            Some(Token::Imag(f)) => Ok(f),
+            _ => panic!("Never fails"),
+        }
+    }
+
+    fn consume_str(&mut self) -> ParseRes<String> {
+        match self.peek() {
+            Some(&Token::Str(_)) => (),
+            _ => return Err(self.unexpected("string")),
+        }
+        match self.next() {
+            Some(Token::Str(s)) => Ok(s),
+            _ => panic!("Never fails"),
+        }
+    }
+
+    fn consume_bytes(&mut self) -> ParseRes<Vec<u8>> {
+        match self.peek() {
+            Some(&Token::Bytes(_)) => (),
+            _ => return Err(self.unexpected("bytes")),
+        }
         match self.next() {
-            Some(Token::Str(ref s)) => s.clone(),
-            _ => panic!("Parse Error: str expected"),
+            Some(Token::Bytes(b)) => Ok(b),
+            _ => panic!("Never fails"),
         }
     }
 }
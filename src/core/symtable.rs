@@ -0,0 +1,499 @@
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use syntax::*;
+
+/*
+The symbol table pass decides, before any code is generated, where each name
+of each block lives. The module, each function, lambda and comprehension,
+and each class body is a block. A name is
+
+- global when it is declared `global`, or when it is bound neither in its
+  block nor in any function around it,
+- local when it is bound in its block, which is any assignment, `for` or
+  `except ... as` target, parameter, `def` or `class`,
+- free when it is declared `nonlocal`, or when it is only used in its block
+  and bound in a function around it,
+- a cell when it is local and some nested function uses it as a free name.
+
+Class bodies do not count as functions around the blocks nested in them, so
+a method cannot see the names of its class.
+
+The pass also rejects what is out of place in its block: `return` and
+`yield` outside functions, and `break` and `continue` outside loops.
+*/
+
+/// Where the value of a name used in a block is kept.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Scope {
+    Local,
+    Cell,
+    Free,
+    GlobalExplicit,
+    GlobalImplicit,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BlockKind {
+    Module,
+    Function,
+    Class,
+}
+
+/// The names of one block and the tables of the blocks nested in it.
+pub struct SymbolTable {
+    pub kind: BlockKind,
+    symbols: HashMap<Id, Scope>,
+    /// the locals nested functions use, which are kept in cells
+    pub cellvars: Vec<Id>,
+    /// the names taken from enclosing functions, in the order the closure
+    /// hands their cells over
+    pub freevars: Vec<Id>,
+    children: HashMap<usize, Rc<SymbolTable>>,
+}
+
+/// Blocks are told apart by the address of the node that opens them: the
+/// parameters of a function or lambda, the clauses of a comprehension, or
+/// the body of a class.
+pub fn block_key<T>(node: &T) -> usize {
+    node as *const T as usize
+}
+
+impl SymbolTable {
+    /// Names the block never binds nor declares are global.
+    pub fn scope(&self, name: &Id) -> Scope {
+        *self.symbols.get(name).unwrap_or(&Scope::GlobalImplicit)
+    }
+
+    pub fn child<T>(&self, node: &T) -> Rc<SymbolTable> {
+        let table = self.children.get(&block_key(node)).expect("Implementation Error: symtable child");
+        Rc::clone(table)
+    }
+}
+
+// What a block binds, uses and declares, before its names are resolved
+struct Block {
+    kind: BlockKind,
+    key: usize,
+    // what a comprehension's block is called in error messages
+    comprehension: Option<&'static str>,
+    // how many loops the statement being visited is in
+    loop_depth: usize,
+    params: Vec<Id>,
+    bound: HashSet<Id>,
+    used: HashSet<Id>,
+    globals: HashSet<Id>,
+    nonlocals: Vec<(Id, Pos)>,
+    children: Vec<Block>,
+}
+
+impl Block {
+    fn new(kind: BlockKind, key: usize, params: Vec<Id>) -> Block {
+        Block {
+            kind: kind,
+            key: key,
+            comprehension: None,
+            loop_depth: 0,
+            bound: params.iter().cloned().collect(),
+            params: params,
+            used: HashSet::new(),
+            globals: HashSet::new(),
+            nonlocals: vec![],
+            children: vec![],
+        }
+    }
+
+    fn is_nonlocal(&self, name: &Id) -> bool {
+        self.nonlocals.iter().any(|&(ref id, _)| id == name)
+    }
+
+    fn declare(&mut self, name: &Id, global: bool, pos: Pos) -> Result<(), SyntaxError> {
+        let (what, other) = if global { ("global", "nonlocal") } else { ("nonlocal", "global") };
+        let msg = if self.params.contains(name) {
+            format!("name '{}' is parameter and {}", name, what)
+        } else if (global && self.is_nonlocal(name)) || (!global && self.globals.contains(name)) {
+            format!("name '{}' is {} and {}", name, other, what)
+        } else if self.bound.contains(name) {
+            format!("name '{}' is assigned to before {} declaration", name, what)
+        } else if self.used.contains(name) {
+            format!("name '{}' is used prior to {} declaration", name, what)
+        } else {
+            if global {
+                self.globals.insert(name.clone());
+            } else if !self.is_nonlocal(name) {
+                self.nonlocals.push((name.clone(), pos));
+            }
+            return Ok(());
+        };
+        Err(SyntaxError::new(&msg, pos))
+    }
+
+    fn visit_program(&mut self, prog: &Program) -> Result<(), SyntaxError> {
+        for stmt in prog {
+            self.visit_stmt(stmt)?;
+        }
+        Ok(())
+    }
+
+    fn visit_loop_body(&mut self, prog: &Program) -> Result<(), SyntaxError> {
+        self.loop_depth += 1;
+        let res = self.visit_program(prog);
+        self.loop_depth -= 1;
+        res
+    }
+
+    fn visit_stmt(&mut self, stmt: &Stmt) -> Result<(), SyntaxError> {
+        match *stmt {
+            Stmt::StmtSimple(ref stmt, span) => self.visit_simple_stmt(stmt, span.start),
+            Stmt::StmtCompound(ref stmt, _) => self.visit_compound_stmt(stmt),
+        }
+    }
+
+    fn visit_simple_stmt(&mut self, stmt: &SimpleStmt, pos: Pos) -> Result<(), SyntaxError> {
+        match *stmt {
+            SimpleStmt::ExprStmt(ref expr) | SimpleStmt::AssertStmt(ref expr) => self.visit_expr(expr)?,
+            SimpleStmt::ReturnStmt(ref expr) => {
+                if self.kind != BlockKind::Function {
+                    return Err(SyntaxError::new("'return' outside function", pos));
+                }
+                self.visit_expr(expr)?;
+            },
+            SimpleStmt::AssignStmt(ref target, ref expr) => {
+                self.visit_expr(expr)?;
+                self.visit_target(target)?;
+            },
+            SimpleStmt::RaiseStmt(ref expr, ref cause) => {
+                for e in expr.iter().chain(cause.iter()) {
+                    self.visit_expr(e)?;
+                }
+            },
+            SimpleStmt::DelStmt(ref target) => self.visit_target(target)?,
+            SimpleStmt::GlobalStmt(ref names) => {
+                for name in names {
+                    self.declare(name, true, pos)?;
+                }
+            },
+            SimpleStmt::NonlocalStmt(ref names) => {
+                if self.kind == BlockKind::Module {
+                    return Err(SyntaxError::new("nonlocal declaration not allowed at module level", pos));
+                }
+                for name in names {
+                    self.declare(name, false, pos)?;
+                }
+            },
+            SimpleStmt::BreakStmt if self.loop_depth == 0 => {
+                return Err(SyntaxError::new("'break' outside loop", pos));
+            },
+            SimpleStmt::ContinueStmt if self.loop_depth == 0 => {
+                return Err(SyntaxError::new("'continue' not properly in loop", pos));
+            },
+            SimpleStmt::BreakStmt | SimpleStmt::ContinueStmt | SimpleStmt::PassStmt => {},
+        }
+        Ok(())
+    }
+
+    fn visit_compound_stmt(&mut self, stmt: &CompoundStmt) -> Result<(), SyntaxError> {
+        match *stmt {
+            CompoundStmt::IfStmt(ref expr, ref prog_then, ref prog_else) => {
+                self.visit_expr(expr)?;
+                self.visit_program(prog_then)?;
+                self.visit_program(prog_else)?;
+            },
+            CompoundStmt::WhileStmt(ref expr, ref prog) => {
+                self.visit_expr(expr)?;
+                self.visit_loop_body(prog)?;
+            },
+            CompoundStmt::ForStmt(ref target, ref expr, ref prog) => {
+                self.visit_expr(expr)?;
+                self.visit_target(target)?;
+                self.visit_loop_body(prog)?;
+            },
+            CompoundStmt::TryStmt(ref prog_try, ref handlers, ref prog_else, ref prog_finally) => {
+                self.visit_program(prog_try)?;
+                for handler in handlers {
+                    if let Some(ref typ) = handler.typ {
+                        self.visit_expr(typ)?;
+                    }
+                    if let Some(ref name) = handler.name {
+                        self.bind(name);
+                    }
+                    self.visit_program(&handler.body)?;
+                }
+                self.visit_program(prog_else)?;
+                if let Some(ref prog_finally) = *prog_finally {
+                    self.visit_program(prog_finally)?;
+                }
+            },
+            CompoundStmt::DefStmt(ref id, ref parms, ref prog) => {
+                let mut block = self.visit_parameters(parms)?;
+                block.visit_program(prog)?;
+                self.children.push(block);
+                self.bind(id);
+            },
+            CompoundStmt::ClassStmt(ref id, ref bases, ref prog) => {
+                for base in bases {
+                    self.visit_expr(base)?;
+                }
+                let mut block = Block::new(BlockKind::Class, block_key(prog), vec![]);
+                block.visit_program(prog)?;
+                self.children.push(block);
+                self.bind(id);
+            },
+        }
+        Ok(())
+    }
+
+    // The defaults are evaluated here, and the function gets a block of its own.
+    fn visit_parameters(&mut self, parms: &Parameters) -> Result<Block, SyntaxError> {
+        for default in &parms.defaults {
+            self.visit_expr(default)?;
+        }
+        for &(_, ref default) in &parms.kwonly {
+            if let Some(ref default) = *default {
+                self.visit_expr(default)?;
+            }
+        }
+        let mut params = parms.args.clone();
+        params.extend(parms.kwonly.iter().map(|&(ref arg, _)| arg.clone()));
+        params.extend(parms.vararg.iter().cloned());
+        params.extend(parms.kwarg.iter().cloned());
+        Ok(Block::new(BlockKind::Function, block_key(parms), params))
+    }
+
+    // Only functions can be generators. The code of a module or a class
+    // body, or a comprehension's hidden function, must run to its end.
+    fn check_yield(&self, expr: &Expr) -> Result<(), SyntaxError> {
+        let msg = match (self.kind, self.comprehension) {
+            (_, Some(what)) => format!("'yield' inside {}", what),
+            (BlockKind::Function, None) => return Ok(()),
+            _ => "'yield' outside function".to_string(),
+        };
+        Err(SyntaxError::new(&msg, expr.span.start))
+    }
+
+    fn bind(&mut self, name: &Id) {
+        self.bound.insert(name.clone());
+    }
+
+    fn visit_target(&mut self, target: &Target) -> Result<(), SyntaxError> {
+        match *target {
+            Target::IdentTarget(ref id) => self.bind(id),
+            Target::AttrTarget(ref expr, _) => self.visit_expr(expr)?,
+            Target::SubscrTarget(ref e1, ref e2) => {
+                self.visit_expr(e1)?;
+                self.visit_expr(e2)?;
+            },
+            Target::TupleTarget(ref targets) => {
+                for target in targets {
+                    self.visit_target(target)?;
+                }
+            },
+            Target::StarredTarget(ref target) => self.visit_target(target)?,
+        }
+        Ok(())
+    }
+
+    // Only the first iterable is evaluated outside the comprehension's block.
+    fn visit_comprehension(&mut self, elts: Vec<&Expr>, comps: &Vec<Comprehension>, what: &'static str)
+                           -> Result<(), SyntaxError> {
+        self.visit_expr(&comps[0].iter)?;
+        let mut block = Block::new(BlockKind::Function, block_key(comps), vec![".0".to_string()]);
+        block.comprehension = Some(what);
+        for (i, comp) in comps.iter().enumerate() {
+            if i > 0 {
+                block.visit_expr(&comp.iter)?;
+            }
+            block.visit_target(&comp.target)?;
+            for cond in &comp.ifs {
+                block.visit_expr(cond)?;
+            }
+        }
+        for elt in elts {
+            block.visit_expr(elt)?;
+        }
+        self.children.push(block);
+        Ok(())
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) -> Result<(), SyntaxError> {
+        match expr.kind {
+            ExprKind::VarExpr(ref id) => {
+                self.used.insert(id.clone());
+            },
+            ExprKind::IntExpr(_) | ExprKind::FloatExpr(_) | ExprKind::ImagExpr(_) |
+            ExprKind::BoolExpr(_) | ExprKind::StrExpr(_) | ExprKind::BytesExpr(_) |
+            ExprKind::NoneExpr => {},
+            ExprKind::FormattedValue(ref e, _, ref spec) => {
+                self.visit_expr(e)?;
+                if let Some(ref spec) = *spec {
+                    self.visit_expr(spec)?;
+                }
+            },
+            ExprKind::AddExpr(ref e1, ref e2) | ExprKind::SubExpr(ref e1, ref e2) |
+            ExprKind::MulExpr(ref e1, ref e2) | ExprKind::TrueDivExpr(ref e1, ref e2) |
+            ExprKind::FloorDivExpr(ref e1, ref e2) | ExprKind::ModExpr(ref e1, ref e2) |
+            ExprKind::PowExpr(ref e1, ref e2) | ExprKind::AndExpr(ref e1, ref e2) |
+            ExprKind::OrExpr(ref e1, ref e2) | ExprKind::SubscrExpr(ref e1, ref e2) => {
+                self.visit_expr(e1)?;
+                self.visit_expr(e2)?;
+            },
+            ExprKind::NegExpr(ref e) | ExprKind::PosExpr(ref e) | ExprKind::NotExpr(ref e) |
+            ExprKind::AttrExpr(ref e, _) | ExprKind::StarredExpr(ref e) => self.visit_expr(e)?,
+            ExprKind::CompareExpr(ref e, ref rest) => {
+                self.visit_expr(e)?;
+                for &(_, ref e) in rest {
+                    self.visit_expr(e)?;
+                }
+            },
+            ExprKind::CondExpr(ref cond, ref e1, ref e2) => {
+                self.visit_expr(cond)?;
+                self.visit_expr(e1)?;
+                self.visit_expr(e2)?;
+            },
+            ExprKind::LambdaExpr(ref parms, ref body) => {
+                let mut block = self.visit_parameters(parms)?;
+                block.visit_expr(body)?;
+                self.children.push(block);
+            },
+            ExprKind::CallExpr(ref fun, ref args, ref keywords) => {
+                self.visit_expr(fun)?;
+                for arg in args {
+                    self.visit_expr(arg)?;
+                }
+                for kw in keywords {
+                    self.visit_expr(&kw.value)?;
+                }
+            },
+            ExprKind::SliceExpr(ref lower, ref upper, ref step) => {
+                for e in lower.iter().chain(upper.iter()).chain(step.iter()) {
+                    self.visit_expr(e)?;
+                }
+            },
+            ExprKind::JoinedStr(ref elts) | ExprKind::ListExpr(ref elts) |
+            ExprKind::TupleExpr(ref elts) | ExprKind::SetExpr(ref elts) => {
+                for elt in elts {
+                    self.visit_expr(elt)?;
+                }
+            },
+            ExprKind::DictExpr(ref pl) => {
+                for &(ref key, ref value) in pl {
+                    self.visit_expr(key)?;
+                    self.visit_expr(value)?;
+                }
+            },
+            ExprKind::ListComp(ref elt, ref comps) => {
+                self.visit_comprehension(vec![elt], comps, "list comprehension")?
+            },
+            ExprKind::SetComp(ref elt, ref comps) => {
+                self.visit_comprehension(vec![elt], comps, "set comprehension")?
+            },
+            ExprKind::GeneratorExp(ref elt, ref comps) => {
+                self.visit_comprehension(vec![elt], comps, "generator expression")?
+            },
+            ExprKind::DictComp(ref key, ref value, ref comps) => {
+                self.visit_comprehension(vec![key, value], comps, "dict comprehension")?
+            },
+            ExprKind::YieldExpr(ref value) => {
+                self.check_yield(expr)?;
+                if let Some(ref value) = *value {
+                    self.visit_expr(value)?;
+                }
+            },
+            ExprKind::YieldFrom(ref e) => {
+                self.check_yield(expr)?;
+                self.visit_expr(e)?;
+            },
+        }
+        Ok(())
+    }
+
+    // Resolves the names of the block, given the names bound in the
+    // functions around it, and gives back its table along with the names it
+    // needs from those functions.
+    fn analyze(self, enclosing: &HashSet<Id>) -> Result<(Rc<SymbolTable>, HashSet<Id>), SyntaxError> {
+        let mut symbols = HashMap::new();
+        for name in &self.globals {
+            symbols.insert(name.clone(), Scope::GlobalExplicit);
+        }
+        for &(ref name, pos) in &self.nonlocals {
+            if !enclosing.contains(name) {
+                return Err(SyntaxError::new(&format!("no binding for nonlocal '{}' found", name), pos));
+            }
+            symbols.insert(name.clone(), Scope::Free);
+        }
+        for name in &self.bound {
+            symbols.entry(name.clone()).or_insert(Scope::Local);
+        }
+        for name in &self.used {
+            let scope = if self.kind != BlockKind::Module && enclosing.contains(name) {
+                Scope::Free
+            } else {
+                Scope::GlobalImplicit
+            };
+            symbols.entry(name.clone()).or_insert(scope);
+        }
+
+        let mut child_enclosing: HashSet<Id> = match self.kind {
+            BlockKind::Module => HashSet::new(),
+            _ => enclosing.difference(&self.globals).cloned().collect(),
+        };
+        if self.kind == BlockKind::Function {
+            for (name, scope) in &symbols {
+                if *scope == Scope::Local {
+                    child_enclosing.insert(name.clone());
+                }
+            }
+        }
+
+        let mut children = HashMap::new();
+        let mut needed = HashSet::new();
+        for child in self.children {
+            let key = child.key;
+            let (table, free) = child.analyze(&child_enclosing)?;
+            children.insert(key, table);
+            needed.extend(free);
+        }
+
+        // a class passes the names its methods need through, even the ones
+        // it binds itself
+        let mut free: HashSet<Id> = HashSet::new();
+        for name in needed {
+            match symbols.get(&name).cloned() {
+                Some(Scope::Local) if self.kind == BlockKind::Function => {
+                    symbols.insert(name, Scope::Cell);
+                },
+                Some(Scope::Local) => {
+                    free.insert(name);
+                },
+                None => {
+                    symbols.insert(name, Scope::Free);
+                },
+                _ => {},
+            }
+        }
+        free.extend(symbols.iter().filter(|&(_, scope)| *scope == Scope::Free).map(|(name, _)| name.clone()));
+
+        let mut cellvars: Vec<Id> = symbols.iter().filter(|&(_, scope)| *scope == Scope::Cell)
+            .map(|(name, _)| name.clone()).collect();
+        cellvars.sort();
+        let mut freevars: Vec<Id> = free.iter().cloned().collect();
+        freevars.sort();
+        let table = SymbolTable {
+            kind: self.kind,
+            symbols: symbols,
+            cellvars: cellvars,
+            freevars: freevars,
+            children: children,
+        };
+        Ok((Rc::new(table), free))
+    }
+}
+
+/// Builds the symbol table of a module, which holds those of the blocks
+/// nested in it.
+pub fn symtable(prog: &Program) -> Result<Rc<SymbolTable>, SyntaxError> {
+    let mut block = Block::new(BlockKind::Module, block_key(prog), vec![]);
+    block.visit_program(prog)?;
+    let (table, _) = block.analyze(&HashSet::new())?;
+    Ok(table)
+}
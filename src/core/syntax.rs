@@ -1,48 +1,253 @@
+use object::bigint::BigInt;
+
 pub type Id = String;
+pub type Line = usize;
+
+/// A source position. Lines and columns both count from 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pos {
+    pub line: Line,
+    pub col: usize,
+}
+
+/// The source range of a token or node, `end` being just past its last
+/// character.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Span {
+    pub start: Pos,
+    pub end: Pos,
+}
+
+impl Span {
+    pub fn new(start: Pos, end: Pos) -> Span {
+        Span { start: start, end: end }
+    }
+}
+
+/// The exception type a `SyntaxError` is raised as.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SyntaxErrorKind {
+    Syntax,
+    Indentation,
+    Tab,
+}
+
+/// An error found while tokenizing or parsing. `expected` and `found`
+/// describe the tokens involved when the error is a token mismatch.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SyntaxError {
+    pub kind: SyntaxErrorKind,
+    pub msg: String,
+    pub pos: Pos,
+    pub expected: Option<String>,
+    pub found: Option<String>,
+}
+
+impl SyntaxError {
+    pub fn new(msg: &str, pos: Pos) -> SyntaxError {
+        SyntaxError { kind: SyntaxErrorKind::Syntax, msg: msg.to_string(), pos: pos,
+                      expected: None, found: None }
+    }
+
+    pub fn indentation(msg: &str, pos: Pos) -> SyntaxError {
+        SyntaxError { kind: SyntaxErrorKind::Indentation, ..SyntaxError::new(msg, pos) }
+    }
+
+    pub fn tab(msg: &str, pos: Pos) -> SyntaxError {
+        SyntaxError { kind: SyntaxErrorKind::Tab, ..SyntaxError::new(msg, pos) }
+    }
+
+    pub fn mismatch(expected: &str, found: &str, pos: Pos) -> SyntaxError {
+        SyntaxError {
+            kind: SyntaxErrorKind::Syntax,
+            msg: format!("expected {}, found {}", expected, found),
+            pos: pos,
+            expected: Some(expected.to_string()),
+            found: Some(found.to_string()),
+        }
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self.kind {
+            SyntaxErrorKind::Syntax => "SyntaxError",
+            SyntaxErrorKind::Indentation => "IndentationError",
+            SyntaxErrorKind::Tab => "TabError",
+        }
+    }
+
+    /// Formats the error the way CPython reports one in a file, pointing at
+    /// the column in the quoted source line.
+    pub fn format(&self, filename: &str, source: &str) -> String {
+        let mut res = format!("  File \"{}\", line {}\n", filename, self.pos.line);
+        if let Some(line) = self.pos.line.checked_sub(1).and_then(|i| source.lines().nth(i)) {
+            let indent = line.len() - line.trim_start().len();
+            res.push_str(&format!("    {}\n", line.trim()));
+            let col = self.pos.col.saturating_sub(indent + 1);
+            res.push_str(&format!("    {}^\n", " ".repeat(col)));
+        }
+        res.push_str(&format!("{}: {}\n", self.type_name(), self.msg));
+        res
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CompOp {
+    Lt,
+    LtE,
+    Gt,
+    GtE,
+    EqEq,
+    NotEq,
+    In,
+    NotIn,
+    Is,
+    IsNot,
+}
+
+/// The conversion of a replacement field, `!s`, `!r` or `!a`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Conversion {
+    Str,
+    Repr,
+    Ascii,
+}
 
 #[derive(Clone)]
-pub enum Expr {
+pub struct Expr {
+    pub kind: ExprKind,
+    pub span: Span,
+}
+
+#[derive(Clone)]
+pub enum ExprKind {
     VarExpr(Id),
-    IntExpr(i32),
+    IntExpr(BigInt),
+    FloatExpr(f64),
+    ImagExpr(f64),
     BoolExpr(bool),
     StrExpr(String),
+    BytesExpr(Vec<u8>),
+    JoinedStr(Vec<Expr>),
+    FormattedValue(Box<Expr>, Option<Conversion>, Option<Box<Expr>>),
     NoneExpr,
     AddExpr(Box<Expr>, Box<Expr>),
-    LtExpr(Box<Expr>, Box<Expr>),
-    EqEqExpr(Box<Expr>, Box<Expr>),
-    CallExpr(Box<Expr>, Vec<Expr>),
+    SubExpr(Box<Expr>, Box<Expr>),
+    MulExpr(Box<Expr>, Box<Expr>),
+    TrueDivExpr(Box<Expr>, Box<Expr>),
+    FloorDivExpr(Box<Expr>, Box<Expr>),
+    ModExpr(Box<Expr>, Box<Expr>),
+    PowExpr(Box<Expr>, Box<Expr>),
+    NegExpr(Box<Expr>),
+    PosExpr(Box<Expr>),
+    CompareExpr(Box<Expr>, Vec<(CompOp, Expr)>),
+    AndExpr(Box<Expr>, Box<Expr>),
+    OrExpr(Box<Expr>, Box<Expr>),
+    NotExpr(Box<Expr>),
+    /// `e1 if cond else e2`, as `(cond, e1, e2)`
+    CondExpr(Box<Expr>, Box<Expr>, Box<Expr>),
+    LambdaExpr(Box<Parameters>, Box<Expr>),
+    CallExpr(Box<Expr>, Vec<Expr>, Vec<Keyword>),
     AttrExpr(Box<Expr>, Id),
     SubscrExpr(Box<Expr>, Box<Expr>),
+    SliceExpr(Option<Box<Expr>>, Option<Box<Expr>>, Option<Box<Expr>>),
     ListExpr(Vec<Expr>),
-    DictExpr(Vec<(Expr, Expr)>)
+    TupleExpr(Vec<Expr>),
+    DictExpr(Vec<(Expr, Expr)>),
+    SetExpr(Vec<Expr>),
+    ListComp(Box<Expr>, Vec<Comprehension>),
+    SetComp(Box<Expr>, Vec<Comprehension>),
+    DictComp(Box<Expr>, Box<Expr>, Vec<Comprehension>),
+    GeneratorExp(Box<Expr>, Vec<Comprehension>),
+    YieldExpr(Option<Box<Expr>>),
+    YieldFrom(Box<Expr>),
+    StarredExpr(Box<Expr>),
 }
 
 impl Expr {
     pub fn to_string(&self) -> String {
-        let str = match self {
-            &Expr::VarExpr(_) => "VarExpr",
-            &Expr::IntExpr(_) => "IntExpr",
-            &Expr::BoolExpr(_) => "BoolExpr",
-            &Expr::StrExpr(_) => "StrExpr",
-            &Expr::NoneExpr => "NoneExpr",
-            &Expr::AddExpr(_, _) => "AddExpr",
-            &Expr::LtExpr(_, _) => "LtExpr",
-            &Expr::EqEqExpr(_, _) => "EqEqExpr",
-            &Expr::CallExpr(_, _) => "CallExpr",
-            &Expr::AttrExpr(_, _) => "AttrExpr",
-            &Expr::SubscrExpr(_, _) => "SubscrExpr",
-            &Expr::ListExpr(_) => "ListExpr",
-            &Expr::DictExpr(_) => "DictExpr",
+        let str = match self.kind {
+            ExprKind::VarExpr(_) => "VarExpr",
+            ExprKind::IntExpr(_) => "IntExpr",
+            ExprKind::FloatExpr(_) => "FloatExpr",
+            ExprKind::ImagExpr(_) => "ImagExpr",
+            ExprKind::BoolExpr(_) => "BoolExpr",
+            ExprKind::StrExpr(_) => "StrExpr",
+            ExprKind::BytesExpr(_) => "BytesExpr",
+            ExprKind::JoinedStr(_) => "JoinedStr",
+            ExprKind::FormattedValue(_, _, _) => "FormattedValue",
+            ExprKind::NoneExpr => "NoneExpr",
+            ExprKind::AddExpr(_, _) => "AddExpr",
+            ExprKind::SubExpr(_, _) => "SubExpr",
+            ExprKind::MulExpr(_, _) => "MulExpr",
+            ExprKind::TrueDivExpr(_, _) => "TrueDivExpr",
+            ExprKind::FloorDivExpr(_, _) => "FloorDivExpr",
+            ExprKind::ModExpr(_, _) => "ModExpr",
+            ExprKind::PowExpr(_, _) => "PowExpr",
+            ExprKind::NegExpr(_) => "NegExpr",
+            ExprKind::PosExpr(_) => "PosExpr",
+            ExprKind::CompareExpr(_, _) => "CompareExpr",
+            ExprKind::AndExpr(_, _) => "AndExpr",
+            ExprKind::OrExpr(_, _) => "OrExpr",
+            ExprKind::NotExpr(_) => "NotExpr",
+            ExprKind::CondExpr(_, _, _) => "CondExpr",
+            ExprKind::LambdaExpr(_, _) => "LambdaExpr",
+            ExprKind::CallExpr(_, _, _) => "CallExpr",
+            ExprKind::AttrExpr(_, _) => "AttrExpr",
+            ExprKind::SubscrExpr(_, _) => "SubscrExpr",
+            ExprKind::SliceExpr(_, _, _) => "SliceExpr",
+            ExprKind::ListExpr(_) => "ListExpr",
+            ExprKind::TupleExpr(_) => "TupleExpr",
+            ExprKind::DictExpr(_) => "DictExpr",
+            ExprKind::SetExpr(_) => "SetExpr",
+            ExprKind::ListComp(_, _) => "ListComp",
+            ExprKind::SetComp(_, _) => "SetComp",
+            ExprKind::DictComp(_, _, _) => "DictComp",
+            ExprKind::GeneratorExp(_, _) => "GeneratorExp",
+            ExprKind::YieldExpr(_) => "YieldExpr",
+            ExprKind::YieldFrom(_) => "YieldFrom",
+            ExprKind::StarredExpr(_) => "StarredExpr",
         };
         str.to_string()
     }
 }
 
+/// A `for target in iter` clause of a comprehension with the `if`
+/// conditions that follow it.
+#[derive(Clone)]
+pub struct Comprehension {
+    pub target: Target,
+    pub iter: Expr,
+    pub ifs: Vec<Expr>,
+}
+
+/// A keyword argument of a call, or `**mapping` when `arg` is `None`.
+#[derive(Clone)]
+pub struct Keyword {
+    pub arg: Option<Id>,
+    pub value: Expr,
+}
+
+/// The parameters of a `def` or a `lambda`. `defaults` belong to the last of `args`, and
+/// the first `posonly` of `args` are positional-only.
+#[derive(Clone, Default)]
+pub struct Parameters {
+    pub args: Vec<Id>,
+    pub posonly: usize,
+    pub defaults: Vec<Expr>,
+    pub vararg: Option<Id>,
+    pub kwonly: Vec<(Id, Option<Expr>)>,
+    pub kwarg: Option<Id>,
+}
+
+/// An assignment target. A tuple or list of targets unpacks an iterable,
+/// with at most one starred target taking the items left over.
 #[derive(Clone)]
 pub enum Target {
     IdentTarget(Id),
     AttrTarget(Box<Expr>, Id),
-    SubscrTarget(Box<Expr>, Box<Expr>)
+    SubscrTarget(Box<Expr>, Box<Expr>),
+    TupleTarget(Vec<Target>),
+    StarredTarget(Box<Target>),
 }
 
 #[derive(Clone)]
@@ -51,9 +256,13 @@ pub enum SimpleStmt {
     AssignStmt(Target, Expr),
     BreakStmt,
     ContinueStmt,
-    RaiseStmt(Expr),
+    PassStmt,
+    RaiseStmt(Option<Expr>, Option<Expr>),
     ReturnStmt(Expr),
     AssertStmt(Expr),
+    DelStmt(Target),
+    GlobalStmt(Vec<Id>),
+    NonlocalStmt(Vec<Id>),
 }
 
 #[derive(Clone)]
@@ -61,15 +270,23 @@ pub enum CompoundStmt {
     IfStmt(Expr, Program, Program),
     WhileStmt(Expr, Program),
     ForStmt(Target, Expr, Program),
-    TryStmt(Program, Program),
-    DefStmt(Id, Vec<Id>, Program),
+    TryStmt(Program, Vec<ExceptHandler>, Program, Option<Program>),
+    DefStmt(Id, Parameters, Program),
     ClassStmt(Id, Vec<Expr>, Program)
 }
 
+#[derive(Clone)]
+pub struct ExceptHandler {
+    pub typ: Option<Expr>,
+    pub name: Option<Id>,
+    pub body: Program,
+    pub span: Span,
+}
+
 #[derive(Clone)]
 pub enum Stmt {
-    StmtSimple(SimpleStmt),
-    StmtCompound(CompoundStmt)
+    StmtSimple(SimpleStmt, Span),
+    StmtCompound(CompoundStmt, Span)
 }
 
 pub type Program = Vec<Stmt>;
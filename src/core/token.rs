@@ -1,29 +1,61 @@
+use std::fmt;
+use object::bigint::BigInt;
+use syntax::Pos;
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum Token {
     Ident(String),
-    Int(i32),
+    Int(BigInt),
+    Float(f64),
+    Imag(f64),
     Str(String),
+    Bytes(Vec<u8>),
+    FStr(Vec<FStrPiece>),
     None,
     True,
     False,
     If,
+    Elif,
     Else,
     While,
     For,
     In,
+    And,
+    Or,
+    Not,
+    Is,
     Break,
     Continue,
+    Pass,
     Try,
     Except,
+    Finally,
+    As,
     Raise,
+    From,
     Def,
     Return,
     Assert,
+    Del,
+    Global,
+    Nonlocal,
     Class,
+    Lambda,
+    Yield,
     Plus,
+    Minus,
+    Star,
+    DoubleStar,
+    Slash,
+    DoubleSlash,
+    Percent,
     Eq,
     EqEq,
+    NotEq,
     Lt,
+    LtE,
+    Gt,
+    GtE,
     LParen,
     RParen,
     LBracket,
@@ -38,3 +70,82 @@ pub enum Token {
     Dedent,
     EOF,
 }
+
+/// A piece of an f-string. The expression of a replacement field is kept
+/// as source, along with where it starts.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FStrPiece {
+    Lit(String),
+    Field { expr: String, pos: Pos, conversion: Option<char>, spec: Option<Vec<FStrPiece>> },
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match *self {
+            Token::Ident(ref s) => return write!(f, "name '{}'", s),
+            Token::Int(_) | Token::Float(_) | Token::Imag(_) => "number",
+            Token::Str(_) => "string",
+            Token::Bytes(_) => "bytes",
+            Token::FStr(_) => "f-string",
+            Token::None => "'None'",
+            Token::True => "'True'",
+            Token::False => "'False'",
+            Token::If => "'if'",
+            Token::Elif => "'elif'",
+            Token::Else => "'else'",
+            Token::While => "'while'",
+            Token::For => "'for'",
+            Token::In => "'in'",
+            Token::And => "'and'",
+            Token::Or => "'or'",
+            Token::Not => "'not'",
+            Token::Is => "'is'",
+            Token::Break => "'break'",
+            Token::Continue => "'continue'",
+            Token::Pass => "'pass'",
+            Token::Try => "'try'",
+            Token::Except => "'except'",
+            Token::Finally => "'finally'",
+            Token::As => "'as'",
+            Token::Raise => "'raise'",
+            Token::From => "'from'",
+            Token::Def => "'def'",
+            Token::Return => "'return'",
+            Token::Assert => "'assert'",
+            Token::Del => "'del'",
+            Token::Global => "'global'",
+            Token::Nonlocal => "'nonlocal'",
+            Token::Class => "'class'",
+            Token::Lambda => "'lambda'",
+            Token::Yield => "'yield'",
+            Token::Plus => "'+'",
+            Token::Minus => "'-'",
+            Token::Star => "'*'",
+            Token::DoubleStar => "'**'",
+            Token::Slash => "'/'",
+            Token::DoubleSlash => "'//'",
+            Token::Percent => "'%'",
+            Token::Eq => "'='",
+            Token::EqEq => "'=='",
+            Token::NotEq => "'!='",
+            Token::Lt => "'<'",
+            Token::LtE => "'<='",
+            Token::Gt => "'>'",
+            Token::GtE => "'>='",
+            Token::LParen => "'('",
+            Token::RParen => "')'",
+            Token::LBracket => "'['",
+            Token::RBracket => "']'",
+            Token::LBrace => "'{'",
+            Token::RBrace => "'}'",
+            Token::Colon => "':'",
+            Token::Comma => "','",
+            Token::Dot => "'.'",
+            Token::NewLine => "newline",
+            Token::Indent => "indent",
+            Token::Dedent => "dedent",
+            Token::EOF => "end of file",
+        };
+        write!(f, "{}", s)
+    }
+}
@@ -2,35 +2,78 @@ use std::fs::File;
 use std::io::{BufReader};
 use std::io::prelude::*;
 use std::rc::Rc;
+use std::thread;
 
 use opcode::*;
 use lexer::*;
 use parser::*;
 use compile::*;
+use symtable::symtable;
 use env::Env;
 use eval::*;
 use error::*;
 use builtinmodule::*;
+use object::PyObject;
+use object::codeobj::ArgSpec;
+use syntax::SyntaxError;
 
-pub fn run_prog_string(prog: String) {
-    match tokenize(prog) {
-        Ok(tokens) => {
-            let ast = tokens.into_iter().peekable().parse();
-            let code = compile(&ast);
-            // print_code(&code);
-            let env = Rc::new(Env::new());
-            pyerr_clear();
-            load_builtins(Rc::clone(&env));
-            eval(&code, env);
+/// Compiles a whole source file into the code object of its module.
+pub fn compile_source(prog: &str, filename: &str) -> Result<Rc<PyObject>, Vec<SyntaxError>> {
+    let tokens = tokenize(prog.to_string()).map_err(|err| vec![err])?;
+    let ast = Tokens::new(tokens.into_iter()).parse_all()?;
+    let symtable = symtable(&ast).map_err(|err| vec![err])?;
+    let code = compile(&ast, filename, symtable);
+    // print_code(&code);
+    Ok(PyObject::pycode_new(code, ArgSpec::default(), vec![], vec![], "<module>", filename, 1))
+}
+
+fn run_source(prog: String, filename: &str) {
+    let env = Rc::new(Env::new());
+    pyerr_clear();
+    if load_builtins(Rc::clone(&env)).is_err() {
+        pyerr_print(filename, &prog);
+        return;
+    }
+    match compile_source(&prog, filename) {
+        Ok(codeobj) => {
+            eval(&codeobj, env);
+            if pyerr_occurred() {
+                pyerr_print(filename, &prog);
+            }
+        },
+        Err(errors) => {
+            for err in &errors {
+                eprint!("{}", err.format(filename, &prog));
+            }
+            pyerr_set_syntax_error(&errors[0], filename, &prog);
         },
-        Err(err) => panic!(err.to_string()),
     };
 }
 
+pub fn run_prog_string(prog: String) {
+    run_source(prog, "<string>");
+}
+
 pub fn run(path: &str) {
     let file = File::open(path).unwrap();
     let mut buf_reader = BufReader::new(file);
     let mut prog = String::new();
     buf_reader.read_to_string(&mut prog).expect("Error: read_to_string");
-    run_prog_string(prog);
+    run_source(prog, path);
+}
+
+// every Python call nests eval, so the recursion limit needs a deep stack
+const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+/// Runs `f` on a thread whose stack can hold the full recursion limit.
+/// The interpreter state is thread local, so `f` should also inspect the
+/// result it needs before returning.
+pub fn with_interpreter_stack<F, T>(f: F) -> T
+where F: FnOnce() -> T + Send + 'static, T: Send + 'static {
+    thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(f)
+        .expect("Error: spawn")
+        .join()
+        .expect("Error: join")
 }
@@ -1,5 +1,5 @@
 extern crate core;
 
 fn main() {
-    core::utils::run("test.py");
+    core::utils::with_interpreter_stack(|| core::utils::run("test.py"));
 }
@@ -1,6 +1,9 @@
 extern crate core;
 
 use core::error::pyerr_occurred;
+use core::lexer::tokenize;
+use core::parser::{TokenStream, Tokens};
+use core::syntax::SyntaxErrorKind;
 use core::utils::*;
 
 macro_rules! test_cases {
@@ -8,8 +11,11 @@ macro_rules! test_cases {
         $(
             #[test]
             fn $i() {
-                run(&["tests/tests/", stringify!($i), ".py"].join(""));
-                assert!(!pyerr_occurred())
+                let occurred = with_interpreter_stack(|| {
+                    run(&["tests/tests/", stringify!($i), ".py"].join(""));
+                    pyerr_occurred()
+                });
+                assert!(!occurred)
             }
         )*
     }
@@ -27,18 +33,59 @@ fn assert_false() {
     assert!(pyerr_occurred())
 }
 
+#[test]
+fn parse_errors_recover() {
+    let prog = "x = = 1\ny = 2\nif y:\n    z = * 3\n    z = 4\nelse:\n    z = 3\nw = 1 +\n";
+    let tokens = tokenize(prog.to_string()).ok().expect("tokenize");
+    let errors = Tokens::new(tokens.into_iter()).parse_all().err().expect("parse_all");
+    let positions: Vec<_> = errors.iter().map(|err| (err.pos.line, err.pos.col)).collect();
+    assert_eq!(positions, vec![(1, 5), (4, 9), (8, 8)]);
+}
+
+#[test]
+fn crlf_line_endings() {
+    run_prog_string("x = 1\r\nif x == 1:\r\n    x = 2\r\nassert x == 2\r\n".to_string());
+    assert!(!pyerr_occurred())
+}
+
+#[test]
+fn indentation_errors() {
+    let kind = |prog: &str| tokenize(prog.to_string()).err().map(|err| err.kind);
+    assert_eq!(kind("if x:\n        y\n    z\n"), Some(SyntaxErrorKind::Indentation));
+    assert_eq!(kind("if x:\n\ty\n        z\n"), Some(SyntaxErrorKind::Tab));
+    assert_eq!(kind("if x:\n    \ty\n\tz\n"), Some(SyntaxErrorKind::Tab));
+    assert_eq!(kind("if x:\n\ty\n\tz\n"), None);
+    assert_eq!(kind("x = (1,\n"), Some(SyntaxErrorKind::Syntax));
+}
+
+#[test]
+fn syntax_error_raised() {
+    let occurred = with_interpreter_stack(|| {
+        run_prog_string("x = 1\ny = ]\n".to_string());
+        pyerr_occurred()
+    });
+    assert!(occurred)
+}
+
 test_cases![
     blank_lines, parse_string, consecutive_call, if_false, if_true,
     while_normal, while_continue, while_break,
     def, def_argument, def_recursive, def_internal, def_ho, def_lexical_scope,
     class_var, class_instance_var, class_method, assign_attr, class_update, class_init,
-    dict_basic,
+    dict_basic, dict_hash, dict_methods,
     string_add, meta_add, inherit_meta_add, meta_add_lazy,
     list_basic, list_append,
     builtin_len,
     inheritance_simple, inheritance_complex,
-    bool_arith,
+    bool_arith, arith_basic, arith_precedence, meta_arith,
+    compare_ops, compare_chain, meta_compare_reflected,
+    bool_ops, meta_bool, membership, identity, meta_contains,
+    float_basic, float_repr, float_ctor, long_big, long_hash,
     type_call,
     for_stmt,
-    try_catch_basic, try_catch_loop, try_catch_fun, catch_type_error
+    try_catch_basic, try_catch_loop, try_catch_fun, catch_type_error, exc_taxonomy,
+    try_typed, try_finally, try_reraise, exc_chain,
+    code_location, syntax_error, if_elif, lexer_layout, string_literals,
+    fstring_format, numeric_literals, tuple_unpack, slicing, func_args,
+    lambda_cond, comprehensions, generators, scoping
 ];
@@ -0,0 +1,18 @@
+assert 7 - 3 == 4
+assert 6 * 7 == 42
+assert 7 // 2 == 3
+assert -7 // 2 == -4
+assert 7 % 3 == 1
+assert -7 % 3 == 2
+assert 7 % -3 == -2
+assert 2 ** 10 == 1024
+assert -5 + 2 == -3
+assert +5 == 5
+assert --5 == 5
+
+x = 1
+try:
+    1 // 0
+except:
+    x = 2
+assert x == 2
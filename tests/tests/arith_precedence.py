@@ -0,0 +1,8 @@
+assert 1 + 2 * 3 == 7
+assert (1 + 2) * 3 == 9
+assert 10 - 4 - 3 == 3
+assert 100 // 10 // 5 == 2
+assert 2 ** 3 ** 2 == 512
+assert -2 ** 2 == -4
+assert 2 * -3 == -6
+assert 1 + 7 % 4 * 2 == 7
@@ -0,0 +1,26 @@
+assert (1 and 2) == 2
+assert (0 and 2) == 0
+assert (0 or 3) == 3
+assert (4 or 3) == 4
+assert ("" or "x") == "x"
+assert not 0
+assert not None
+assert not []
+assert (not 5) == False
+assert not 1 == 2
+assert 1 < 2 and 2 < 3
+assert 1 > 2 or 2 < 3
+assert not (1 > 2 or 3 > 4)
+assert (None or 0 or "last") == "last"
+assert (True and 0 or 7) == 7
+
+class Counter:
+    n = 0
+
+def f(x):
+    Counter.n = Counter.n + 1
+    return x
+
+x = f(0) and f(1)
+x = f(1) or f(2)
+assert Counter.n == 2
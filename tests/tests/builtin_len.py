@@ -3,3 +3,27 @@ x2 = [1, 2, 3]
 x3 = {"abc": 1}
 
 assert len(x1) + len(x2) + len(x3) == 4
+
+class Sized:
+    def __init__(self, n):
+        self.n = n
+
+    def __len__(self):
+        return self.n
+
+def len_error(obj):
+    try:
+        len(obj)
+    except ValueError as err:
+        return "ValueError: " + err.args[0]
+    except TypeError as err:
+        return "TypeError: " + err.args[0]
+    except OverflowError as err:
+        return "OverflowError: " + err.args[0]
+    return None
+
+assert len(Sized(3)) == 3 and len(Sized(True)) == 1
+assert len_error(Sized(-1)) == "ValueError: __len__() should return >= 0"
+assert len_error(Sized("3")) == "TypeError: 'str' object cannot be interpreted as an integer"
+assert len_error(Sized(2 ** 64)) == "OverflowError: cannot fit 'int' into an index-sized integer"
+assert len_error(5) == "TypeError: object of type 'int' has no len()"
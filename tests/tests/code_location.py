@@ -0,0 +1,29 @@
+def outer():
+    def inner(x):
+        return x
+    return inner
+
+f = outer()
+assert f.__name__ == "inner"
+assert f.__code__.co_name == "inner"
+assert f.__code__.co_firstlineno == 2
+assert outer.__code__.co_firstlineno == 1
+assert outer.__code__.co_filename == "tests/tests/code_location.py"
+
+class Point:
+    def norm(self):
+        return self.x * self.x + self.y * self.y
+
+assert Point.norm.__code__.co_firstlineno == 14
+
+def fail(d):
+    return d["missing"]
+
+tb = 0
+try:
+    fail({})
+except KeyError as e:
+    tb = e.__traceback__
+assert tb.tb_lineno == 24
+assert tb.tb_next.tb_lineno == 20
+assert tb.tb_next.tb_next is None
@@ -0,0 +1,16 @@
+class Counter:
+    n = 0
+
+def f(x):
+    Counter.n = Counter.n + 1
+    return x
+
+assert 1 < 2 < 3
+assert (1 < 3 < 2) == False
+assert 1 < 2 <= 2 != 3 > 0
+
+assert (f(3) < f(1) < f(2)) == False
+assert Counter.n == 2
+
+assert f(1) < f(2) < f(3)
+assert Counter.n == 5
@@ -0,0 +1,41 @@
+assert 1 != 2
+assert 2 <= 2
+assert 1 <= 2
+assert 3 > 2
+assert 3 >= 3
+assert "abc" < "abd"
+assert "b" >= "a"
+assert "a" != 1
+assert (1 == "a") == False
+
+x = 1
+try:
+    1 < "a"
+except:
+    x = 2
+assert x == 2
+
+# lists compare item by item, like tuples
+assert [1, 2] == [1, 2] and [1, 2] != [1, 3] and not ([1] == [1, 2])
+assert [1, 2] < [1, 3] and [1, 2] < [1, 2, 0] and [2] > [1, 5] and [1] <= [1] and [3] >= [2, 9]
+assert [1.0, [2]] == [1, [2]] and [] == [] and [1] != (1,)
+assert [1] in [[1]] and not ([2] in [[1]])
+nan = float("nan")
+assert [nan] == [nan] and not ([float("nan")] == [float("nan")])
+x = 1
+try:
+    [1] < ["a"]
+except TypeError:
+    x = 2
+assert x == 2
+
+# dicts are equal when they map the same keys to equal values
+assert {1: "a", 2: "b"} == {2: "b", 1: "a"} and {} == {}
+assert {1: "a"} != {1: "b"} and {1: "a"} != {2: "a"} and {1: "a"} != {1: "a", 2: "b"}
+assert {1: [1.0]} == {1.0: [1]} and {1: 2} != [1]
+x = 1
+try:
+    {} < {}
+except TypeError:
+    x = 2
+assert x == 2
@@ -0,0 +1,108 @@
+# list comprehensions
+xs = [1, 2, 3, 4, 5, 6]
+assert repr([x * x for x in xs]) == "[1, 4, 9, 16, 25, 36]"
+assert repr([x for x in xs if x % 2 == 0]) == "[2, 4, 6]"
+assert repr([x for x in xs if x > 1 if x < 4]) == "[2, 3]"
+assert repr([(x, y) for x in [1, 2] for y in ["a", "b"]]) == "[(1, 'a'), (1, 'b'), (2, 'a'), (2, 'b')]"
+assert repr([y for x in [[1, 2], [3]] for y in x]) == "[1, 2, 3]"
+assert repr([[x * y for y in [1, 2]] for x in [1, 10]]) == "[[1, 2], [10, 20]]"
+assert repr([a + b for a, b in [(1, 2), (3, 4)]]) == "[3, 7]"
+assert len([x for x in []]) == 0
+assert repr([x if x > 2 else 0 for x in xs[:4]]) == "[0, 0, 3, 4]"
+
+# the loop variables do not leak into the enclosing scope
+x = "outer"
+squares = [x * x for x in xs]
+assert x == "outer"
+def no_leak():
+    [leaked for leaked in [1, 2]]
+    try:
+        leaked
+    except NameError:
+        return True
+    return False
+assert no_leak()
+
+# only the first iterable is evaluated in the enclosing scope; the rest
+# can refer to the loop variables
+def pairs(n):
+    return [(i, j) for i in range(n) for j in range(i)]
+class range:
+    def __init__(self, n):
+        self.n = n
+        self.i = 0
+    def __iter__(self):
+        return self
+    def __next__(self):
+        if self.i >= self.n:
+            raise StopIteration
+        self.i = self.i + 1
+        return self.i - 1
+assert repr(pairs(3)) == "[(1, 0), (2, 0), (2, 1)]"
+
+# closures see the enclosing function's names
+def scale(k):
+    return [k * v for v in [1, 2, 3]]
+assert repr(scale(3)) == "[3, 6, 9]"
+
+# set comprehensions and displays
+s = {x % 3 for x in xs}
+assert s == {0, 1, 2}
+assert len(s) == 3
+assert {1, 2, 2, 3} == {3, 2, 1}
+assert repr({7}) == "{7}" and repr(set()) == "set()"
+assert 2 in s and 5 not in s
+s.add(5)
+s.discard(0)
+assert s == {1, 2, 5}
+try:
+    s.remove(9)
+    assert False
+except KeyError:
+    pass
+assert set(["a", "a", "b"]) == {"a", "b"}
+
+# dict comprehensions
+d = {k: k * 2 for k in [1, 2, 3]}
+assert repr(d) == "{1: 2, 2: 4, 3: 6}"
+assert repr({k: v for k, v in [("a", 1), ("b", 2)] if v > 1}) == "{'b': 2}"
+assert {1: "a", 2: "b"}[2] == "b"
+assert len({}) == 0
+
+# generator expressions are lazy
+log = []
+def note(v):
+    log.append(v)
+    return v
+gen = (note(v) for v in [1, 2, 3])
+assert len(log) == 0
+for v in gen:
+    if v == 2:
+        break
+assert repr(log) == "[1, 2]"
+for v in gen:
+    pass
+assert repr(log) == "[1, 2, 3]"
+for v in gen:
+    assert False
+
+# a sole argument needs no parentheses of its own
+def total(it):
+    n = 0
+    for v in it:
+        n = n + v
+    return n
+assert total(x for x in xs if x > 4) == 11
+assert total((x * 2 for x in [1, 2])) == 6
+assert repr(type(x for x in xs)) == "<class 'generator'>"
+
+# errors
+def syntax_msg(src):
+    try:
+        exec(src)
+    except SyntaxError as err:
+        return err.msg
+    return None
+
+assert syntax_msg("f(x for x in y, 1)") == "Generator expression must be parenthesized"
+assert syntax_msg("[*x for x in y]") == "iterable unpacking cannot be used in comprehension"
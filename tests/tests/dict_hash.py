@@ -0,0 +1,105 @@
+class Key:
+    def __init__(self, name):
+        self.name = name
+
+    def __hash__(self):
+        return 42
+
+    def __eq__(self, other):
+        return self.name == other.name
+
+a = Key("a")
+b = Key("b")
+d = {a: 1, b: 2}
+assert len(d) == 2
+assert d[a] == 1
+assert d[b] == 2
+assert d[Key("a")] == 1
+
+d[Key("a")] = 3
+assert len(d) == 2
+assert d[a] == 3
+assert repr(d) == "{" + repr(a) + ": 3, " + repr(b) + ": 2}"
+
+class EqOnly:
+    def __eq__(self, other):
+        return True
+
+x = 1
+try:
+    d[EqOnly()] = 1
+except TypeError:
+    x = 2
+assert x == 2
+
+x = 1
+try:
+    d[[1, 2]] = 1
+except TypeError:
+    x = 2
+assert x == 2
+
+x = 1
+try:
+    d = {[1]: 2}
+except TypeError:
+    x = 2
+assert x == 2
+
+x = 1
+try:
+    d[{}]
+except TypeError:
+    x = 2
+assert x == 2
+
+class Plain:
+    pass
+
+p = Plain()
+d[p] = "plain"
+assert d[p] == "plain"
+
+squares = {}
+i = 0
+while i < 200:
+    squares[i] = i * i
+    i = i + 1
+assert len(squares) == 200
+assert squares[0] == 0
+assert squares[150] == 22500
+assert squares[199.0] == 39601
+
+# a key's __eq__ may use the container being searched
+class Meddler:
+    def __init__(self, target):
+        self.target = target
+        self.calls = 0
+    def __hash__(self):
+        return 7
+    def __eq__(self, other):
+        self.calls = self.calls + 1
+        self.target[len(self.target) + 100] = 0
+        return False
+
+d = {}
+m = Meddler(d)
+d[m] = 1
+d[Meddler(d)] = 2
+assert m.calls == 1
+assert d[m] == 1
+assert len(d) == 3
+
+class SetMeddler:
+    def __init__(self, target):
+        self.target = target
+    def __hash__(self):
+        return 7
+    def __eq__(self, other):
+        self.target.add(len(self.target) + 100)
+        return False
+
+s = set()
+s.add(SetMeddler(s))
+assert not (SetMeddler(s) in s)
+assert len(s) == 2
@@ -0,0 +1,129 @@
+d = {"a": 1, "b": 2, "c": 3}
+
+keys = []
+for k in d:
+    keys.append(k)
+assert repr(keys) == "['a', 'b', 'c']"
+
+ks = d.keys()
+vs = d.values()
+its = d.items()
+assert len(ks) == 3
+assert "a" in ks
+assert not ("z" in ks)
+assert 2 in vs
+assert ("b", 2) in its
+assert not (("b", 3) in its)
+assert not (["b", 2] in its)
+assert repr(ks) == "dict_keys(['a', 'b', 'c'])"
+assert repr(vs) == "dict_values([1, 2, 3])"
+assert repr(its) == "dict_items([('a', 1), ('b', 2), ('c', 3)])"
+
+d["d"] = 4
+assert len(ks) == 4
+assert "d" in ks
+total = 0
+for v in vs:
+    total = total + v
+assert total == 10
+
+assert d.get("a") == 1
+assert d.get("z") is None
+assert d.get("z", 0) == 0
+
+assert d.pop("d") == 4
+assert d.pop("d", "gone") == "gone"
+assert len(d) == 3
+
+x = 1
+try:
+    d.pop("d")
+except KeyError:
+    x = 2
+assert x == 2
+
+assert d.popitem() == ("c", 3)
+assert len(d) == 2
+
+assert d.setdefault("a", 100) == 1
+assert d.setdefault("e", 5) == 5
+assert d["e"] == 5
+assert d.setdefault("f") is None
+
+d.update({"a": 10, "g": 7})
+assert d["a"] == 10
+d.update([["h", 8]])
+assert d["h"] == 8
+
+c = d.copy()
+c["a"] = 0
+assert d["a"] == 10
+assert len(c) == len(d)
+
+del d["a"]
+assert not ("a" in d)
+x = 1
+try:
+    del d["a"]
+except KeyError:
+    x = 2
+assert x == 2
+
+d.clear()
+assert len(d) == 0
+assert repr(d) == "{}"
+
+x = 1
+try:
+    d.popitem()
+except KeyError:
+    x = 2
+assert x == 2
+
+e = dict()
+assert len(e) == 0
+f = dict([["x", 1], ["y", 2]])
+assert f["y"] == 2
+g = dict(f)
+g["z"] = 3
+assert len(f) == 2
+assert len(g) == 3
+
+x = 1
+try:
+    dict([[1, 2, 3]])
+except ValueError:
+    x = 2
+assert x == 2
+
+x = 1
+try:
+    {}["missing"]
+except KeyError:
+    x = 2
+assert x == 2
+
+l = [1, 2, 3]
+del l[1]
+assert repr(l) == "[1, 3]"
+
+r = {}
+i = 0
+while i < 50:
+    r[i] = i
+    i = i + 1
+i = 0
+while i < 50:
+    if i % 2 == 0:
+        del r[i]
+    else:
+        r[i] = r[i] * 2
+    i = i + 1
+assert len(r) == 25
+assert r[49] == 98
+r[0] = "back"
+order = []
+for k in r:
+    order.append(k)
+assert order[0] == 1
+assert order[25] == 0
@@ -0,0 +1,75 @@
+def fail():
+    raise KeyError("k")
+
+x = 0
+try:
+    try:
+        fail()
+    except KeyError as e:
+        raise ValueError("v") from e
+except ValueError as e:
+    x = e
+assert type(x.__cause__) is KeyError
+assert x.__context__ is x.__cause__
+assert x.__suppress_context__
+
+x = 0
+try:
+    try:
+        1 // 0
+    except ZeroDivisionError:
+        [][1]
+except IndexError as e:
+    x = e
+assert type(x.__context__) is ZeroDivisionError
+assert x.__cause__ is None
+assert not x.__suppress_context__
+
+x = 0
+try:
+    try:
+        fail()
+    except KeyError:
+        raise TypeError from None
+except TypeError as e:
+    x = e
+assert x.__cause__ is None
+assert x.__suppress_context__
+assert type(x.__context__) is KeyError
+
+x = 0
+try:
+    try:
+        raise KeyError
+    except KeyError:
+        raise
+except KeyError as e:
+    x = e
+assert x.__context__ is None
+
+x = 0
+try:
+    raise IndexError from 42
+except TypeError:
+    x = 1
+assert x == 1
+
+e = ValueError("set")
+e.__cause__ = KeyError()
+assert e.__suppress_context__
+e.note = "attrs"
+assert e.note == "attrs"
+assert e.args == ("set",)
+
+x = 0
+try:
+    fail()
+except KeyError as e:
+    x = e
+tb = x.__traceback__
+assert tb.tb_lineno == 66
+assert tb.tb_next.tb_lineno == 2
+assert tb.tb_next.tb_next is None
+
+y = ValueError()
+assert y.__traceback__ is None
@@ -0,0 +1,108 @@
+classes = [BaseException, Exception, TypeError, StopIteration, AttributeError]
+classes.append([ArithmeticError, ZeroDivisionError, OverflowError])
+classes.append([LookupError, KeyError, IndexError, ValueError])
+classes.append([NameError, UnboundLocalError])
+classes.append([RuntimeError, RecursionError, NotImplementedError, AssertionError])
+assert len(classes) == 9
+assert repr(KeyError) == "<class 'KeyError'>"
+
+x = 1
+try:
+    undefined_name
+except NameError:
+    x = 2
+assert x == 2
+
+x = 1
+try:
+    [1, 2][5]
+except IndexError:
+    x = 2
+assert x == 2
+
+l = [1, 2]
+l[1] = 3
+assert l[1] == 3
+x = 1
+try:
+    l[2] = 0
+except IndexError:
+    x = 2
+assert x == 2
+
+x = 1
+try:
+    {}["a"]
+except KeyError:
+    x = 2
+assert x == 2
+
+x = 1
+try:
+    assert 1 == 2
+except AssertionError:
+    x = 2
+assert x == 2
+
+def down(n):
+    return down(n + 1)
+
+x = 1
+try:
+    down(0)
+except RecursionError:
+    x = 2
+assert x == 2
+
+def deep(n):
+    if n == 0:
+        return 0
+    else:
+        return deep(n - 1) + 1
+assert deep(500) == 500
+
+x = 1
+try:
+    raise NotImplementedError("todo")
+except NotImplementedError:
+    x = 2
+assert x == 2
+
+x = 1
+try:
+    l.missing
+except AttributeError:
+    x = 2
+assert x == 2
+
+d = {1: 1, 2: 2}
+x = 1
+try:
+    for k in d:
+        d[k + 10] = k
+except RuntimeError:
+    x = 2
+assert x == 2
+
+# str and repr are built from args
+assert str(ValueError()) == "" and repr(ValueError()) == "ValueError()"
+assert str(ValueError("bad")) == "bad" and repr(ValueError("bad")) == "ValueError('bad')"
+assert str(ValueError(1)) == "1" and repr(TypeError(1, "a")) == "TypeError(1, 'a')"
+assert str(ValueError(1, "a")) == "(1, 'a')"
+assert str(KeyError("k")) == "'k'" and repr(KeyError("k")) == "KeyError('k')"
+assert str(KeyError()) == "" and str(KeyError(1, 2)) == "(1, 2)"
+
+class AppError(Exception):
+    pass
+
+class Labeled(Exception):
+    def __str__(self):
+        return "labeled"
+
+assert str(AppError("app")) == "app" and repr(AppError("app")) == "AppError('app')"
+assert AppError("app", 1).args == ("app", 1)
+assert str(Labeled("x")) == "labeled"
+try:
+    {}["missing"]
+except KeyError as err:
+    assert str(err) == "'missing'"
@@ -0,0 +1,45 @@
+assert 1.5 + 1.5 == 3.0
+assert 1 + 0.5 == 1.5
+assert 0.5 + 1 == 1.5
+assert 3 / 2 == 1.5
+assert 4 / 2 == 2.0
+assert 7.5 // 2 == 3.0
+assert -7.5 // 2 == -4.0
+assert 7.5 % 2 == 1.5
+assert -7.5 % 2 == 0.5
+assert 2 ** -1 == 0.5
+assert 2.0 ** 3 == 8.0
+inf = float("inf")
+assert repr((-8.0) ** (1/3)) == "(1.0000000000000002+1.7320508075688772j)"
+assert repr((-8) ** 0.5) == "(1.7319121124709868e-16+2.8284271247461903j)"
+assert (-2.0) ** inf == inf and (-0.5) ** inf == 0.0
+assert (-1.0) ** inf == 1.0 and (-2.0) ** -inf == 0.0
+assert 0.0 ** -inf == inf and (-inf) ** 0.5 == inf
+assert (-2.0) ** 3.0 == -8.0
+nan = float("nan")
+assert (-2.0) ** nan != (-2.0) ** nan and 1.0 ** nan == 1.0
+try:
+    0.0 ** -1.0
+    assert False
+except ZeroDivisionError:
+    pass
+assert -1.5 < 0
+assert 1 == 1.0
+assert 2 > 1.5
+assert 1.5 < 2
+assert .5 == 0.5
+assert 1e-3 == 0.001
+assert 2.5e2 == 250
+assert 1E3 == 1000.0
+assert not 0.0
+assert 0.1 + 0.2 != 0.3
+
+d = {1: "int"}
+assert d[1.0] == "int"
+
+x = 1
+try:
+    1.0 / 0
+except:
+    x = 2
+assert x == 2
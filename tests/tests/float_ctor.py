@@ -0,0 +1,31 @@
+assert float() == 0.0
+assert float(3) == 3.0
+assert float(2.5) == 2.5
+assert float("  1.25 ") == 1.25
+assert float("1e3") == 1000.0
+assert float("-.5") == -0.5
+assert type(float("1")) == float
+
+x = 1
+try:
+    float("abc")
+except:
+    x = 2
+assert x == 2
+
+# ints too large for a float are not rounded to infinity
+def overflow(f):
+    try:
+        f()
+    except OverflowError as err:
+        return err.args[0]
+    return None
+
+assert overflow(lambda: float(10 ** 400)) == "int too large to convert to float"
+assert overflow(lambda: 10 ** 400 + 0.5) == "int too large to convert to float"
+assert overflow(lambda: 0.5 * -10 ** 400) == "int too large to convert to float"
+assert overflow(lambda: complex(10 ** 400)) == "int too large to convert to float"
+assert overflow(lambda: (10 ** 400) ** -1) == "int too large to convert to float"
+assert overflow(lambda: format(10 ** 400, "f")) == "int too large to convert to float"
+assert not (10 ** 400 == 1j) and 10 ** 400 != 1j
+assert float(2 ** 1023) == 8.98846567431158e+307
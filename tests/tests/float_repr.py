@@ -0,0 +1,19 @@
+assert repr(1.0) == "1.0"
+assert repr(0.1) == "0.1"
+assert repr(0.1 + 0.2) == "0.30000000000000004"
+assert repr(1e16) == "1e+16"
+assert repr(1e15) == "1000000000000000.0"
+assert repr(0.0001) == "0.0001"
+assert repr(0.00001) == "1e-05"
+assert repr(-2.5) == "-2.5"
+assert repr(1.5e-10) == "1.5e-10"
+assert repr(123456789.125) == "123456789.125"
+assert repr(float("inf")) == "inf"
+assert repr(-float("inf")) == "-inf"
+assert repr(float("nan")) == "nan"
+assert repr(1) == "1"
+assert repr(True) == "True"
+assert repr(None) == "None"
+assert repr("it's") == '"' + "it's" + '"'
+assert repr('ab') == "'ab'"
+assert repr([1, 2.5, "a"]) == "[1, 2.5, 'a']"
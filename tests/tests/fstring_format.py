@@ -0,0 +1,176 @@
+# f-strings
+name = "world"
+n = 42
+assert f"hello {name}" == "hello world"
+assert f"{n} + 1 = {n + 1}" == "42 + 1 = 43"
+assert F'{{literal}} {n}' == "{literal} 42"
+assert f"{name!r}" == "'world'"
+assert f"{'é'!a}" == "'\\xe9'"
+assert f"{n:>5}|{n:<5}|{n:^6}|" == "   42|42   |  42  |"
+assert f"{n:05d}" == "00042"
+assert f"{-n:+}" == "-42"
+assert f"{n:+}" == "+42"
+assert f"{255:#x} {255:X} {5:b} {8:o}" == "0xff FF 101 10"
+assert f"{1234567:,}" == "1,234,567"
+assert f"{3.14159:.2f}" == "3.14"
+assert f"{0.5:%}" == "50.000000%"
+assert f"{12345.678:e}" == "1.234568e+04"
+assert f"{0.0001:g}" == "0.0001"
+assert f"{1234567.0:g}" == "1.23457e+06"
+assert f"{1.5:10.3f}|" == "     1.500|"
+width = 6
+assert f"{n:>{width}}" == "    42"
+assert f"{'abc':.2}" == "ab"
+assert f"{'x':*^5}" == "**x**"
+assert f"a" f"{n}" "b" == "a42b"
+assert f"{True}" == "True"
+assert f"{True:d}" == "1"
+assert f"{None}" == "None"
+d = {"k": 1}
+assert f"{d['k']}" == "1"
+assert f"""{
+    n
+}""" == "42"
+assert rf"\n{n}" == "\\n42"
+assert rf"\{n}" == "\\42" and rf"{n}\}}" == "42\\}"
+
+# __format__ and __str__
+class Money:
+    def __init__(self, cents):
+        self.cents = cents
+
+    def __str__(self):
+        return "$" + format(self.cents / 100, ".2f")
+
+    def __format__(self, spec):
+        if spec == "cents":
+            return format(self.cents) + "c"
+        return str(self)
+
+m = Money(1250)
+assert f"{m}" == "$12.50"
+assert f"{m:cents}" == "1250c"
+assert f"{m!s:>8}" == "  $12.50"
+assert str(m) == "$12.50"
+
+class Plain:
+    def __repr__(self):
+        return "<plain>"
+
+assert f"{Plain()}" == "<plain>"
+e = 0
+try:
+    f"{Plain():>10}"
+except TypeError as err:
+    e = err
+assert type(e) is TypeError
+
+# format() and str.format
+assert format(3) == "3"
+assert format(3.5, "06.2f") == "003.50"
+assert format("ab", ">4") == "  ab"
+assert "{} and {}".format(1, "two") == "1 and two"
+assert "{1}{0}{1}".format("a", "b") == "bab"
+assert "{0:>{1}}".format("x", 3) == "  x"
+assert "{0[1]} {0[0]!r}".format(["p", "q"]) == "q 'p'"
+assert "{.cents}".format(m) == "1250"
+assert "{{}}".format() == "{}"
+assert str(12) == "12"
+assert str() == ""
+
+e = 0
+try:
+    "{} {1}".format(1, 2)
+except ValueError as err:
+    e = err
+assert type(e) is ValueError
+
+e = 0
+try:
+    format(1, "s")
+except ValueError as err:
+    e = err
+assert e.args[0] == "Unknown format code 's' for object of type 'int'"
+
+e = 0
+try:
+    "{2}".format(1)
+except IndexError as err:
+    e = err
+assert type(e) is IndexError
+
+e = 0
+try:
+    format(5, "99999999999999999999")
+except ValueError as err:
+    e = err
+assert e.args[0] == "Too many decimal digits in format string"
+
+e = 0
+try:
+    f"{1.5:.99999999999999999999f}"
+except ValueError as err:
+    e = err
+assert e.args[0] == "Too many decimal digits in format string"
+
+# widths that parse but cannot be allocated raise instead of aborting
+e = 0
+try:
+    format(1, "99999999999999999")
+except MemoryError as err:
+    e = err
+assert type(e) is MemoryError and e.args == ()
+e = 0
+try:
+    f"{'s':<99999999999999999}"
+except MemoryError as err:
+    e = err
+assert type(e) is MemoryError
+
+e = 0
+try:
+    format(1.5, ".99999999999999999f")
+except ValueError as err:
+    e = err
+assert e.args[0] == "precision too big"
+
+# precisions past the exact digits of a double are padded with zeros
+s = f"{1.5:.70000f}"
+assert len(s) == 70002 and s[:5] == "1.500" and s[-3:] == "000"
+s = format(0.1, ".1080f")
+assert s[:22] == "0.10000000000000000555" and s[-5:] == "00000"
+s = format(2.5, ".70000e")
+assert len(s) == 70006 and s[:4] == "2.50" and s[-4:] == "e+00"
+assert format(0.5, ".70000g") == "0.5"
+assert len(format(0.5, "#.70000g")) == 70002
+
+# f-string syntax errors
+e = 0
+try:
+    exec("f'{}'")
+except SyntaxError as err:
+    e = err
+assert e.msg == "f-string: empty expression not allowed"
+
+e = 0
+try:
+    exec("f'a}'")
+except SyntaxError as err:
+    e = err
+assert e.msg == "f-string: single '}' is not allowed"
+
+e = 0
+try:
+    exec("x = 1\nf'{x!z}'")
+except SyntaxError as err:
+    e = err
+assert e.lineno == 2
+assert e.msg == "f-string: invalid conversion character: expected 's', 'r', or 'a'"
+
+e = 0
+try:
+    exec("f'{1 +}'")
+except SyntaxError as err:
+    e = err
+assert e.lineno == 1
+assert e.offset == 7
@@ -0,0 +1,128 @@
+# defaults are evaluated once, when the function is defined
+n = 10
+def f(a, b=n, c=n + 1):
+    return (a, b, c)
+n = 0
+assert f(1) == (1, 10, 11)
+assert f(1, 2) == (1, 2, 11)
+assert f(1, 2, 3) == (1, 2, 3)
+assert f.__defaults__ == (10, 11)
+
+def append(x, acc=[]):
+    acc.append(x)
+    return acc
+append(1)
+assert repr(append(2)) == "[1, 2]"
+
+# keyword arguments
+assert f(1, c=5) == (1, 10, 5)
+assert f(c=3, b=2, a=1) == (1, 2, 3)
+assert f(1, b=2) == (1, 2, 11)
+
+# *args and **kwargs
+def g(a, *args, **kwargs):
+    return (a, args, kwargs)
+a, args, kwargs = g(1)
+assert args == () and repr(kwargs) == "{}"
+a, args, kwargs = g(1, 2, 3, x=4, y=5)
+assert a == 1 and args == (2, 3)
+assert repr(kwargs) == "{'x': 4, 'y': 5}"
+
+xs = [1, 2]
+assert f(*xs) == (1, 2, 11)
+assert f(0, *xs) == (0, 1, 2)
+assert f(*(1,), *[2], 3) == (1, 2, 3)
+d = {"b": 20, "c": 30}
+assert f(1, **d) == (1, 20, 30)
+assert f(**{"a": 1}, **{"c": 3}) == (1, 10, 3)
+assert f(*[1], c=7, **{"b": 8}) == (1, 8, 7)
+a, args, kwargs = g(*xs, **d)
+assert a == 1 and args == (2,)
+assert repr(kwargs) == "{'b': 20, 'c': 30}"
+
+# keyword-only and positional-only parameters
+def h(a, /, b, *, c, d=4):
+    return (a, b, c, d)
+assert h(1, 2, c=3) == (1, 2, 3, 4)
+assert h(1, b=2, c=3, d=5) == (1, 2, 3, 5)
+assert h.__kwdefaults__["d"] == 4
+
+def k(*args, sep=" "):
+    return (args, sep)
+assert k("a", "b") == (("a", "b"), " ")
+assert k("a", sep="-") == (("a",), "-")
+
+def p(a, /, **kwargs):
+    return (a, kwargs["a"])
+assert p(1, a=2) == (1, 2)
+
+# methods and classes
+class Point:
+    def __init__(self, x=0, y=0):
+        self.x = x
+        self.y = y
+    def moved(self, dx=0, *, dy=0):
+        return Point(self.x + dx, self.y + dy)
+
+pt = Point(y=2).moved(dy=1)
+assert pt.x == 0 and pt.y == 3
+pt = Point(1).moved(5)
+assert pt.x == 6 and pt.y == 0
+
+# builtins that take keyword arguments
+assert repr(dict(a=1, b=2)) == "{'a': 1, 'b': 2}"
+e = {"a": 1}
+e.update({"b": 2}, c=3)
+assert repr(e) == "{'a': 1, 'b': 2, 'c': 3}"
+assert "{name} is {0}".format(3, name="x") == "x is 3"
+assert "{p.x}".format(p=Point(5)) == "5"
+
+# errors; exec runs in a fresh namespace, so the functions are defined again
+defs = "def f(a, b=1, c=2): pass\ndef h(a, /, b, *, c, d=4): pass\n"
+
+def error_msg(src):
+    try:
+        exec(defs + src)
+    except TypeError as err:
+        return err.args[0]
+    except KeyError as err:
+        return err.args[0]
+    return None
+
+def syntax_msg(src):
+    try:
+        exec(defs + src)
+    except SyntaxError as err:
+        return err.msg
+    return None
+
+assert error_msg("f()") == "f() missing 1 required positional argument: 'a'"
+assert error_msg("def f2(a, b, c): pass\nf2()") == "f2() missing 3 required positional arguments: 'a', 'b', and 'c'"
+assert error_msg("def f2(a, b): pass\nf2()") == "f2() missing 2 required positional arguments: 'a' and 'b'"
+assert error_msg("f(1, 2, 3, 4)") == "f() takes from 1 to 3 positional arguments but 4 were given"
+assert error_msg("def f2(): pass\nf2(1)") == "f2() takes 0 positional arguments but 1 was given"
+assert error_msg("def f2(a): pass\nf2(1, 2)") == "f2() takes 1 positional argument but 2 were given"
+assert error_msg("h(1, 2, 3, c=3)") == "h() takes 2 positional arguments but 3 positional arguments (and 1 keyword-only argument) were given"
+assert error_msg("f(1, a=2)") == "f() got multiple values for argument 'a'"
+assert error_msg("f(1, z=2)") == "f() got an unexpected keyword argument 'z'"
+assert error_msg("h(1, 2)") == "h() missing 1 required keyword-only argument: 'c'"
+assert error_msg("h(a=1, b=2, c=3)") == "h() got some positional-only arguments passed as keyword arguments: 'a'"
+assert error_msg("f(1, **{'b': 2}, b=3)") == "f() got multiple values for keyword argument 'b'"
+assert error_msg("f(**[1])") == "f() argument after ** must be a mapping, not list"
+assert error_msg("f(**{1: 2})") == "keywords must be strings"
+assert error_msg("len([], x=1)") == "len() takes no keyword arguments"
+assert error_msg("'{x}'.format(y=1)") == "x"
+
+assert syntax_msg("f(a=1, 2)") == "positional argument follows keyword argument"
+assert syntax_msg("f(**d, 2)") == "positional argument follows keyword argument unpacking"
+assert syntax_msg("f(**d, *xs)") == "iterable argument unpacking follows keyword argument unpacking"
+assert syntax_msg("f(a=1, a=2)") == "keyword argument repeated: a"
+assert syntax_msg("f(a.b=1)") == 'expression cannot contain assignment, perhaps you meant "=="?'
+assert syntax_msg("def f2(a, a): pass") == "duplicate argument 'a' in function definition"
+assert syntax_msg("def f2(a=1, b): pass") == "non-default argument follows default argument"
+assert syntax_msg("def f2(a, /, b, /): pass") == "/ may appear only once"
+assert syntax_msg("def f2(*, a, /): pass") == "/ must be ahead of *"
+assert syntax_msg("def f2(/, a): pass") == "at least one argument must precede /"
+assert syntax_msg("def f2(*a, *b): pass") == "* argument may appear only once"
+assert syntax_msg("def f2(*): pass") == "named arguments must follow bare *"
+assert syntax_msg("def f2(**kw, a): pass") == "arguments cannot follow var-keyword argument"
@@ -0,0 +1,223 @@
+# the body runs lazily, one yield at a time
+log = []
+def count(n):
+    log.append("start")
+    i = 0
+    while i < n:
+        yield i
+        i = i + 1
+    log.append("end")
+
+gen = count(3)
+assert len(log) == 0
+assert next(gen) == 0
+assert repr(log) == "['start']"
+assert next(gen) == 1 and next(gen) == 2
+try:
+    next(gen)
+    assert False
+except StopIteration:
+    pass
+assert repr(log) == "['start', 'end']"
+assert next(gen, "done") == "done"
+assert repr(type(gen)) == "<class 'generator'>"
+
+# for loops and anything else that iterates consume generators
+total = 0
+for v in count(5):
+    total = total + v
+assert total == 10
+assert tuple(count(3)) == (0, 1, 2)
+a, b = count(2)
+assert a == 0 and b == 1
+assert 2 in count(4)
+assert repr([v * 2 for v in count(3)]) == "[0, 2, 4]"
+
+# locals and loops survive between yields
+def fib():
+    a, b = 0, 1
+    while True:
+        yield a
+        a, b = b, a + b
+f = fib()
+assert tuple(next(f) for _ in count(8)) == (0, 1, 1, 2, 3, 5, 8, 13)
+
+# the return value ends up in StopIteration.value
+def returns():
+    yield 1
+    return "result"
+gen = returns()
+next(gen)
+try:
+    next(gen)
+    assert False
+except StopIteration as err:
+    assert err.value == "result"
+    assert err.args == ("result",)
+assert StopIteration().value is None
+
+# send delivers the result of the yield expression
+def echo():
+    received = []
+    value = yield "ready"
+    while value is not None:
+        received.append(value)
+        value = yield value * 2
+    return received
+gen = echo()
+assert gen.send(None) == "ready"
+assert gen.send(1) == 2
+assert gen.send(5) == 10
+try:
+    gen.send(None)
+    assert False
+except StopIteration as err:
+    assert repr(err.value) == "[1, 5]"
+
+def running_total():
+    total = 0
+    while True:
+        total = total + (yield total)
+gen = running_total()
+next(gen)
+gen.send(3)
+assert gen.send(4) == 7
+
+def just_started():
+    try:
+        count(1).send(1)
+    except TypeError as err:
+        return err.args[0]
+assert just_started() == "can't send non-None value to a just-started generator"
+
+# throw raises where the generator stopped
+def guarded():
+    while True:
+        try:
+            yield "waiting"
+        except ValueError as err:
+            yield "caught " + err.args[0]
+gen = guarded()
+next(gen)
+assert gen.throw(ValueError("bad")) == "caught bad"
+assert next(gen) == "waiting"
+assert gen.throw(ValueError, "worse") == "caught worse"
+try:
+    gen.throw(KeyError)
+    assert False
+except KeyError:
+    pass
+assert next(gen, "finished") == "finished"
+
+# a generator that has not started is finished by throw
+try:
+    count(3).throw(IndexError)
+    assert False
+except IndexError:
+    pass
+
+# close raises GeneratorExit inside, so finally clauses run
+log = []
+def closing():
+    try:
+        yield 1
+        yield 2
+    finally:
+        log.append("cleanup")
+gen = closing()
+next(gen)
+assert gen.close() is None
+assert repr(log) == "['cleanup']"
+assert next(gen, "closed") == "closed"
+gen.close()
+count(3).close()
+
+def stubborn():
+    while True:
+        try:
+            yield
+        except GeneratorExit:
+            pass
+gen = stubborn()
+next(gen)
+try:
+    gen.close()
+    assert False
+except RuntimeError as err:
+    assert err.args[0] == "generator ignored GeneratorExit"
+
+# yield from delegates next, send and throw and gives the return value
+def inner():
+    x = yield 1
+    y = yield x
+    return x + y
+def outer():
+    result = yield from inner()
+    yield "inner returned " + repr(result)
+    yield from [7, 8]
+gen = outer()
+assert next(gen) == 1
+assert gen.send(10) == 10
+assert gen.send(5) == "inner returned 15"
+assert next(gen) == 7 and next(gen) == 8
+assert next(gen, None) is None
+
+def chain(*iterables):
+    for it in iterables:
+        yield from it
+assert tuple(chain((1, 2), count(2), [3])) == (1, 2, 0, 1, 3)
+
+def delegating():
+    try:
+        yield from guarded()
+    except KeyError:
+        yield "outer caught"
+gen = delegating()
+next(gen)
+assert gen.throw(ValueError("x")) == "caught x"
+assert gen.throw(KeyError) == "outer caught"
+
+log = []
+def outer_closing():
+    try:
+        yield from closing()
+    finally:
+        log.append("outer cleanup")
+gen = outer_closing()
+next(gen)
+gen.close()
+assert repr(log) == "['cleanup', 'outer cleanup']"
+
+# a generator's handled exception does not leak into its caller
+def handling():
+    try:
+        raise KeyError("inside")
+    except KeyError:
+        yield 1
+gen = handling()
+try:
+    raise ValueError("outside")
+except ValueError:
+    next(gen)
+    try:
+        raise
+    except ValueError as err:
+        assert err.args[0] == "outside"
+
+# StopIteration escaping a generator becomes a RuntimeError
+def leaky():
+    yield next(count(0))
+try:
+    next(leaky())
+    assert False
+except RuntimeError as err:
+    assert err.args[0] == "generator raised StopIteration"
+
+def reentrant():
+    yield next(gen)
+gen = reentrant()
+try:
+    next(gen)
+    assert False
+except ValueError as err:
+    assert err.args[0] == "generator already executing"
@@ -0,0 +1,14 @@
+class A:
+    x = 0
+
+a = A()
+b = a
+assert a is b
+assert a is not A()
+assert None is None
+assert not (None is not None)
+assert True is True
+assert A is A
+assert (a is b) == True
+xs = []
+assert xs is not []
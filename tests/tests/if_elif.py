@@ -0,0 +1,63 @@
+def sign(n):
+    if n < 0:
+        return -1
+    elif n == 0:
+        return 0
+    elif n < 10:
+        return 1
+    else:
+        return 10
+
+assert sign(-5) == -1
+assert sign(0) == 0
+assert sign(3) == 1
+assert sign(42) == 10
+
+x = 0
+if x == 1:
+    x = 2
+assert x == 0
+
+if x == 0:
+    x = 3
+elif x == 3:
+    x = 4
+assert x == 3
+
+def grade(n):
+    if n >= 90: return "A"
+    elif n >= 80: return "B"
+    else: return "C"
+
+assert grade(95) == "A"
+assert grade(85) == "B"
+assert grade(10) == "C"
+
+i = 0
+while i < 5: i = i + 1
+assert i == 5
+
+total = 0
+for v in [1, 2, 3]: total = total + v
+assert total == 6
+
+class Empty:
+    pass
+
+def nothing():
+    pass
+
+assert nothing() is None
+assert type(Empty()) is Empty
+
+try: pass
+except KeyError: pass
+else: x = 5
+assert x == 5
+
+e = 0
+try:
+    exec("if x:")
+except SyntaxError as err:
+    e = err
+assert e.msg == "expected an indented block"
@@ -0,0 +1,76 @@
+# conditional expressions
+x = 5
+assert (1 if x > 3 else 2) == 1
+assert (1 if x > 10 else 2) == 2
+assert ("a" if x else "b") == "a"
+# the condition is evaluated first and only one branch runs
+log = []
+def note(v):
+    log.append(v)
+    return v
+y = note(1) if note(True) else note(2)
+assert y == 1 and repr(log) == "[True, 1]"
+# chains associate to the right
+def sign(n):
+    return -1 if n < 0 else 0 if n == 0 else 1
+assert sign(-4) == -1 and sign(0) == 0 and sign(9) == 1
+# lower precedence than or, and and not
+assert (1 if not x or False else 2) == 2
+assert (0 or 3 if x else 4) == 3
+t = 1, 2 if x else 3
+assert t == (1, 2)
+
+# lambdas
+sq = lambda n: n * n
+assert sq(4) == 16
+assert sq.__name__ == "<lambda>"
+assert (lambda: 42)() == 42
+add = lambda a, b=10, *rest, k=0, **kw: (a + b + k, rest, kw)
+assert add(1)[:2] == (11, ())
+total, rest, kw = add(1, 2, 3, 4, k=5, z=6)
+assert total == 8 and rest == (3, 4)
+assert kw["z"] == 6
+
+# defaults are evaluated when the lambda is made
+n = 3
+f = lambda v=n: v
+n = 4
+assert f() == 3
+
+# closures over the enclosing scope, and the body is an expression
+def adder(m):
+    return lambda v: v + m
+assert adder(2)(5) == 7
+pick = lambda c: "yes" if c else "no"
+assert pick(1) == "yes" and pick(0) == "no"
+nested = lambda a: lambda b: a * b
+assert nested(3)(4) == 12
+
+# as arguments and in displays
+def apply(fn, *args):
+    return fn(*args)
+assert apply(lambda a, b: a - b, 5, 3) == 2
+assert apply(fn=lambda: "kw") == "kw"
+fs = [lambda: 1, lambda: 2]
+assert fs[1]() == 2
+table = {"inc": lambda v: v + 1}
+assert table["inc"](1) == 2
+
+# errors
+def syntax_msg(src):
+    try:
+        exec(src)
+    except SyntaxError as err:
+        return err.msg
+    return None
+
+def error_msg(src):
+    try:
+        exec(src)
+    except TypeError as err:
+        return err.args[0]
+    return None
+
+assert syntax_msg("x = 1 if True") == "expected 'else', found newline"
+assert syntax_msg("f = lambda x, x: x") == "duplicate argument 'x' in function definition"
+assert error_msg("(lambda a: a)()") == "<lambda>() missing 1 required positional argument: 'a'"
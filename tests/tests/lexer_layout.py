@@ -0,0 +1,39 @@
+# comments and line joining
+x = 1  # a trailing comment
+    # an indented comment line is blank
+
+total = (1 +
+         2 +
+         3)
+assert total == 6
+
+items = [
+    1,
+    2,  # inside brackets
+    3,
+]
+assert len(items) == 3
+
+d = {
+    "a": 1,
+    "b": 2,
+}
+assert d["b"] == 2
+
+y = 1 + \
+    2
+assert y == 3
+
+if x == 1 and \
+   y == 3:
+    x = 2
+assert x == 2
+
+def f(a,
+      b):
+	if a:
+		return b
+	return 0
+
+assert f(1, 5) == 5
+assert f(0, 5) == 0
@@ -0,0 +1,66 @@
+def fact(n):
+    acc = 1
+    i = 1
+    while i <= n:
+        acc = acc * i
+        i = i + 1
+    return acc
+
+f = fact(30)
+assert repr(f) == "265252859812191058636308480000000"
+assert f // fact(28) == 870
+assert f % 1000000007 == 109361473
+assert -f // 7 == -37893265687455865519472640000000
+assert -(f + 1) // 7 == -37893265687455865519472640000001
+assert -(f + 1) % 7 == 6
+
+big = 2 ** 100
+assert big == 1267650600228229401496703205376
+assert big - big == 0
+assert big + 1 > big
+assert -big < 0
+assert big * big == 2 ** 200
+assert big // 2 ** 99 == 2
+assert 9223372036854775807 + 1 == 9223372036854775808
+assert -9223372036854775808 - 1 == -9223372036854775809
+assert 2 ** 64 / 2 ** 63 == 2.0
+assert 10 ** 400 / 10 ** 399 == 10.0 and -(10 ** 400) / 10 ** 399 == -10.0
+assert 10 ** 30 / 3 == 3.333333333333333e+29 and (2 ** 53 + 3) / 1 == 9007199254740996.0
+assert 1 / 10 ** 400 == 0.0 and (2 ** 1100 + 1) / 2 ** 2175 == 5e-324
+x = 0
+try:
+    10 ** 400 / 3
+except OverflowError as err:
+    x = err.args[0]
+assert x == "integer division result too large for a float"
+assert 2 ** 64 == 18446744073709551616.0
+assert 2 ** 53 + 1 != 2.0 ** 53 and 2 ** 53 + 1 > 2.0 ** 53 and 2.0 ** 53 < 2 ** 53 + 1
+assert 2 ** 53 == 2.0 ** 53 and len({2 ** 53 + 1: "a", 2.0 ** 53: "b"}) == 2
+assert 10 ** 400 != float("inf") and 10 ** 400 < float("inf") and -10 ** 400 > float("-inf")
+assert 3 < 3.5 and 4 > 3.5 and -4 < -3.5 and -3 > -3.5 and 3.5 <= 4 and not (3 >= 3.5)
+assert not (1 == float("nan")) and 1 != float("nan") and not (float("nan") < 1)
+assert True == 1.0 and 7 == 7.0 and 7.0 >= 7
+assert 0 ** 0 == 1 and 1 ** 0 == 1 and (-1) ** 0 == 1
+assert 0 ** 5 == 0 and (-1) ** 2 ** 70 == 1 and (-1) ** (2 ** 70 + 1) == -1
+
+assert int() == 0
+assert int(7.9) == 7
+assert int(-7.9) == -7
+assert int(1e20) == 100000000000000000000
+assert int("  -123456789012345678901234567890 ") == -123456789012345678901234567890
+assert int("ff", 16) == 255
+assert int(True) == 1
+
+x = 1
+try:
+    int("12a")
+except ValueError:
+    x = 2
+assert x == 2
+
+x = 1
+try:
+    [1][2 ** 40]
+except:
+    x = 2
+assert x == 2
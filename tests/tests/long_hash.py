@@ -0,0 +1,11 @@
+d = {1: "one", 2 ** 70: "big", -5: "neg"}
+assert d[1.0] == "one"
+assert d[True] == "one"
+assert d[float(2 ** 70)] == "big"
+assert d[-5.0] == "neg"
+
+d[0.5] = "half"
+assert d[0.5] == "half"
+d[2 ** 61 - 1] = "modulus"
+assert d[2 ** 61 - 1] == "modulus"
+assert len(d) == 5
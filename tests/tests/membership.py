@@ -0,0 +1,21 @@
+xs = [1, 2, "three"]
+assert 1 in xs
+assert "three" in xs
+assert 4 not in xs
+assert not (2 not in xs)
+
+d = {"a": 1, 2: "b"}
+assert "a" in d
+assert 2 in d
+assert 1 not in d
+
+assert "ell" in "hello"
+assert "" in "hello"
+assert "z" not in "hello"
+
+x = 1
+try:
+    1 in "abc"
+except:
+    x = 2
+assert x == 2
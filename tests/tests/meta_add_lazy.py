@@ -9,7 +9,7 @@ class B(A):
 
 
 class C(A):
-    pass = 1
+    pass
 
 
 
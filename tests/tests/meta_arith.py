@@ -0,0 +1,83 @@
+class Vec:
+    def __init__(self, x, y):
+        self.x = x
+        self.y = y
+
+    def __sub__(self, other):
+        return Vec(self.x - other.x, self.y - other.y)
+
+    def __mul__(self, k):
+        return Vec(self.x * k, self.y * k)
+
+    def __truediv__(self, k):
+        return Vec(self.x // k, self.y // k)
+
+    def __floordiv__(self, k):
+        return Vec(self.x // k, self.y // k)
+
+    def __mod__(self, k):
+        return Vec(self.x % k, self.y % k)
+
+    def __pow__(self, k):
+        return Vec(self.x ** k, self.y ** k)
+
+    def __neg__(self):
+        return Vec(-self.x, -self.y)
+
+    def __pos__(self):
+        return self
+
+class SubVec(Vec):
+    pass
+
+v = SubVec(6, 9) - Vec(1, 2)
+assert v.x == 5
+assert v.y == 7
+v = -(v * 2)
+assert v.x == -10
+assert v.y == -14
+v = (Vec(7, 9) / 2) ** 2
+assert v.x == 9
+assert v.y == 16
+v = +(Vec(7, 9) % 4)
+assert v.x == 3
+assert v.y == 1
+assert (Vec(7, 9) // 3).y == 3
+
+# NotImplemented hands the operation to the right operand's reflected method
+class Meters:
+    def __init__(self, n):
+        self.n = n
+
+    def __add__(self, other):
+        if type(other) == Meters:
+            return Meters(self.n + other.n)
+        return NotImplemented
+
+    def __mul__(self, other):
+        return NotImplemented
+
+class Feet:
+    def __init__(self, n):
+        self.n = n
+
+    def __radd__(self, other):
+        return Meters(other.n + self.n * 0.25)
+
+    def __rsub__(self, other):
+        return "Feet.__rsub__"
+
+assert (Meters(1) + Meters(2)).n == 3
+assert (Meters(1) + Feet(4)).n == 2.0
+assert Meters(1) - Feet(4) == "Feet.__rsub__"
+
+def unsupported(f):
+    try:
+        f()
+    except TypeError as err:
+        return err.args[0]
+    return None
+
+assert unsupported(lambda: Meters(1) + 1) == "unsupported operand type(s) for +: 'Meters' and 'int'"
+assert unsupported(lambda: Meters(1) * Feet(1)) == "unsupported operand type(s) for *: 'Meters' and 'Feet'"
+assert unsupported(lambda: Feet(1) // Feet(1)) == "unsupported operand type(s) for //: 'Feet' and 'Feet'"
@@ -0,0 +1,32 @@
+class Empty:
+    def __bool__(self):
+        return False
+
+class Sized:
+    def __init__(self, n):
+        self.n = n
+
+    def __len__(self):
+        return self.n
+
+class Plain:
+    x = 0
+
+assert not Empty()
+assert not Sized(0)
+assert Sized(2) and Plain()
+assert (Empty() or 42) == 42
+
+def bool_error(obj):
+    try:
+        if obj:
+            pass
+    except ValueError as err:
+        return "ValueError: " + err.args[0]
+    except TypeError as err:
+        return "TypeError: " + err.args[0]
+    return None
+
+assert bool_error(Sized(-1)) == "ValueError: __len__() should return >= 0"
+assert bool_error(Sized(1.0)) == "TypeError: 'float' object cannot be interpreted as an integer"
+assert bool_error(Sized(2)) is None
@@ -0,0 +1,60 @@
+class Num:
+    def __init__(self, n):
+        self.n = n
+
+    def __lt__(self, other):
+        if type(other) == Num:
+            return self.n < other.n
+        else:
+            return self.n < other
+
+    def __eq__(self, other):
+        if type(other) == Num:
+            return self.n == other.n
+        else:
+            return NotImplemented
+
+class Other:
+    pass
+
+assert Num(1) < Num(2)
+assert Num(3) > Num(2)
+assert 5 > Num(2)
+assert Num(1) == Num(1)
+assert Num(1) != Num(2)
+assert (Num(1) == Other()) == False
+
+o = Other()
+assert o == o
+assert o != Other()
+
+x = 1
+try:
+    Other() < Other()
+except:
+    x = 2
+assert x == 2
+
+# a subclass on the right gets to compare first
+class Base:
+    def __lt__(self, other):
+        return "Base.__lt__"
+    def __eq__(self, other):
+        return "Base.__eq__"
+
+class Derived(Base):
+    def __gt__(self, other):
+        return "Derived.__gt__"
+    def __eq__(self, other):
+        return "Derived.__eq__"
+
+assert (Base() < Derived()) == "Derived.__gt__"
+assert (Derived() < Base()) == "Base.__lt__"
+assert (Base() == Derived()) == "Derived.__eq__"
+assert (Base() < Base()) == "Base.__lt__"
+
+class Declines(Base):
+    def __gt__(self, other):
+        return NotImplemented
+
+assert (Base() < Declines()) == "Base.__lt__"
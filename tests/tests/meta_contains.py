@@ -0,0 +1,33 @@
+class Evens:
+    def __contains__(self, n):
+        return n % 2 == 0
+
+assert 4 in Evens()
+assert 3 not in Evens()
+
+class CountIter:
+    def __init__(self, n):
+        self.i = 0
+        self.n = n
+
+    def __next__(self):
+        if self.i < self.n:
+            self.i = self.i + 1
+            return self.i
+        else:
+            raise StopIteration
+
+class Count:
+    def __init__(self, n):
+        self.n = n
+
+    def __iter__(self):
+        return CountIter(self.n)
+
+assert 3 in Count(5)
+assert 6 not in Count(5)
+
+total = 0
+for i in Count(4):
+    total = total + i
+assert total == 10
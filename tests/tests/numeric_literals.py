@@ -0,0 +1,108 @@
+# integer prefixes
+assert 0x1F == 31
+assert 0XFF == 255
+assert 0o17 == 15
+assert 0O7 == 7
+assert 0b1011 == 11
+assert 0B1 == 1
+assert 0xdeadbeefdeadbeefdeadbeef == 68915718021581205938132336367
+assert -0x10 == -16
+
+# underscores separate digits
+assert 1_000_000 == 1000000
+assert 0x_ff == 255
+assert 0b_1_0 == 2
+assert 1_0.2_5 == 10.25
+assert 1e1_0 == 10000000000.0
+assert 0_0 == 0
+assert 00 == 0
+assert 007.5 == 7.5
+
+# imaginary literals
+z = 1 + 2j
+assert type(z) is complex
+assert type(3J) is complex
+assert z.real == 1.0
+assert z.imag == 2.0
+assert repr(z) == "(1+2j)"
+assert repr(2j) == "2j"
+assert repr(1.5 - 2.5j) == "(1.5-2.5j)"
+assert repr(-(1 + 0j)) == "(-1-0j)"
+assert repr(1e3j) == "1000j"
+assert repr(.5j) == "0.5j"
+assert repr(1_0j) == "10j"
+
+# arithmetic mixes with ints and floats
+assert z + 1 == 2 + 2j
+assert 1 + z == 2 + 2j
+assert 2.5 * z == 2.5 + 5j
+assert z * z == -3 + 4j
+assert z - z == 0
+assert (1 + 2j) / (3 - 4j) == -0.2 + 0.4j
+assert 1j ** 2 == -1
+assert z ** 0 == 1
+assert (2 + 0j) ** -1 == 0.5
+assert -z == -1 - 2j
+assert z.conjugate() == 1 - 2j
+assert 1 == 1 + 0j
+assert 1.5 == 1.5 + 0j
+assert z != 1
+assert not 0j
+assert 1j
+
+# hashes agree with equal ints and floats
+d = {2: "two", 2.5: "float", 1 + 2j: "complex"}
+assert d[2 + 0j] == "two"
+assert d[2.5 + 0j] == "float"
+assert d[complex(1, 2)] == "complex"
+
+# the complex constructor
+assert complex() == 0j
+assert complex(1) == 1 + 0j
+assert complex(1, 2) == z
+assert complex(1j, 1j) == -1 + 1j
+assert complex("1+2j") == z
+assert complex(" (3-1.5j) ") == 3 - 1.5j
+assert complex("-2j") == -2j
+assert complex("j") == 1j
+assert complex("1e2") == 100
+
+e = 0
+try:
+    complex("1+")
+except ValueError as err:
+    e = err
+assert e.args[0] == "complex() arg is a malformed string"
+
+e = 0
+try:
+    z / 0
+except ZeroDivisionError as err:
+    e = err
+assert e.args[0] == "complex division by zero"
+
+e = 0
+try:
+    z // 2
+except TypeError as err:
+    e = err
+assert type(e) is TypeError
+
+def syntax_msg(src):
+    try:
+        exec(src)
+    except SyntaxError as err:
+        return err.msg
+    return None
+
+assert syntax_msg("x = 012") == "leading zeros in decimal integer literals are not permitted; use an 0o prefix for octal integers"
+assert syntax_msg("x = 0_7") == "leading zeros in decimal integer literals are not permitted; use an 0o prefix for octal integers"
+assert syntax_msg("x = 0b102") == "invalid digit '2' in binary literal"
+assert syntax_msg("x = 0o8") == "invalid digit '8' in octal literal"
+assert syntax_msg("x = 0x") == "invalid hexadecimal literal"
+assert syntax_msg("x = 0xfg") == "invalid hexadecimal literal"
+assert syntax_msg("x = 0x_") == "invalid hexadecimal literal"
+assert syntax_msg("x = 0b1__0") == "invalid binary literal"
+assert syntax_msg("x = 1_") == "invalid decimal literal"
+assert syntax_msg("x = 1__000") == "invalid decimal literal"
+assert syntax_msg("x = 1.5_") == "invalid decimal literal"
@@ -0,0 +1,145 @@
+# assignments inside a function bind locals unless declared global
+counter = 0
+def bump():
+    global counter
+    counter = counter + 1
+bump()
+bump()
+assert counter == 2
+
+def shadow():
+    counter = 100
+    return counter
+assert shadow() == 100 and counter == 2
+
+def define_global():
+    global created
+    created = "here"
+define_global()
+assert created == "here"
+
+# reading a local before it is assigned
+x = "global"
+def unbound():
+    y = x
+    x = 1
+try:
+    unbound()
+    assert False
+except UnboundLocalError as err:
+    assert err.args[0] == "local variable 'x' referenced before assignment"
+except NameError:
+    assert False
+
+def unbound_cell():
+    def inner():
+        return v
+    try:
+        inner()
+    except NameError as err:
+        return err.args[0]
+    v = 1
+assert unbound_cell() == "free variable 'v' referenced before assignment in enclosing scope"
+
+# closures share variables with the function that binds them
+def make_counter():
+    count = 0
+    def incr():
+        nonlocal count
+        count = count + 1
+        return count
+    def get():
+        return count
+    return incr, get
+incr, get = make_counter()
+incr()
+incr()
+assert incr() == 3 and get() == 3
+other_incr, other_get = make_counter()
+assert other_incr() == 1 and get() == 3
+
+def adder(n):
+    return lambda x: x + n
+assert adder(3)(4) == 7
+assert adder(1).__closure__[0].cell_contents == 1
+assert adder.__closure__ is None
+assert adder(1).__code__.co_freevars == ("n",)
+assert adder.__code__.co_cellvars == ("n",)
+
+# the cell is read when the closure runs, not when it is made
+def late():
+    fs = []
+    i = 0
+    while i < 3:
+        fs.append(lambda: i)
+        i = i + 1
+    return fs
+assert tuple(f() for f in late()) == (3, 3, 3)
+def bound_now():
+    return [(lambda i=i: i) for i in [0, 1, 2]]
+assert tuple(f() for f in bound_now()) == (0, 1, 2)
+
+# nonlocal reaches through several levels
+def outer():
+    v = "outer"
+    def middle():
+        def inner():
+            nonlocal v
+            v = "inner"
+        inner()
+        return v
+    return middle(), v
+assert outer() == ("inner", "inner")
+
+# a function defined in a class body does not see the class's names
+y = "module"
+class C:
+    y = "class"
+    seen = y
+    def method(self):
+        return y
+    items = [y for _ in [0]]
+assert C.seen == "class"
+assert C().method() == "module"
+assert repr(C.items) == "['module']"
+
+def class_in_function():
+    z = "function"
+    class D:
+        zz = z
+        def method(self):
+            return z
+    return D.zz, D().method()
+assert class_in_function() == ("function", "function")
+
+# comprehensions and generators see the enclosing function's locals
+def scaled(xs, k):
+    return [x * k for x in xs if x != k]
+assert repr(scaled([1, 2, 3], 2)) == "[2, 6]"
+def gen_closure(k):
+    def g():
+        yield k
+        yield k + 1
+    return g()
+assert tuple(gen_closure(5)) == (5, 6)
+
+# builtins stay visible from functions
+def uses_builtin():
+    return len([1, 2])
+assert uses_builtin() == 2
+
+# misplaced declarations are syntax errors
+def syntax_msg(src):
+    try:
+        exec(src)
+    except SyntaxError as err:
+        return err.msg
+    return None
+
+assert syntax_msg("nonlocal x") == "nonlocal declaration not allowed at module level"
+assert syntax_msg("def f():\n    nonlocal x\n") == "no binding for nonlocal 'x' found"
+assert syntax_msg("def f(x):\n    global x\n") == "name 'x' is parameter and global"
+assert syntax_msg("def f():\n    x = 1\n    global x\n") == "name 'x' is assigned to before global declaration"
+assert syntax_msg("def f():\n    print(x)\n    global x\n") == "name 'x' is used prior to global declaration"
+assert syntax_msg("def f():\n    x = 1\n    def g():\n        global x\n        nonlocal x\n") == "name 'x' is global and nonlocal"
+assert syntax_msg("def f():\n    global x\n    def g():\n        nonlocal x\n") == "no binding for nonlocal 'x' found"
@@ -0,0 +1,121 @@
+# negative indices
+xs = [1, 2, 3, 4, 5]
+assert xs[-1] == 5
+assert xs[-5] == 1
+assert (1, 2, 3)[-2] == 2
+assert "abc"[-1] == "c"
+assert "abc"[0] == "a"
+assert "héllo"[1] == "é"
+xs[-1] = 50
+assert xs[4] == 50
+
+# slicing lists, tuples and strings
+assert repr(xs[1:3]) == "[2, 3]"
+assert repr(xs[:2]) == "[1, 2]"
+assert repr(xs[3:]) == "[4, 50]"
+assert repr(xs[:]) == "[1, 2, 3, 4, 50]"
+assert repr(xs[::2]) == "[1, 3, 50]"
+assert repr(xs[::-1]) == "[50, 4, 3, 2, 1]"
+assert repr(xs[-2:]) == "[4, 50]"
+assert repr(xs[1:-1]) == "[2, 3, 4]"
+assert repr(xs[10:]) == "[]"
+assert repr(xs[-100:2]) == "[1, 2]"
+assert repr(xs[4:1:-2]) == "[50, 3]"
+assert repr(xs[3:1]) == "[]"
+ys = xs[:]
+assert ys is not xs
+assert (1, 2, 3, 4)[1:3] == (2, 3)
+assert (1, 2, 3, 4)[::-1] == (4, 3, 2, 1)
+assert "hello"[1:4] == "ell"
+assert "hello"[::-1] == "olleh"
+assert "hello"[-3:] == "llo"
+assert "hello"[::2] == "hlo"
+assert "hello"[10:20] == ""
+lo, hi = 1, 3
+assert "hello"[lo:hi] == "el"
+assert "hello"[lo + 1:] == "llo"
+
+# slice assignment and deletion
+xs = [1, 2, 3, 4, 5]
+xs[1:3] = [20, 30, 40]
+assert repr(xs) == "[1, 20, 30, 40, 4, 5]"
+xs[:2] = ()
+assert repr(xs) == "[30, 40, 4, 5]"
+xs[2:2] = [7, 8]
+assert repr(xs) == "[30, 40, 7, 8, 4, 5]"
+xs[3:1] = [0]
+assert repr(xs) == "[30, 40, 7, 0, 8, 4, 5]"
+xs[::2] = [1, 2, 3, 4]
+assert repr(xs) == "[1, 40, 2, 0, 3, 4, 4]"
+xs[:] = xs
+assert repr(xs) == "[1, 40, 2, 0, 3, 4, 4]"
+del xs[1:3]
+assert repr(xs) == "[1, 0, 3, 4, 4]"
+del xs[::-2]
+assert repr(xs) == "[0, 4]"
+del xs[-1]
+assert repr(xs) == "[0]"
+
+# slice objects
+s = slice(1, 5, 2)
+assert type(s) is slice
+assert s.start == 1 and s.stop == 5 and s.step == 2
+assert repr(slice(3)) == "slice(None, 3, None)"
+assert repr(slice(1, None)) == "slice(1, None, None)"
+assert slice(1, 2) == slice(1, 2, None)
+assert slice(1, 2) != slice(1, 3)
+assert s.indices(4) == (1, 4, 2)
+assert slice(None, None, -1).indices(3) == (2, -1, -1)
+assert repr([0, 1, 2, 3, 4, 5][s]) == "[1, 3]"
+assert "abcdef"[slice(None, None, -2)] == "fdb"
+
+# user classes get the slice object
+class Seq:
+    def __init__(self):
+        self.log = []
+    def __getitem__(self, key):
+        return key
+    def __setitem__(self, key, value):
+        self.log.append(key)
+    def __delitem__(self, key):
+        self.log.append(key)
+
+q = Seq()
+assert q[1:2] == slice(1, 2, None)
+assert q[::3] == slice(None, None, 3)
+assert q[1:2, 3] == (slice(1, 2, None), 3)
+assert q[:, ::] == (slice(None, None, None), slice(None, None, None))
+assert q["k"] == "k"
+q[1:] = 0
+del q[:-1]
+assert q.log[0] == slice(1, None, None)
+assert q.log[1] == slice(None, -1, None)
+
+class SubSeq(Seq):
+    pass
+assert SubSeq()[-1] == -1
+
+# errors
+def error_msg(src):
+    try:
+        exec(src)
+    except TypeError as err:
+        return err.args[0]
+    except ValueError as err:
+        return err.args[0]
+    except IndexError as err:
+        return err.args[0]
+    return None
+
+assert error_msg("[1, 2][2]") == "list index out of range"
+assert error_msg("[1, 2][-3]") == "list index out of range"
+assert error_msg("(1, 2)[-3]") == "tuple index out of range"
+assert error_msg("'ab'[2]") == "string index out of range"
+assert error_msg("[1][-2] = 0") == "list assignment index out of range"
+assert error_msg("[1]['a']") == "list indices must be integers or slices, not str"
+assert error_msg("[1][::0]") == "slice step cannot be zero"
+assert error_msg("[1]['a':]") == "slice indices must be integers or None or have an __index__ method"
+assert error_msg("x = [1, 2, 3]\nx[::2] = [1]") == "attempt to assign sequence of size 1 to extended slice of size 2"
+assert error_msg("(1, 2)[0:1] = [3]") == "'tuple' object does not support item assignment"
+assert error_msg("1[0]") == "'int' object is not subscriptable"
+assert error_msg("del 'ab'[0]") == "'str' object doesn't support item deletion"
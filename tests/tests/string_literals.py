@@ -0,0 +1,65 @@
+# escape sequences
+assert len("a\tb") == 3
+assert "\x41\x42" == "AB"
+assert "\101" == "A"
+assert "é" == "\N{LATIN SMALL LETTER E WITH ACUTE}"
+assert len("\U0001F600") == 1
+assert len("\N{BULLET}") == 1
+assert 'it\'s' == "it's"
+assert "say \"hi\"" == 'say "hi"'
+assert len("\\") == 1
+assert len("\q") == 2
+assert "a\
+b" == "ab"
+
+# triple quoted strings span lines
+s = """first
+second"""
+assert len(s) == 12
+assert s == "first\nsecond"
+assert '''a 'quoted' "word"''' == "a 'quoted' \"word\""
+
+# raw strings keep backslashes
+assert len(r"\n") == 2
+assert R'\x41' == "\\x41"
+assert r"a\"b" == "a\\\"b"
+
+# bytes
+b = b"a\x00\n"
+assert len(b) == 3
+assert repr(b) == "b'a\\x00\\n'"
+assert b"ab" + b"c" == b"abc"
+assert b"ab" != "ab"
+assert b"b" in b"abc"
+assert 97 in b"abc"
+assert type(b) is bytes
+assert rb"\n" == b"\\n"
+assert repr(b"it's") == "b\"it's\""
+
+# adjacent literals are joined
+assert "ab" "cd" == "abcd"
+assert ("multi"
+        'ple') == "multiple"
+assert b"x" b"y" == b"xy"
+
+e = 0
+try:
+    exec("x = '''abc\ndef")
+except SyntaxError as err:
+    e = err
+assert e.msg == "unterminated triple-quoted string literal (detected at line 2)"
+assert e.lineno == 1
+
+e = 0
+try:
+    exec("x = b'a' 'b'")
+except SyntaxError as err:
+    e = err
+assert e.msg == "cannot mix bytes and nonbytes literals"
+
+e = 0
+try:
+    exec("x = '\\x4'")
+except SyntaxError as err:
+    e = err
+assert type(e) is SyntaxError
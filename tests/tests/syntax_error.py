@@ -0,0 +1,69 @@
+e = 0
+try:
+    exec("x = = 1")
+except SyntaxError as err:
+    e = err
+assert type(e) is SyntaxError
+assert e.msg == "expected expression, found '='"
+assert e.filename == "<string>"
+assert e.lineno == 1
+assert e.offset == 5
+assert e.text == "x = = 1"
+
+e = 0
+try:
+    compile("y = (1 + 2", "m.py", "exec")
+except SyntaxError as err:
+    e = err
+assert e.filename == "m.py"
+assert e.msg == "'(' was never closed"
+assert e.offset == 5
+
+e = 0
+try:
+    exec("s = 'abc")
+except SyntaxError as err:
+    e = err
+assert e.msg == "unterminated string literal (detected at line 1)"
+assert e.offset == 5
+
+c = compile("z = 2 ** 10", "m.py", "exec")
+assert c.co_filename == "m.py"
+assert exec(c) is None
+
+e = 0
+try:
+    exec("1 // 0")
+except ZeroDivisionError as err:
+    e = err
+assert type(e) is ZeroDivisionError
+
+def syntax_msg(src):
+    try:
+        exec(src)
+    except SyntaxError as err:
+        return err.msg
+    return None
+
+assert syntax_msg("x = 1\nyield 5\nx = 2\n") == "'yield' outside function"
+assert syntax_msg("class C:\n    yield 1\n") == "'yield' outside function"
+assert syntax_msg("yield from [1]") == "'yield' outside function"
+assert syntax_msg("x = [(yield 1) for i in [1, 2]]") == "'yield' inside list comprehension"
+assert syntax_msg("def f():\n    return {(yield i) for i in [1]}\n") == "'yield' inside set comprehension"
+assert syntax_msg("def f():\n    return ((yield i) for i in [1])\n") == "'yield' inside generator expression"
+assert syntax_msg("def f():\n    return [i for i in (yield)]\n") is None
+
+# statements out of place in their block
+e = 0
+try:
+    exec("x = 1\nwhile x:\n    x = 0\ncontinue\n")
+except SyntaxError as err:
+    e = err
+assert e.msg == "'continue' not properly in loop"
+assert e.lineno == 4 and e.offset == 1
+assert syntax_msg("break") == "'break' outside loop"
+assert syntax_msg("return 1") == "'return' outside function"
+assert syntax_msg("class C:\n    return\n") == "'return' outside function"
+assert syntax_msg("while True:\n    def f():\n        break\n") == "'break' outside loop"
+assert syntax_msg("for i in [1]:\n    if i:\n        continue\n    break\n") is None
+assert syntax_msg("def f():\n    return 1\n") is None
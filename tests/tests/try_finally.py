@@ -0,0 +1,113 @@
+log = []
+try:
+    log.append(1)
+finally:
+    log.append(2)
+assert repr(log) == "[1, 2]"
+
+log = []
+try:
+    try:
+        log.append(1)
+        raise KeyError
+    finally:
+        log.append(2)
+except KeyError:
+    log.append(3)
+assert repr(log) == "[1, 2, 3]"
+
+log = []
+def ret():
+    try:
+        return 1
+    finally:
+        log.append("finally")
+
+assert ret() == 1
+assert repr(log) == "['finally']"
+
+def override():
+    try:
+        return 1
+    finally:
+        return 2
+
+assert override() == 2
+
+def swallow():
+    try:
+        raise ValueError
+    finally:
+        return 3
+
+assert swallow() == 3
+
+log = []
+for i in [1, 2, 3]:
+    try:
+        if i == 2:
+            continue
+        else:
+            log.append(i)
+    finally:
+        log.append(0)
+assert repr(log) == "[1, 0, 0, 3, 0]"
+
+log = []
+while True:
+    try:
+        break
+    finally:
+        log.append("done")
+assert repr(log) == "['done']"
+
+log = []
+try:
+    try:
+        raise TypeError
+    except TypeError:
+        log.append("except")
+    else:
+        log.append("else")
+    finally:
+        log.append("finally")
+except:
+    log.append("outer")
+assert repr(log) == "['except', 'finally']"
+
+log = []
+try:
+    log.append("try")
+except TypeError:
+    log.append("except")
+else:
+    log.append("else")
+finally:
+    log.append("finally")
+assert repr(log) == "['try', 'else', 'finally']"
+
+log = []
+try:
+    try:
+        raise KeyError
+    except TypeError:
+        log.append("except")
+    finally:
+        log.append("finally")
+except KeyError:
+    log.append("outer")
+assert repr(log) == "['finally', 'outer']"
+
+def nested():
+    for i in [1, 2]:
+        try:
+            try:
+                return i
+            finally:
+                log.append("inner")
+        finally:
+            log.append("outer")
+
+log = []
+assert nested() == 1
+assert repr(log) == "['inner', 'outer']"
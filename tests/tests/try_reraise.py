@@ -0,0 +1,66 @@
+x = 0
+try:
+    try:
+        raise KeyError("k")
+    except KeyError:
+        raise
+except KeyError as e:
+    x = e
+assert type(x) is KeyError
+
+x = 0
+try:
+    try:
+        raise KeyError
+    except KeyError as e:
+        try:
+            raise ValueError
+        except ValueError:
+            x = 1
+        raise
+except KeyError:
+    x = x + 1
+assert x == 2
+
+def reraise():
+    raise
+
+x = 0
+try:
+    try:
+        raise IndexError
+    except IndexError:
+        reraise()
+except IndexError:
+    x = 1
+assert x == 1
+
+x = 0
+try:
+    raise
+except RuntimeError:
+    x = 1
+assert x == 1
+
+x = 0
+for i in [1, 2]:
+    try:
+        raise ValueError
+    except ValueError:
+        continue
+assert i == 2
+try:
+    raise
+except RuntimeError:
+    x = 1
+assert x == 1
+
+x = 0
+try:
+    try:
+        raise TypeError
+    finally:
+        x = 1
+except TypeError:
+    x = x + 1
+assert x == 2
@@ -0,0 +1,152 @@
+x = 0
+try:
+    {}["a"]
+except IndexError:
+    x = 1
+except KeyError:
+    x = 2
+except Exception:
+    x = 3
+assert x == 2
+
+x = 0
+try:
+    1 // 0
+except ArithmeticError as e:
+    x = e
+assert type(x) is ZeroDivisionError
+
+x = 0
+try:
+    [][0]
+except (KeyError, IndexError) as e:
+    x = 1
+assert x == 1
+
+x = 0
+try:
+    undefined_name
+except (TypeError):
+    x = 1
+except:
+    x = 2
+assert x == 2
+
+x = 0
+try:
+    try:
+        1 + "a"
+    except KeyError:
+        x = 1
+except TypeError:
+    x = 2
+assert x == 2
+
+x = 0
+try:
+    x = 1
+except Exception:
+    x = 2
+else:
+    x = x + 10
+assert x == 11
+
+x = 0
+try:
+    raise ValueError
+except ValueError:
+    x = 1
+else:
+    x = 2
+assert x == 1
+
+class MyError(ValueError):
+    pass
+
+x = 0
+try:
+    raise MyError("boom")
+except ValueError as e:
+    x = e
+assert type(x) is MyError
+
+x = 0
+try:
+    try:
+        raise KeyError
+    except 42:
+        x = 1
+except TypeError:
+    x = 2
+assert x == 2
+
+def find(l, i):
+    try:
+        return l[i]
+    except IndexError:
+        return None
+
+assert find([1, 2], 1) == 2
+assert find([1, 2], 5) is None
+
+# the name of an except clause is unbound when the clause ends
+try:
+    1 // 0
+except ZeroDivisionError as err:
+    assert type(err) is ZeroDivisionError
+x = 0
+try:
+    err
+except NameError:
+    x = 1
+assert x == 1
+
+def unbinds(reraise):
+    try:
+        try:
+            raise KeyError("k")
+        except KeyError as e:
+            if reraise:
+                raise ValueError("v")
+    except ValueError:
+        pass
+    try:
+        e
+    except UnboundLocalError:
+        return True
+    return False
+assert unbinds(False) and unbinds(True)
+
+def returns_from_handler():
+    try:
+        raise KeyError("k")
+    except KeyError as e:
+        return e.args[0]
+assert returns_from_handler() == "k"
+
+def in_loop():
+    n = 0
+    for i in [1, 2, 3]:
+        try:
+            raise KeyError(i)
+        except KeyError as e:
+            if i == 1:
+                continue
+            n = n + e.args[0]
+            if i == 2:
+                break
+    return n
+assert in_loop() == 2
+
+def shared():
+    try:
+        raise KeyError("k")
+    except KeyError as e:
+        f = lambda: e
+        assert f().args[0] == "k"
+    try:
+        f()
+    except NameError:
+        return True
+    return False
+assert shared()
@@ -0,0 +1,144 @@
+# tuple displays
+t = (1, "a", None)
+assert type(t) is tuple
+assert len(t) == 3
+assert t[1] == "a"
+assert repr(t) == "(1, 'a', None)"
+assert repr(()) == "()"
+assert repr((1,)) == "(1,)"
+assert (1) == 1
+u = 1, 2
+assert u == (1, 2)
+assert (1, 2) + (3,) == (1, 2, 3)
+assert 2 in u
+assert not (3 in u)
+assert not ()
+assert tuple() == ()
+assert tuple([1, 2]) == (1, 2)
+assert tuple({"a": 1, "b": 2}) == ("a", "b")
+
+# comparisons are lexicographic
+assert (1, 2) < (1, 3)
+assert (1, 2) < (1, 2, 0)
+assert (2,) > (1, 9)
+assert (1, 2) <= (1, 2)
+assert (1, 2) != (1, 2.5)
+assert (1, (2, 3)) == (1, (2, 3))
+
+# tuples are hashable and can be dict keys
+d = {(1, 2): "a", (1, (2, 3)): "b"}
+assert d[(1, 2)] == "a"
+assert d[1, (2, 3)] == "b"
+assert d[(1.0, 2)] == "a"
+
+e = 0
+try:
+    d[(1, [2])] = 3
+except TypeError as err:
+    e = err
+assert e.args[0] == "unhashable type: 'list'"
+
+e = 0
+try:
+    t[0] = 2
+except TypeError as err:
+    e = err
+assert e.args[0] == "'tuple' object does not support item assignment"
+
+e = 0
+try:
+    t[3]
+except IndexError as err:
+    e = err
+assert e.args[0] == "tuple index out of range"
+
+# packing and unpacking
+a, b = 1, 2
+a, b = b, a
+assert a == 2 and b == 1
+(a, b), c = [3, 4], 5
+assert (a, b, c) == (3, 4, 5)
+[a, b] = {"x": 1, "y": 2}
+assert a == "x" and b == "y"
+x, = [7]
+assert x == 7
+
+class P:
+    pass
+p = P()
+l = [0, 0]
+p.x, l[1] = 8, 9
+assert p.x == 8 and l == l and l[1] == 9
+
+# starred assignment
+first, *rest = [1, 2, 3]
+assert first == 1
+assert repr(rest) == "[2, 3]"
+*init, last = (1, 2, 3)
+assert repr(init) == "[1, 2]" and last == 3
+a, *mid, b = 1, 2
+assert repr(mid) == "[]"
+[*all] = ()
+assert repr(all) == "[]"
+
+# for targets
+pairs = []
+for k, v in {"a": 1, "b": 2}.items():
+    pairs.append(k + str(v))
+assert repr(pairs) == "['a1', 'b2']"
+total = 0
+for i, (j, *ks) in [(1, (2, 3, 4)), (5, (6,))]:
+    total = total + i + j + len(ks)
+assert total == 16
+for x in 1, 2:
+    total = total + x
+assert total == 19
+
+# starred items in displays
+xs = [1, 2]
+assert repr([0, *xs, *(3, 4), 5]) == "[0, 1, 2, 3, 4, 5]"
+assert (*xs, 3) == (1, 2, 3)
+assert (*{"a": 1},) == ("a",)
+y = *xs, *xs
+assert y == (1, 2, 1, 2)
+
+def pair():
+    return 1, 2
+assert pair() == (1, 2)
+
+# length mismatches
+def unpack_error(src):
+    try:
+        exec(src)
+    except ValueError as err:
+        return err.args[0]
+    except TypeError as err:
+        return err.args[0]
+    return None
+
+assert unpack_error("a, b = 1, 2, 3") == "too many values to unpack (expected 2)"
+assert unpack_error("a, b, c = [1, 2]") == "not enough values to unpack (expected 3, got 2)"
+assert unpack_error("a, *b, c = [1]") == "not enough values to unpack (expected at least 2, got 1)"
+assert unpack_error("a, b = 1") == "cannot unpack non-iterable int object"
+
+def syntax_msg(src):
+    try:
+        exec(src)
+    except SyntaxError as err:
+        return err.msg
+    return None
+
+assert syntax_msg("*a, *b = [1, 2]") == "multiple starred expressions in assignment"
+assert syntax_msg("*a = [1]") == "starred assignment target must be in a list or tuple"
+assert syntax_msg("x = *a") == "can't use starred expression here"
+assert syntax_msg("print((*a))") == "cannot use starred expression here"
+assert syntax_msg("1, 2 = x") == "cannot assign to expression"
+
+# a group of exception classes is a tuple
+e = 0
+try:
+    {}["k"]
+except (IndexError, KeyError) as err:
+    e = err
+assert type(e) is KeyError
+assert e.args == ("k",)